@@ -440,6 +440,10 @@ impl sc_cli::CliConfiguration for NormalizedRunCmd {
 		self.base.prometheus_config(default_listen_port, chain_spec)
 	}
 
+	fn prometheus_execution_metrics(&self) -> sc_cli::Result<bool> {
+		self.base.prometheus_execution_metrics()
+	}
+
 	fn disable_grandpa(&self) -> sc_cli::Result<bool> {
 		self.base.disable_grandpa()
 	}
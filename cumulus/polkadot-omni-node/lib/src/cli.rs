@@ -31,6 +31,7 @@ use sc_cli::{
 	RpcEndpoint, SharedParams, SubstrateCli,
 };
 use sc_service::{config::PrometheusConfig, BasePath};
+use sp_core::hashing::twox_128;
 use std::{
 	fmt::{Debug, Display, Formatter},
 	marker::PhantomData,
@@ -106,6 +107,12 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Export a finalized block and its state into a snapshot file.
+	Snapshot(sc_cli::SnapshotCmd),
+
+	/// Seed a fresh database from a snapshot file.
+	ImportSnapshot(sc_cli::ImportSnapshotCmd),
+
 	/// Subcommand for generating and managing chain specifications.
 	///
 	/// A `chain-spec-builder` subcommand corresponds to the existing `chain-spec-builder` tool
@@ -205,6 +212,10 @@ pub struct Cli<Config: CliConfig> {
 	#[arg(long)]
 	pub enable_statement_store: bool,
 
+	/// The limits enforced by the statement store, if enabled.
+	#[command(flatten)]
+	pub statement_store_params: sc_cli::StatementStoreParams,
+
 	#[arg(skip)]
 	pub(crate) _phantom: PhantomData<Config>,
 }
@@ -240,6 +251,7 @@ impl<Config: CliConfig> Cli<Config> {
 			export_pov: self.export_pov_to_path.clone(),
 			max_pov_percentage: self.run.experimental_max_pov_percentage,
 			enable_statement_store: self.enable_statement_store,
+			statement_store_options: self.statement_store_params.options(),
 		}
 	}
 }
@@ -269,6 +281,12 @@ impl<Config: CliConfig> SubstrateCli for Cli<Config> {
 		Config::copyright_start_year() as i32
 	}
 
+	fn block_timestamp_key() -> Option<Vec<u8>> {
+		// The `Now` storage item of the timestamp pallet, which parachain runtimes using Aura
+		// include.
+		Some([twox_128(b"Timestamp"), twox_128(b"Now")].concat())
+	}
+
 	fn load_spec(&self, id: &str) -> Result<Box<dyn ChainSpec>, String> {
 		match &self.chain_spec_loader {
 			Some(chain_spec_loader) => chain_spec_loader.load_spec(id),
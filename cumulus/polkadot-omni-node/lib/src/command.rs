@@ -190,6 +190,22 @@ where
 				node.prepare_revert_cmd(config, cmd)
 			})
 		},
+		Some(Subcommand::Snapshot(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let node =
+					new_node_spec(&config, &cmd_config.runtime_resolver, &cli.node_extra_args())?;
+				node.prepare_snapshot_cmd(config, cmd)
+			})
+		},
+		Some(Subcommand::ImportSnapshot(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let node =
+					new_node_spec(&config, &cmd_config.runtime_resolver, &cli.node_extra_args())?;
+				node.prepare_import_snapshot_cmd(config, cmd)
+			})
+		},
 		Some(Subcommand::ChainSpecBuilder(cmd)) =>
 			cmd.run().map_err(|err| sc_cli::Error::Application(err.into())),
 
@@ -277,6 +293,10 @@ where
 					let runner = cli.create_runner(cmd)?;
 					runner.sync_run(|config| cmd.run(&config, SUBSTRATE_REFERENCE_HARDWARE.clone()))
 				},
+				BenchmarkCmd::Compare(cmd) => {
+					let runner = cli.create_runner(cmd)?;
+					runner.sync_run(|_| cmd.run())
+				},
 				#[allow(unreachable_patterns)]
 				_ => Err("Benchmarking sub-command unsupported or compilation feature missing. \
 					Make sure to compile omni-node with --features=runtime-benchmarks \
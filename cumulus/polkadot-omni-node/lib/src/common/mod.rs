@@ -33,7 +33,7 @@ use cumulus_primitives_core::{
 	CollectCollationInfo, GetCoreSelectorApi, GetParachainInfo, RelayParentOffsetApi,
 };
 use sc_client_db::DbHash;
-use sc_offchain::OffchainWorkerApi;
+use sc_offchain::{OffchainWorkerApi, TasksApi};
 use serde::de::DeserializeOwned;
 use sp_api::{ApiExt, CallApiAt, ConstructRuntimeApi, Metadata};
 use sp_block_builder::BlockBuilder;
@@ -73,6 +73,7 @@ pub trait NodeRuntimeApi<Block: BlockT>:
 	+ BlockBuilder<Block>
 	+ TaggedTransactionQueue<Block>
 	+ OffchainWorkerApi<Block>
+	+ TasksApi<Block>
 	+ CollectCollationInfo<Block>
 	+ GetCoreSelectorApi<Block>
 	+ ValidateStatement<Block>
@@ -89,6 +90,7 @@ impl<T, Block: BlockT> NodeRuntimeApi<Block> for T where
 		+ BlockBuilder<Block>
 		+ TaggedTransactionQueue<Block>
 		+ OffchainWorkerApi<Block>
+		+ TasksApi<Block>
 		+ GetCoreSelectorApi<Block>
 		+ RelayParentOffsetApi<Block>
 		+ CollectCollationInfo<Block>
@@ -130,4 +132,7 @@ pub struct NodeExtraArgs {
 
 	/// If true then the statement store will be enabled.
 	pub enable_statement_store: bool,
+
+	/// The options of the statement store, if enabled.
+	pub statement_store_options: sc_statement_store::Options,
 }
@@ -25,7 +25,10 @@ use crate::common::{
 use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
 use sc_rpc::{
 	dev::{Dev, DevApiServer},
+	pruning::{Pruning, PruningApiServer},
+	snapshot::{Snapshot, SnapshotApiServer},
 	statement::{StatementApiServer, StatementStore},
+	SubscriptionTaskExecutor,
 };
 use sp_runtime::traits::Block as BlockT;
 use std::{marker::PhantomData, sync::Arc};
@@ -41,6 +44,7 @@ pub(crate) trait BuildRpcExtensions<Client, Backend, Pool, StatementStore> {
 		backend: Arc<Backend>,
 		pool: Arc<Pool>,
 		statement_store: Option<Arc<StatementStore>>,
+		subscription_executor: SubscriptionTaskExecutor,
 	) -> sc_service::error::Result<RpcExtension>;
 }
 
@@ -66,15 +70,20 @@ where
 			sc_transaction_pool::TransactionPoolHandle<Block, ParachainClient<Block, RuntimeApi>>,
 		>,
 		statement_store: Option<Arc<sc_statement_store::Store>>,
+		subscription_executor: SubscriptionTaskExecutor,
 	) -> sc_service::error::Result<RpcExtension> {
 		let build = || -> Result<RpcExtension, Box<dyn std::error::Error + Send + Sync>> {
 			let mut module = RpcExtension::new(());
 
 			module.merge(System::new(client.clone(), pool).into_rpc())?;
 			module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+			module.merge(Pruning::new(backend.clone()).into_rpc())?;
+			module.merge(Snapshot::new(backend.clone()).into_rpc())?;
 			module.merge(StateMigration::new(client.clone(), backend).into_rpc())?;
 			if let Some(statement_store) = statement_store {
-				module.merge(StatementStore::new(statement_store).into_rpc())?;
+				module.merge(
+					StatementStore::new(statement_store, subscription_executor).into_rpc(),
+				)?;
 			}
 			module.merge(Dev::new(client).into_rpc())?;
 
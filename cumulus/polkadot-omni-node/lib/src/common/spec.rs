@@ -374,6 +374,7 @@ pub(crate) trait NodeSpec: BaseNodeSpec {
 						sync_service.clone(),
 						params.keystore_container.local_keystore(),
 						statement_handler_proto,
+						node_extra_args.statement_store_options.clone(),
 					)
 				})
 				.transpose()?;
@@ -411,18 +412,32 @@ pub(crate) trait NodeSpec: BaseNodeSpec {
 				);
 			}
 
+			if parachain_config.offchain_worker.submit_tasks {
+				let task_worker = sc_offchain::tasks::TaskWorker::new(
+					client.clone(),
+					OffchainTransactionPoolFactory::new(transaction_pool.clone()),
+					Default::default(),
+				);
+				task_manager.spawn_handle().spawn(
+					"offchain-tasks-runner",
+					"offchain-work",
+					task_worker.run(client.clone()).boxed(),
+				);
+			}
+
 			let rpc_builder = {
 				let client = client.clone();
 				let transaction_pool = transaction_pool.clone();
 				let backend_for_rpc = backend.clone();
 				let statement_store = statement_store.clone();
 
-				Box::new(move |_| {
+				Box::new(move |subscription_executor| {
 					Self::BuildRpcExtensions::build_rpc_extensions(
 						client.clone(),
 						backend_for_rpc.clone(),
 						transaction_pool.clone(),
 						statement_store.clone(),
+						subscription_executor,
 					)
 				})
 			};
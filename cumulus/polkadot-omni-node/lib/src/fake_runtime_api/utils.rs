@@ -123,6 +123,16 @@ macro_rules! impl_node_runtime_apis {
 				}
 			}
 
+			impl sp_offchain::TasksApi<$block> for $runtime {
+				fn valid_tasks(_: u32) -> Vec<(Vec<u8>, Weight)> {
+					unimplemented!()
+				}
+
+				fn task_extrinsic(_: Vec<u8>) -> Option<<$block as BlockT>::Extrinsic> {
+					unimplemented!()
+				}
+			}
+
 			impl sp_session::SessionKeys<$block> for $runtime {
 				fn generate_session_keys(_: Option<Vec<u8>>) -> Vec<u8> {
 					unimplemented!()
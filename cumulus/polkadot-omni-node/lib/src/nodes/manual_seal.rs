@@ -144,6 +144,19 @@ impl<NodeSpec: NodeSpecT> ManualSealNode<NodeSpec> {
 			);
 		}
 
+		if config.offchain_worker.submit_tasks {
+			let task_worker = sc_offchain::tasks::TaskWorker::new(
+				client.clone(),
+				OffchainTransactionPoolFactory::new(transaction_pool.clone()),
+				Default::default(),
+			);
+			task_manager.spawn_handle().spawn(
+				"offchain-tasks-runner",
+				"offchain-work",
+				task_worker.run(client.clone()).boxed(),
+			);
+		}
+
 		let proposer = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
 			client.clone(),
@@ -256,12 +269,13 @@ impl<NodeSpec: NodeSpecT> ManualSealNode<NodeSpec> {
 			let transaction_pool = transaction_pool.clone();
 			let backend_for_rpc = backend.clone();
 
-			Box::new(move |_| {
+			Box::new(move |subscription_executor| {
 				let mut module = NodeSpec::BuildRpcExtensions::build_rpc_extensions(
 					client.clone(),
 					backend_for_rpc.clone(),
 					transaction_pool.clone(),
 					None,
+					subscription_executor,
 				)?;
 				module
 					.merge(ManualSeal::new(manual_seal_sink.clone()).into_rpc())
@@ -457,6 +457,8 @@ pub fn run() -> Result<()> {
 					cmd.run(&config, SUBSTRATE_REFERENCE_HARDWARE.clone())
 						.map_err(Error::SubstrateCli)
 				}),
+				BenchmarkCmd::Compare(cmd) =>
+					runner.sync_run(|_| cmd.run().map_err(Error::SubstrateCli)),
 				// NOTE: this allows the Polkadot client to leniently implement
 				// new benchmark commands.
 				#[allow(unreachable_patterns)]
@@ -2797,6 +2797,7 @@ sp_api::impl_runtime_apis! {
 	}
 
 	#[cfg(feature = "try-runtime")]
+	#[api_version(2)]
 	impl frame_try_runtime::TryRuntime<Block> for Runtime {
 		fn on_runtime_upgrade(checks: frame_try_runtime::UpgradeCheckSelect) -> (Weight, Weight) {
 			log::info!("try-runtime::on_runtime_upgrade westend.");
@@ -2814,6 +2815,22 @@ sp_api::impl_runtime_apis! {
 			// have a backtrace here.
 			Executive::try_execute_block(block, state_root_check, signature_check, select).unwrap()
 		}
+
+		fn step_multi_block_migrations(
+			header: <Block as BlockT>::Header,
+			inherent_data: sp_inherents::InherentData,
+			checks: frame_try_runtime::UpgradeCheckSelect,
+		) -> frame_try_runtime::MultiBlockMigrationsStep {
+			// NOTE: intentional unwrap: we don't want to propagate the error backwards, and want to
+			// have a backtrace here.
+			Executive::try_step_multi_block_migrations(
+				&header,
+				|| inherent_data.create_extrinsics(),
+				MbmServiceWeight::get(),
+				checks,
+			)
+			.unwrap()
+		}
 	}
 
 	#[cfg(feature = "runtime-benchmarks")]
@@ -109,11 +109,20 @@ fn check_treasury_pallet_id() {
 #[cfg(all(test, feature = "try-runtime"))]
 mod remote_tests {
 	use super::*;
-	use frame_support::traits::{TryState, TryStateSelect::All};
+	use frame_support::{
+		inherent::ProvideInherent,
+		traits::{TryState, TryStateSelect::All},
+	};
 	use frame_try_runtime::{runtime_decl_for_try_runtime::TryRuntime, UpgradeCheckSelect};
+	use polkadot_primitives::vstaging::InherentData as ParachainsInherentData;
 	use remote_externalities::{
 		Builder, Mode, OfflineConfig, OnlineConfig, SnapshotConfig, Transport,
 	};
+	use sp_consensus_babe::{
+		digests::{PreDigest, SecondaryPlainPreDigest},
+		BABE_ENGINE_ID,
+	};
+	use sp_runtime::{traits::Header as _, Digest, DigestItem};
 	use std::env::var;
 
 	#[tokio::test]
@@ -145,6 +154,80 @@ mod remote_tests {
 		ext.execute_with(|| Runtime::on_runtime_upgrade(UpgradeCheckSelect::PreAndPost));
 	}
 
+	#[tokio::test]
+	async fn run_multi_block_migrations() {
+		if var("RUN_MIGRATION_TESTS").is_err() {
+			return;
+		}
+
+		sp_tracing::try_init_simple();
+		let transport: Transport =
+			var("WS").unwrap_or("wss://westend-rpc.polkadot.io:443".to_string()).into();
+		let maybe_state_snapshot: Option<SnapshotConfig> = var("SNAP").map(|s| s.into()).ok();
+		let max_blocks = var("MBM_MAX_BLOCKS").map_or(1_000, |s| s.parse().unwrap());
+		let mut ext = Builder::<Block>::default()
+			.mode(if let Some(state_snapshot) = maybe_state_snapshot {
+				Mode::OfflineOrElseOnline(
+					OfflineConfig { state_snapshot: state_snapshot.clone() },
+					OnlineConfig {
+						transport,
+						state_snapshot: Some(state_snapshot),
+						..Default::default()
+					},
+				)
+			} else {
+				Mode::Online(OnlineConfig { transport, ..Default::default() })
+			})
+			.build()
+			.await
+			.unwrap();
+		let mut parent = ext.header.clone();
+		ext.execute_with(|| {
+			Runtime::on_runtime_upgrade(UpgradeCheckSelect::PreAndPost);
+
+			let report = frame_try_runtime::run_multi_block_migrations(max_blocks, || {
+				let slot = Babe::current_slot() + 1;
+				let pre_digest =
+					PreDigest::SecondaryPlain(SecondaryPlainPreDigest { authority_index: 0, slot });
+				let header = Header::new(
+					parent.number + 1,
+					Default::default(),
+					Default::default(),
+					parent.hash(),
+					Digest {
+						logs: vec![DigestItem::PreRuntime(BABE_ENGINE_ID, pre_digest.encode())],
+					},
+				);
+
+				let mut inherent_data = sp_inherents::InherentData::new();
+				inherent_data
+					.put_data(Timestamp::INHERENT_IDENTIFIER, &(*slot * Babe::slot_duration()))
+					.unwrap();
+				inherent_data
+					.put_data(
+						ParaInherent::INHERENT_IDENTIFIER,
+						&ParachainsInherentData {
+							bitfields: Vec::new(),
+							backed_candidates: Vec::new(),
+							disputes: Vec::new(),
+							parent_header: core::mem::replace(&mut parent, header.clone()),
+						},
+					)
+					.unwrap();
+
+				Executive::try_step_multi_block_migrations(
+					&header,
+					|| inherent_data.create_extrinsics(),
+					MbmServiceWeight::get(),
+					UpgradeCheckSelect::All,
+				)
+			})
+			.unwrap();
+			assert!(report.completed, "MBMs did not complete within {max_blocks} blocks");
+			assert_eq!(report.overweight_blocks(), 0, "MBMs exceeded the service weight");
+		});
+	}
+
 	#[tokio::test]
 	async fn delegate_stake_migration() {
 		// Intended to be run only manually.
@@ -33,6 +33,10 @@ pub struct Cli {
 	#[clap(flatten)]
 	pub mixnet_params: sc_cli::MixnetParams,
 
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub statement_store_params: sc_cli::StatementStoreParams,
+
 	/// Disable automatic hardware benchmarks.
 	///
 	/// By default these benchmarks are automatically ran at startup and measure
@@ -43,6 +47,14 @@ pub struct Cli {
 	#[arg(long)]
 	pub no_hardware_benchmarks: bool,
 
+	/// Keep the state of the first block of every staking era regardless of the state pruning
+	/// mode.
+	///
+	/// Requires ParityDb. Kept states can be listed and released with the `db keep-state`
+	/// command and the `pruning_*` RPC methods.
+	#[arg(long)]
+	pub keep_era_start_states: bool,
+
 	#[allow(missing_docs)]
 	#[clap(flatten)]
 	pub storage_monitor: sc_storage_monitor::StorageMonitorParams,
@@ -106,6 +118,16 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Export a finalized block and its state into a snapshot file.
+	Snapshot(sc_cli::SnapshotCmd),
+
+	/// Seed a fresh database from a snapshot file.
+	ImportSnapshot(sc_cli::ImportSnapshotCmd),
+
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Database maintenance utilities.
+	#[command(subcommand)]
+	Db(sc_cli::DbSubcommand),
 }
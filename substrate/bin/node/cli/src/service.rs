@@ -44,7 +44,7 @@ use sc_telemetry::{Telemetry, TelemetryWorker};
 use sc_transaction_pool::TransactionPoolHandle;
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use sp_api::ProvideRuntimeApi;
-use sp_core::crypto::Pair;
+use sp_core::{crypto::Pair, hashing::twox_128, storage::StorageKey};
 use sp_runtime::{generic, traits::Block as BlockT, SaturatedConversion};
 use std::{path::Path, sync::Arc};
 
@@ -174,6 +174,7 @@ pub fn create_extrinsic(
 pub fn new_partial(
 	config: &Configuration,
 	mixnet_config: Option<&sc_mixnet::Config>,
+	statement_store_options: sc_statement_store::Options,
 ) -> Result<
 	sc_service::PartialComponents<
 		FullClient,
@@ -294,7 +295,7 @@ pub fn new_partial(
 
 	let statement_store = sc_statement_store::Store::new_shared(
 		&config.data_path,
-		Default::default(),
+		statement_store_options,
 		client.clone(),
 		keystore_container.local_keystore(),
 		config.prometheus_registry(),
@@ -388,6 +389,8 @@ pub struct NewFullBase {
 	pub task_manager: TaskManager,
 	/// The client instance of the node.
 	pub client: Arc<FullClient>,
+	/// The backend of the node.
+	pub backend: Arc<sc_service::TFullBackend<Block>>,
 	/// The networking service of the node.
 	pub network: Arc<dyn NetworkService>,
 	/// The syncing service of the node.
@@ -402,6 +405,7 @@ pub struct NewFullBase {
 pub fn new_full_base<N: NetworkBackend<Block, <Block as BlockT>::Hash>>(
 	config: Configuration,
 	mixnet_config: Option<sc_mixnet::Config>,
+	statement_store_options: sc_statement_store::Options,
 	disable_hardware_benchmarks: bool,
 	with_startup_data: impl FnOnce(
 		&sc_consensus_babe::BabeBlockImport<
@@ -421,6 +425,7 @@ pub fn new_full_base<N: NetworkBackend<Block, <Block as BlockT>::Hash>>(
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 	let enable_offchain_worker = config.offchain_worker.enabled;
+	let submit_tasks = config.offchain_worker.submit_tasks;
 
 	let hwbench = (!disable_hardware_benchmarks)
 		.then(|| {
@@ -441,7 +446,7 @@ pub fn new_full_base<N: NetworkBackend<Block, <Block as BlockT>::Hash>>(
 		transaction_pool,
 		other:
 			(rpc_builder, import_setup, rpc_setup, mut telemetry, statement_store, mixnet_api_backend),
-	} = new_partial(&config, mixnet_config.as_ref())?;
+	} = new_partial(&config, mixnet_config.as_ref(), statement_store_options)?;
 
 	let metrics = N::register_notification_metrics(
 		config.prometheus_config.as_ref().map(|cfg| &cfg.registry),
@@ -808,9 +813,23 @@ pub fn new_full_base<N: NetworkBackend<Block, <Block as BlockT>::Hash>>(
 		);
 	}
 
+	if submit_tasks {
+		let task_worker = sc_offchain::tasks::TaskWorker::new(
+			client.clone(),
+			OffchainTransactionPoolFactory::new(transaction_pool.clone()),
+			Default::default(),
+		);
+		task_manager.spawn_handle().spawn(
+			"offchain-tasks-runner",
+			"offchain-work",
+			task_worker.run(client.clone()).boxed(),
+		);
+	}
+
 	Ok(NewFullBase {
 		task_manager,
 		client,
+		backend,
 		network,
 		sync: sync_service,
 		transaction_pool,
@@ -821,31 +840,37 @@ pub fn new_full_base<N: NetworkBackend<Block, <Block as BlockT>::Hash>>(
 /// Builds a new service for a full client.
 pub fn new_full(config: Configuration, cli: Cli) -> Result<TaskManager, ServiceError> {
 	let mixnet_config = cli.mixnet_params.config(config.role.is_authority());
+	let statement_store_options = cli.statement_store_params.options();
 	let database_path = config.database.path().map(Path::to_path_buf);
 
-	let task_manager = match config.network.network_backend {
-		sc_network::config::NetworkBackendType::Libp2p => {
-			let task_manager = new_full_base::<sc_network::NetworkWorker<_, _>>(
+	let NewFullBase { task_manager, client, backend, .. } = match config.network.network_backend {
+		sc_network::config::NetworkBackendType::Libp2p =>
+			new_full_base::<sc_network::NetworkWorker<_, _>>(
 				config,
 				mixnet_config,
+				statement_store_options,
 				cli.no_hardware_benchmarks,
 				|_, _| (),
-			)
-			.map(|NewFullBase { task_manager, .. }| task_manager)?;
-			task_manager
-		},
-		sc_network::config::NetworkBackendType::Litep2p => {
-			let task_manager = new_full_base::<sc_network::Litep2pNetworkBackend>(
+			)?,
+		sc_network::config::NetworkBackendType::Litep2p =>
+			new_full_base::<sc_network::Litep2pNetworkBackend>(
 				config,
 				mixnet_config,
+				statement_store_options,
 				cli.no_hardware_benchmarks,
 				|_, _| (),
-			)
-			.map(|NewFullBase { task_manager, .. }| task_manager)?;
-			task_manager
-		},
+			)?,
 	};
 
+	if cli.keep_era_start_states {
+		let active_era = [twox_128(b"Staking"), twox_128(b"ActiveEra")].concat();
+		task_manager.spawn_handle().spawn_blocking(
+			"keep-era-start-states",
+			None,
+			sc_service::keep_states_on_change(client, backend, StorageKey(active_era)),
+		);
+	}
+
 	if let Some(database_path) = database_path {
 		sc_storage_monitor::StorageMonitorService::try_spawn(
 			cli.storage_monitor,
@@ -925,6 +950,7 @@ mod tests {
 					new_full_base::<sc_network::NetworkWorker<_, _>>(
 						config,
 						None,
+						Default::default(),
 						false,
 						|block_import: &sc_consensus_babe::BabeBlockImport<Block, _, _>,
 						 babe_link: &sc_consensus_babe::BabeLink<Block>| {
@@ -1129,6 +1155,7 @@ mod tests {
 					new_full_base::<sc_network::NetworkWorker<_, _>>(
 						config,
 						None,
+						Default::default(),
 						false,
 						|_, _| (),
 					)?;
@@ -44,7 +44,7 @@ use sc_consensus_grandpa::{
 	FinalityProofProvider, GrandpaJustificationStream, SharedAuthoritySet, SharedVoterState,
 };
 pub use sc_rpc::SubscriptionTaskExecutor;
-use sc_transaction_pool_api::TransactionPool;
+use sc_transaction_pool_api::{TransactionPool, TransactionPoolInspect};
 use sp_api::ProvideRuntimeApi;
 use sp_application_crypto::RuntimeAppPublic;
 use sp_block_builder::BlockBuilder;
@@ -139,7 +139,7 @@ where
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: BabeApi<Block>,
 	C::Api: BlockBuilder<Block>,
-	P: TransactionPool + 'static,
+	P: TransactionPool + TransactionPoolInspect<BlockHash = Hash, Hash = Hash> + 'static,
 	SC: SelectChain<Block> + 'static,
 	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
 	B::State: sc_client_api::backend::StateBackend<sp_runtime::traits::HashingFor<Block>>,
@@ -154,7 +154,10 @@ where
 	use sc_rpc::{
 		dev::{Dev, DevApiServer},
 		mixnet::MixnetApiServer,
+		pruning::{Pruning, PruningApiServer},
+		snapshot::{Snapshot, SnapshotApiServer},
 		statement::StatementApiServer,
+		txpool::{TxPool, TxPoolApiServer},
 	};
 	use sc_sync_state_rpc::{SyncState, SyncStateApiServer};
 	use substrate_frame_rpc_system::{System, SystemApiServer};
@@ -171,7 +174,8 @@ where
 		finality_provider,
 	} = grandpa;
 
-	io.merge(System::new(client.clone(), pool).into_rpc())?;
+	io.merge(System::new(client.clone(), pool.clone()).into_rpc())?;
+	io.merge(TxPool::new(pool).into_rpc())?;
 	// Making synchronous calls in light client freezes the browser currently,
 	// more context: https://github.com/paritytech/substrate/pull/3480
 	// These RPCs should use an asynchronous caller instead.
@@ -190,7 +194,7 @@ where
 	)?;
 	io.merge(
 		Grandpa::new(
-			subscription_executor,
+			subscription_executor.clone(),
 			shared_authority_set.clone(),
 			shared_voter_state,
 			justification_stream,
@@ -204,9 +208,12 @@ where
 			.into_rpc(),
 	)?;
 
+	io.merge(Pruning::new(backend.clone()).into_rpc())?;
+	io.merge(Snapshot::new(backend.clone()).into_rpc())?;
 	io.merge(StateMigration::new(client.clone(), backend).into_rpc())?;
 	io.merge(Dev::new(client).into_rpc())?;
-	let statement_store = sc_rpc::statement::StatementStore::new(statement_store).into_rpc();
+	let statement_store =
+		sc_rpc::statement::StatementStore::new(statement_store, subscription_executor).into_rpc();
 	io.merge(statement_store)?;
 
 	if let Some(mixnet_api) = mixnet_api {
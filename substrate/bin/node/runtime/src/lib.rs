@@ -3138,6 +3138,36 @@ pallet_revive::impl_runtime_apis_plus_revive!(
 		}
 	}
 
+	impl sp_offchain::TasksApi<Block> for Runtime {
+		fn valid_tasks(limit: u32) -> Vec<(Vec<u8>, Weight)> {
+			// Tasks can only be executed with `do_task`, which is experimental.
+			if cfg!(feature = "experimental") {
+				System::valid_tasks(limit as usize)
+					.into_iter()
+					.map(|(task, weight)| (task.encode(), weight))
+					.collect()
+			} else {
+				Vec::new()
+			}
+		}
+
+		fn task_extrinsic(task: Vec<u8>) -> Option<<Block as BlockT>::Extrinsic> {
+			#[cfg(feature = "experimental")]
+			{
+				let task = RuntimeTask::decode(&mut &task[..]).ok()?;
+				let call = frame_system::Call::<Runtime>::do_task { task };
+				Some(<Runtime as frame_system::offchain::CreateBare<RuntimeCall>>::create_bare(
+					call.into(),
+				))
+			}
+			#[cfg(not(feature = "experimental"))]
+			{
+				let _ = task;
+				None
+			}
+		}
+	}
+
 	impl sp_consensus_grandpa::GrandpaApi<Block> for Runtime {
 		fn grandpa_authorities() -> sp_consensus_grandpa::AuthorityList {
 			Grandpa::grandpa_authorities()
@@ -3634,6 +3664,7 @@ pallet_revive::impl_runtime_apis_plus_revive!(
 	}
 
 	#[cfg(feature = "try-runtime")]
+	#[api_version(2)]
 	impl frame_try_runtime::TryRuntime<Block> for Runtime {
 		fn on_runtime_upgrade(checks: frame_try_runtime::UpgradeCheckSelect) -> (Weight, Weight) {
 			// NOTE: intentional unwrap: we don't want to propagate the error backwards, and want to
@@ -3653,6 +3684,22 @@ pallet_revive::impl_runtime_apis_plus_revive!(
 			// have a backtrace here.
 			Executive::try_execute_block(block, state_root_check, signature_check, select).unwrap()
 		}
+
+		fn step_multi_block_migrations(
+			header: <Block as BlockT>::Header,
+			inherent_data: InherentData,
+			checks: frame_try_runtime::UpgradeCheckSelect,
+		) -> frame_try_runtime::MultiBlockMigrationsStep {
+			// NOTE: intentional unwrap: we don't want to propagate the error backwards, and want to
+			// have a backtrace here.
+			Executive::try_step_multi_block_migrations(
+				&header,
+				|| inherent_data.create_extrinsics(),
+				MbmServiceWeight::get(),
+				checks,
+			)
+			.unwrap()
+		}
 	}
 
 	#[cfg(feature = "runtime-benchmarks")]
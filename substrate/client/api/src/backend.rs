@@ -668,6 +668,39 @@ pub trait Backend<Block: BlockT>: AuxStore + Send + Sync {
 
 	/// Tells whether the backend requires full-sync mode.
 	fn requires_full_sync(&self) -> bool;
+
+	/// Keep the state of the canonical block `hash` regardless of the pruning mode, until it is
+	/// released with [`Self::release_state`].
+	///
+	/// Unlike [`Self::pin_block`], this survives restarts. Backends that can not keep states
+	/// return an error.
+	fn keep_state(&self, _hash: Block::Hash) -> sp_blockchain::Result<()> {
+		Err(sp_blockchain::Error::Backend("Keeping states is not supported by the backend".into()))
+	}
+
+	/// Allow pruning of a state previously passed to [`Self::keep_state`].
+	fn release_state(&self, _hash: Block::Hash) -> sp_blockchain::Result<()> {
+		Ok(())
+	}
+
+	/// Returns the blocks whose state is kept regardless of the pruning mode, ordered by number.
+	fn kept_states(&self) -> Vec<(Block::Hash, NumberFor<Block>)> {
+		Vec::new()
+	}
+
+	/// Write a portable snapshot of the finalized block `hash` and its state to `output`,
+	/// returning the number of trie nodes written.
+	///
+	/// Backends that can not export snapshots return an error.
+	fn export_snapshot(
+		&self,
+		_hash: Block::Hash,
+		_output: &mut dyn std::io::Write,
+	) -> sp_blockchain::Result<u64> {
+		Err(sp_blockchain::Error::Backend(
+			"Exporting snapshots is not supported by the backend".into(),
+		))
+	}
 }
 
 /// Mark for all Backend implementations, that are making use of state data, stored locally.
@@ -72,13 +72,8 @@ impl ChainInfoCmd {
 	where
 		B: BlockT,
 	{
-		let db_config = sc_client_db::DatabaseSettings {
-			trie_cache_maximum_size: config.trie_cache_maximum_size,
-			state_pruning: config.state_pruning.clone(),
-			source: config.database.clone(),
-			blocks_pruning: config.blocks_pruning,
-			metrics_registry: None,
-		};
+		let db_config =
+			sc_client_db::DatabaseSettings { metrics_registry: None, ..config.db_config() };
 		let backend = sc_service::new_db_backend::<B>(db_config)?;
 		let info: ChainInfo<B> = backend.blockchain().info().into();
 		let mut out = io::stdout();
@@ -0,0 +1,107 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{DatabaseParams, PruningParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use log::info;
+use sc_service::{
+	config::{NetworkConfiguration, NodeKeyConfig, SyncMode},
+	ChainSpec,
+};
+use sp_runtime::traits::Block as BlockT;
+use std::{fs, io, path::PathBuf, sync::Arc};
+
+/// The `import-snapshot` command used to seed a fresh database from a file written by the
+/// `snapshot` command.
+///
+/// The snapshot block becomes the finalized and best block. Blocks below it are downloaded by
+/// the regular sync once the node is started.
+///
+/// As for warp sync, the database is created without the genesis state, which is why the
+/// command configures the node with [`SyncMode::Warp`].
+#[derive(Debug, Clone, Parser)]
+pub struct ImportSnapshotCmd {
+	/// Input file name.
+	#[arg()]
+	pub input: PathBuf,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl ImportSnapshotCmd {
+	/// Run the `import-snapshot` command
+	pub async fn run<B>(&self, backend: Arc<sc_client_db::Backend<B>>) -> error::Result<()>
+	where
+		B: BlockT,
+	{
+		info!("Importing snapshot from {}...", self.input.display());
+		let mut file = io::BufReader::new(fs::File::open(&self.input)?);
+		let snapshot = backend.import_snapshot(&mut file)?;
+		info!(
+			"Imported #{} ({:?}) with {} trie nodes",
+			snapshot.number, snapshot.hash, snapshot.nodes
+		);
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ImportSnapshotCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+
+	fn network_config(
+		&self,
+		_chain_spec: &Box<dyn ChainSpec>,
+		_is_dev: bool,
+		_is_validator: bool,
+		net_config_dir: PathBuf,
+		client_id: &str,
+		node_name: &str,
+		node_key: NodeKeyConfig,
+		_default_listen_port: u16,
+	) -> error::Result<NetworkConfiguration> {
+		let mut network_config =
+			NetworkConfiguration::new(node_name, client_id, node_key, Some(net_config_dir));
+		network_config.sync_mode = SyncMode::Warp;
+		Ok(network_config)
+	}
+}
@@ -0,0 +1,97 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+use crate::{
+	error,
+	params::{BlockNumberOrHash, DatabaseParams, PruningParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use log::info;
+use sc_client_api::{backend::Backend, HeaderBackend};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::{fmt::Debug, str::FromStr, sync::Arc};
+
+/// The `db keep-state` command used to keep the state of a canonical block regardless of the
+/// state pruning mode, or to release a kept state.
+///
+/// Without a block, the command lists the kept states. States can also be kept on a running node
+/// with the `pruning_keepState` RPC.
+#[derive(Debug, Clone, Parser)]
+pub struct KeepStateCmd {
+	/// Block hash or number of a canonical block whose state has not been pruned yet.
+	#[arg(value_name = "HASH or NUMBER")]
+	pub block: Option<BlockNumberOrHash>,
+
+	/// Allow pruning of the state of the block again.
+	#[arg(long, requires = "block")]
+	pub release: bool,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl KeepStateCmd {
+	/// Run the `db keep-state` command
+	pub async fn run<B, BA>(&self, backend: Arc<BA>) -> error::Result<()>
+	where
+		B: BlockT,
+		BA: Backend<B>,
+		<B::Hash as FromStr>::Err: Debug,
+		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
+	{
+		let Some(block) = &self.block else {
+			for (hash, number) in backend.kept_states() {
+				info!("#{number} ({hash:?})");
+			}
+			return Ok(())
+		};
+
+		let hash = backend.blockchain().expect_block_hash_from_id(&block.parse()?)?;
+		if self.release {
+			backend.release_state(hash)?;
+			info!("Released state of {hash:?}");
+		} else {
+			info!("Keeping state of {hash:?}...");
+			backend.keep_state(hash)?;
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for KeepStateCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
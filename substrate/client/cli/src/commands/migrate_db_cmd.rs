@@ -0,0 +1,59 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{arg_enums::Database, error, params::SharedParams, CliConfiguration};
+use clap::Parser;
+use sp_runtime::traits::Block as BlockT;
+use std::fmt::Debug;
+
+/// The `db migrate` command used to convert the database of a chain to another backend.
+///
+/// The source database is left untouched, so a database written by an older node version has to
+/// be opened with the node once to upgrade it first. An interrupted migration continues where it
+/// stopped when the command is run again.
+#[derive(Debug, Clone, Parser)]
+pub struct MigrateDbCmd {
+	/// Backend of the existing database.
+	#[arg(long, value_name = "DB", ignore_case = true, value_enum)]
+	pub from: Database,
+
+	/// Backend of the database to create.
+	#[arg(long, value_name = "DB", ignore_case = true, value_enum)]
+	pub to: Database,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl MigrateDbCmd {
+	/// Run the migrate command
+	pub fn run<B: BlockT>(&self, config: &sc_service::Configuration) -> error::Result<()> {
+		let cache_size = self.database_cache_size()?.unwrap_or(1024);
+		let from = self.database_config(&config.data_path, cache_size, self.from)?;
+		let to = self.database_config(&config.data_path, cache_size, self.to)?;
+		sc_client_db::migrate_database::<B>(&from, &to)?;
+		Ok(())
+	}
+}
+
+impl CliConfiguration for MigrateDbCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
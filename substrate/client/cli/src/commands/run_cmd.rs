@@ -278,6 +278,10 @@ impl CliConfiguration for RunCmd {
 			.prometheus_config(default_listen_port, chain_spec.id().to_string()))
 	}
 
+	fn prometheus_execution_metrics(&self) -> Result<bool> {
+		Ok(self.prometheus_params.prometheus_execution_metrics)
+	}
+
 	fn disable_grandpa(&self) -> Result<bool> {
 		Ok(self.no_grandpa)
 	}
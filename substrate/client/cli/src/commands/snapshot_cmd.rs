@@ -0,0 +1,123 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{BlockNumberOrHash, DatabaseParams, PruningParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use log::info;
+use sc_client_api::{backend::Backend as _, HeaderBackend};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::{
+	fmt::Debug,
+	fs, io,
+	path::{Path, PathBuf},
+	str::FromStr,
+	sync::Arc,
+};
+
+/// The `snapshot` command used to export a finalized block together with its state into a
+/// portable file that can be loaded with `import-snapshot`.
+///
+/// The command opens the database of a stopped node. Snapshots of a running node can be
+/// exported with the `snapshot_export` RPC.
+#[derive(Debug, Clone, Parser)]
+pub struct SnapshotCmd {
+	/// Output file name.
+	#[arg()]
+	pub output: PathBuf,
+
+	/// Block hash or number of a finalized block.
+	/// Default is the last finalized block.
+	#[arg(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl SnapshotCmd {
+	/// Run the `snapshot` command
+	pub async fn run<B>(&self, backend: Arc<sc_client_db::Backend<B>>) -> error::Result<()>
+	where
+		B: BlockT,
+		<B::Hash as FromStr>::Err: Debug,
+		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
+	{
+		let blockchain = backend.blockchain();
+		let hash = match self.at.as_ref().map(|b| b.parse()).transpose()? {
+			Some(id) => blockchain.expect_block_hash_from_id(&id)?,
+			None => blockchain.info().finalized_hash,
+		};
+
+		// Write to a temporary file so that an existing snapshot is only replaced by a complete
+		// one.
+		let mut partial = self.output.clone().into_os_string();
+		partial.push(".partial");
+		let partial = PathBuf::from(partial);
+
+		info!("Exporting snapshot of {hash:?} to {}...", self.output.display());
+		let snapshot = match write_snapshot(&backend, hash, &partial) {
+			Ok(snapshot) => snapshot,
+			Err(err) => {
+				let _ = fs::remove_file(&partial);
+				return Err(err)
+			},
+		};
+		fs::rename(&partial, &self.output)?;
+		info!("Exported #{} with {} trie nodes", snapshot.number, snapshot.nodes);
+
+		Ok(())
+	}
+}
+
+fn write_snapshot<B: BlockT>(
+	backend: &sc_client_db::Backend<B>,
+	hash: B::Hash,
+	path: &Path,
+) -> error::Result<sc_client_db::SnapshotInfo<B>> {
+	let mut file = io::BufWriter::new(fs::File::create(path)?);
+	let snapshot = sc_client_db::export_snapshot(backend, hash, &mut file)?;
+	file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+	Ok(snapshot)
+}
+
+impl CliConfiguration for SnapshotCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
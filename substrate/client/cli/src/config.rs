@@ -19,18 +19,19 @@
 //! Configuration trait for a CLI based on substrate
 
 use crate::{
-	arg_enums::Database, error::Result, DatabaseParams, ImportParams, KeystoreParams,
-	NetworkParams, NodeKeyParams, OffchainWorkerParams, PruningParams, RpcEndpoint, SharedParams,
-	SubstrateCli,
+	arg_enums::Database,
+	error::{Error, Result},
+	DatabaseParams, ImportParams, KeystoreParams, NetworkParams, NodeKeyParams,
+	OffchainWorkerParams, PruningParams, RpcEndpoint, SharedParams, SubstrateCli,
 };
 use log::warn;
 use names::{Generator, Name};
 use sc_service::{
 	config::{
-		BasePath, Configuration, DatabaseSource, ExecutorConfiguration, IpNetwork, KeystoreConfig,
-		NetworkConfiguration, NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode,
-		Role, RpcBatchRequestConfig, RpcConfiguration, RpcMethods, TelemetryEndpoints,
-		TransactionPoolOptions, WasmExecutionMethod,
+		BasePath, Configuration, Constraints, DatabaseSource, ExecutorConfiguration, IpNetwork,
+		KeystoreConfig, NetworkConfiguration, NodeKeyConfig, OffchainWorkerConfig,
+		PrometheusConfig, PruningMode, Role, RpcBatchRequestConfig, RpcConfiguration, RpcMethods,
+		TelemetryEndpoints, TransactionPoolOptions, WasmExecutionMethod,
 	},
 	BlocksPruning, ChainSpec, TracingReceiver,
 };
@@ -387,6 +388,14 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(None)
 	}
 
+	/// Returns `true` if the execution metrics of imported blocks should be exported to
+	/// Prometheus.
+	///
+	/// By default this is `false`.
+	fn prometheus_execution_metrics(&self) -> Result<bool> {
+		Ok(false)
+	}
+
 	/// Get the telemetry endpoints (if any)
 	///
 	/// By default this is retrieved from the chain spec loaded by `load_spec`.
@@ -514,6 +523,17 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		let keystore = self.keystore_config(&config_dir)?;
 		let telemetry_endpoints = self.telemetry_endpoints(&chain_spec)?;
 		let runtime_cache_size = self.runtime_cache_size()?;
+		let state_pruning = self.state_pruning()?;
+		let block_timestamp_key = C::block_timestamp_key();
+		let prunes_by_age = matches!(
+			state_pruning,
+			Some(PruningMode::Constrained(Constraints { max_age: Some(_), .. }))
+		);
+		if prunes_by_age && block_timestamp_key.is_none() {
+			return Err(Error::Input(
+				"`--state-pruning-max-age` is not supported by this node".into(),
+			))
+		}
 
 		let rpc_addrs: Option<Vec<sc_service::config::RpcEndpoint>> = self
 			.rpc_addr(DCV::rpc_listen_port())?
@@ -523,7 +543,7 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			impl_name: C::impl_name(),
 			impl_version: C::impl_version(),
 			tokio_handle,
-			transaction_pool: self.transaction_pool(is_dev)?,
+			transaction_pool: self.transaction_pool(is_dev)?.with_journal_base_path(&config_dir),
 			network: self.network_config(
 				&chain_spec,
 				is_dev,
@@ -539,8 +559,9 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			data_path: config_dir,
 			trie_cache_maximum_size: self.trie_cache_maximum_size()?,
 			warm_up_trie_cache: self.warm_up_trie_cache()?,
-			state_pruning: self.state_pruning()?,
+			state_pruning,
 			blocks_pruning: self.blocks_pruning()?,
+			block_timestamp_key,
 			executor: ExecutorConfiguration {
 				wasm_method: self.wasm_method()?,
 				default_heap_pages: self.default_heap_pages()?,
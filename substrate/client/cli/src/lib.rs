@@ -27,6 +27,7 @@
 use clap::{CommandFactory, FromArgMatches, Parser};
 use log::warn;
 use sc_service::Configuration;
+use sc_tracing::execution_metrics::ExecutionMetrics;
 
 pub mod arg_enums;
 pub mod commands;
@@ -90,6 +91,14 @@ pub trait SubstrateCli: Sized {
 	/// Copyright starting year (x-current year)
 	fn copyright_start_year() -> i32;
 
+	/// Storage key of the SCALE encoded `u64` timestamp of blocks, in milliseconds, used to prune
+	/// the state by age.
+	///
+	/// Pruning the state by age is not available when `None`, the default.
+	fn block_timestamp_key() -> Option<Vec<u8>> {
+		None
+	}
+
 	/// Chain spec factory
 	fn load_spec(&self, id: &str) -> std::result::Result<Box<dyn ChainSpec>, String>;
 
@@ -222,7 +231,16 @@ pub trait SubstrateCli: Sized {
 
 		let config = command.create_configuration(self, tokio_runtime.handle().clone())?;
 
+		let execution_metrics = match config.prometheus_registry() {
+			Some(registry) if command.prometheus_execution_metrics()? =>
+				Some(ExecutionMetrics::register(registry).map_err(sc_service::Error::from)?),
+			_ => None,
+		};
+
 		command.init(&Self::support_url(), &Self::impl_version(), |logger_builder| {
+			if let Some(execution_metrics) = execution_metrics {
+				logger_builder.with_execution_metrics(execution_metrics);
+			}
 			logger_hook(logger_builder, &config)
 		})?;
 
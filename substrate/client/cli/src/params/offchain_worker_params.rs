@@ -47,6 +47,12 @@ pub struct OffchainWorkerParams {
 	/// Allows the runtime to write directly to offchain workers DB during block import.
 	#[arg(long = "enable-offchain-indexing", value_name = "ENABLE_OFFCHAIN_INDEXING", default_value_t = false, action = ArgAction::Set)]
 	pub indexing_enabled: bool,
+
+	/// Submit the tasks of the runtime as unsigned transactions once they are valid.
+	///
+	/// Tasks are checked on every new best block.
+	#[arg(long)]
+	pub submit_tasks: bool,
 }
 
 impl OffchainWorkerParams {
@@ -60,6 +66,7 @@ impl OffchainWorkerParams {
 		};
 
 		let indexing_enabled = self.indexing_enabled;
-		Ok(OffchainWorkerConfig { enabled, indexing_enabled })
+		let submit_tasks = self.submit_tasks;
+		Ok(OffchainWorkerConfig { enabled, indexing_enabled, submit_tasks })
 	}
 }
@@ -36,6 +36,14 @@ pub struct PrometheusParams {
 	/// Prometheus metric endpoint is enabled by default.
 	#[arg(long)]
 	pub no_prometheus: bool,
+	/// Export the time spent in the phases of the import of blocks, such as their execution, and
+	/// the time spent executing their extrinsics and hooks, along with the weight they declared,
+	/// by pallet.
+	///
+	/// The times of the extrinsics and hooks are only exported for runtimes built with the
+	/// `with-tracing` feature.
+	#[arg(long)]
+	pub prometheus_execution_metrics: bool,
 }
 
 impl PrometheusParams {
@@ -18,7 +18,7 @@
 
 use crate::error;
 use clap::Args;
-use sc_service::{BlocksPruning, PruningMode};
+use sc_service::{BlocksPruning, Constraints, PruningMode};
 
 /// Parameters to define the pruning mode
 #[derive(Debug, Clone, Args)]
@@ -45,6 +45,22 @@ pub struct PruningParams {
 	#[arg(alias = "pruning", long, value_name = "PRUNING_MODE")]
 	pub state_pruning: Option<DatabasePruningMode>,
 
+	/// Prune the state of finalized blocks older than the given number of days.
+	///
+	/// The age of a block is taken from the timestamp it sets in its own storage. Blocks that set
+	/// none, such as the genesis block, are pruned once a later block is old enough. Can be
+	/// combined with a `NUMBER` state pruning mode and `--state-pruning-max-size`, in which case
+	/// the state of a block is pruned as soon as any of the limits is exceeded.
+	#[arg(long, value_name = "DAYS")]
+	pub state_pruning_max_age: Option<u32>,
+
+	/// Prune the state of the oldest finalized blocks while the state kept for pruning exceeds
+	/// the given number of GiB.
+	///
+	/// Can be combined with a `NUMBER` state pruning mode and `--state-pruning-max-age`.
+	#[arg(long, value_name = "GIB")]
+	pub state_pruning_max_size: Option<u32>,
+
 	/// Specify the blocks pruning mode.
 	///
 	/// This mode specifies when the block's body (including justifications)
@@ -69,7 +85,24 @@ pub struct PruningParams {
 impl PruningParams {
 	/// Get the pruning value from the parameters
 	pub fn state_pruning(&self) -> error::Result<Option<PruningMode>> {
-		Ok(self.state_pruning.map(|v| v.into()))
+		if self.state_pruning_max_age.is_none() && self.state_pruning_max_size.is_none() {
+			return Ok(self.state_pruning.map(|v| v.into()))
+		}
+
+		let max_blocks = match self.state_pruning {
+			None => None,
+			Some(DatabasePruningMode::Custom(n)) => Some(n),
+			Some(_) => return Err(error::Error::Input(
+				"`--state-pruning-max-age` and `--state-pruning-max-size` can not be used with \
+				 an archive state pruning mode"
+					.into(),
+			)),
+		};
+		Ok(Some(PruningMode::Constrained(Constraints {
+			max_blocks,
+			max_age: self.state_pruning_max_age.map(|days| u64::from(days) * 24 * 60 * 60 * 1000),
+			max_size: self.state_pruning_max_size.map(|gib| u64::from(gib) << 30),
+		})))
 	}
 
 	/// Get the block pruning value from the parameters
@@ -162,4 +195,36 @@ mod tests {
 		assert!(matches!(dbg!(pruning.state_pruning), Some(DatabasePruningMode::ArchiveCanonical)));
 		assert!(matches!(pruning.blocks_pruning, DatabasePruningMode::ArchiveCanonical));
 	}
+
+	#[test]
+	fn state_pruning_limits_work() {
+		let Cli { pruning } = Cli::parse_from([
+			"",
+			"--state-pruning=1000",
+			"--state-pruning-max-age=7",
+			"--state-pruning-max-size=2",
+		]);
+		assert_eq!(
+			pruning.state_pruning().unwrap(),
+			Some(PruningMode::Constrained(Constraints {
+				max_blocks: Some(1000),
+				max_age: Some(7 * 24 * 60 * 60 * 1000),
+				max_size: Some(2 << 30),
+			}))
+		);
+
+		let Cli { pruning } = Cli::parse_from(["", "--state-pruning-max-age=1"]);
+		assert_eq!(
+			pruning.state_pruning().unwrap(),
+			Some(PruningMode::Constrained(Constraints {
+				max_blocks: None,
+				max_age: Some(24 * 60 * 60 * 1000),
+				max_size: None,
+			}))
+		);
+
+		let Cli { pruning } =
+			Cli::parse_from(["", "--state-pruning=archive", "--state-pruning-max-size=1"]);
+		assert!(pruning.state_pruning().is_err());
+	}
 }
@@ -0,0 +1,66 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use clap::Args;
+
+/// Parameters used to create the statement store options.
+#[derive(Debug, Clone, Args)]
+pub struct StatementStoreParams {
+	/// Maximum number of statements a single account may have in the statement store.
+	///
+	/// Caps the limit returned by the runtime. By default only the runtime limit applies.
+	#[arg(long, value_name = "COUNT")]
+	pub statement_store_max_account_statements: Option<usize>,
+
+	/// Maximum number of kilobytes of the statements of a single account in the statement store.
+	///
+	/// Caps the limit returned by the runtime. By default only the runtime limit applies.
+	#[arg(long, value_name = "KBYTES")]
+	pub statement_store_max_account_kbytes: Option<usize>,
+
+	/// Maximum number of statements a single account may add to the statement store per rate
+	/// limit period.
+	///
+	/// By default there is no rate limit.
+	#[arg(long, value_name = "COUNT")]
+	pub statement_store_max_account_submissions: Option<u32>,
+
+	/// Length of the statement store rate limit period in seconds.
+	#[arg(long, value_name = "SECONDS", default_value_t = 60)]
+	pub statement_store_rate_limit_period: u64,
+
+	/// Maximum number of kilobytes of the statements with a given topic in the statement store.
+	///
+	/// By default there is no limit.
+	#[arg(long, value_name = "KBYTES")]
+	pub statement_store_max_topic_kbytes: Option<usize>,
+}
+
+impl StatementStoreParams {
+	/// Returns the statement store options.
+	pub fn options(&self) -> sc_statement_store::Options {
+		sc_statement_store::Options {
+			max_account_statements: self.statement_store_max_account_statements,
+			max_account_size: self.statement_store_max_account_kbytes.map(|kb| kb * 1024),
+			max_account_submissions_per_period: self.statement_store_max_account_submissions,
+			rate_limit_period_sec: self.statement_store_rate_limit_period,
+			max_topic_size: self.statement_store_max_topic_kbytes.map(|kb| kb * 1024),
+			..Default::default()
+		}
+	}
+}
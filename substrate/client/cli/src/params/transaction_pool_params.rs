@@ -18,6 +18,7 @@
 
 use clap::{Args, ValueEnum};
 use sc_transaction_pool::TransactionPoolOptions;
+use std::path::PathBuf;
 
 /// Type of transaction pool to be used
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -59,17 +60,39 @@ pub struct TransactionPoolParams {
 	/// The type of transaction pool to be instantiated.
 	#[arg(long, value_enum, default_value_t = TransactionPoolType::ForkAware)]
 	pub pool_type: TransactionPoolType,
+
+	/// Persist the transaction pool in an on-disk journal.
+	///
+	/// Pending transactions are periodically written to `txpool/journal` in the chain
+	/// configuration directory, and revalidated and re-added to the pool at startup.
+	#[arg(long)]
+	pub pool_journal: bool,
+
+	/// Maximum number of kilobytes of transactions stored in the transaction pool journal.
+	#[arg(long, value_name = "KBYTES", default_value_t = 20480, requires = "pool_journal")]
+	pub pool_journal_kbytes: usize,
 }
 
 impl TransactionPoolParams {
 	/// Fill the given `PoolConfiguration` by looking at the cli parameters.
+	///
+	/// A relative journal path is returned, to be resolved against the chain configuration
+	/// directory.
 	pub fn transaction_pool(&self, is_dev: bool) -> TransactionPoolOptions {
-		TransactionPoolOptions::new_with_params(
+		let options = TransactionPoolOptions::new_with_params(
 			self.pool_limit,
 			self.pool_kbytes * 1024,
 			self.tx_ban_seconds,
 			self.pool_type.into(),
 			is_dev,
-		)
+		);
+		if self.pool_journal {
+			options.with_journal(
+				PathBuf::from("txpool").join("journal"),
+				self.pool_journal_kbytes * 1024,
+			)
+		} else {
+			options
+		}
 	}
 }
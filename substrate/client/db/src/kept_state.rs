@@ -0,0 +1,203 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Canonical states that are excluded from pruning.
+//!
+//! Keeping a state takes an extra reference on every trie node it is made of, so that the nodes
+//! outlive the deletions issued as the pruning window moves past the block. This relies on the
+//! database counting references, which ParityDb does.
+
+use crate::{apply_state_commit, columns, snapshot::for_each_node, Backend};
+use log::info;
+use sc_client_api::{backend::Backend as _, TrieCacheContext};
+use sp_blockchain::{Error as ClientError, HeaderBackend, Result as ClientResult};
+use sp_database::Transaction;
+use sp_runtime::traits::{
+	Block as BlockT, Hash, HashingFor, Header as HeaderT, NumberFor, SaturatedConversion,
+};
+use sp_state_machine::backend::AsTrieBackend;
+
+const LOG_TARGET: &str = "db::kept-state";
+
+/// Amount of trie node data to reference before committing a batch.
+const BATCH_BYTES: usize = 64 * 1024 * 1024;
+
+/// Keep the state of the canonical block `hash` regardless of the pruning mode. See
+/// [`sc_client_api::backend::Backend::keep_state`].
+///
+/// This walks the whole state of the block, so it may take a while. It does nothing if the state is
+/// already kept or if the pruning mode keeps all canonical states anyway.
+pub(crate) fn keep_state<Block: BlockT>(
+	backend: &Backend<Block>,
+	hash: Block::Hash,
+) -> ClientResult<()> {
+	if backend.storage.state_db.pruning_mode().is_archive() {
+		return Ok(())
+	}
+	if !backend.storage.db.supports_ref_counting() {
+		return Err(ClientError::Backend(
+			"Keeping states requires a database with reference counting, such as ParityDb".into(),
+		))
+	}
+
+	let header = backend.blockchain.expect_header(hash)?;
+	// Pins the state, so it is not pruned before its nodes are referenced.
+	let state = backend.state_at(hash, TrieCacheContext::Untrusted)?;
+	let number = (*header.number()).saturated_into::<u64>();
+	let Some(commit) = backend
+		.storage
+		.state_db
+		.keep(&hash, number)
+		.map_err(ClientError::from_state_db)?
+	else {
+		return Ok(())
+	};
+
+	// Reference counts taken by an interrupted walk are never released. They only leak space, so
+	// the nodes are committed in batches rather than all at once.
+	let mut transaction = Transaction::new();
+	let mut batch_bytes = 0;
+	let mut nodes = 0u64;
+	let db = state.as_trie_backend().essence();
+	let result = for_each_node::<HashingFor<Block>, _>(db, header.state_root(), &mut |_, data| {
+		let node_hash = HashingFor::<Block>::hash(data);
+		transaction.set(columns::STATE, node_hash.as_ref(), data);
+		batch_bytes += data.len();
+		nodes += 1;
+		if batch_bytes >= BATCH_BYTES {
+			backend.storage.db.commit(std::mem::take(&mut transaction))?;
+			batch_bytes = 0;
+		}
+		Ok(())
+	});
+	if let Err(e) = result {
+		backend.storage.state_db.release(&hash);
+		return Err(e)
+	}
+
+	apply_state_commit(&mut transaction, commit);
+	backend.storage.db.commit(transaction)?;
+	info!(target: LOG_TARGET, "Keeping state of #{number} ({hash:?}), {nodes} trie nodes");
+	Ok(())
+}
+
+/// Allow pruning of a state previously passed to [`keep_state`].
+pub(crate) fn release_state<Block: BlockT>(
+	backend: &Backend<Block>,
+	hash: Block::Hash,
+) -> ClientResult<()> {
+	if !backend.storage.state_db.kept_blocks().iter().any(|(kept, _)| *kept == hash) {
+		return Ok(())
+	}
+
+	let header = backend.blockchain.expect_header(hash)?;
+	let state = backend.state_at(hash, TrieCacheContext::Untrusted)?;
+	// Released in a single transaction, as a partial release could drop nodes of a state that is
+	// still recorded as kept.
+	let mut transaction = Transaction::new();
+	let db = state.as_trie_backend().essence();
+	for_each_node::<HashingFor<Block>, _>(db, header.state_root(), &mut |_, data| {
+		let node_hash = HashingFor::<Block>::hash(data);
+		transaction.remove(columns::STATE, node_hash.as_ref());
+		Ok(())
+	})?;
+
+	if let Some(commit) = backend.storage.state_db.release(&hash) {
+		apply_state_commit(&mut transaction, commit);
+		backend.storage.db.commit(transaction)?;
+		info!(target: LOG_TARGET, "Released state of {hash:?}");
+	}
+	Ok(())
+}
+
+/// Returns the blocks whose state is kept regardless of the pruning mode.
+pub(crate) fn kept_states<Block: BlockT>(
+	backend: &Backend<Block>,
+) -> Vec<(Block::Hash, NumberFor<Block>)> {
+	backend
+		.storage
+		.state_db
+		.kept_blocks()
+		.into_iter()
+		.map(|(hash, number)| (hash, number.saturated_into()))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		tests::{insert_header, Block},
+		BlocksPruning, DatabaseSettings, DatabaseSource, PruningMode,
+	};
+	use sp_state_machine::Backend as _;
+
+	fn paritydb_backend(path: &std::path::Path) -> Backend<Block> {
+		let settings = DatabaseSettings {
+			trie_cache_maximum_size: None,
+			state_pruning: Some(PruningMode::blocks_pruning(1)),
+			source: DatabaseSource::ParityDb { path: path.into() },
+			blocks_pruning: BlocksPruning::KeepFinalized,
+			metrics_registry: None,
+			block_timestamp: None,
+		};
+		Backend::new(settings, 0).unwrap()
+	}
+
+	#[test]
+	fn kept_state_outlives_pruning() {
+		let dir = tempfile::tempdir().unwrap();
+		let backend = paritydb_backend(dir.path());
+		let block0 = insert_header(&backend, 0, Default::default(), None, Default::default());
+		let block1 = insert_header(&backend, 1, block0, None, Default::default());
+		backend.keep_state(block1).unwrap();
+		// Keeping twice takes no extra references.
+		backend.keep_state(block1).unwrap();
+
+		let mut parent = block1;
+		for number in 2..6 {
+			parent = insert_header(&backend, number, parent, None, Default::default());
+		}
+		assert!(backend.state_at(block0, TrieCacheContext::Untrusted).is_err());
+		assert!(backend.keep_state(block0).is_err());
+		assert_eq!(backend.kept_states(), vec![(block1, 1)]);
+		let state = backend.state_at(block1, TrieCacheContext::Untrusted).unwrap();
+		assert_eq!(state.storage(block0.as_ref()).unwrap(), Some(block0.as_ref().to_vec()));
+		drop(state);
+
+		// The kept state survives a restart.
+		drop(backend);
+		let backend = paritydb_backend(dir.path());
+		assert_eq!(backend.kept_states(), vec![(block1, 1)]);
+		assert!(backend.state_at(block1, TrieCacheContext::Untrusted).is_ok());
+
+		backend.release_state(block1).unwrap();
+		assert!(backend.kept_states().is_empty());
+		assert!(backend.state_at(block1, TrieCacheContext::Untrusted).is_err());
+		let state = backend.state_at(parent, TrieCacheContext::Untrusted).unwrap();
+		assert_eq!(state.storage(block1.as_ref()).unwrap(), Some(block1.as_ref().to_vec()));
+	}
+
+	#[test]
+	fn keeping_state_requires_ref_counting() {
+		let backend = Backend::<Block>::new_test(1, 0);
+		let block0 = insert_header(&backend, 0, Default::default(), None, Default::default());
+		assert!(backend.keep_state(block0).is_err());
+		assert!(backend.kept_states().is_empty());
+	}
+}
@@ -33,9 +33,13 @@ pub mod offchain;
 pub mod bench;
 
 mod children;
+mod kept_state;
+#[cfg(any(feature = "rocksdb", test))]
+mod migrate;
 mod parity_db;
 mod pinned_blocks_cache;
 mod record_stats_state;
+mod snapshot;
 mod stats;
 #[cfg(any(feature = "rocksdb", test))]
 mod upgrade;
@@ -96,10 +100,11 @@ use sp_trie::{cache::SharedTrieCache, prefixed_key, MemoryDB, MerkleValue, Prefi
 use utils::BLOCK_GAP_CURRENT_VERSION;
 
 // Re-export the Database trait so that one can pass an implementation of it.
-pub use sc_state_db::PruningMode;
+pub use sc_state_db::{Constraints, PruningMode};
 pub use sp_database::Database;
 
 pub use bench::BenchmarkingState;
+pub use snapshot::{export_snapshot, SnapshotInfo};
 
 const CACHE_HEADERS: usize = 8;
 
@@ -316,6 +321,26 @@ pub struct DatabaseSettings {
 
 	/// Prometheus metrics registry.
 	pub metrics_registry: Option<Registry>,
+
+	/// Reads the timestamp of blocks, used to prune the state by age.
+	///
+	/// Must be set to open the database with a state pruning mode limited by age.
+	pub block_timestamp: Option<BlockTimestamp>,
+}
+
+/// Reads the timestamp of a block, in milliseconds, from the storage changes made by the block.
+pub type BlockTimestamp = Arc<dyn Fn(&StorageCollection) -> Option<u64> + Send + Sync>;
+
+/// A [`BlockTimestamp`] reading the SCALE encoded `u64` stored at `key`, such as the `Now` storage
+/// item of the FRAME timestamp pallet.
+pub fn storage_timestamp(key: Vec<u8>) -> BlockTimestamp {
+	Arc::new(move |storage_updates| {
+		storage_updates
+			.iter()
+			.find(|(k, _)| *k == key)
+			.and_then(|(_, value)| value.as_ref())
+			.and_then(|value| u64::decode(&mut value.as_slice()).ok())
+	})
 }
 
 /// Block pruning settings.
@@ -414,6 +439,26 @@ impl DatabaseSource {
 	}
 }
 
+/// Copy the database at `from` into a new database at `to`, converting between backends.
+///
+/// Only migrating from RocksDB to ParityDb is supported, as ParityDb does not keep the original
+/// keys of most columns. The source database is never written to, so it must already be at the
+/// current database version. An interrupted migration continues where it stopped when this is
+/// called again with the same arguments.
+pub fn migrate_database<Block: BlockT>(
+	from: &DatabaseSource,
+	to: &DatabaseSource,
+) -> ClientResult<()> {
+	match (from, to) {
+		#[cfg(feature = "rocksdb")]
+		(DatabaseSource::RocksDb { path: from, .. }, DatabaseSource::ParityDb { path: to }) =>
+			migrate::rocksdb_to_paritydb::<Block>(from, to),
+		_ => Err(ClientError::Backend(format!(
+			"Migrating a database from {from} to {to} is not supported",
+		))),
+	}
+}
+
 impl std::fmt::Display for DatabaseSource {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		let name = match self {
@@ -1119,6 +1164,7 @@ pub struct Backend<Block: BlockT> {
 	state_usage: Arc<StateUsageStats>,
 	genesis_state: RwLock<Option<Arc<DbGenesisStorage<Block>>>>,
 	shared_trie_cache: Option<sp_trie::cache::SharedTrieCache<HashingFor<Block>>>,
+	block_timestamp: Option<BlockTimestamp>,
 }
 
 impl<Block: BlockT> Backend<Block> {
@@ -1176,6 +1222,7 @@ impl<Block: BlockT> Backend<Block> {
 			source: DatabaseSource::Custom { db, require_create_flag: true },
 			blocks_pruning,
 			metrics_registry: None,
+			block_timestamp: None,
 		};
 
 		Self::new(db_setting, canonicalization_delay).expect("failed to create test-db")
@@ -1231,6 +1278,14 @@ impl<Block: BlockT> Backend<Block> {
 		apply_state_commit(&mut db_init_transaction, state_db_init_commit_set);
 
 		let state_pruning_used = state_db.pruning_mode();
+		// Checked against the mode in use, which may have been stored by an earlier run.
+		if let PruningMode::Constrained(Constraints { max_age: Some(_), .. }) = state_pruning_used {
+			if config.block_timestamp.is_none() {
+				return Err(ClientError::Backend(
+					"Pruning the state by age requires reading block timestamps".into(),
+				))
+			}
+		}
 		let is_archive_pruning = state_pruning_used.is_archive();
 		let blockchain = BlockchainDb::new(db.clone())?;
 
@@ -1267,6 +1322,7 @@ impl<Block: BlockT> Backend<Block> {
 			blocks_pruning: config.blocks_pruning,
 			genesis_state: RwLock::new(None),
 			shared_trie_cache,
+			block_timestamp: config.block_timestamp.clone(),
 		};
 
 		// Older DB versions have no last state key. Check if the state is available and set it.
@@ -1615,10 +1671,20 @@ impl<Block: BlockT> Backend<Block> {
 				}
 				self.state_usage.tally_writes(ops, bytes);
 				let number_u64 = number.saturated_into::<u64>();
+				let timestamp = self
+					.block_timestamp
+					.as_ref()
+					.and_then(|block_timestamp| block_timestamp(&operation.storage_updates));
 				let commit = self
 					.storage
 					.state_db
-					.insert_block(&hash, number_u64, pending_block.header.parent_hash(), changeset)
+					.insert_block(
+						&hash,
+						number_u64,
+						pending_block.header.parent_hash(),
+						changeset,
+						timestamp,
+					)
 					.map_err(|e: sc_state_db::Error<sp_database::error::DatabaseError>| {
 						sp_blockchain::Error::from_state_db(e)
 					})?;
@@ -2592,6 +2658,26 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		)
 	}
 
+	fn keep_state(&self, hash: Block::Hash) -> ClientResult<()> {
+		kept_state::keep_state(self, hash)
+	}
+
+	fn release_state(&self, hash: Block::Hash) -> ClientResult<()> {
+		kept_state::release_state(self, hash)
+	}
+
+	fn kept_states(&self) -> Vec<(Block::Hash, NumberFor<Block>)> {
+		kept_state::kept_states(self)
+	}
+
+	fn export_snapshot(
+		&self,
+		hash: Block::Hash,
+		output: &mut dyn std::io::Write,
+	) -> ClientResult<u64> {
+		snapshot::export_snapshot(self, hash, output).map(|snapshot| snapshot.nodes)
+	}
+
 	fn pin_block(&self, hash: <Block as BlockT>::Hash) -> sp_blockchain::Result<()> {
 		let hint = || {
 			let header_metadata = self.blockchain.header_metadata(hash);
@@ -2814,6 +2900,7 @@ pub(crate) mod tests {
 				source: DatabaseSource::Custom { db: backing, require_create_flag: false },
 				blocks_pruning: BlocksPruning::KeepFinalized,
 				metrics_registry: None,
+				block_timestamp: None,
 			},
 			0,
 		)
@@ -2824,6 +2911,29 @@ pub(crate) mod tests {
 		}
 	}
 
+	#[test]
+	fn pruning_by_age_requires_block_timestamps() {
+		let settings = |block_timestamp| DatabaseSettings {
+			trie_cache_maximum_size: None,
+			state_pruning: Some(PruningMode::Constrained(Constraints {
+				max_blocks: None,
+				max_age: Some(1000),
+				max_size: None,
+			})),
+			source: DatabaseSource::Custom {
+				db: sp_database::as_database(kvdb_memorydb::create(crate::utils::NUM_COLUMNS)),
+				require_create_flag: false,
+			},
+			blocks_pruning: BlocksPruning::KeepFinalized,
+			metrics_registry: None,
+			block_timestamp,
+		};
+
+		assert!(Backend::<Block>::new(settings(None), 0).is_err());
+		let block_timestamp = storage_timestamp(b"now".to_vec());
+		assert!(Backend::<Block>::new(settings(Some(block_timestamp)), 0).is_ok());
+	}
+
 	#[test]
 	fn set_state_data() {
 		set_state_data_inner(StateVersion::V0);
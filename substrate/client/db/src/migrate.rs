@@ -0,0 +1,295 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Conversion of a RocksDB database into a ParityDb one.
+//!
+//! Columns are copied key by key. Trie nodes drop the key prefix RocksDB stores them under and
+//! become reference counted, the reference counters RocksDB keeps for indexed transactions turn
+//! into ParityDb references, and the state database journals are rewritten to match. Progress is
+//! committed together with every batch, so an interrupted migration resumes where it stopped.
+
+use crate::{
+	columns,
+	utils::{meta_keys, DatabaseType, NUM_COLUMNS},
+	DbHash, DB_HASH_LEN,
+};
+use codec::{Decode, Encode};
+use log::info;
+use sp_blockchain::{Error as ClientError, Result as ClientResult};
+use sp_database::{Database, Transaction};
+use sp_runtime::traits::Block as BlockT;
+use std::{io, path::Path};
+
+const LOG_TARGET: &str = "db::migrate";
+
+/// Amount of data to copy before committing a batch to the target database.
+const BATCH_BYTES: usize = 64 * 1024 * 1024;
+
+/// Position of a migration, stored in the meta column of the target database until it is done.
+#[derive(Encode, Decode)]
+struct Progress {
+	/// Column being copied.
+	column: u32,
+	/// Last key of `column` that was copied.
+	last_key: Option<Vec<u8>>,
+}
+
+/// Reads the state database meta entries from the source database.
+struct SourceMetaDb<'a>(&'a kvdb_rocksdb::Database);
+
+impl sc_state_db::MetaDb for SourceMetaDb<'_> {
+	type Error = io::Error;
+
+	fn get_meta(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+		self.0.get(columns::STATE_META, key)
+	}
+}
+
+/// Copy the RocksDB database at `from` into a new ParityDb database at `to`.
+///
+/// If `to` holds the result of an interrupted migration, copying continues from where it
+/// stopped. The source database is only read and must already be at the current database
+/// version, as upgrading it would write to it.
+pub(crate) fn rocksdb_to_paritydb<Block: BlockT>(from: &Path, to: &Path) -> ClientResult<()> {
+	// Upgrading would write to the source, so only databases of the current version are accepted.
+	match crate::upgrade::current_version(from) {
+		Ok(crate::upgrade::CURRENT_VERSION) => (),
+		Ok(version) =>
+			return Err(ClientError::Backend(format!(
+				"The database at {} has version {version}, expected {}: open the database with \
+				 the node once to upgrade it",
+				from.display(),
+				crate::upgrade::CURRENT_VERSION,
+			))),
+		Err(crate::upgrade::UpgradeError::MissingDatabaseVersionFile) =>
+			return Err(ClientError::Backend(format!(
+				"No RocksDB database found at {}",
+				from.display()
+			))),
+		Err(e) => return Err(ClientError::Backend(e.to_string())),
+	}
+
+	let mut config = kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS);
+	config.create_if_missing = false;
+	let source = kvdb_rocksdb::Database::open(&config, from).map_err(io_error)?;
+	let target = crate::parity_db::open::<DbHash>(to, DatabaseType::Full, true, false)
+		.map_err(|e| ClientError::Backend(e.to_string()))?;
+
+	let progress = match target.get(columns::META, meta_keys::MIGRATION) {
+		Some(progress) => {
+			let progress = Progress::decode(&mut progress.as_slice())
+				.map_err(|e| ClientError::Backend(format!("Invalid migration progress: {e}")))?;
+			info!(target: LOG_TARGET, "Resuming migration at column {}", progress.column);
+			progress
+		},
+		None if target.get(columns::META, meta_keys::TYPE).is_some() =>
+			return Err(ClientError::Backend(format!(
+				"A database already exists at {}",
+				to.display()
+			))),
+		None => Progress { column: 0, last_key: None },
+	};
+
+	info!(target: LOG_TARGET, "Migrating {} to {}", from.display(), to.display());
+	for column in progress.column..NUM_COLUMNS {
+		let resume_from = (column == progress.column).then(|| progress.last_key.clone()).flatten();
+		copy_column(&source, &*target, column, resume_from)?;
+	}
+
+	// The journals reference trie nodes by their database key, which has changed above.
+	let journals = sc_state_db::migrate_journals::<Block::Hash, Vec<u8>, _>(
+		&SourceMetaDb(&source),
+		|mut key: Vec<u8>| {
+			key.drain(..key.len().saturating_sub(DB_HASH_LEN));
+			key
+		},
+	)
+	.map_err(ClientError::from_state_db)?;
+
+	let mut transaction = Transaction::new();
+	for (key, value) in journals {
+		transaction.set_from_vec(columns::STATE_META, &key, value);
+	}
+	transaction.remove(columns::META, meta_keys::MIGRATION);
+	target.commit(transaction)?;
+
+	info!(target: LOG_TARGET, "Migration to {} complete", to.display());
+	Ok(())
+}
+
+/// Copy `column` from `source` to `target`, skipping keys up to and including `resume_from`.
+fn copy_column(
+	source: &kvdb_rocksdb::Database,
+	target: &dyn Database<DbHash>,
+	column: u32,
+	resume_from: Option<Vec<u8>>,
+) -> ClientResult<()> {
+	let mut transaction = Transaction::new();
+	let mut batch_bytes = 0;
+	let mut keys = 0u64;
+	let mut bytes = 0u64;
+
+	for item in source.iter(column) {
+		let (key, value) = item.map_err(io_error)?;
+		if resume_from.as_ref().is_some_and(|resume_from| key[..] <= resume_from[..]) {
+			continue
+		}
+
+		let size = key.len() + value.len();
+		match column {
+			columns::STATE => {
+				let hash = &key[key.len().saturating_sub(DB_HASH_LEN)..];
+				transaction.set_from_vec(column, hash, value);
+			},
+			columns::TRANSACTION => {
+				// RocksDB keeps the reference counter under the key followed by a zero byte.
+				if key.len() != DB_HASH_LEN {
+					continue
+				}
+				let mut counter_key = key.to_vec();
+				counter_key.push(0);
+				let references = match source.get(column, &counter_key).map_err(io_error)? {
+					Some(counter) =>
+						counter.as_slice().try_into().map(u32::from_le_bytes).map_err(|_| {
+							ClientError::Backend("Invalid transaction counter".into())
+						})?,
+					None => 1,
+				};
+				for _ in 0..references {
+					transaction.set(column, &key, &value);
+				}
+			},
+			_ => transaction.set_from_vec(column, &key, value),
+		}
+
+		keys += 1;
+		bytes += size as u64;
+		batch_bytes += size;
+		if batch_bytes >= BATCH_BYTES {
+			let progress = Progress { column, last_key: Some(key.to_vec()) };
+			transaction.set_from_vec(columns::META, meta_keys::MIGRATION, progress.encode());
+			target.commit(std::mem::take(&mut transaction))?;
+			batch_bytes = 0;
+			info!(
+				target: LOG_TARGET,
+				"Column {column}: copied {keys} keys ({} MiB)",
+				bytes / 1024 / 1024,
+			);
+		}
+	}
+
+	let progress = Progress { column: column + 1, last_key: None };
+	transaction.set_from_vec(columns::META, meta_keys::MIGRATION, progress.encode());
+	target.commit(transaction)?;
+	info!(
+		target: LOG_TARGET,
+		"Column {column}: done, copied {keys} keys ({} MiB)",
+		bytes / 1024 / 1024,
+	);
+	Ok(())
+}
+
+fn io_error(e: io::Error) -> ClientError {
+	ClientError::Backend(format!("RocksDB error: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		tests::{insert_header, Block},
+		Backend, BlocksPruning, DatabaseSettings, DatabaseSource, PruningMode,
+	};
+	use sc_client_api::{backend::Backend as _, blockchain::HeaderBackend, TrieCacheContext};
+	use sp_state_machine::Backend as _;
+
+	fn settings(source: DatabaseSource) -> DatabaseSettings {
+		DatabaseSettings {
+			trie_cache_maximum_size: None,
+			state_pruning: Some(PruningMode::blocks_pruning(4)),
+			source,
+			blocks_pruning: BlocksPruning::Some(4),
+			metrics_registry: None,
+			block_timestamp: None,
+		}
+	}
+
+	#[test]
+	fn migrate_rocksdb_to_paritydb() {
+		let dir = tempfile::tempdir().unwrap();
+		let rocksdb_path = dir.path().join("rocksdb");
+		let paritydb_path = dir.path().join("paritydb");
+
+		let blocks = {
+			let config = kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS);
+			let db = kvdb_rocksdb::Database::open(&config, &rocksdb_path).unwrap();
+			crate::upgrade::update_version(&rocksdb_path).unwrap();
+			let source = DatabaseSource::Custom {
+				db: sp_database::as_database(db),
+				require_create_flag: false,
+			};
+			// Keep a couple of blocks non-canonical so that both journals have entries.
+			let backend = Backend::<Block>::new(settings(source), 2).unwrap();
+			let mut blocks =
+				vec![insert_header(&backend, 0, Default::default(), None, Default::default())];
+			for number in 1..8 {
+				let parent = *blocks.last().unwrap();
+				blocks.push(insert_header(&backend, number, parent, None, Default::default()));
+			}
+			blocks
+		};
+
+		rocksdb_to_paritydb::<Block>(&rocksdb_path, &paritydb_path).unwrap();
+		assert!(rocksdb_to_paritydb::<Block>(&rocksdb_path, &paritydb_path).is_err());
+
+		let source = DatabaseSource::ParityDb { path: paritydb_path };
+		let backend = Backend::<Block>::new(settings(source), 2).unwrap();
+		assert_eq!(backend.blockchain().info().best_hash, blocks[7]);
+		for number in 5..8 {
+			let state = backend.state_at(blocks[number], TrieCacheContext::Untrusted).unwrap();
+			let parent = blocks[number - 1];
+			assert_eq!(state.storage(parent.as_ref()).unwrap(), Some(parent.as_ref().to_vec()));
+		}
+
+		// Import and prune on top of the migrated journals.
+		let mut parent = blocks[7];
+		for number in 8..12 {
+			parent = insert_header(&backend, number, parent, None, Default::default());
+		}
+		let state = backend.state_at(parent, TrieCacheContext::Untrusted).unwrap();
+		assert_eq!(state.storage(blocks[7].as_ref()).unwrap(), Some(blocks[7].as_ref().to_vec()));
+		assert!(backend.state_at(blocks[3], TrieCacheContext::Untrusted).is_err());
+	}
+
+	#[test]
+	fn outdated_source_is_not_upgraded() {
+		let dir = tempfile::tempdir().unwrap();
+		let rocksdb_path = dir.path().join("rocksdb");
+		let paritydb_path = dir.path().join("paritydb");
+
+		let config = kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS);
+		drop(kvdb_rocksdb::Database::open(&config, &rocksdb_path).unwrap());
+		let version_file = rocksdb_path.join("db_version");
+		std::fs::write(&version_file, "3").unwrap();
+
+		let err = rocksdb_to_paritydb::<Block>(&rocksdb_path, &paritydb_path).unwrap_err();
+		assert!(err.to_string().contains("open the database with the node once to upgrade it"));
+		assert_eq!(std::fs::read_to_string(&version_file).unwrap(), "3");
+		assert!(!paritydb_path.exists());
+	}
+}
@@ -0,0 +1,509 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Portable snapshots of finalized state.
+//!
+//! A snapshot contains the header, body and justifications of a single finalized block together
+//! with every trie node of its state, including the nodes of default child tries and values that
+//! are stored outside of their leaf. Nodes are written along with the prefix they are addressed
+//! by, so a snapshot taken from a RocksDB database can be imported into ParityDb and vice versa.
+//!
+//! Importing a snapshot into a fresh database makes the snapshot block the finalized and best
+//! block, the same way warp sync does: the database must have been created without the genesis
+//! state, and the state of the snapshot block is committed through the state database, which
+//! keeps track of it for pruning. The history below it is recorded as a block gap that is filled
+//! in by the regular sync afterwards.
+//!
+//! Snapshots can be exported from any [`sc_client_api::Backend`] with [`export_snapshot`], e.g.
+//! from a running node. [`Backend`] also exports them through
+//! [`sc_client_api::Backend::export_snapshot`].
+
+use crate::{columns, Backend, DbStateBuilder};
+use codec::{Decode, Encode, IoReader};
+use hash_db::{HashDBRef, Hasher, Prefix};
+use log::info;
+use sc_client_api::{
+	backend::{Backend as _, BlockImportOperation as _, NewBlockState},
+	TrieCacheContext,
+};
+use sp_blockchain::{Backend as _, Error as ClientError, HeaderBackend, Result as ClientResult};
+use sp_core::storage::{well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX, ChildInfo};
+use sp_database::Transaction;
+use sp_runtime::{
+	traits::{Block as BlockT, HashingFor, Header as HeaderT, NumberFor, Zero},
+	Justifications,
+};
+use sp_state_machine::{backend::AsTrieBackend, DBValue};
+use sp_trie::{prefixed_key, trie_types::TrieDBBuilder, KeySpacedDB};
+use std::{
+	io::{Read, Write},
+	mem,
+};
+use trie_db::{
+	node::{Node, Value},
+	TrieDBIterator, TrieDBNodeIterator,
+};
+
+const LOG_TARGET: &str = "db::snapshot";
+
+/// Magic bytes every snapshot starts with.
+const SNAPSHOT_MAGIC: [u8; 8] = *b"SUBSNAPS";
+
+/// Current version of the snapshot format.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Size of the trie nodes after which they are committed to the database during an import.
+const IMPORT_BATCH_BYTES: usize = 64 * 1024 * 1024;
+
+/// Leading part of a snapshot, describing the block the state belongs to.
+#[derive(Encode, Decode)]
+struct SnapshotHeader<Block: BlockT> {
+	magic: [u8; 8],
+	version: u32,
+	genesis_hash: Block::Hash,
+	header: Block::Header,
+	body: Option<Vec<Block::Extrinsic>>,
+	justifications: Option<Justifications>,
+}
+
+/// Records following the [`SnapshotHeader`].
+#[derive(Encode, Decode)]
+enum SnapshotRecord {
+	/// An encoded trie node or value, with the prefix it is stored under.
+	Node { prefix: Vec<u8>, padding: Option<u8>, data: Vec<u8> },
+	/// End of the snapshot, with the number of nodes written before it.
+	End { nodes: u64 },
+}
+
+/// Summary of an exported or imported snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotInfo<Block: BlockT> {
+	/// Hash of the snapshot block.
+	pub hash: Block::Hash,
+	/// Number of the snapshot block.
+	pub number: NumberFor<Block>,
+	/// Number of trie nodes in the snapshot.
+	pub nodes: u64,
+}
+
+/// Write a snapshot of the finalized block `hash` and its state to `output`.
+///
+/// The state is pinned for the duration of the export, so this is safe to call on a backend
+/// that is concurrently importing and pruning blocks, e.g. the backend of a running node.
+pub fn export_snapshot<Block, B>(
+	backend: &B,
+	hash: Block::Hash,
+	output: &mut dyn Write,
+) -> ClientResult<SnapshotInfo<Block>>
+where
+	Block: BlockT,
+	B: sc_client_api::Backend<Block>,
+{
+	let blockchain = backend.blockchain();
+	let info = blockchain.info();
+	let header = blockchain.expect_header(hash)?;
+	let number = *header.number();
+	if number > info.finalized_number || blockchain.hash(number)? != Some(hash) {
+		return Err(ClientError::Backend(format!(
+			"Can not export snapshot of {hash:?}: block is not finalized",
+		)))
+	}
+
+	let state = backend.state_at(hash, TrieCacheContext::Untrusted)?;
+	let root = *header.state_root();
+	let snapshot = SnapshotHeader::<Block> {
+		magic: SNAPSHOT_MAGIC,
+		version: SNAPSHOT_VERSION,
+		genesis_hash: info.genesis_hash,
+		header,
+		body: blockchain.body(hash)?,
+		justifications: blockchain.justifications(hash)?,
+	};
+	output.write_all(&snapshot.encode()).map_err(io_error)?;
+
+	let mut nodes = 0u64;
+	let db = state.as_trie_backend().essence();
+	for_each_node::<HashingFor<Block>, _>(db, &root, &mut |prefix, data| {
+		let record = SnapshotRecord::Node {
+			prefix: prefix.0.to_vec(),
+			padding: prefix.1,
+			data: data.to_vec(),
+		};
+		output.write_all(&record.encode()).map_err(io_error)?;
+		nodes += 1;
+		Ok(())
+	})?;
+	output.write_all(&SnapshotRecord::End { nodes }.encode()).map_err(io_error)?;
+	output.flush().map_err(io_error)?;
+
+	info!(target: LOG_TARGET, "Exported snapshot of #{number} ({hash:?}) with {nodes} trie nodes");
+	Ok(SnapshotInfo { hash, number, nodes })
+}
+
+impl<Block: BlockT> Backend<Block> {
+	/// Import a snapshot written by [`export_snapshot`] from `input`.
+	///
+	/// The database must not contain anything but the genesis block of the same chain, imported
+	/// without its state as for warp sync. Trie nodes are committed to the database in batches
+	/// while they are read. Once the state has been checked for completeness, the snapshot block
+	/// is imported as finalized and best block. If the import fails, the snapshot block is not
+	/// imported and the import can be retried, but the trie nodes committed so far are left in
+	/// the database.
+	pub fn import_snapshot(&self, input: &mut impl Read) -> ClientResult<SnapshotInfo<Block>> {
+		let mut input = IoReader(input);
+		let snapshot = SnapshotHeader::<Block>::decode(&mut input).map_err(decode_error)?;
+		if snapshot.magic != SNAPSHOT_MAGIC {
+			return Err(ClientError::Backend("Input is not a state snapshot".into()))
+		}
+		if snapshot.version != SNAPSHOT_VERSION {
+			return Err(ClientError::Backend(format!(
+				"Unsupported snapshot version {}, expected {SNAPSHOT_VERSION}",
+				snapshot.version,
+			)))
+		}
+
+		let info = self.blockchain.info();
+		if snapshot.genesis_hash != info.genesis_hash {
+			return Err(ClientError::Backend(format!(
+				"Snapshot belongs to a different chain: genesis {:?}, expected {:?}",
+				snapshot.genesis_hash, info.genesis_hash,
+			)))
+		}
+		if !info.best_number.is_zero() || !info.finalized_number.is_zero() {
+			return Err(ClientError::Backend(
+				"Snapshots can only be imported into a fresh database".into(),
+			))
+		}
+		if info.finalized_state.is_some() {
+			return Err(ClientError::Backend(
+				"Snapshots can only be imported into a database created without the genesis state"
+					.into(),
+			))
+		}
+
+		let hash = snapshot.header.hash();
+		let number = *snapshot.header.number();
+		let root = *snapshot.header.state_root();
+
+		let mut transaction = Transaction::new();
+		let mut batch_bytes = 0;
+		let mut nodes = 0u64;
+		loop {
+			match SnapshotRecord::decode(&mut input).map_err(decode_error)? {
+				SnapshotRecord::Node { prefix, padding, data } => {
+					let node_hash = <HashingFor<Block> as Hasher>::hash(&data);
+					// Same key as for nodes committed through the state database.
+					let mut key = prefixed_key::<HashingFor<Block>>(&node_hash, (&prefix, padding));
+					self.storage.db.sanitize_key(&mut key);
+					batch_bytes += key.len() + data.len();
+					transaction.set_from_vec(columns::STATE, &key, data);
+					nodes += 1;
+					if batch_bytes >= IMPORT_BATCH_BYTES {
+						self.storage.db.commit(mem::take(&mut transaction))?;
+						batch_bytes = 0;
+					}
+				},
+				SnapshotRecord::End { nodes: expected } if expected == nodes => break,
+				SnapshotRecord::End { nodes: expected } =>
+					return Err(ClientError::Backend(format!(
+						"Snapshot is incomplete: expected {expected} trie nodes, found {nodes}",
+					))),
+			}
+		}
+		self.storage.db.commit(transaction)?;
+
+		// Nodes are keyed by their own hash, so walking the trie proves the state is complete and
+		// matches the header before the block is imported.
+		let state = DbStateBuilder::<HashingFor<Block>>::new(self.storage.clone(), root).build();
+		for_each_node::<HashingFor<Block>, _>(state.essence(), &root, &mut |_, _| Ok(()))?;
+
+		// The nodes are already in the database, so the block is committed with an empty change
+		// set: the state database only records the block as the first canonical one.
+		let mut op = self.begin_operation()?;
+		self.begin_state_operation(&mut op, Default::default())?;
+		op.set_block_data(
+			snapshot.header,
+			snapshot.body,
+			None,
+			snapshot.justifications,
+			NewBlockState::Final,
+		)?;
+		self.commit_operation(op)?;
+
+		info!(target: LOG_TARGET, "Imported snapshot of #{number} ({hash:?}) with {nodes} trie nodes");
+		Ok(SnapshotInfo { hash, number, nodes })
+	}
+}
+
+/// Call `f` with every node of the state rooted at `root`, including all default child tries.
+pub(crate) fn for_each_node<H, F>(
+	db: &dyn HashDBRef<H, DBValue>,
+	root: &H::Out,
+	f: &mut F,
+) -> ClientResult<()>
+where
+	H: Hasher,
+	F: FnMut(Prefix, &[u8]) -> ClientResult<()>,
+{
+	for_each_trie_node(db, root, &[], f)?;
+	for (child_info, child_root) in child_roots(db, root)? {
+		let keyspace = child_info.keyspace();
+		let child_db = KeySpacedDB::new(db, keyspace);
+		for_each_trie_node::<H, _>(&child_db, &child_root, keyspace, f)?;
+	}
+	Ok(())
+}
+
+/// Call `f` with every node of a single trie, prepending `keyspace` to the node prefixes.
+fn for_each_trie_node<H, F>(
+	db: &dyn HashDBRef<H, DBValue>,
+	root: &H::Out,
+	keyspace: &[u8],
+	f: &mut F,
+) -> ClientResult<()>
+where
+	H: Hasher,
+	F: FnMut(Prefix, &[u8]) -> ClientResult<()>,
+{
+	let trie = TrieDBBuilder::<H>::new(db, root).build();
+	for item in TrieDBNodeIterator::new(&trie).map_err(trie_error)? {
+		let (prefix, node_hash, node) = item.map_err(trie_error)?;
+		// Inline nodes are part of the encoding of their parent.
+		if node_hash.is_none() {
+			continue
+		}
+		emit(f, keyspace, prefix.as_prefix(), node.data())?;
+
+		let (partial, value) = match node.node() {
+			Node::Leaf(partial, value) => (partial, Some(value)),
+			Node::NibbledBranch(partial, _, value) => (partial, value),
+			_ => continue,
+		};
+		if let Some(Value::Node(value_hash)) = value {
+			// Values are stored under the full key of the node holding them.
+			let mut key = prefix.clone();
+			key.append_partial(partial.right());
+			let mut hash = H::Out::default();
+			hash.as_mut().copy_from_slice(value_hash);
+			let data = db
+				.get(&hash, key.as_prefix())
+				.ok_or_else(|| ClientError::Backend(format!("Missing trie value {hash:?}")))?;
+			emit(f, keyspace, key.as_prefix(), &data)?;
+		}
+	}
+	Ok(())
+}
+
+/// Call `f` with a node, prepending `keyspace` to its prefix.
+fn emit<F>(f: &mut F, keyspace: &[u8], prefix: Prefix, data: &[u8]) -> ClientResult<()>
+where
+	F: FnMut(Prefix, &[u8]) -> ClientResult<()>,
+{
+	let mut key = Vec::with_capacity(keyspace.len() + prefix.0.len());
+	key.extend_from_slice(keyspace);
+	key.extend_from_slice(prefix.0);
+	f((&key, prefix.1), data)
+}
+
+/// Collect the roots of all default child tries referenced from the top trie.
+fn child_roots<H: Hasher>(
+	db: &dyn HashDBRef<H, DBValue>,
+	root: &H::Out,
+) -> ClientResult<Vec<(ChildInfo, H::Out)>> {
+	let trie = TrieDBBuilder::<H>::new(db, root).build();
+	let mut roots = Vec::new();
+	for item in
+		TrieDBIterator::new_prefixed(&trie, DEFAULT_CHILD_STORAGE_KEY_PREFIX).map_err(trie_error)?
+	{
+		let (key, value) = item.map_err(trie_error)?;
+		let mut child_root = H::Out::default();
+		if value.len() != child_root.as_ref().len() {
+			return Err(ClientError::InvalidState)
+		}
+		child_root.as_mut().copy_from_slice(&value);
+		let child_info = ChildInfo::new_default(&key[DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..]);
+		roots.push((child_info, child_root));
+	}
+	Ok(roots)
+}
+
+fn trie_error(e: impl std::fmt::Display) -> ClientError {
+	ClientError::Backend(format!("Trie error: {e}"))
+}
+
+fn decode_error(e: codec::Error) -> ClientError {
+	ClientError::Backend(format!("Invalid snapshot: {e}"))
+}
+
+fn io_error(e: std::io::Error) -> ClientError {
+	ClientError::Backend(format!("Snapshot I/O error: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::{insert_disconnected_header, insert_header, Block};
+	use sc_client_api::{
+		backend::{Backend as _, BlockImportOperation as _},
+		blockchain::{BlockGap, BlockGapType},
+	};
+	use sp_core::{storage::StorageChild, H256};
+	use sp_runtime::{testing::Header, StateVersion, Storage};
+	use sp_state_machine::Backend as _;
+
+	const ENGINE_ID: sp_runtime::ConsensusEngineId = *b"TEST";
+
+	fn child_info() -> ChildInfo {
+		ChildInfo::new_default(b"child")
+	}
+
+	/// Build a chain of four blocks with the last one finalized and carrying a state with a child
+	/// trie and a value large enough to be stored outside of its leaf.
+	fn source_backend() -> (Backend<Block>, H256) {
+		let backend = Backend::<Block>::new_test(10, 0);
+		let block0 = insert_header(&backend, 0, Default::default(), None, Default::default());
+		let block1 = insert_header(&backend, 1, block0, None, Default::default());
+		let block2 = insert_header(&backend, 2, block1, None, Default::default());
+		backend.finalize_block(block1, None).unwrap();
+		backend.finalize_block(block2, None).unwrap();
+
+		let storage = Storage {
+			top: vec![(b"small".to_vec(), vec![1, 2, 3]), (b"large".to_vec(), vec![7; 64])]
+				.into_iter()
+				.collect(),
+			children_default: vec![(
+				child_info().storage_key().to_vec(),
+				StorageChild {
+					data: vec![(b"key".to_vec(), b"value".to_vec())].into_iter().collect(),
+					child_info: child_info(),
+				},
+			)]
+			.into_iter()
+			.collect(),
+		};
+
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, Default::default()).unwrap();
+		let state_root = op.reset_storage(storage, StateVersion::V1).unwrap();
+		let header = Header {
+			number: 3,
+			parent_hash: block2,
+			state_root,
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		};
+		let hash = header.hash();
+		op.set_block_data(
+			header,
+			Some(vec![]),
+			None,
+			Some(Justifications::from((ENGINE_ID, vec![1, 2, 3]))),
+			NewBlockState::Final,
+		)
+		.unwrap();
+		backend.commit_operation(op).unwrap();
+
+		(backend, hash)
+	}
+
+	/// Create a backend holding the genesis block without its state, as for warp sync.
+	fn target_backend() -> Backend<Block> {
+		let backend = Backend::<Block>::new_test(10, 0);
+		insert_disconnected_header(&backend, 0, Default::default(), Default::default(), true);
+		backend
+	}
+
+	#[test]
+	fn export_and_import_snapshot() {
+		let (source, hash) = source_backend();
+		let mut snapshot = Vec::new();
+		let exported = export_snapshot(&source, hash, &mut snapshot).unwrap();
+		assert_eq!(exported.number, 3);
+
+		let target = target_backend();
+		let imported = target.import_snapshot(&mut &snapshot[..]).unwrap();
+		assert_eq!(imported, exported);
+
+		let info = target.blockchain().info();
+		assert_eq!(info.finalized_hash, hash);
+		assert_eq!(info.best_hash, hash);
+		assert_eq!(
+			info.block_gap,
+			Some(BlockGap { start: 1, end: 2, gap_type: BlockGapType::MissingHeaderAndBody }),
+		);
+		assert_eq!(
+			target.blockchain().justifications(hash).unwrap(),
+			Some(Justifications::from((ENGINE_ID, vec![1, 2, 3]))),
+		);
+
+		let state = target.state_at(hash, TrieCacheContext::Untrusted).unwrap();
+		assert_eq!(state.storage(b"small").unwrap(), Some(vec![1, 2, 3]));
+		assert_eq!(state.storage(b"large").unwrap(), Some(vec![7; 64]));
+		assert_eq!(state.child_storage(&child_info(), b"key").unwrap(), Some(b"value".to_vec()));
+
+		// Blocks are imported and finalized on top of the snapshot block.
+		let child = insert_header(&target, 4, hash, None, Default::default());
+		target.finalize_block(child, None).unwrap();
+		assert_eq!(target.blockchain().info().finalized_hash, child);
+		assert!(target.state_at(child, TrieCacheContext::Untrusted).is_ok());
+	}
+
+	#[test]
+	fn import_snapshot_rejects_invalid_input() {
+		let (source, hash) = source_backend();
+		let mut snapshot = Vec::new();
+		export_snapshot(&source, hash, &mut snapshot).unwrap();
+
+		// Only finalized blocks can be exported.
+		let best = insert_header(&source, 4, hash, None, Default::default());
+		assert!(export_snapshot(&source, best, &mut Vec::<u8>::new()).is_err());
+
+		// The database must be fresh.
+		assert!(source.import_snapshot(&mut &snapshot[..]).is_err());
+
+		// The genesis state must not have been committed.
+		let with_genesis_state = Backend::<Block>::new_test(10, 0);
+		insert_header(&with_genesis_state, 0, Default::default(), None, Default::default());
+		assert!(with_genesis_state.import_snapshot(&mut &snapshot[..]).is_err());
+
+		let target = target_backend();
+
+		// A truncated snapshot is rejected without importing the block.
+		let truncated = &snapshot[..snapshot.len() - 10];
+		assert!(target.import_snapshot(&mut &truncated[..]).is_err());
+		assert_eq!(target.blockchain().info().best_number, 0);
+
+		// Dropping a node fails the completeness check.
+		let mut input = &snapshot[..];
+		let header = SnapshotHeader::<Block>::decode(&mut input).unwrap();
+		let mut records = Vec::new();
+		while let Ok(record) = SnapshotRecord::decode(&mut input) {
+			records.push(record);
+		}
+		let mut damaged = header.encode();
+		records.remove(1);
+		records.pop();
+		let nodes = records.len() as u64;
+		records.into_iter().for_each(|record| damaged.extend(record.encode()));
+		damaged.extend(SnapshotRecord::End { nodes }.encode());
+		assert!(target.import_snapshot(&mut &damaged[..]).is_err());
+		assert_eq!(target.blockchain().info().best_number, 0);
+
+		target.import_snapshot(&mut &snapshot[..]).unwrap();
+	}
+}
@@ -65,6 +65,8 @@ pub mod meta_keys {
 	pub const LEAF_PREFIX: &[u8; 4] = b"leaf";
 	/// Children prefix list key.
 	pub const CHILDREN_PREFIX: &[u8; 8] = b"children";
+	/// Progress of an unfinished database migration.
+	pub const MIGRATION: &[u8; 7] = b"migrate";
 }
 
 /// Database metadata.
@@ -82,6 +82,9 @@ mod rep {
 	pub const DUPLICATE_STATEMENT: Rep = Rep::new(-(1 << 7), "Duplicate statement");
 	/// Reputation change when a peer sends us particularly useful statement
 	pub const EXCELLENT_STATEMENT: Rep = Rep::new(1 << 8, "High priority statement");
+	/// Reputation change when a peer sends us a statement of an account that is flooding the
+	/// store.
+	pub const FLOOD_STATEMENT: Rep = Rep::new(-(1 << 8), "Statement flood");
 }
 
 const LOG_TARGET: &str = "statement-gossip";
@@ -436,6 +439,7 @@ where
 			SubmitResult::Known => self.network.report_peer(who, rep::ANY_STATEMENT_REFUND),
 			SubmitResult::KnownExpired => {},
 			SubmitResult::Ignored => {},
+			SubmitResult::RateLimited => self.network.report_peer(who, rep::FLOOD_STATEMENT),
 			SubmitResult::Bad(_) => self.network.report_peer(who, rep::BAD_STATEMENT),
 			SubmitResult::InternalError(_) => {},
 		}
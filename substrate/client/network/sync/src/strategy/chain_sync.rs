@@ -36,6 +36,7 @@ use crate::{
 	service::network::NetworkServiceHandle,
 	strategy::{
 		disconnected_peers::DisconnectedPeers,
+		state::MAX_PARALLEL_STATE_REQUESTS,
 		state_sync::{ImportResult, StateSync, StateSyncProvider},
 		warp::{WarpSyncPhase, WarpSyncProgress},
 		StrategyKey, SyncingAction, SyncingStrategy,
@@ -48,7 +49,7 @@ use futures::{channel::oneshot, FutureExt};
 use log::{debug, error, info, trace, warn};
 use prometheus_endpoint::{register, Gauge, PrometheusError, Registry, U64};
 use prost::Message;
-use sc_client_api::{blockchain::BlockGap, BlockBackend, ProofProvider};
+use sc_client_api::{blockchain::BlockGap, AuxStore, BlockBackend, ProofProvider};
 use sc_consensus::{BlockImportError, BlockImportStatus, IncomingBlock};
 use sc_network::{IfDisconnected, ProtocolName};
 use sc_network_common::sync::message::{
@@ -277,8 +278,8 @@ pub(crate) enum PeerSyncState<B: BlockT> {
 	DownloadingStale(B::Hash),
 	/// Downloading justification for given block hash.
 	DownloadingJustification(B::Hash),
-	/// Downloading state.
-	DownloadingState,
+	/// Downloading the given chunk of the state.
+	DownloadingState(usize),
 	/// Actively downloading block history after warp sync.
 	DownloadingGap(NumberFor<B>),
 }
@@ -352,6 +353,7 @@ where
 		+ BlockBackend<B>
 		+ HeaderMetadata<B, Error = sp_blockchain::Error>
 		+ ProofProvider<B>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -374,6 +376,11 @@ where
 		}
 
 		if let Some(state) = self.peers.remove(peer_id) {
+			if let (PeerSyncState::DownloadingState(chunk), Some(state_sync)) =
+				(&state.state, &mut self.state_sync)
+			{
+				state_sync.cancel_request(*chunk);
+			}
 			if !state.state.is_available() {
 				if let Some(bad_peer) =
 					self.disconnected_peers.on_disconnect_during_request(*peer_id)
@@ -886,7 +893,7 @@ where
 			.collect::<Vec<_>>();
 		self.actions.extend(justification_requests);
 
-		let state_request = self.state_request().into_iter().map(|(peer_id, request)| {
+		let state_requests = self.state_requests().into_iter().map(|(peer_id, request)| {
 			trace!(
 				target: LOG_TARGET,
 				"Created `StrategyRequest` to {peer_id}.",
@@ -914,7 +921,7 @@ where
 				remove_obsolete: false,
 			}
 		});
-		self.actions.extend(state_request);
+		self.actions.extend(state_requests);
 
 		Ok(std::mem::take(&mut self.actions))
 	}
@@ -927,6 +934,7 @@ where
 		+ BlockBackend<B>
 		+ HeaderMetadata<B, Error = sp_blockchain::Error>
 		+ ProofProvider<B>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -1364,7 +1372,7 @@ where
 					},
 					PeerSyncState::Available |
 					PeerSyncState::DownloadingJustification(..) |
-					PeerSyncState::DownloadingState => Vec::new(),
+					PeerSyncState::DownloadingState(_) => Vec::new(),
 				}
 			} else {
 				// When request.is_none() this is a block announcement. Just accept blocks.
@@ -1655,7 +1663,12 @@ where
 				PeerSyncState::DownloadingNew(_) |
 				PeerSyncState::DownloadingStale(_) |
 				PeerSyncState::DownloadingGap(_) |
-				PeerSyncState::DownloadingState => {
+				PeerSyncState::DownloadingState(_) => {
+					if let (PeerSyncState::DownloadingState(chunk), Some(state_sync)) =
+						(peer_sync.state, &mut self.state_sync)
+					{
+						state_sync.cancel_request(chunk);
+					}
 					// Cancel a request first, as `add_peer` may generate a new request.
 					self.actions
 						.push(SyncingAction::CancelRequest { peer_id, key: Self::STRATEGY_KEY });
@@ -1940,36 +1953,45 @@ where
 		requests
 	}
 
-	/// Get a state request scheduled by sync to be sent out (if any).
-	fn state_request(&mut self) -> Option<(PeerId, StateRequest)> {
+	/// Get the state requests scheduled by sync to be sent out, each for a different chunk of
+	/// the state.
+	fn state_requests(&mut self) -> Vec<(PeerId, StateRequest)> {
+		let mut requests = Vec::new();
 		if self.allowed_requests.is_empty() {
-			return None;
+			return requests;
 		}
-		if self.state_sync.is_some() &&
-			self.peers.iter().any(|(_, peer)| peer.state == PeerSyncState::DownloadingState)
-		{
-			// Only one pending state request is allowed.
-			return None;
+		let Some(sync) = &mut self.state_sync else { return requests };
+		if sync.is_complete() {
+			return requests;
 		}
-		if let Some(sync) = &self.state_sync {
-			if sync.is_complete() {
-				return None;
-			}
 
-			for (id, peer) in self.peers.iter_mut() {
-				if peer.state.is_available() &&
-					peer.common_number >= sync.target_number() &&
-					self.disconnected_peers.is_peer_available(&id)
-				{
-					peer.state = PeerSyncState::DownloadingState;
-					let request = sync.next_request();
-					trace!(target: LOG_TARGET, "New StateRequest for {}: {:?}", id, request);
-					self.allowed_requests.clear();
-					return Some((*id, request));
-				}
+		let mut in_flight = self
+			.peers
+			.values()
+			.filter(|peer| matches!(peer.state, PeerSyncState::DownloadingState(_)))
+			.count();
+		for (id, peer) in self.peers.iter_mut() {
+			if in_flight >= MAX_PARALLEL_STATE_REQUESTS {
+				break;
 			}
+			if peer.state.is_available() &&
+				peer.common_number >= sync.target_number() &&
+				self.disconnected_peers.is_peer_available(&id)
+			{
+				let Some((chunk, request)) = sync.next_request() else { break };
+				peer.state = PeerSyncState::DownloadingState(chunk);
+				trace!(
+					target: LOG_TARGET,
+					"New state request to {id} for chunk {chunk}: {request:?}.",
+				);
+				requests.push((*id, request));
+				in_flight += 1;
+			}
+		}
+		if !requests.is_empty() {
+			self.allowed_requests.clear();
 		}
-		None
+		requests
 	}
 
 	#[must_use]
@@ -1986,13 +2008,15 @@ where
 			},
 		};
 
+		let mut chunk = None;
 		if let Some(peer) = self.peers.get_mut(peer_id) {
-			if let PeerSyncState::DownloadingState = peer.state {
+			if let PeerSyncState::DownloadingState(requested) = peer.state {
 				peer.state = PeerSyncState::Available;
 				self.allowed_requests.set_all();
+				chunk = Some(requested);
 			}
 		}
-		let import_result = if let Some(sync) = &mut self.state_sync {
+		let import_result = if let (Some(sync), Some(chunk)) = (&mut self.state_sync, chunk) {
 			debug!(
 				target: LOG_TARGET,
 				"Importing state data from {} with {} keys, {} proof nodes.",
@@ -2000,7 +2024,7 @@ where
 				response.entries.len(),
 				response.proof.len(),
 			);
-			sync.import(response)
+			sync.import(chunk, response)
 		} else {
 			debug!(target: LOG_TARGET, "Ignored obsolete state response from {peer_id}");
 			return Err(BadPeer(*peer_id, rep::NOT_REQUESTED));
@@ -1341,3 +1341,41 @@ fn sync_gap_filled_regardless_of_blocks_origin() {
 		assert!(sync.gap_sync.is_none());
 	}
 }
+
+#[test]
+fn light_state_sync_requests_chunks_in_parallel() {
+	let client = Arc::new(TestClientBuilder::new().build());
+	let block = build_block(&client, None, false);
+
+	let mut sync = ChainSync::new(
+		ChainSyncMode::LightState { skip_proofs: false, storage_chain_mode: false },
+		client.clone(),
+		1,
+		64,
+		ProtocolName::Static(""),
+		Arc::new(MockBlockDownloader::new()),
+		None,
+		std::iter::empty(),
+	)
+	.unwrap();
+
+	for _ in 0..10 {
+		sync.add_peer(PeerId::random(), block.hash(), *block.header().number());
+	}
+	sync.attempt_state_sync(block.hash(), *block.header().number(), false);
+
+	// Every request goes to a different peer for a different chunk.
+	let requests = sync.state_requests();
+	assert_eq!(requests.len(), MAX_PARALLEL_STATE_REQUESTS);
+	let peers = requests.iter().map(|(peer_id, _)| *peer_id).collect::<HashSet<_>>();
+	assert_eq!(peers.len(), MAX_PARALLEL_STATE_REQUESTS);
+	let starts = requests
+		.iter()
+		.map(|(_, request)| request.start.clone())
+		.collect::<HashSet<_>>();
+	assert_eq!(starts.len(), MAX_PARALLEL_STATE_REQUESTS);
+
+	// No more requests while the maximum number of requests is in flight.
+	sync.allowed_requests.set_all();
+	assert!(sync.state_requests().is_empty());
+}
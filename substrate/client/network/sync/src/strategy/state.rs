@@ -32,7 +32,7 @@ use crate::{
 use futures::{channel::oneshot, FutureExt};
 use log::{debug, error, trace};
 use prost::Message;
-use sc_client_api::ProofProvider;
+use sc_client_api::{AuxStore, HeaderBackend, ProofProvider};
 use sc_consensus::{BlockImportError, BlockImportStatus, IncomingBlock};
 use sc_network::{IfDisconnected, ProtocolName};
 use sc_network_common::sync::message::BlockAnnounce;
@@ -52,11 +52,18 @@ mod rep {
 
 	/// Reputation change for peers which send us a known bad state.
 	pub const BAD_STATE: Rep = Rep::new(-(1 << 29), "Bad state");
+
+	/// Reputation change for peers which send us state we didn't request.
+	pub const NOT_REQUESTED: Rep = Rep::new(-(1 << 29), "Not requested state data");
 }
 
+/// Maximum number of state requests in flight, each to a different peer.
+pub(crate) const MAX_PARALLEL_STATE_REQUESTS: usize = 8;
+
 enum PeerState {
 	Available,
-	DownloadingState,
+	/// Downloading the given chunk of the state.
+	DownloadingState(usize),
 }
 
 impl PeerState {
@@ -95,7 +102,7 @@ impl<B: BlockT> StateStrategy<B> {
 		protocol_name: ProtocolName,
 	) -> Self
 	where
-		Client: ProofProvider<B> + Send + Sync + 'static,
+		Client: ProofProvider<B> + AuxStore + HeaderBackend<B> + Send + Sync + 'static,
 	{
 		let peers = initial_peers
 			.map(|(peer_id, best_number)| {
@@ -149,7 +156,8 @@ impl<B: BlockT> StateStrategy<B> {
 	/// Notify that a peer has disconnected.
 	pub fn remove_peer(&mut self, peer_id: &PeerId) {
 		if let Some(state) = self.peers.remove(peer_id) {
-			if !state.state.is_available() {
+			if let PeerState::DownloadingState(chunk) = state.state {
+				self.state_sync.cancel_request(chunk);
 				if let Some(bad_peer) =
 					self.disconnected_peers.on_disconnect_during_request(*peer_id)
 				{
@@ -192,9 +200,14 @@ impl<B: BlockT> StateStrategy<B> {
 		peer_id: &PeerId,
 		response: &[u8],
 	) -> Result<(), BadPeer> {
-		if let Some(peer) = self.peers.get_mut(&peer_id) {
-			peer.state = PeerState::Available;
-		}
+		let Some(PeerState::DownloadingState(chunk)) = self
+			.peers
+			.get_mut(&peer_id)
+			.map(|peer| std::mem::replace(&mut peer.state, PeerState::Available))
+		else {
+			debug!(target: LOG_TARGET, "Unexpected state response from {peer_id}");
+			return Err(BadPeer(*peer_id, rep::NOT_REQUESTED));
+		};
 
 		let response = match StateResponse::decode(response) {
 			Ok(response) => response,
@@ -203,6 +216,7 @@ impl<B: BlockT> StateStrategy<B> {
 					target: LOG_TARGET,
 					"Failed to decode state response from peer {peer_id:?}: {error:?}.",
 				);
+				self.state_sync.cancel_request(chunk);
 
 				return Err(BadPeer(*peer_id, rep::BAD_RESPONSE));
 			},
@@ -216,7 +230,7 @@ impl<B: BlockT> StateStrategy<B> {
 			response.proof.len(),
 		);
 
-		match self.state_sync.import(response) {
+		match self.state_sync.import(chunk, response) {
 			ImportResult::Import(hash, header, state, body, justifications) => {
 				let origin = BlockOrigin::NetworkInitialSync;
 				let block = IncomingBlock {
@@ -282,36 +296,44 @@ impl<B: BlockT> StateStrategy<B> {
 		}
 	}
 
-	/// Produce state request.
-	fn state_request(&mut self) -> Option<(PeerId, StateRequest)> {
+	/// Produce state requests for the available peers.
+	///
+	/// Each request covers a different chunk of the state, see [`StateSyncProvider`].
+	fn state_requests(&mut self) -> Vec<(PeerId, StateRequest)> {
+		let mut requests = Vec::new();
 		if self.state_sync.is_complete() {
-			return None
+			return requests
 		}
 
-		if self
-			.peers
-			.values()
-			.any(|peer| matches!(peer.state, PeerState::DownloadingState))
-		{
-			// Only one state request at a time is possible.
-			return None
+		let mut in_flight = self.peers.values().filter(|peer| !peer.state.is_available()).count();
+		while in_flight < MAX_PARALLEL_STATE_REQUESTS {
+			let Some(peer_id) = self.next_peer(self.state_sync.target_number()) else { break };
+			let Some((chunk, request)) = self.state_sync.next_request() else { break };
+			if let Some(peer) = self.peers.get_mut(&peer_id) {
+				peer.state = PeerState::DownloadingState(chunk);
+			}
+			trace!(
+				target: LOG_TARGET,
+				"New state request to {peer_id} for chunk {chunk}: {request:?}.",
+			);
+			requests.push((peer_id, request));
+			in_flight += 1;
 		}
-
-		let peer_id =
-			self.schedule_next_peer(PeerState::DownloadingState, self.state_sync.target_number())?;
-		let request = self.state_sync.next_request();
-		trace!(
-			target: LOG_TARGET,
-			"New state request to {peer_id}: {request:?}.",
-		);
-		Some((peer_id, request))
+		requests
 	}
 
+	#[cfg(test)]
 	fn schedule_next_peer(
 		&mut self,
 		new_state: PeerState,
 		min_best_number: NumberFor<B>,
 	) -> Option<PeerId> {
+		let peer_id = self.next_peer(min_best_number)?;
+		self.peers.get_mut(&peer_id)?.state = new_state;
+		Some(peer_id)
+	}
+
+	fn next_peer(&mut self, min_best_number: NumberFor<B>) -> Option<PeerId> {
 		let mut targets: Vec<_> = self.peers.values().map(|p| p.best_number).collect();
 		if targets.is_empty() {
 			return None
@@ -321,16 +343,15 @@ impl<B: BlockT> StateStrategy<B> {
 		let threshold = std::cmp::max(median, min_best_number);
 		// Find a random peer that is synced as much as peer majority and is above
 		// `min_best_number`.
-		for (peer_id, peer) in self.peers.iter_mut() {
-			if peer.state.is_available() &&
-				peer.best_number >= threshold &&
-				self.disconnected_peers.is_peer_available(peer_id)
-			{
-				peer.state = new_state;
-				return Some(*peer_id)
-			}
-		}
-		None
+		let disconnected_peers = &mut self.disconnected_peers;
+		self.peers
+			.iter()
+			.find(|(peer_id, peer)| {
+				peer.state.is_available() &&
+					peer.best_number >= threshold &&
+					disconnected_peers.is_peer_available(peer_id)
+			})
+			.map(|(peer_id, _)| *peer_id)
 	}
 
 	/// Returns the current sync status.
@@ -355,7 +376,7 @@ impl<B: BlockT> StateStrategy<B> {
 		&mut self,
 		network_service: &NetworkServiceHandle,
 	) -> impl Iterator<Item = SyncingAction<B>> {
-		let state_request = self.state_request().into_iter().map(|(peer_id, request)| {
+		let state_requests = self.state_requests().into_iter().map(|(peer_id, request)| {
 			let (tx, rx) = oneshot::channel();
 
 			network_service.start_request(
@@ -378,7 +399,7 @@ impl<B: BlockT> StateStrategy<B> {
 				remove_obsolete: false,
 			}
 		});
-		self.actions.extend(state_request);
+		self.actions.extend(state_requests);
 
 		std::mem::take(&mut self.actions).into_iter()
 	}
@@ -398,12 +419,13 @@ mod test {
 		service::network::NetworkServiceProvider,
 		strategy::state_sync::{ImportResult, StateSyncProgress, StateSyncProvider},
 	};
-	use codec::Decode;
+	use codec::{Decode, Encode};
 	use sc_block_builder::BlockBuilderBuilder;
 	use sc_client_api::KeyValueStates;
 	use sc_consensus::{ImportedAux, ImportedState};
 	use sp_core::H256;
 	use sp_runtime::traits::Zero;
+	use std::collections::HashSet;
 	use substrate_test_runtime_client::{
 		runtime::{Block, Hash},
 		BlockBuilderExt, DefaultTestClientBuilderExt, TestClientBuilder, TestClientBuilderExt,
@@ -413,8 +435,9 @@ mod test {
 		pub StateSync<B: BlockT> {}
 
 		impl<B: BlockT> StateSyncProvider<B> for StateSync<B> {
-			fn import(&mut self, response: StateResponse) -> ImportResult<B>;
-			fn next_request(&self) -> StateRequest;
+			fn import(&mut self, chunk: usize, response: StateResponse) -> ImportResult<B>;
+			fn next_request(&mut self) -> Option<(usize, StateRequest)>;
+			fn cancel_request(&mut self, chunk: usize);
 			fn is_complete(&self) -> bool;
 			fn target_number(&self) -> NumberFor<B>;
 			fn target_hash(&self) -> B::Hash;
@@ -446,7 +469,7 @@ mod test {
 		);
 
 		assert!(state_strategy
-			.schedule_next_peer(PeerState::DownloadingState, Zero::zero())
+			.schedule_next_peer(PeerState::DownloadingState(0), Zero::zero())
 			.is_none());
 	}
 
@@ -479,7 +502,7 @@ mod test {
 			);
 
 			let peer_id =
-				state_strategy.schedule_next_peer(PeerState::DownloadingState, Zero::zero());
+				state_strategy.schedule_next_peer(PeerState::DownloadingState(0), Zero::zero());
 			assert!(*peers.get(&peer_id.unwrap()).unwrap() >= 6);
 		}
 	}
@@ -512,7 +535,7 @@ mod test {
 				ProtocolName::Static(""),
 			);
 
-			let peer_id = state_strategy.schedule_next_peer(PeerState::DownloadingState, 10);
+			let peer_id = state_strategy.schedule_next_peer(PeerState::DownloadingState(0), 10);
 			assert!(*peers.get(&peer_id.unwrap()).unwrap() == 10);
 		}
 	}
@@ -553,7 +576,7 @@ mod test {
 		// Disconnect the peer with an inflight request.
 		state_strategy.add_peer(tenth_peer, H256::random(), 10);
 		let peer_id: Option<PeerId> =
-			state_strategy.schedule_next_peer(PeerState::DownloadingState, 10);
+			state_strategy.schedule_next_peer(PeerState::DownloadingState(0), 10);
 		assert_eq!(tenth_peer, peer_id.unwrap());
 		state_strategy.remove_peer(&tenth_peer);
 
@@ -563,12 +586,12 @@ mod test {
 		// No peer available for 10'th best block because of the backoff.
 		state_strategy.add_peer(tenth_peer, H256::random(), 10);
 		let peer_id: Option<PeerId> =
-			state_strategy.schedule_next_peer(PeerState::DownloadingState, 10);
+			state_strategy.schedule_next_peer(PeerState::DownloadingState(0), 10);
 		assert!(peer_id.is_none());
 
 		// Other requests can still happen.
 		let peer_id: Option<PeerId> =
-			state_strategy.schedule_next_peer(PeerState::DownloadingState, 9);
+			state_strategy.schedule_next_peer(PeerState::DownloadingState(0), 9);
 		assert_eq!(ninth_peer, peer_id.unwrap());
 	}
 
@@ -596,14 +619,14 @@ mod test {
 			ProtocolName::Static(""),
 		);
 
-		let (_peer_id, request) = state_strategy.state_request().unwrap();
+		let (_peer_id, request) = state_strategy.state_requests().pop().unwrap();
 		let hash = Hash::decode(&mut &*request.block).unwrap();
 
 		assert_eq!(hash, target_block.header().hash());
 	}
 
 	#[test]
-	fn no_parallel_state_requests() {
+	fn parallel_state_requests_cover_different_chunks() {
 		let client = Arc::new(TestClientBuilder::new().set_no_genesis().build());
 		let target_block = BlockBuilderBuilder::new(&*client)
 			.on_parent_block(client.chain_info().best_hash)
@@ -626,17 +649,59 @@ mod test {
 			ProtocolName::Static(""),
 		);
 
-		// First request is sent.
-		assert!(state_strategy.state_request().is_some());
+		// One request per peer synced at least to the median.
+		let requests = state_strategy.state_requests();
+		assert_eq!(requests.len(), 5);
+		let peers = requests.iter().map(|(peer_id, _)| *peer_id).collect::<HashSet<_>>();
+		assert_eq!(peers.len(), 5);
+		let starts = requests
+			.iter()
+			.map(|(_, request)| request.start.clone())
+			.collect::<HashSet<_>>();
+		assert_eq!(starts.len(), 5);
+
+		// No more requests while all suitable peers are busy.
+		assert!(state_strategy.state_requests().is_empty());
+	}
+
+	#[test]
+	fn chunk_of_disconnected_peer_is_requested_again() {
+		let client = Arc::new(TestClientBuilder::new().set_no_genesis().build());
+		let target_block = BlockBuilderBuilder::new(&*client)
+			.on_parent_block(client.chain_info().best_hash)
+			.with_parent_block_number(client.chain_info().best_number)
+			.build()
+			.unwrap()
+			.build()
+			.unwrap()
+			.block;
 
-		// No parallel request is sent.
-		assert!(state_strategy.state_request().is_none());
+		let initial_peers = (0..2).map(|_| (PeerId::random(), 10));
+
+		let mut state_strategy = StateStrategy::new(
+			client.clone(),
+			target_block.header().clone(),
+			None,
+			None,
+			false,
+			initial_peers,
+			ProtocolName::Static(""),
+		);
+
+		let requests = state_strategy.state_requests();
+		assert_eq!(requests.len(), 2);
+		let (disconnected_peer, request) = &requests[1];
+		state_strategy.remove_peer(disconnected_peer);
+
+		let new_peer = PeerId::random();
+		state_strategy.add_peer(new_peer, H256::random(), 10);
+		assert_eq!(state_strategy.state_requests(), vec![(new_peer, request.clone())]);
 	}
 
 	#[test]
 	fn received_state_response_makes_peer_available_again() {
 		let mut state_sync_provider = MockStateSync::<Block>::new();
-		state_sync_provider.expect_import().return_once(|_| ImportResult::Continue);
+		state_sync_provider.expect_import().return_once(|_, _| ImportResult::Continue);
 		let peer_id = PeerId::random();
 		let initial_peers = std::iter::once((peer_id, 10));
 		let mut state_strategy = StateStrategy::new_with_provider(
@@ -645,7 +710,7 @@ mod test {
 			ProtocolName::Static(""),
 		);
 		// Manually set the peer's state.
-		state_strategy.peers.get_mut(&peer_id).unwrap().state = PeerState::DownloadingState;
+		state_strategy.peers.get_mut(&peer_id).unwrap().state = PeerState::DownloadingState(0);
 
 		let dummy_response = StateResponse::default().encode_to_vec();
 		state_strategy.on_state_response(&peer_id, dummy_response);
@@ -657,7 +722,9 @@ mod test {
 	fn bad_state_response_drops_peer() {
 		let mut state_sync_provider = MockStateSync::<Block>::new();
 		// Provider says that state response is bad.
-		state_sync_provider.expect_import().return_once(|_| ImportResult::BadResponse);
+		state_sync_provider
+			.expect_import()
+			.return_once(|_, _| ImportResult::BadResponse);
 		let peer_id = PeerId::random();
 		let initial_peers = std::iter::once((peer_id, 10));
 		let mut state_strategy = StateStrategy::new_with_provider(
@@ -666,7 +733,7 @@ mod test {
 			ProtocolName::Static(""),
 		);
 		// Manually set the peer's state.
-		state_strategy.peers.get_mut(&peer_id).unwrap().state = PeerState::DownloadingState;
+		state_strategy.peers.get_mut(&peer_id).unwrap().state = PeerState::DownloadingState(0);
 		let dummy_response = StateResponse::default().encode_to_vec();
 		// Receiving response drops the peer.
 		assert!(matches!(
@@ -679,7 +746,7 @@ mod test {
 	fn partial_state_response_doesnt_generate_actions() {
 		let mut state_sync_provider = MockStateSync::<Block>::new();
 		// Sync provider says that the response is partial.
-		state_sync_provider.expect_import().return_once(|_| ImportResult::Continue);
+		state_sync_provider.expect_import().return_once(|_, _| ImportResult::Continue);
 		let peer_id = PeerId::random();
 		let initial_peers = std::iter::once((peer_id, 10));
 		let mut state_strategy = StateStrategy::new_with_provider(
@@ -688,7 +755,7 @@ mod test {
 			ProtocolName::Static(""),
 		);
 		// Manually set the peer's state .
-		state_strategy.peers.get_mut(&peer_id).unwrap().state = PeerState::DownloadingState;
+		state_strategy.peers.get_mut(&peer_id).unwrap().state = PeerState::DownloadingState(0);
 
 		let dummy_response = StateResponse::default().encode_to_vec();
 		state_strategy.on_state_response(&peer_id, dummy_response);
@@ -723,7 +790,7 @@ mod test {
 			body.clone(),
 			justifications.clone(),
 		);
-		state_sync_provider.expect_import().return_once(move |_| import);
+		state_sync_provider.expect_import().return_once(move |_, _| import);
 
 		// Reference values to check against.
 		let expected_origin = BlockOrigin::NetworkInitialSync;
@@ -750,7 +817,7 @@ mod test {
 			ProtocolName::Static(""),
 		);
 		// Manually set the peer's state .
-		state_strategy.peers.get_mut(&peer_id).unwrap().state = PeerState::DownloadingState;
+		state_strategy.peers.get_mut(&peer_id).unwrap().state = PeerState::DownloadingState(0);
 
 		// Receive response.
 		let dummy_response = StateResponse::default().encode_to_vec();
@@ -845,6 +912,55 @@ mod test {
 		assert!(matches!(&state_strategy.actions[0], SyncingAction::Finished));
 	}
 
+	#[test]
+	fn warp_state_sync_resumes_after_restart_with_newer_target() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let genesis = client.header(client.info().genesis_hash).unwrap().unwrap();
+		// The targets of warp sync are proven by the warp proof and unknown to the client.
+		let warp_target = |number| {
+			<Block as BlockT>::Header::new(
+				number,
+				Default::default(),
+				*genesis.state_root(),
+				Default::default(),
+				Default::default(),
+			)
+		};
+		let peers = || (0..3).map(|_| (PeerId::random(), 50));
+
+		let mut state_strategy = StateStrategy::new(
+			client.clone(),
+			warp_target(10),
+			None,
+			None,
+			false,
+			peers(),
+			ProtocolName::Static(""),
+		);
+		let (peer_id, request) = state_strategy.state_requests().pop().unwrap();
+		let (proof, _) =
+			client.read_proof_collection(genesis.hash(), &request.start, 1024).unwrap();
+		let response = StateResponse { proof: proof.encode(), ..Default::default() };
+		state_strategy.on_state_response(&peer_id, response.encode_to_vec());
+		assert!(state_strategy.actions.is_empty());
+		let downloaded = state_strategy.state_sync.progress().size;
+		assert!(downloaded > 0);
+		drop(state_strategy);
+
+		// After a restart, warp sync proves a newer target.
+		let state_strategy = StateStrategy::new(
+			client.clone(),
+			warp_target(20),
+			None,
+			None,
+			false,
+			peers(),
+			ProtocolName::Static(""),
+		);
+		assert_eq!(state_strategy.state_sync.target_hash(), warp_target(10).hash());
+		assert_eq!(state_strategy.state_sync.progress().size, downloaded);
+	}
+
 	#[test]
 	fn finished_strategy_doesnt_generate_more_actions() {
 		let target_hash = Hash::random();
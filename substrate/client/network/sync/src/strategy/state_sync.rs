@@ -17,29 +17,65 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! State sync support.
+//!
+//! The top trie is downloaded in chunks, each covering a range of keys and tracking its own
+//! position, so that several peers can serve parts of the same state concurrently. Sync starts
+//! with a single chunk, which is split whenever a peer is available and every other chunk is
+//! already being downloaded. Child tries are downloaded by the chunk holding their root key.
+//!
+//! Every downloaded response is stored in the aux database along with the position of each
+//! chunk. The target block is stored once, when the first response is. A state sync started
+//! after a restart continues the interrupted one, provided that its target is still recent enough
+//! for peers to serve its state.
 
 use crate::{
 	schema::v1::{KeyValueStateEntry, StateEntry, StateRequest, StateResponse},
 	LOG_TARGET,
 };
 use codec::{Decode, Encode};
-use log::debug;
-use sc_client_api::{CompactProof, KeyValueStates, ProofProvider};
+use log::{debug, info, warn};
+use sc_client_api::{AuxStore, CompactProof, HeaderBackend, KeyValueStates, ProofProvider};
 use sc_consensus::ImportedState;
 use smallvec::SmallVec;
 use sp_core::storage::well_known_keys;
 use sp_runtime::{
-	traits::{Block as BlockT, Header, NumberFor},
+	traits::{Block as BlockT, Header, NumberFor, Saturating},
 	Justifications,
 };
-use std::{collections::HashMap, fmt, sync::Arc};
+use std::{
+	cmp::Reverse,
+	collections::{HashMap, HashSet},
+	fmt,
+	sync::Arc,
+};
+
+/// Aux key of the target block of an interrupted state sync.
+const TARGET_KEY: &[u8] = b"state_sync_target";
+
+/// Aux key of the progress of an interrupted state sync.
+const PROGRESS_KEY: &[u8] = b"state_sync_progress";
+
+/// Prefix of the aux keys of the responses downloaded by an interrupted state sync.
+const PART_PREFIX: &[u8] = b"state_sync_part";
+
+/// Maximum number of blocks the target of an interrupted state sync may be behind the target of
+/// a new one to be resumed instead. Half of the default state pruning window, so that peers still
+/// have the state when the download completes.
+const MAX_RESUMED_TARGET_AGE: u32 = 128;
+
+/// Key values of a trie along with its root, which is empty for the top trie.
+type StateLevel = (Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>);
 
 /// Generic state sync provider. Used for mocking in tests.
 pub trait StateSyncProvider<B: BlockT>: Send + Sync {
-	/// Validate and import a state response.
-	fn import(&mut self, response: StateResponse) -> ImportResult<B>;
-	/// Produce next state request.
-	fn next_request(&self) -> StateRequest;
+	/// Validate and import a state response to the request for `chunk`.
+	fn import(&mut self, chunk: usize, response: StateResponse) -> ImportResult<B>;
+	/// Produce the next state request, along with the chunk it is for.
+	///
+	/// Returns `None` if every remaining part of the state is already requested.
+	fn next_request(&mut self) -> Option<(usize, StateRequest)>;
+	/// Make `chunk` available for a new request, after its request failed.
+	fn cancel_request(&mut self, chunk: usize);
 	/// Check if the state is complete.
 	fn is_complete(&self) -> bool;
 	/// Returns target block number.
@@ -89,8 +125,101 @@ pub enum ImportResult<B: BlockT> {
 	BadResponse,
 }
 
-struct StateSyncMetadata<B: BlockT> {
+/// A range of top trie keys downloaded independently of the other ranges.
+struct Chunk {
+	/// The chunk holds the keys strictly after this one. Empty for the first chunk.
+	after: Vec<u8>,
+	/// Last key of the chunk, `None` for the last chunk.
+	until: Option<Vec<u8>>,
+	/// Last keys downloaded, used as the start of the next request.
 	last_key: SmallVec<[Vec<u8>; 2]>,
+	/// A request for the chunk is in flight.
+	requested: bool,
+	/// All keys of the chunk are downloaded.
+	complete: bool,
+}
+
+impl Chunk {
+	fn new(after: Vec<u8>, until: Option<Vec<u8>>) -> Self {
+		Self { after, until, last_key: SmallVec::default(), requested: false, complete: false }
+	}
+
+	fn request_start(&self) -> Vec<Vec<u8>> {
+		if self.last_key.is_empty() && !self.after.is_empty() {
+			vec![self.after.clone()]
+		} else {
+			self.last_key.clone().into_vec()
+		}
+	}
+
+	/// Top trie key the download has reached.
+	fn cursor(&self) -> &[u8] {
+		self.last_key.first().unwrap_or(&self.after)
+	}
+
+	/// Whether the download has reached the last key of the chunk.
+	fn reached_end(&self) -> bool {
+		self.last_key.len() == 1 &&
+			self.until.as_ref().map_or(false, |until| self.last_key[0] >= *until)
+	}
+}
+
+/// Position of a chunk, as stored in the aux database.
+#[derive(Encode, Decode)]
+struct ChunkProgress {
+	after: Vec<u8>,
+	until: Option<Vec<u8>>,
+	last_key: Vec<Vec<u8>>,
+	complete: bool,
+}
+
+impl From<&Chunk> for ChunkProgress {
+	fn from(chunk: &Chunk) -> Self {
+		Self {
+			after: chunk.after.clone(),
+			until: chunk.until.clone(),
+			last_key: chunk.last_key.to_vec(),
+			complete: chunk.complete,
+		}
+	}
+}
+
+impl From<ChunkProgress> for Chunk {
+	fn from(progress: ChunkProgress) -> Self {
+		Self {
+			after: progress.after,
+			until: progress.until,
+			last_key: progress.last_key.into(),
+			requested: false,
+			complete: progress.complete,
+		}
+	}
+}
+
+/// Target block of a state sync, as stored in the aux database.
+#[derive(Encode, Decode)]
+struct Target<B: BlockT> {
+	header: B::Header,
+	body: Option<Vec<B::Extrinsic>>,
+	justifications: Option<Justifications>,
+	skip_proof: bool,
+}
+
+/// Progress of a state sync, as stored in the aux database after every response.
+#[derive(Encode, Decode)]
+struct Progress {
+	chunks: Vec<ChunkProgress>,
+	imported_bytes: u64,
+	/// Number of responses stored under [`PART_PREFIX`].
+	parts: u32,
+}
+
+fn part_key(index: u32) -> Vec<u8> {
+	(PART_PREFIX, index).encode()
+}
+
+struct StateSyncMetadata<B: BlockT> {
+	chunks: Vec<Chunk>,
 	target_header: B::Header,
 	target_body: Option<Vec<B::Extrinsic>>,
 	target_justifications: Option<Justifications>,
@@ -113,17 +242,72 @@ impl<B: BlockT> StateSyncMetadata<B> {
 		*self.target_header.state_root()
 	}
 
-	fn next_request(&self) -> StateRequest {
-		StateRequest {
-			block: self.target_hash().encode(),
-			start: self.last_key.clone().into_vec(),
-			no_proof: self.skip_proof,
+	fn next_request(&mut self) -> Option<(usize, StateRequest)> {
+		if self.complete {
+			return None
+		}
+		let index = match self.chunks.iter().position(|chunk| !chunk.requested && !chunk.complete) {
+			Some(index) => index,
+			None => self.split_chunk()?,
+		};
+		let block = self.target_hash().encode();
+		let chunk = &mut self.chunks[index];
+		chunk.requested = true;
+		let request =
+			StateRequest { block, start: chunk.request_start(), no_proof: self.skip_proof };
+		Some((index, request))
+	}
+
+	/// Move the second half of the largest remaining key range to a new chunk.
+	///
+	/// The response to the request in flight for the split chunk is truncated once it arrives.
+	/// Unverified responses are not split, as they can not be truncated reliably.
+	fn split_chunk(&mut self) -> Option<usize> {
+		if self.skip_proof {
+			return None
 		}
+		let (index, middle) = self
+			.chunks
+			.iter()
+			.enumerate()
+			.filter(|(_, chunk)| !chunk.complete)
+			.filter_map(|(index, chunk)| {
+				let until = chunk.until.as_deref();
+				let middle = middle_key(chunk.cursor(), until)?;
+				Some((range_width(chunk.cursor(), until), index, middle))
+			})
+			.max_by_key(|(width, _, _)| *width)
+			.map(|(_, index, middle)| (index, middle))?;
+
+		let until = self.chunks[index].until.replace(middle.clone());
+		debug!(
+			target: LOG_TARGET,
+			"Splitting state chunk {index} at {}",
+			sp_core::hexdisplay::HexDisplay::from(&middle),
+		);
+		self.chunks.push(Chunk::new(middle, until));
+		Some(self.chunks.len() - 1)
 	}
 
 	fn progress(&self) -> StateSyncProgress {
-		let cursor = *self.last_key.get(0).and_then(|last| last.get(0)).unwrap_or(&0u8);
-		let percent_done = cursor as u32 * 100 / 256;
+		// Position of a key in the key space, in 1/65536.
+		let position = |key: &[u8]| {
+			(u32::from(key.get(0).copied().unwrap_or(0)) << 8) |
+				u32::from(key.get(1).copied().unwrap_or(0))
+		};
+		let done: u32 = self
+			.chunks
+			.iter()
+			.map(|chunk| {
+				let reached = if chunk.complete {
+					chunk.until.as_deref().map_or(1 << 16, position)
+				} else {
+					position(chunk.cursor())
+				};
+				reached.saturating_sub(position(&chunk.after))
+			})
+			.sum();
+		let percent_done = (done.min(1 << 16) * 100) >> 16;
 		StateSyncProgress {
 			percentage: percent_done,
 			size: self.imported_bytes,
@@ -136,6 +320,74 @@ impl<B: BlockT> StateSyncMetadata<B> {
 	}
 }
 
+/// Returns a key roughly halfway between `from` and `to`, or the end of the key space if `to` is
+/// `None`. Keys are compared as big-endian fractions.
+fn middle_key(from: &[u8], to: Option<&[u8]>) -> Option<Vec<u8>> {
+	let len = from.len().max(to.map_or(0, |to| to.len())) + 1;
+	let digit = |key: Option<&[u8]>, i: usize| {
+		u16::from(key.and_then(|key| key.get(i)).copied().unwrap_or(0))
+	};
+
+	let mut sum = vec![0u16; len];
+	let mut carry = 0;
+	for i in (0..len).rev() {
+		let digits = digit(Some(from), i) + digit(to, i) + carry;
+		sum[i] = digits & 0xff;
+		carry = digits >> 8;
+	}
+	// The end of the key space stands for 1.
+	let mut remainder = carry + u16::from(to.is_none());
+	let mut middle: Vec<u8> = sum
+		.into_iter()
+		.map(|digit| {
+			let value = (remainder << 8) | digit;
+			remainder = value % 2;
+			(value / 2) as u8
+		})
+		.collect();
+	while middle.last() == Some(&0) {
+		middle.pop();
+	}
+
+	(from < middle.as_slice() && to.map_or(true, |to| middle.as_slice() < to)).then_some(middle)
+}
+
+/// Rough size of the key range between `from` and `to`, comparable between ranges.
+fn range_width(from: &[u8], to: Option<&[u8]>) -> (Reverse<usize>, u128) {
+	let common = to.map_or(0, |to| from.iter().zip(to).take_while(|(a, b)| a == b).count());
+	let window = |key: &[u8]| {
+		(0..8).fold(0u128, |value, i| (value << 8) | u128::from(*key.get(common + i).unwrap_or(&0)))
+	};
+	let end = to.map_or(1 << 64, window);
+	(Reverse(common), end.saturating_sub(window(from)))
+}
+
+/// Drop the top trie entries past `until`, along with the child tries only reached through them.
+///
+/// When the request started inside a child trie, the first child level continues that trie.
+/// Returns `true` if any entry was dropped.
+fn truncate_key_values(values: &mut KeyValueStates, until: &[u8], resumed_child: bool) -> bool {
+	let Some(top) = values.0.first_mut() else { return false };
+	let end = top.key_values.partition_point(|(key, _)| key.as_slice() <= until);
+	if end == top.key_values.len() {
+		return false
+	}
+	top.key_values.truncate(end);
+
+	let child_roots: HashSet<Vec<u8>> = top
+		.key_values
+		.iter()
+		.filter(|(key, _)| well_known_keys::is_child_storage_key(key))
+		.map(|(_, root)| root.clone())
+		.collect();
+	let mut level = 0;
+	values.0.retain(|state| {
+		level += 1;
+		level == 1 || (level == 2 && resumed_child) || child_roots.contains(&state.state_root)
+	});
+	true
+}
+
 /// State sync state machine.
 ///
 /// Accumulates partial state data until it is ready to be imported.
@@ -143,14 +395,19 @@ pub struct StateSync<B: BlockT, Client> {
 	metadata: StateSyncMetadata<B>,
 	state: HashMap<Vec<u8>, (Vec<(Vec<u8>, Vec<u8>)>, Vec<Vec<u8>>)>,
 	client: Arc<Client>,
+	/// Number of responses stored in the aux database.
+	parts: u32,
 }
 
 impl<B, Client> StateSync<B, Client>
 where
 	B: BlockT,
-	Client: ProofProvider<B> + Send + Sync + 'static,
+	Client: ProofProvider<B> + AuxStore + HeaderBackend<B> + Send + Sync + 'static,
 {
 	///  Create a new instance.
+	///
+	/// Continues the state sync interrupted by a restart instead, if its target is an ancestor of
+	/// `target_header` that is recent enough. `target_header` must be a finalized block.
 	pub fn new(
 		client: Arc<Client>,
 		target_header: B::Header,
@@ -158,10 +415,10 @@ where
 		target_justifications: Option<Justifications>,
 		skip_proof: bool,
 	) -> Self {
-		Self {
+		let mut state_sync = Self {
 			client,
 			metadata: StateSyncMetadata {
-				last_key: SmallVec::default(),
+				chunks: vec![Chunk::new(Vec::new(), None)],
 				target_header,
 				target_body,
 				target_justifications,
@@ -170,7 +427,138 @@ where
 				skip_proof,
 			},
 			state: HashMap::default(),
+			parts: 0,
+		};
+		state_sync.resume();
+		state_sync
+	}
+
+	/// Load the progress of an interrupted state sync, if it can be continued.
+	fn resume(&mut self) {
+		let (target, progress) = match self.read_progress() {
+			Ok(Some(stored)) => stored,
+			Ok(None) => return,
+			Err(e) => {
+				warn!(target: LOG_TARGET, "Discarding unreadable state sync progress: {e}");
+				self.clear_progress(0);
+				return
+			},
+		};
+
+		let number = *target.header.number();
+		let hash = target.header.hash();
+		if !self.is_resumable(&target) {
+			debug!(target: LOG_TARGET, "Discarding state sync progress of #{number} ({hash})");
+			self.clear_progress(progress.parts);
+			return
+		}
+
+		for index in 0..progress.parts {
+			let levels = match self.client.get_aux(&part_key(index)) {
+				Ok(Some(encoded)) => Vec::<StateLevel>::decode(&mut &encoded[..]).ok(),
+				_ => None,
+			};
+			let Some(levels) = levels else {
+				warn!(target: LOG_TARGET, "Discarding incomplete state sync progress of #{number}");
+				self.state.clear();
+				self.clear_progress(progress.parts);
+				return
+			};
+			for (state_root, key_values) in levels {
+				self.process_state_key_values(state_root, key_values);
+			}
 		}
+
+		self.metadata.target_header = target.header;
+		self.metadata.target_body = target.body;
+		self.metadata.target_justifications = target.justifications;
+		self.metadata.chunks = progress.chunks.into_iter().map(Into::into).collect();
+		self.metadata.imported_bytes = progress.imported_bytes;
+		self.parts = progress.parts;
+		info!(
+			target: LOG_TARGET,
+			"Resuming state sync of #{number} ({hash}), {} MiB already downloaded",
+			progress.imported_bytes / (1024 * 1024),
+		);
+	}
+
+	/// Read the target and the progress of an interrupted state sync, if any.
+	fn read_progress(&self) -> Result<Option<(Target<B>, Progress)>, String> {
+		let Some(target) = self.client.get_aux(TARGET_KEY).map_err(|e| e.to_string())? else {
+			return Ok(None)
+		};
+		let target = Target::<B>::decode(&mut &target[..]).map_err(|e| e.to_string())?;
+		let progress = self
+			.client
+			.get_aux(PROGRESS_KEY)
+			.map_err(|e| e.to_string())?
+			.ok_or_else(|| "missing progress".to_string())?;
+		let progress = Progress::decode(&mut &progress[..]).map_err(|e| e.to_string())?;
+		Ok(Some((target, progress)))
+	}
+
+	/// Whether the interrupted state sync of `resumed` can be continued instead of syncing the
+	/// current target.
+	fn is_resumable(&self, resumed: &Target<B>) -> bool {
+		let target = &self.metadata.target_header;
+		if resumed.skip_proof != self.metadata.skip_proof {
+			return false
+		}
+		if resumed.header.hash() == target.hash() {
+			return true
+		}
+		if resumed.header.number() >= target.number() ||
+			(*target.number()).saturating_sub(*resumed.header.number()) >
+				MAX_RESUMED_TARGET_AGE.into()
+		{
+			return false
+		}
+		// The resumed target must be an ancestor of the current one.
+		match (self.client.hash(*resumed.header.number()), self.client.hash(*target.number())) {
+			(Ok(Some(hash)), _) => hash == resumed.header.hash(),
+			// Warp sync does not import the headers below its target. Both targets are then
+			// finalized blocks proven by a warp proof, so the older one is an ancestor.
+			(Ok(None), Ok(None)) => true,
+			_ => false,
+		}
+	}
+
+	/// Store a downloaded response and the current progress. The target block is stored along
+	/// with the first response.
+	fn persist(&mut self, part: Vec<u8>) {
+		let target = (self.parts == 0).then(|| {
+			Target::<B> {
+				header: self.metadata.target_header.clone(),
+				body: self.metadata.target_body.clone(),
+				justifications: self.metadata.target_justifications.clone(),
+				skip_proof: self.metadata.skip_proof,
+			}
+			.encode()
+		});
+		let progress = Progress {
+			chunks: self.metadata.chunks.iter().map(Into::into).collect(),
+			imported_bytes: self.metadata.imported_bytes,
+			parts: self.parts + 1,
+		}
+		.encode();
+		let part_key = part_key(self.parts);
+		let mut insert = vec![(&part_key[..], &part[..]), (PROGRESS_KEY, &progress[..])];
+		insert.extend(target.as_deref().map(|target| (TARGET_KEY, target)));
+		match self.client.insert_aux(&insert, &[]) {
+			Ok(()) => self.parts += 1,
+			Err(e) => warn!(target: LOG_TARGET, "Failed to store state sync progress: {e}"),
+		}
+	}
+
+	/// Remove the stored target and progress and the first `parts` stored responses.
+	fn clear_progress(&mut self, parts: u32) {
+		let keys: Vec<_> = (0..parts).map(part_key).collect();
+		let keys: Vec<_> =
+			keys.iter().map(Vec::as_slice).chain([TARGET_KEY, PROGRESS_KEY]).collect();
+		if let Err(e) = self.client.insert_aux(&[], &keys) {
+			warn!(target: LOG_TARGET, "Failed to remove state sync progress: {e}");
+		}
+		self.parts = 0;
 	}
 
 	fn process_state_key_values(
@@ -180,14 +568,10 @@ where
 	) {
 		let is_top = state_root.is_empty();
 
+		// A child trie referenced from several chunks may be downloaded more than once, the
+		// duplicate entries are merged on import.
 		let entry = self.state.entry(state_root).or_default();
 
-		if entry.0.len() > 0 && entry.1.len() > 1 {
-			// Already imported child_trie with same root.
-			// Warning this will not work with parallel download.
-			return;
-		}
-
 		let mut child_storage_roots = Vec::new();
 
 		for (key, value) in key_values {
@@ -205,23 +589,24 @@ where
 		}
 	}
 
-	fn process_state_verified(&mut self, values: KeyValueStates) {
-		for values in values.0 {
-			self.process_state_key_values(values.state_root, values.key_values);
-		}
-	}
-
-	fn process_state_unverified(&mut self, response: StateResponse) -> bool {
+	/// Returns the levels of an unverified response and whether the chunk is complete.
+	fn read_state_unverified(
+		&mut self,
+		chunk: usize,
+		response: StateResponse,
+	) -> (Vec<StateLevel>, bool) {
+		let mut levels = Vec::new();
 		let mut complete = true;
 		// if the trie is a child trie and one of its parent trie is empty,
 		// the parent cursor stays valid.
 		// Empty parent trie content only happens when all the response content
 		// is part of a single child trie.
-		if self.metadata.last_key.len() == 2 && response.entries[0].entries.is_empty() {
+		let last_key = &mut self.metadata.chunks[chunk].last_key;
+		if last_key.len() == 2 && response.entries[0].entries.is_empty() {
 			// Do not remove the parent trie position.
-			self.metadata.last_key.pop();
+			last_key.pop();
 		} else {
-			self.metadata.last_key.clear();
+			last_key.clear();
 		}
 		for state in response.entries {
 			debug!(
@@ -233,28 +618,35 @@ where
 
 			if !state.complete {
 				if let Some(e) = state.entries.last() {
-					self.metadata.last_key.push(e.key.clone());
+					self.metadata.chunks[chunk].last_key.push(e.key.clone());
 				}
 				complete = false;
 			}
 
 			let KeyValueStateEntry { state_root, entries, complete: _ } = state;
-			self.process_state_key_values(
+			levels.push((
 				state_root,
-				entries.into_iter().map(|StateEntry { key, value }| (key, value)),
-			);
+				entries.into_iter().map(|StateEntry { key, value }| (key, value)).collect(),
+			));
 		}
-		complete
+		(levels, complete)
 	}
 }
 
 impl<B, Client> StateSyncProvider<B> for StateSync<B, Client>
 where
 	B: BlockT,
-	Client: ProofProvider<B> + Send + Sync + 'static,
+	Client: ProofProvider<B> + AuxStore + HeaderBackend<B> + Send + Sync + 'static,
 {
 	///  Validate and import a state response.
-	fn import(&mut self, response: StateResponse) -> ImportResult<B> {
+	fn import(&mut self, chunk: usize, response: StateResponse) -> ImportResult<B> {
+		match self.metadata.chunks.get_mut(chunk) {
+			Some(state) if state.requested => state.requested = false,
+			_ => {
+				debug!(target: LOG_TARGET, "Unexpected state response for chunk {chunk}");
+				return ImportResult::BadResponse
+			},
+		}
 		if response.entries.is_empty() && response.proof.is_empty() {
 			debug!(target: LOG_TARGET, "Bad state response");
 			return ImportResult::BadResponse
@@ -263,7 +655,7 @@ where
 			debug!(target: LOG_TARGET, "Missing proof");
 			return ImportResult::BadResponse
 		}
-		let complete = if !self.metadata.skip_proof {
+		let (levels, chunk_complete) = if !self.metadata.skip_proof {
 			debug!(target: LOG_TARGET, "Importing state from {} trie nodes", response.proof.len());
 			let proof_size = response.proof.len() as u64;
 			let proof = match CompactProof::decode(&mut response.proof.as_ref()) {
@@ -273,35 +665,50 @@ where
 					return ImportResult::BadResponse
 				},
 			};
-			let (values, completed) = match self.client.verify_range_proof(
-				self.metadata.target_root(),
-				proof,
-				self.metadata.last_key.as_slice(),
-			) {
-				Err(e) => {
-					debug!(
-						target: LOG_TARGET,
-						"StateResponse failed proof verification: {}",
-						e,
-					);
-					return ImportResult::BadResponse
-				},
-				Ok(values) => values,
-			};
+			let start = self.metadata.chunks[chunk].request_start();
+			let (mut values, completed) =
+				match self.client.verify_range_proof(self.metadata.target_root(), proof, &start) {
+					Err(e) => {
+						debug!(
+							target: LOG_TARGET,
+							"StateResponse failed proof verification: {}",
+							e,
+						);
+						return ImportResult::BadResponse
+					},
+					Ok(values) => values,
+				};
 			debug!(target: LOG_TARGET, "Imported with {} keys", values.len());
 
-			let complete = completed == 0;
-			if !complete && !values.update_last_key(completed, &mut self.metadata.last_key) {
-				debug!(target: LOG_TARGET, "Error updating key cursor, depth: {}", completed);
+			let state = &mut self.metadata.chunks[chunk];
+			let truncated = state
+				.until
+				.as_ref()
+				.map_or(false, |until| truncate_key_values(&mut values, until, start.len() == 2));
+			let complete = truncated || completed == 0 || {
+				if !values.update_last_key(completed, &mut state.last_key) {
+					debug!(target: LOG_TARGET, "Error updating key cursor, depth: {}", completed);
+				}
+				state.reached_end()
 			};
 
-			self.process_state_verified(values);
 			self.metadata.imported_bytes += proof_size;
-			complete
+			let levels =
+				values.0.into_iter().map(|level| (level.state_root, level.key_values)).collect();
+			(levels, complete)
 		} else {
-			self.process_state_unverified(response)
+			self.read_state_unverified(chunk, response)
 		};
-		if complete {
+		self.metadata.chunks[chunk].complete = chunk_complete;
+
+		let part = levels.encode();
+		for (state_root, key_values) in levels {
+			self.process_state_key_values(state_root, key_values);
+		}
+
+		if self.metadata.chunks.iter().all(|chunk| chunk.complete) {
+			// The state is not resumed once the download is complete, even if the import fails.
+			self.clear_progress(self.parts);
 			self.metadata.complete = true;
 			let target_hash = self.metadata.target_hash();
 			ImportResult::Import(
@@ -312,15 +719,23 @@ where
 				self.metadata.target_justifications.clone(),
 			)
 		} else {
+			self.persist(part);
 			ImportResult::Continue
 		}
 	}
 
 	/// Produce next state request.
-	fn next_request(&self) -> StateRequest {
+	fn next_request(&mut self) -> Option<(usize, StateRequest)> {
 		self.metadata.next_request()
 	}
 
+	/// Make a chunk available for a new request.
+	fn cancel_request(&mut self, chunk: usize) {
+		if let Some(state) = self.metadata.chunks.get_mut(chunk) {
+			state.requested = false;
+		}
+	}
+
 	/// Check if the state is complete.
 	fn is_complete(&self) -> bool {
 		self.metadata.complete
@@ -341,3 +756,225 @@ where
 		self.metadata.progress()
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use sc_client_api::StorageProvider;
+	use sp_core::storage::ChildInfo;
+	use std::collections::BTreeMap;
+	use substrate_test_runtime_client::{
+		runtime::Block, DefaultTestClientBuilderExt, TestClientBuilder, TestClientBuilderExt,
+	};
+
+	#[test]
+	fn middle_key_works() {
+		assert_eq!(middle_key(&[], None), Some(vec![0x80]));
+		assert_eq!(middle_key(&[0x80], None), Some(vec![0xc0]));
+		assert_eq!(middle_key(&[0x10], Some(&[0x30])), Some(vec![0x20]));
+		assert_eq!(middle_key(&[0x10], Some(&[0x11])), Some(vec![0x10, 0x80]));
+		assert_eq!(middle_key(&[0xff, 0xff], None), Some(vec![0xff, 0xff, 0x80]));
+		assert_eq!(middle_key(&[0x10], Some(&[0x10, 0x00])), None);
+	}
+
+	#[test]
+	fn wider_ranges_are_split_first() {
+		assert!(range_width(&[0x10], None) > range_width(&[0x80], None));
+		assert!(range_width(&[0x10], Some(&[0x30])) > range_width(&[0x40], Some(&[0x41])));
+		// Keys sharing a longer prefix are in a narrower range.
+		assert!(
+			range_width(&[0x10, 0x00], Some(&[0x10, 0xff])) < range_width(&[0x10], Some(&[0x11]))
+		);
+	}
+
+	type Client = substrate_test_runtime_client::TestClient;
+
+	fn client_with_state(child_info: &ChildInfo) -> Arc<Client> {
+		let mut builder = TestClientBuilder::new();
+		for i in 0..64u8 {
+			builder = builder.add_extra_storage(vec![i * 4, i], vec![i; 64]);
+		}
+		for i in 0..16u8 {
+			builder = builder.add_extra_child_storage(child_info, vec![i], vec![i; 64]);
+		}
+		Arc::new(builder.build())
+	}
+
+	/// Download with up to four requests in flight, answering the latest one first. Stops after
+	/// `max_responses` responses, dropping the requests in flight.
+	fn download(
+		state_sync: &mut StateSync<Block, Client>,
+		client: &Client,
+		max_responses: Option<usize>,
+	) -> Option<ImportedState<Block>> {
+		let mut requests = Vec::new();
+		for _ in 0..max_responses.unwrap_or(usize::MAX) {
+			while requests.len() < 4 {
+				let Some(request) = state_sync.next_request() else { break };
+				requests.push(request);
+			}
+			let (chunk, request) = requests.pop().unwrap();
+			let (proof, _) = client
+				.read_proof_collection(state_sync.target_hash(), &request.start, 1024)
+				.unwrap();
+			let response = StateResponse { proof: proof.encode(), ..Default::default() };
+			match state_sync.import(chunk, response) {
+				ImportResult::Import(_, _, state, _, _) => {
+					assert!(requests.is_empty());
+					return Some(state)
+				},
+				ImportResult::Continue => (),
+				ImportResult::BadResponse => panic!("Valid response is rejected"),
+			}
+		}
+		None
+	}
+
+	fn assert_state(
+		client: &Client,
+		child_info: &ChildInfo,
+		hash: <Block as BlockT>::Hash,
+		state: ImportedState<Block>,
+	) {
+		let mut top = BTreeMap::new();
+		let mut child = BTreeMap::new();
+		for level in state.state.0 {
+			if level.state_root.is_empty() {
+				top.extend(level.key_values);
+			} else {
+				assert_eq!(
+					level.parent_storage_keys,
+					vec![child_info.prefixed_storage_key().into_inner()]
+				);
+				child.extend(level.key_values);
+			}
+		}
+		let expected_top: BTreeMap<_, _> = client
+			.storage_pairs(hash, None, None)
+			.unwrap()
+			.filter(|(key, _)| !well_known_keys::is_child_storage_key(&key.0))
+			.map(|(key, value)| (key.0, value.0))
+			.collect();
+		assert_eq!(top, expected_top);
+		assert_eq!(child, (0..16u8).map(|i| (vec![i], vec![i; 64])).collect::<BTreeMap<_, _>>());
+	}
+
+	#[test]
+	fn parallel_download_imports_whole_state() {
+		let child_info = ChildInfo::new_default(b"child");
+		let client = client_with_state(&child_info);
+		let hash = client.info().genesis_hash;
+		let header = client.header(hash).unwrap().unwrap();
+
+		let mut state_sync = StateSync::new(client.clone(), header, None, None, false);
+		let state = download(&mut state_sync, &client, None).unwrap();
+		assert!(state_sync.metadata.chunks.len() >= 4);
+		assert!(state_sync.is_complete());
+		assert_eq!(state_sync.progress().percentage, 100);
+		assert_state(&client, &child_info, hash, state);
+	}
+
+	#[test]
+	fn interrupted_sync_resumes() {
+		let child_info = ChildInfo::new_default(b"child");
+		let client = client_with_state(&child_info);
+		let hash = client.info().genesis_hash;
+		let header = client.header(hash).unwrap().unwrap();
+
+		let mut state_sync = StateSync::new(client.clone(), header.clone(), None, None, false);
+		assert!(download(&mut state_sync, &client, Some(3)).is_none());
+		let imported_bytes = state_sync.metadata.imported_bytes;
+		drop(state_sync);
+		assert!(client.get_aux(PROGRESS_KEY).unwrap().is_some());
+
+		let mut state_sync = StateSync::new(client.clone(), header, None, None, false);
+		assert_eq!(state_sync.parts, 3);
+		assert_eq!(state_sync.metadata.imported_bytes, imported_bytes);
+		assert!(state_sync.metadata.chunks.iter().all(|chunk| !chunk.requested));
+		let state = download(&mut state_sync, &client, None).unwrap();
+		assert_state(&client, &child_info, hash, state);
+
+		// The progress is removed once the download is complete.
+		assert!(client.get_aux(TARGET_KEY).unwrap().is_none());
+		assert!(client.get_aux(PROGRESS_KEY).unwrap().is_none());
+		assert!((0..3).all(|index| client.get_aux(&part_key(index)).unwrap().is_none()));
+		let state_sync = StateSync::new(
+			client.clone(),
+			client.header(hash).unwrap().unwrap(),
+			None,
+			None,
+			false,
+		);
+		assert_eq!(state_sync.parts, 0);
+		assert_eq!(state_sync.metadata.imported_bytes, 0);
+	}
+
+	#[test]
+	fn only_recent_targets_are_resumed() {
+		let child_info = ChildInfo::new_default(b"child");
+		let client = client_with_state(&child_info);
+		let hash = client.info().genesis_hash;
+		let header = client.header(hash).unwrap().unwrap();
+		let descendant = |number| {
+			<Block as BlockT>::Header::new(
+				number,
+				Default::default(),
+				Default::default(),
+				hash,
+				Default::default(),
+			)
+		};
+
+		let mut state_sync = StateSync::new(client.clone(), header.clone(), None, None, false);
+		download(&mut state_sync, &client, Some(1));
+		let state_sync = StateSync::new(client.clone(), descendant(100), None, None, false);
+		assert_eq!(state_sync.target_hash(), hash);
+		assert_eq!(state_sync.parts, 1);
+
+		// The resumed target is too old and its state might be pruned before the download is
+		// complete.
+		let state_sync = StateSync::new(client.clone(), descendant(200), None, None, false);
+		assert_eq!(state_sync.target_number(), 200);
+		assert_eq!(state_sync.parts, 0);
+		assert!(client.get_aux(PROGRESS_KEY).unwrap().is_none());
+		assert!(client.get_aux(&part_key(0)).unwrap().is_none());
+
+		// Progress downloaded with proofs is not used to sync without them, and vice versa.
+		let mut state_sync = StateSync::new(client.clone(), header.clone(), None, None, false);
+		download(&mut state_sync, &client, Some(1));
+		let state_sync = StateSync::new(client.clone(), header, None, None, true);
+		assert_eq!(state_sync.parts, 0);
+		assert!(client.get_aux(PROGRESS_KEY).unwrap().is_none());
+
+		// A resumed target that is not on the chain known to the client is discarded.
+		let mut state_sync = StateSync::new(client.clone(), descendant(0), None, None, false);
+		state_sync.persist(Vec::<StateLevel>::new().encode());
+		let state_sync = StateSync::new(client.clone(), descendant(100), None, None, false);
+		assert_eq!(state_sync.target_number(), 100);
+		assert_eq!(state_sync.parts, 0);
+		assert!(client.get_aux(TARGET_KEY).unwrap().is_none());
+	}
+
+	#[test]
+	fn bad_response_makes_chunk_available_again() {
+		let client = Arc::new(TestClientBuilder::new().build());
+		let header = client.header(client.info().genesis_hash).unwrap().unwrap();
+		let mut state_sync = StateSync::<Block, _>::new(client, header, None, None, false);
+
+		let (chunk, request) = state_sync.next_request().unwrap();
+		let (second_chunk, _) = state_sync.next_request().unwrap();
+		assert_ne!(chunk, second_chunk);
+
+		let response = StateResponse { proof: vec![1, 2, 3], ..Default::default() };
+		assert!(matches!(state_sync.import(chunk, response), ImportResult::BadResponse));
+		// Responses are only accepted for chunks with a request in flight.
+		assert!(matches!(
+			state_sync.import(chunk, StateResponse::default()),
+			ImportResult::BadResponse
+		));
+		assert_eq!(state_sync.next_request().unwrap(), (chunk, request));
+
+		state_sync.cancel_request(second_chunk);
+		assert_eq!(state_sync.next_request().unwrap().0, second_chunk);
+	}
+}
@@ -53,9 +53,10 @@ use sp_runtime::traits::{self, Header};
 use threadpool::ThreadPool;
 
 mod api;
+pub mod tasks;
 
 pub use sp_core::offchain::storage::OffchainDb;
-pub use sp_offchain::{OffchainWorkerApi, STORAGE_PREFIX};
+pub use sp_offchain::{OffchainWorkerApi, TasksApi, STORAGE_PREFIX};
 
 const LOG_TARGET: &str = "offchain-worker";
 
@@ -0,0 +1,258 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Submission of the tasks of the runtime.
+//!
+//! The [`TaskWorker`] asks the runtime for the tasks that are valid after every new best block,
+//! using the [`TasksApi`], and submits the extrinsics executing them to the transaction pool.
+//!
+//! A task is not submitted again while it stays valid, until
+//! [`TaskWorkerOptions::resubmit_after`] blocks have passed, in case its transaction was dropped.
+//! The number of valid tasks fetched from the runtime, and the number and the weight of the tasks
+//! submitted after a block are limited, so that the tasks do not flood the transaction pool.
+
+use std::{collections::HashMap, sync::Arc};
+
+use codec::Encode;
+use futures::StreamExt;
+use sc_client_api::BlockchainEvents;
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_offchain::TasksApi;
+use sp_runtime::{
+	traits::{self, Header, NumberFor, Saturating},
+	Weight,
+};
+
+const LOG_TARGET: &str = "offchain-tasks";
+
+/// Options for [`TaskWorker`].
+#[derive(Debug, Clone)]
+pub struct TaskWorkerOptions {
+	/// Maximum number of valid tasks fetched from the runtime after a block.
+	pub max_valid_tasks: u32,
+	/// Maximum number of tasks submitted after a block.
+	pub max_tasks_per_block: usize,
+	/// Maximum total weight of the tasks submitted after a block.
+	pub max_weight_per_block: Weight,
+	/// Number of blocks after which a task that is still valid is submitted again.
+	pub resubmit_after: u32,
+}
+
+impl Default for TaskWorkerOptions {
+	fn default() -> Self {
+		Self {
+			max_valid_tasks: 256,
+			max_tasks_per_block: 16,
+			max_weight_per_block: Weight::MAX,
+			resubmit_after: 32,
+		}
+	}
+}
+
+/// Submits the valid tasks of the runtime as unsigned transactions.
+pub struct TaskWorker<RA, Block: traits::Block> {
+	runtime_api_provider: Arc<RA>,
+	transaction_pool: OffchainTransactionPoolFactory<Block>,
+	options: TaskWorkerOptions,
+	/// The SCALE encoded tasks that were submitted, with the block they were submitted after.
+	submitted: HashMap<Vec<u8>, NumberFor<Block>>,
+}
+
+impl<RA, Block: traits::Block> TaskWorker<RA, Block> {
+	/// Creates new [`TaskWorker`].
+	pub fn new(
+		runtime_api_provider: Arc<RA>,
+		transaction_pool: OffchainTransactionPoolFactory<Block>,
+		options: TaskWorkerOptions,
+	) -> Self {
+		Self { runtime_api_provider, transaction_pool, options, submitted: HashMap::new() }
+	}
+
+	/// Select the tasks to submit after block `number` among the valid `tasks`.
+	fn select(&mut self, number: NumberFor<Block>, tasks: Vec<(Vec<u8>, Weight)>) -> Vec<Vec<u8>> {
+		let resubmit_after: NumberFor<Block> = self.options.resubmit_after.into();
+		// Tasks that are not valid anymore were executed, and may be submitted again once valid.
+		// When the valid tasks were truncated, the missing ones are only forgotten once they may be
+		// submitted again anyway.
+		let truncated = tasks.len() >= self.options.max_valid_tasks as usize;
+		self.submitted.retain(|submitted, at| {
+			tasks.iter().any(|(task, _)| task == submitted) ||
+				(truncated && number.saturating_sub(*at) < resubmit_after)
+		});
+
+		let mut remaining_weight = self.options.max_weight_per_block;
+		tasks
+			.into_iter()
+			.filter(|(task, _)| {
+				self.submitted
+					.get(task)
+					.map_or(true, |at| number.saturating_sub(*at) >= resubmit_after)
+			})
+			.filter(|(_, weight)| {
+				if weight.any_gt(remaining_weight) {
+					return false
+				}
+				remaining_weight = remaining_weight.saturating_sub(*weight);
+				true
+			})
+			.take(self.options.max_tasks_per_block)
+			.map(|(task, _)| task)
+			.collect()
+	}
+}
+
+impl<RA, Block> TaskWorker<RA, Block>
+where
+	Block: traits::Block,
+	RA: ProvideRuntimeApi<Block> + Send + Sync + 'static,
+	RA::Api: TasksApi<Block>,
+{
+	/// Submit the valid tasks on every new best block.
+	pub async fn run<BE: BlockchainEvents<Block>>(mut self, import_events: Arc<BE>) {
+		let mut notifications = import_events.import_notification_stream();
+		while let Some(notification) = notifications.next().await {
+			if notification.is_new_best {
+				self.on_new_best_block(&notification.header);
+			}
+		}
+	}
+
+	fn on_new_best_block(&mut self, header: &Block::Header) {
+		let client = self.runtime_api_provider.clone();
+		let runtime = client.runtime_api();
+		let hash = header.hash();
+		match runtime.has_api::<dyn TasksApi<Block>>(hash) {
+			Ok(true) => {},
+			Ok(false) => {
+				tracing::trace!(target: LOG_TARGET, "Runtime at {hash:?} has no tasks");
+				return
+			},
+			Err(e) => {
+				tracing::error!(target: LOG_TARGET, "Error checking tasks api at {hash:?}: {e}");
+				return
+			},
+		}
+
+		let tasks = match runtime.valid_tasks(hash, self.options.max_valid_tasks) {
+			Ok(tasks) => tasks,
+			Err(e) => {
+				tracing::error!(target: LOG_TARGET, "Error getting valid tasks at {hash:?}: {e}");
+				return
+			},
+		};
+
+		let mut pool = self.transaction_pool.offchain_transaction_pool(hash);
+		for task in self.select(*header.number(), tasks) {
+			let extrinsic = match runtime.task_extrinsic(hash, task.clone()) {
+				Ok(Some(extrinsic)) => extrinsic,
+				Ok(None) => {
+					tracing::debug!(target: LOG_TARGET, "Task cannot be executed at {hash:?}");
+					continue
+				},
+				Err(e) => {
+					tracing::error!(
+						target: LOG_TARGET,
+						"Error creating task extrinsic at {hash:?}: {e}",
+					);
+					continue
+				},
+			};
+
+			if pool.submit_transaction(extrinsic.encode()).is_err() {
+				tracing::debug!(target: LOG_TARGET, "Task transaction rejected at {hash:?}");
+				continue
+			}
+			self.submitted.insert(task, *header.number());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_transaction_pool::BasicPool;
+	use substrate_test_runtime_client::{runtime::Block, Backend, Client};
+
+	fn worker(options: TaskWorkerOptions) -> TaskWorker<Client<Backend>, Block> {
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let pool = Arc::from(BasicPool::new_full(
+			Default::default(),
+			true.into(),
+			None,
+			spawner,
+			client.clone(),
+		));
+		TaskWorker::new(client, OffchainTransactionPoolFactory::new(pool), options)
+	}
+
+	#[test]
+	fn tasks_are_rate_limited() {
+		let mut worker = worker(TaskWorkerOptions {
+			max_tasks_per_block: 2,
+			max_weight_per_block: Weight::from_parts(10, 10),
+			..Default::default()
+		});
+		let task = |i: u8, weight| (vec![i], Weight::from_parts(weight, 0));
+
+		assert_eq!(
+			worker.select(1, vec![task(0, 1), task(1, 1), task(2, 1)]),
+			vec![vec![0], vec![1]]
+		);
+		// Tasks above the remaining weight are skipped.
+		assert_eq!(worker.select(2, vec![task(2, 20), task(3, 6), task(4, 6)]), vec![vec![3]]);
+	}
+
+	#[test]
+	fn tasks_are_not_resubmitted_while_valid() {
+		let mut worker = worker(TaskWorkerOptions { resubmit_after: 4, ..Default::default() });
+		let tasks = || vec![(vec![0], Weight::zero()), (vec![1], Weight::zero())];
+
+		let selected = worker.select(1, tasks());
+		assert_eq!(selected, vec![vec![0], vec![1]]);
+		selected.into_iter().for_each(|task| {
+			worker.submitted.insert(task, 1);
+		});
+
+		assert!(worker.select(2, tasks()).is_empty());
+		assert_eq!(worker.select(5, tasks()), vec![vec![0], vec![1]]);
+
+		// Once executed, a task is submitted again when it becomes valid again.
+		assert!(worker.select(3, vec![(vec![1], Weight::zero())]).is_empty());
+		assert_eq!(worker.select(3, tasks()), vec![vec![0]]);
+	}
+
+	#[test]
+	fn submitted_tasks_are_kept_while_truncated() {
+		let mut worker = worker(TaskWorkerOptions {
+			max_valid_tasks: 1,
+			resubmit_after: 4,
+			..Default::default()
+		});
+
+		assert_eq!(worker.select(1, vec![(vec![0], Weight::zero())]), vec![vec![0]]);
+		worker.submitted.insert(vec![0], 1);
+
+		// The submitted task may be beyond the truncated valid tasks.
+		assert_eq!(worker.select(2, vec![(vec![1], Weight::zero())]), vec![vec![1]]);
+		assert!(worker.submitted.contains_key(&vec![0]));
+		assert_eq!(worker.select(5, vec![(vec![1], Weight::zero())]), vec![vec![1]]);
+		assert!(!worker.submitted.contains_key(&vec![0]));
+	}
+}
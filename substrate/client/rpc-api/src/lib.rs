@@ -33,6 +33,9 @@ pub mod child_state;
 pub mod dev;
 pub mod mixnet;
 pub mod offchain;
+pub mod pruning;
+pub mod snapshot;
 pub mod state;
 pub mod statement;
 pub mod system;
+pub mod txpool;
@@ -0,0 +1,57 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate state pruning API.
+
+pub mod error;
+
+use error::Error;
+use jsonrpsee::proc_macros::rpc;
+use serde::{Deserialize, Serialize};
+
+/// A state kept regardless of the pruning mode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeptState<Hash> {
+	/// Hash of the block.
+	pub hash: Hash,
+	/// Number of the block.
+	pub number: u64,
+}
+
+/// Substrate state pruning RPC API.
+///
+/// Allows keeping the state of chosen blocks on a running node. All methods are unsafe.
+#[rpc(client, server)]
+pub trait PruningApi<Hash> {
+	/// Keeps the state of the canonical block `hash` regardless of the pruning mode, until it is
+	/// released with `pruning_releaseState`.
+	///
+	/// The state of the block must not have been pruned yet. This walks the whole state of the
+	/// block, so it may take a while.
+	#[method(name = "pruning_keepState", with_extensions, blocking)]
+	fn keep_state(&self, hash: Hash) -> Result<(), Error>;
+
+	/// Allows pruning of a state previously kept with `pruning_keepState`.
+	#[method(name = "pruning_releaseState", with_extensions, blocking)]
+	fn release_state(&self, hash: Hash) -> Result<(), Error>;
+
+	/// Returns the states kept regardless of the pruning mode, ordered by block number.
+	#[method(name = "pruning_keptStates", with_extensions)]
+	fn kept_states(&self) -> Result<Vec<KeptState<Hash>>, Error>;
+}
@@ -0,0 +1,57 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Error helpers for the state snapshot RPC module.
+
+use jsonrpsee::types::error::{ErrorObject, ErrorObjectOwned};
+
+/// State snapshot RPC Result type.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// State snapshot RPC errors.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// Failed to create the snapshot file.
+	#[error("Failed to create the snapshot file: {0}")]
+	Io(#[from] std::io::Error),
+	/// Failed to export the snapshot.
+	#[error("Failed to export the snapshot: {0}")]
+	Export(Box<dyn std::error::Error + Send + Sync>),
+	/// The block is unknown or not finalized.
+	#[error("Block {0} is unknown or not finalized")]
+	NotFinalized(String),
+	/// Call to an unsafe RPC was denied.
+	#[error(transparent)]
+	UnsafeRpcCalled(#[from] crate::policy::UnsafeRpcError),
+}
+
+/// Base error code for all snapshot errors.
+const BASE_ERROR: i32 = crate::error::base::SNAPSHOT;
+
+impl From<Error> for ErrorObjectOwned {
+	fn from(e: Error) -> Self {
+		let msg = e.to_string();
+
+		match e {
+			Error::Io(_) => ErrorObject::owned(BASE_ERROR + 1, msg, None::<()>),
+			Error::Export(_) => ErrorObject::owned(BASE_ERROR + 2, msg, None::<()>),
+			Error::NotFinalized(_) => ErrorObject::owned(BASE_ERROR + 3, msg, None::<()>),
+			Error::UnsafeRpcCalled(e) => e.into(),
+		}
+	}
+}
@@ -0,0 +1,50 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate state snapshot API.
+
+pub mod error;
+
+use error::Error;
+use jsonrpsee::proc_macros::rpc;
+use serde::{Deserialize, Serialize};
+
+/// Summary of a snapshot written by the `snapshot_export` RPC.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedSnapshot<Hash> {
+	/// Hash of the snapshot block.
+	pub hash: Hash,
+	/// Number of the snapshot block.
+	pub number: u64,
+	/// Number of trie nodes in the snapshot.
+	pub nodes: u64,
+}
+
+/// Substrate state snapshot RPC API.
+///
+/// Allows exporting snapshots of finalized state from a running node. All methods are unsafe.
+#[rpc(client, server)]
+pub trait SnapshotApi<Hash> {
+	/// Writes a snapshot of a finalized block and its state to `path` on the node's host.
+	///
+	/// Defaults to the last finalized block. The snapshot can be loaded into a fresh database with
+	/// the `import-snapshot` command.
+	#[method(name = "snapshot_export", with_extensions, blocking)]
+	fn export(&self, path: String, at: Option<Hash>) -> Result<ExportedSnapshot<Hash>, Error>;
+}
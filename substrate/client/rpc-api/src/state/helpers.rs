@@ -30,3 +30,23 @@ pub struct ReadProof<Hash> {
 	/// A proof used to prove that storage entries are included in the storage trie
 	pub proof: Vec<Bytes>,
 }
+
+/// Arguments of a view function.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ViewFunctionArgs {
+	/// The SCALE encoded arguments, concatenated.
+	Scale(Bytes),
+	/// The arguments as JSON values, encoded using the types of the metadata.
+	Json(Vec<serde_json::Value>),
+}
+
+/// Result of a view function.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewFunctionResult {
+	/// The SCALE encoded result.
+	pub result: Bytes,
+	/// The result decoded as JSON using the types of the metadata.
+	pub decoded: serde_json::Value,
+}
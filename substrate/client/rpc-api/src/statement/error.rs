@@ -29,6 +29,9 @@ pub enum Error {
 	/// Statement store internal error.
 	#[error("Statement store error")]
 	StatementStore(String),
+	/// Invalid parameters in a statement store query.
+	#[error("Invalid params: {0}")]
+	InvalidParams(String),
 	/// Call to an unsafe RPC was denied.
 	#[error(transparent)]
 	UnsafeRpcCalled(#[from] crate::policy::UnsafeRpcError),
@@ -45,6 +48,8 @@ impl From<Error> for ErrorObjectOwned {
 				format!("Statement store error: {message}"),
 				None::<()>,
 			),
+			Error::InvalidParams(message) =>
+				ErrorObject::owned(BASE_ERROR + 2, format!("Invalid params: {message}"), None::<()>),
 			Error::UnsafeRpcCalled(e) => e.into(),
 		}
 	}
@@ -19,10 +19,21 @@
 //! Substrate Statement Store RPC API.
 
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde::{Deserialize, Serialize};
 use sp_core::Bytes;
 
 pub mod error;
 
+/// A page of statements returned by `statement_page`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementPage {
+	/// SCALE-encoded statements, ordered by hash.
+	pub statements: Vec<Bytes>,
+	/// Cursor to pass as `after` to fetch the next page, `None` if this is the last page.
+	pub next: Option<[u8; 32]>,
+}
+
 /// Substrate statement RPC API
 #[rpc(client, server)]
 pub trait StatementApi {
@@ -85,6 +96,32 @@ pub trait StatementApi {
 		dest: [u8; 32],
 	) -> RpcResult<Vec<Bytes>>;
 
+	/// Return a page of the known statements which include all topics and whose decryption key is
+	/// identified as `dest`, or which have no `DecryptionKey` field if `dest` is `None`.
+	///
+	/// Statements are SCALE-encoded and ordered by hash. Pass the `next` cursor of a page as
+	/// `after` to fetch the following one. `limit` is capped by the server.
+	#[method(name = "statement_page")]
+	fn page(
+		&self,
+		match_all_topics: Vec<[u8; 32]>,
+		dest: Option<[u8; 32]>,
+		after: Option<[u8; 32]>,
+		limit: u32,
+	) -> RpcResult<StatementPage>;
+
+	/// Subscribe to the statements which include all topics and whose decryption key is
+	/// identified as `dest`, or which have no `DecryptionKey` field if `dest` is `None`.
+	///
+	/// SCALE-encoded statements are pushed as they are added to the store. Use `statement_page`
+	/// to fetch the statements that are already known.
+	#[subscription(
+		name = "statement_subscribe" => "statement_statement",
+		unsubscribe = "statement_unsubscribe",
+		item = Bytes,
+	)]
+	fn subscribe_statements(&self, match_all_topics: Vec<[u8; 32]>, dest: Option<[u8; 32]>);
+
 	/// Submit a pre-encoded statement.
 	#[method(name = "statement_submit")]
 	fn submit(&self, encoded: Bytes) -> RpcResult<()>;
@@ -0,0 +1,45 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate transaction pool inspection API.
+
+pub mod error;
+
+use error::Error;
+use jsonrpsee::proc_macros::rpc;
+use sc_transaction_pool_api::inspect::{TransactionInspection, TransactionPoolContent};
+
+/// Substrate transaction pool inspection RPC API.
+///
+/// Intended for debugging transactions stuck in the pool. All methods are unsafe.
+#[rpc(client, server)]
+pub trait TxPoolApi<BlockHash, Hash> {
+	/// Returns the detailed content of the transaction pool.
+	///
+	/// Reports all transactions held by the pool, and the ready and future transactions of every
+	/// view, with their tags, priority and validity.
+	#[method(name = "txpool_content", with_extensions)]
+	async fn content(&self) -> Result<TransactionPoolContent<BlockHash, Hash>, Error>;
+
+	/// Returns the details of a single transaction in the transaction pool.
+	///
+	/// Reports whether the transaction is banned, even if it is not held by the pool anymore, and
+	/// the content of the pool restricted to the transaction and to the views containing it.
+	#[method(name = "txpool_inspect", with_extensions)]
+	async fn inspect(&self, hash: Hash) -> Result<TransactionInspection<BlockHash, Hash>, Error>;
+}
@@ -21,13 +21,14 @@
 use crate::{
 	archive::{
 		error::{Error, Infallible},
-		types::MethodResult,
+		types::{ArchiveBlockPhases, ArchiveEvent, ArchiveStorageChange, MethodResult},
 	},
 	common::events::{
 		ArchiveStorageDiffEvent, ArchiveStorageDiffItem, ArchiveStorageEvent, StorageQuery,
 	},
 };
 use jsonrpsee::proc_macros::rpc;
+use sc_rpc::state::{ViewFunctionArgs, ViewFunctionResult};
 
 #[rpc(client, server)]
 pub trait ArchiveApi<Hash> {
@@ -97,6 +98,55 @@ pub trait ArchiveApi<Hash> {
 		call_parameters: String,
 	) -> Result<MethodResult, Error>;
 
+	/// Call a view function of a pallet at a specified block's state.
+	///
+	/// The view function is looked up by name in the metadata, whose types are used to encode
+	/// JSON arguments and to decode the result.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "archive_v1_callViewFunction", blocking)]
+	fn archive_v1_call_view_function(
+		&self,
+		hash: Hash,
+		pallet: String,
+		function: String,
+		args: ViewFunctionArgs,
+	) -> Result<ViewFunctionResult, Error>;
+
+	/// Get the events deposited while executing a block, grouped by extrinsic.
+	///
+	/// Events are decoded up to the indices of the pallet and of the event. The block is
+	/// re-executed to attribute the events to the phase they were deposited in. If no block with
+	/// that hash is found, null. Only a few blocks are re-executed at the same time, further calls
+	/// fail until one of them completes.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "archive_unstable_events", blocking)]
+	fn archive_unstable_events(
+		&self,
+		hash: Hash,
+	) -> Result<Option<ArchiveBlockPhases<ArchiveEvent>>, Error>;
+
+	/// Get the storage changes made while executing a block, grouped by extrinsic.
+	///
+	/// The changes of each phase are returned in the order they were made, without the changes
+	/// reverted by the runtime. The block is re-executed to collect the changes. If no block with
+	/// that hash is found, null. Only a few blocks are re-executed at the same time, further calls
+	/// fail until one of them completes.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "archive_unstable_storageChanges", blocking)]
+	fn archive_unstable_storage_changes(
+		&self,
+		hash: Hash,
+	) -> Result<Option<ArchiveBlockPhases<ArchiveStorageChange>>, Error>;
+
 	/// Returns storage entries at a specific block's state.
 	///
 	/// # Unstable
@@ -21,8 +21,9 @@
 use crate::{
 	archive::{
 		archive_storage::ArchiveStorageDiff,
+		archive_trace::{block_events, block_storage_changes},
 		error::{Error as ArchiveError, Infallible},
-		types::MethodResult,
+		types::{ArchiveBlockPhases, ArchiveEvent, ArchiveStorageChange, MethodResult},
 		ArchiveApiServer,
 	},
 	common::{
@@ -41,8 +42,14 @@ use sc_client_api::{
 	Backend, BlockBackend, BlockchainEvents, CallExecutor, ChildInfo, ExecutorProvider, StorageKey,
 	StorageProvider,
 };
-use sc_rpc::utils::Subscription;
-use sp_api::{CallApiAt, CallContext};
+use sc_rpc::{
+	state::{
+		view_functions::{self, ViewFunctionError},
+		ViewFunctionArgs, ViewFunctionResult,
+	},
+	utils::Subscription,
+};
+use sp_api::{CallApiAt, CallContext, Metadata, ProvideRuntimeApi};
 use sp_blockchain::{
 	Backend as BlockChainBackend, Error as BlockChainError, HeaderBackend, HeaderMetadata,
 };
@@ -53,7 +60,7 @@ use sp_runtime::{
 };
 use std::{collections::HashSet, marker::PhantomData, sync::Arc};
 
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Semaphore};
 
 pub(crate) const LOG_TARGET: &str = "rpc-spec-v2::archive";
 
@@ -63,6 +70,10 @@ pub(crate) const LOG_TARGET: &str = "rpc-spec-v2::archive";
 /// its down buffer capacity per connection as well.
 const STORAGE_QUERY_BUF: usize = 16;
 
+/// Maximum number of blocks re-executed at the same time by `archive_unstable_events` and
+/// `archive_unstable_storageChanges`. Further calls are rejected until one of them completes.
+const MAX_CONCURRENT_BLOCK_TRACES: usize = 2;
+
 /// An API for archive RPC calls.
 pub struct Archive<BE: Backend<Block>, Block: BlockT, Client> {
 	/// Substrate client.
@@ -73,6 +84,10 @@ pub struct Archive<BE: Backend<Block>, Block: BlockT, Client> {
 	executor: SubscriptionTaskExecutor,
 	/// The hexadecimal encoded hash of the genesis block.
 	genesis_hash: String,
+	/// Limits the number of blocks re-executed at the same time.
+	block_traces: Arc<Semaphore>,
+	/// The metadata listing the view functions of the runtime.
+	view_functions_metadata: view_functions::MetadataCache,
 	/// Phantom member to pin the block type.
 	_phantom: PhantomData<Block>,
 }
@@ -86,7 +101,15 @@ impl<BE: Backend<Block>, Block: BlockT, Client> Archive<BE, Block, Client> {
 		executor: SubscriptionTaskExecutor,
 	) -> Self {
 		let genesis_hash = hex_string(&genesis_hash.as_ref());
-		Self { client, backend, executor, genesis_hash, _phantom: PhantomData }
+		Self {
+			client,
+			backend,
+			executor,
+			genesis_hash,
+			block_traces: Arc::new(Semaphore::new(MAX_CONCURRENT_BLOCK_TRACES)),
+			view_functions_metadata: Default::default(),
+			_phantom: PhantomData,
+		}
 	}
 }
 
@@ -114,8 +137,12 @@ where
 		+ HeaderMetadata<Block, Error = BlockChainError>
 		+ BlockchainEvents<Block>
 		+ CallApiAt<Block>
+		+ ProvideRuntimeApi<Block>
 		+ StorageProvider<Block, BE>
+		+ Send
+		+ Sync
 		+ 'static,
+	Client::Api: Metadata<Block>,
 {
 	fn archive_v1_body(&self, hash: Block::Hash) -> Result<Option<Vec<String>>, Infallible> {
 		let Ok(Some(signed_block)) = self.client.block(hash) else { return Ok(None) };
@@ -214,6 +241,50 @@ where
 		})
 	}
 
+	fn archive_v1_call_view_function(
+		&self,
+		hash: Block::Hash,
+		pallet: String,
+		function: String,
+		args: ViewFunctionArgs,
+	) -> Result<ViewFunctionResult, ArchiveError> {
+		let version = self
+			.client
+			.executor()
+			.runtime_version(hash)
+			.map_err(|e| ArchiveError::RuntimeCall(e.to_string()))?;
+		view_functions::call_view_function(
+			&self.view_functions_metadata,
+			&version,
+			|method, data| self.client.executor().call(hash, method, &data, CallContext::Offchain),
+			&pallet,
+			&function,
+			args,
+		)
+		.map_err(|e| match e {
+			ViewFunctionError::InvalidParam(e) => ArchiveError::InvalidParam(e),
+			ViewFunctionError::RuntimeCall(e) => ArchiveError::RuntimeCall(e),
+		})
+	}
+
+	fn archive_unstable_events(
+		&self,
+		hash: Block::Hash,
+	) -> Result<Option<ArchiveBlockPhases<ArchiveEvent>>, ArchiveError> {
+		let _permit =
+			self.block_traces.try_acquire().map_err(|_| ArchiveError::TooManyBlockTraces)?;
+		block_events(&self.client, hash)
+	}
+
+	fn archive_unstable_storage_changes(
+		&self,
+		hash: Block::Hash,
+	) -> Result<Option<ArchiveBlockPhases<ArchiveStorageChange>>, ArchiveError> {
+		let _permit =
+			self.block_traces.try_acquire().map_err(|_| ArchiveError::TooManyBlockTraces)?;
+		block_storage_changes(&self.client, hash)
+	}
+
 	fn archive_v1_storage(
 		&self,
 		pending: PendingSubscriptionSink,
@@ -0,0 +1,531 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Events and storage changes of the extrinsics of a block.
+//!
+//! The block is re-executed with the block tracing of `sc-tracing`, which records the storage
+//! operations of the runtime. Operations of rolled back storage transactions are discarded and
+//! the remaining ones are attributed to the execution phase `frame_system` stored when they were
+//! made. The storage keys of `frame_system`, the encoding of the phases and the types of the event
+//! records are read from the metadata of the runtime.
+
+use crate::{
+	archive::{
+		error::Error as ArchiveError,
+		types::{ArchiveBlockPhases, ArchiveEvent, ArchiveStorageChange, ArchiveStorageChangeType},
+	},
+	hex_string,
+};
+
+use codec::{Compact, Decode};
+use frame_metadata::{v14::StorageEntryType, RuntimeMetadata, RuntimeMetadataPrefixed};
+use sc_client_api::BlockBackend;
+use sc_tracing::block::BlockExecutor;
+use scale_info::{PortableRegistry, TypeDef, TypeDefPrimitive};
+use sp_api::{Metadata, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::{hexdisplay::HexDisplay, twox_128};
+use sp_rpc::tracing::{Data, TraceBlockResponse};
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+
+/// Tracing target of the storage operations.
+const TRACE_TARGETS: &str = "state";
+
+/// Storage operations that change the state or delimit a storage transaction.
+const TRACE_METHODS: &str = "Put,ChildPut,ChildKill,ClearPrefix,ChildClearPrefix,Append,\
+	StartTransaction,CommitTransaction,RollbackTransaction";
+
+/// Version of the metadata the storage layout of `frame_system` is read from. Older runtimes only
+/// provide version 14, which describes storage the same way.
+const METADATA_VERSION: u32 = 15;
+
+/// Execution phase of a block, as stored by `frame_system`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+	ApplyExtrinsic(u32),
+	Finalization,
+	Initialization,
+}
+
+impl<T> ArchiveBlockPhases<T> {
+	fn push(&mut self, phase: Phase, item: T) {
+		match phase {
+			Phase::Initialization => self.initialization.push(item),
+			Phase::ApplyExtrinsic(index) => {
+				let index = index as usize;
+				if self.extrinsics.len() <= index {
+					self.extrinsics.resize_with(index + 1, Vec::new);
+				}
+				self.extrinsics[index].push(item);
+			},
+			Phase::Finalization => self.finalization.push(item),
+		}
+	}
+}
+
+/// Storage layout of the `frame_system` items the phases and events are read from.
+struct SystemLayout {
+	/// Storage key of `ExecutionPhase`, hex-encoded as in the traces.
+	execution_phase_key: String,
+	/// Storage key of `Events`, hex-encoded as in the traces.
+	events_key: String,
+	/// Variant indices of `ApplyExtrinsic`, `Finalization` and `Initialization`.
+	phase_indices: [u8; 3],
+	/// Types of the runtime.
+	types: PortableRegistry,
+	/// Type of the `event` field of the event records.
+	event_type: u32,
+	/// Type of the topics of the event records.
+	topic_type: u32,
+}
+
+impl SystemLayout {
+	/// Read the layout from the metadata of the runtime of the block `hash`.
+	fn from_metadata<Block, Client>(
+		client: &Client,
+		hash: Block::Hash,
+	) -> Result<Self, ArchiveError>
+	where
+		Block: BlockT,
+		Client: ProvideRuntimeApi<Block>,
+		Client::Api: Metadata<Block>,
+	{
+		let invalid =
+			|error: String| ArchiveError::BlockTrace(format!("Invalid metadata: {error}"));
+		let api = client.runtime_api();
+		let metadata = match api
+			.metadata_at_version(hash, METADATA_VERSION)
+			.map_err(|error| ArchiveError::RuntimeCall(error.to_string()))?
+		{
+			Some(metadata) => metadata,
+			None => api
+				.metadata(hash)
+				.map_err(|error| ArchiveError::RuntimeCall(error.to_string()))?,
+		};
+		let metadata = RuntimeMetadataPrefixed::decode(&mut &metadata[..])
+			.map_err(|error| invalid(error.to_string()))?
+			.1;
+		let (storages, types): (Vec<_>, _) = match &metadata {
+			RuntimeMetadata::V14(metadata) => (
+				metadata.pallets.iter().filter_map(|pallet| pallet.storage.as_ref()).collect(),
+				&metadata.types,
+			),
+			RuntimeMetadata::V15(metadata) => (
+				metadata.pallets.iter().filter_map(|pallet| pallet.storage.as_ref()).collect(),
+				&metadata.types,
+			),
+			_ => return Err(invalid("unsupported version".into())),
+		};
+
+		// `frame_system` is found by its storage items, as its name depends on the runtime.
+		let (storage, events, phase) = storages
+			.into_iter()
+			.find_map(|storage| {
+				let entry = |name| storage.entries.iter().find(|entry| entry.name == name);
+				Some((storage, entry("Events")?, entry("ExecutionPhase")?))
+			})
+			.ok_or_else(|| invalid("`frame_system` storage not found".into()))?;
+
+		// `Events` is a vector of records made of the phase, the event and its topics.
+		let StorageEntryType::Plain(events) = &events.ty else {
+			return Err(invalid("`Events` is not a storage value".into()))
+		};
+		let Some(TypeDef::Sequence(events)) = types.resolve(events.id).map(|ty| &ty.type_def)
+		else {
+			return Err(invalid("`Events` is not a vector".into()))
+		};
+		let Some(TypeDef::Composite(record)) =
+			types.resolve(events.type_param.id).map(|ty| &ty.type_def)
+		else {
+			return Err(invalid("`EventRecord` is not a struct".into()))
+		};
+		let field = |name| {
+			record
+				.fields
+				.iter()
+				.find(|field| field.name.as_deref() == Some(name))
+				.map(|field| field.ty.id)
+				.ok_or_else(|| invalid(format!("`EventRecord::{name}` not found")))
+		};
+		let event_type = field("event")?;
+		let Some(TypeDef::Sequence(topics)) =
+			types.resolve(field("topics")?).map(|ty| &ty.type_def)
+		else {
+			return Err(invalid("`EventRecord::topics` is not a vector".into()))
+		};
+		let topic_type = topics.type_param.id;
+
+		let StorageEntryType::Plain(phase) = &phase.ty else {
+			return Err(invalid("`ExecutionPhase` is not a storage value".into()))
+		};
+		let Some(TypeDef::Variant(phase)) = types.resolve(phase.id).map(|ty| &ty.type_def) else {
+			return Err(invalid("`Phase` is not an enum".into()))
+		};
+		let index = |name| {
+			phase
+				.variants
+				.iter()
+				.find(|variant| variant.name == name)
+				.map(|variant| variant.index)
+				.ok_or_else(|| invalid(format!("`Phase::{name}` not found")))
+		};
+		let key = |item: &str| {
+			let mut key = twox_128(storage.prefix.as_bytes()).to_vec();
+			key.extend(twox_128(item.as_bytes()));
+			HexDisplay::from(&key).to_string()
+		};
+
+		Ok(Self {
+			execution_phase_key: key("ExecutionPhase"),
+			events_key: key("Events"),
+			phase_indices: [
+				index("ApplyExtrinsic")?,
+				index("Finalization")?,
+				index("Initialization")?,
+			],
+			types: types.clone(),
+			event_type,
+			topic_type,
+		})
+	}
+
+	/// Decode an execution phase.
+	fn decode_phase(&self, input: &mut &[u8]) -> Result<Phase, codec::Error> {
+		let index = u8::decode(input)?;
+		match self.phase_indices.iter().position(|phase_index| *phase_index == index) {
+			Some(0) => Ok(Phase::ApplyExtrinsic(u32::decode(input)?)),
+			Some(1) => Ok(Phase::Finalization),
+			Some(2) => Ok(Phase::Initialization),
+			_ => Err("Unknown phase".into()),
+		}
+	}
+
+	/// Decode the event and the topics following the phase of an event record.
+	fn decode_event(&self, input: &mut &[u8]) -> Result<ArchiveEvent, codec::Error> {
+		let event = *input;
+		let [pallet_index, event_index, ..] = event else { return Err("Missing event".into()) };
+		skip_value(&self.types, self.event_type, input)?;
+		let data = event
+			.get(2..event.len() - input.len())
+			.ok_or(codec::Error::from("Missing event"))?;
+
+		let count = Compact::<u32>::decode(input)?.0;
+		let topics = (0..count)
+			.map(|_| {
+				let topic = *input;
+				skip_value(&self.types, self.topic_type, input)?;
+				let topic = &topic[..topic.len() - input.len()];
+				Ok(hex_string(&topic))
+			})
+			.collect::<Result<_, codec::Error>>()?;
+		if !input.is_empty() {
+			return Err("Trailing bytes".into())
+		}
+
+		Ok(ArchiveEvent {
+			pallet_index: *pallet_index,
+			event_index: *event_index,
+			data: hex_string(&data),
+			topics,
+		})
+	}
+}
+
+/// Advance `input` past a SCALE encoded value of the type `id`.
+fn skip_value(types: &PortableRegistry, id: u32, input: &mut &[u8]) -> Result<(), codec::Error> {
+	fn skip(input: &mut &[u8], len: usize) -> Result<(), codec::Error> {
+		*input = input.get(len..).ok_or("Not enough data")?;
+		Ok(())
+	}
+
+	let ty = types.resolve(id).ok_or(codec::Error::from("Unknown type"))?;
+	match &ty.type_def {
+		TypeDef::Composite(composite) => composite
+			.fields
+			.iter()
+			.try_for_each(|field| skip_value(types, field.ty.id, input)),
+		TypeDef::Variant(variant) => {
+			let index = u8::decode(input)?;
+			let variant = variant
+				.variants
+				.iter()
+				.find(|variant| variant.index == index)
+				.ok_or(codec::Error::from("Unknown variant"))?;
+			variant
+				.fields
+				.iter()
+				.try_for_each(|field| skip_value(types, field.ty.id, input))
+		},
+		TypeDef::Sequence(sequence) => {
+			let len = Compact::<u32>::decode(input)?.0;
+			(0..len).try_for_each(|_| skip_value(types, sequence.type_param.id, input))
+		},
+		TypeDef::Array(array) =>
+			(0..array.len).try_for_each(|_| skip_value(types, array.type_param.id, input)),
+		TypeDef::Tuple(tuple) =>
+			tuple.fields.iter().try_for_each(|field| skip_value(types, field.id, input)),
+		TypeDef::Primitive(TypeDefPrimitive::Str) => {
+			let len = Compact::<u32>::decode(input)?.0;
+			skip(input, len as usize)
+		},
+		TypeDef::Primitive(primitive) => skip(input, primitive_size(primitive)),
+		TypeDef::Compact(_) => Compact::<u128>::decode(input).map(|_| ()),
+		TypeDef::BitSequence(bits) => {
+			let len = Compact::<u32>::decode(input)?.0 as usize;
+			let store = match types.resolve(bits.bit_store_type.id).map(|ty| &ty.type_def) {
+				Some(TypeDef::Primitive(primitive)) => primitive_size(primitive),
+				_ => return Err("Unsupported bit store type".into()),
+			};
+			skip(input, len.div_ceil(store * 8) * store)
+		},
+	}
+}
+
+/// Size of an encoded primitive, other than a string.
+fn primitive_size(primitive: &TypeDefPrimitive) -> usize {
+	match primitive {
+		TypeDefPrimitive::Bool | TypeDefPrimitive::U8 | TypeDefPrimitive::I8 => 1,
+		TypeDefPrimitive::U16 | TypeDefPrimitive::I16 => 2,
+		TypeDefPrimitive::Char | TypeDefPrimitive::U32 | TypeDefPrimitive::I32 => 4,
+		TypeDefPrimitive::U64 | TypeDefPrimitive::I64 => 8,
+		TypeDefPrimitive::U128 | TypeDefPrimitive::I128 => 16,
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => 32,
+		TypeDefPrimitive::Str => 0,
+	}
+}
+
+/// Storage changes made by the phases of a block.
+struct TracedBlock {
+	/// Number of extrinsics of the block.
+	extrinsics: usize,
+	/// Storage changes, along with the phase they were made in.
+	changes: Vec<(Phase, ArchiveStorageChange)>,
+	layout: SystemLayout,
+}
+
+/// Parse a hex-encoded traced value.
+fn parse_hex(value: &str) -> Result<Vec<u8>, ArchiveError> {
+	array_bytes::hex2bytes(value)
+		.map_err(|_| ArchiveError::BlockTrace(format!("Invalid traced value: {value}")))
+}
+
+/// Get a value recorded by a trace event.
+fn trace_value<'a>(data: &'a Data, name: &str) -> Result<&'a str, ArchiveError> {
+	data.string_values
+		.get(name)
+		.map(String::as_str)
+		.ok_or_else(|| ArchiveError::BlockTrace(format!("Missing traced value: {name}")))
+}
+
+/// Re-execute a block and collect the storage changes it made, along with the phase they were
+/// made in.
+///
+/// Returns `None` if the block is not found.
+fn trace_storage_changes<Block, Client>(
+	client: &Arc<Client>,
+	hash: Block::Hash,
+) -> Result<Option<TracedBlock>, ArchiveError>
+where
+	Block: BlockT + 'static,
+	Client: HeaderBackend<Block>
+		+ BlockBackend<Block>
+		+ ProvideRuntimeApi<Block>
+		+ Send
+		+ Sync
+		+ 'static,
+	Client::Api: Metadata<Block>,
+{
+	let Ok(Some(extrinsics)) = client.block_body(hash) else { return Ok(None) };
+	let layout = SystemLayout::from_metadata(&**client, hash)?;
+
+	let executor = BlockExecutor::new(
+		client.clone(),
+		hash,
+		Some(TRACE_TARGETS.into()),
+		// Keep the events of all storage keys.
+		Some(String::new()),
+		Some(TRACE_METHODS.into()),
+	);
+	let trace = match executor.trace_block() {
+		Ok(TraceBlockResponse::BlockTrace(trace)) => trace,
+		Ok(TraceBlockResponse::TraceError(error)) =>
+			return Err(ArchiveError::BlockTrace(error.error)),
+		Err(error) => return Err(ArchiveError::BlockTrace(error.to_string())),
+	};
+
+	let mut phase = Phase::Initialization;
+	// Changes of the committed state, followed by the changes of each open transaction.
+	let mut layers = vec![Vec::new()];
+	for event in trace.events {
+		let values = &event.data;
+		let method = trace_value(values, "method")?;
+		let child_trie_key = values.string_values.get("child_info").map(|key| format!("0x{key}"));
+		let change = match method {
+			"StartTransaction" => {
+				layers.push(Vec::new());
+				continue
+			},
+			"CommitTransaction" | "RollbackTransaction" => {
+				if layers.len() > 1 {
+					let changes = layers.pop().expect("There is more than one layer; qed");
+					if method == "CommitTransaction" {
+						layers
+							.last_mut()
+							.expect("There is at least one layer; qed")
+							.extend(changes);
+					}
+				}
+				continue
+			},
+			"Put" | "ChildPut" => {
+				let key = trace_value(values, "key")?;
+				let value = match trace_value(values, "value")? {
+					"None" => None,
+					value => Some(parse_hex(
+						value
+							.strip_prefix("Some(")
+							.and_then(|v| v.strip_suffix(')'))
+							.unwrap_or(value),
+					)?),
+				};
+				let change = ArchiveStorageChange {
+					change_type: ArchiveStorageChangeType::Put,
+					key: format!("0x{key}"),
+					value: value.as_ref().map(hex_string),
+					child_trie_key,
+				};
+				layers
+					.last_mut()
+					.expect("There is at least one layer; qed")
+					.push((phase, change));
+
+				// The new phase is stored after the last change of the previous one.
+				if method == "Put" && key == layout.execution_phase_key {
+					if let Some(value) = value {
+						phase = layout.decode_phase(&mut &value[..]).map_err(|error| {
+							ArchiveError::BlockTrace(format!("Invalid execution phase: {error}"))
+						})?;
+					}
+				}
+				continue
+			},
+			"Append" => ArchiveStorageChange {
+				change_type: ArchiveStorageChangeType::Append,
+				key: format!("0x{}", trace_value(values, "key")?),
+				value: Some(format!("0x{}", trace_value(values, "value")?)),
+				child_trie_key,
+			},
+			"ClearPrefix" | "ChildClearPrefix" => ArchiveStorageChange {
+				change_type: ArchiveStorageChangeType::ClearPrefix,
+				key: format!("0x{}", trace_value(values, "prefix")?),
+				value: None,
+				child_trie_key,
+			},
+			"ChildKill" => ArchiveStorageChange {
+				change_type: ArchiveStorageChangeType::KillChildTrie,
+				key: String::new(),
+				value: None,
+				child_trie_key,
+			},
+			_ => continue,
+		};
+		layers
+			.last_mut()
+			.expect("There is at least one layer; qed")
+			.push((phase, change));
+	}
+
+	Ok(Some(TracedBlock { extrinsics: extrinsics.len(), changes: layers.remove(0), layout }))
+}
+
+/// Re-execute a block and collect the storage changes made by each of its phases.
+///
+/// Returns `None` if the block is not found.
+pub fn block_storage_changes<Block, Client>(
+	client: &Arc<Client>,
+	hash: Block::Hash,
+) -> Result<Option<ArchiveBlockPhases<ArchiveStorageChange>>, ArchiveError>
+where
+	Block: BlockT + 'static,
+	Client: HeaderBackend<Block>
+		+ BlockBackend<Block>
+		+ ProvideRuntimeApi<Block>
+		+ Send
+		+ Sync
+		+ 'static,
+	Client::Api: Metadata<Block>,
+{
+	let Some(TracedBlock { extrinsics, changes, .. }) = trace_storage_changes(client, hash)? else {
+		return Ok(None)
+	};
+
+	let mut phases = ArchiveBlockPhases::new(extrinsics);
+	for (phase, change) in changes {
+		phases.push(phase, change);
+	}
+	Ok(Some(phases))
+}
+
+/// Re-execute a block and collect the events deposited by each of its phases.
+///
+/// Events are the records appended to `System::Events`. The fields of the events are skipped
+/// using the types in the metadata of the runtime, to separate them from the topics.
+///
+/// Returns `None` if the block is not found.
+pub fn block_events<Block, Client>(
+	client: &Arc<Client>,
+	hash: Block::Hash,
+) -> Result<Option<ArchiveBlockPhases<ArchiveEvent>>, ArchiveError>
+where
+	Block: BlockT + 'static,
+	Client: HeaderBackend<Block>
+		+ BlockBackend<Block>
+		+ ProvideRuntimeApi<Block>
+		+ Send
+		+ Sync
+		+ 'static,
+	Client::Api: Metadata<Block>,
+{
+	let Some(TracedBlock { extrinsics, changes, layout }) = trace_storage_changes(client, hash)?
+	else {
+		return Ok(None)
+	};
+
+	let events_key = format!("0x{}", layout.events_key);
+	let mut phases = ArchiveBlockPhases::new(extrinsics);
+	for (_, change) in changes {
+		if change.change_type != ArchiveStorageChangeType::Append ||
+			change.child_trie_key.is_some() ||
+			change.key != events_key
+		{
+			continue
+		}
+		let Some(record) = change.value else { continue };
+		let record = parse_hex(&record)?;
+
+		// An event record starts with the phase it was deposited in, followed by the event.
+		let mut input = &record[..];
+		let invalid =
+			|| ArchiveError::BlockTrace(format!("Invalid event record: {}", hex_string(&record)));
+		let phase = layout.decode_phase(&mut input).map_err(|_| invalid())?;
+		let event = layout.decode_event(&mut input).map_err(|_| invalid())?;
+		phases.push(phase, event);
+	}
+	Ok(Some(phases))
+}
@@ -32,6 +32,12 @@ pub enum Error {
 	/// Failed to fetch leaves.
 	#[error("Failed to fetch leaves of the chain: {0}")]
 	FetchLeaves(String),
+	/// Failed to trace the execution of a block.
+	#[error("Failed to trace block: {0}")]
+	BlockTrace(String),
+	/// Too many blocks are being traced at the same time.
+	#[error("Too many blocks are being traced, try again later")]
+	TooManyBlockTraces,
 }
 
 // Base code for all `archive` errors.
@@ -42,6 +48,10 @@ const INVALID_PARAM_ERROR: i32 = BASE_ERROR + 1;
 const RUNTIME_CALL_ERROR: i32 = BASE_ERROR + 2;
 /// Failed to fetch leaves.
 const FETCH_LEAVES_ERROR: i32 = BASE_ERROR + 3;
+/// Failed to trace a block.
+const BLOCK_TRACE_ERROR: i32 = BASE_ERROR + 4;
+/// Too many blocks are being traced.
+const TOO_MANY_BLOCK_TRACES_ERROR: i32 = BASE_ERROR + 5;
 
 impl From<Error> for ErrorObject<'static> {
 	fn from(e: Error) -> Self {
@@ -51,6 +61,9 @@ impl From<Error> for ErrorObject<'static> {
 			Error::InvalidParam(_) => ErrorObject::owned(INVALID_PARAM_ERROR, msg, None::<()>),
 			Error::RuntimeCall(_) => ErrorObject::owned(RUNTIME_CALL_ERROR, msg, None::<()>),
 			Error::FetchLeaves(_) => ErrorObject::owned(FETCH_LEAVES_ERROR, msg, None::<()>),
+			Error::BlockTrace(_) => ErrorObject::owned(BLOCK_TRACE_ERROR, msg, None::<()>),
+			Error::TooManyBlockTraces =>
+				ErrorObject::owned(TOO_MANY_BLOCK_TRACES_ERROR, msg, None::<()>),
 		}
 		.into()
 	}
@@ -26,6 +26,7 @@
 mod tests;
 
 mod archive_storage;
+mod archive_trace;
 mod types;
 
 pub mod api;
@@ -34,4 +35,7 @@ pub mod error;
 
 pub use api::ArchiveApiServer;
 pub use archive::Archive;
-pub use types::{MethodResult, MethodResultErr, MethodResultOk};
+pub use types::{
+	ArchiveBlockPhases, ArchiveEvent, ArchiveStorageChange, ArchiveStorageChangeType, MethodResult,
+	MethodResultErr, MethodResultOk,
+};
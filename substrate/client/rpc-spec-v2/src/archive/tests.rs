@@ -17,7 +17,10 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use crate::{
-	archive::MethodResult,
+	archive::{
+		ArchiveBlockPhases, ArchiveEvent, ArchiveStorageChange, ArchiveStorageChangeType,
+		MethodResult,
+	},
 	common::events::{
 		ArchiveStorageDiffEvent, ArchiveStorageDiffItem, ArchiveStorageDiffOperationType,
 		ArchiveStorageDiffResult, ArchiveStorageDiffType, ArchiveStorageEvent, StorageQuery,
@@ -343,6 +346,60 @@ async fn archive_call() {
 	assert_eq!(result, expected);
 }
 
+#[tokio::test]
+async fn archive_events_and_storage_changes() {
+	let (client, api) = setup_api();
+
+	// Invalid block hash.
+	let invalid_hash = hex_string(&INVALID_HASH);
+	let events: Option<ArchiveBlockPhases<ArchiveEvent>> =
+		api.call("archive_unstable_events", [&invalid_hash]).await.unwrap();
+	assert!(events.is_none());
+	let changes: Option<ArchiveBlockPhases<ArchiveStorageChange>> =
+		api.call("archive_unstable_storageChanges", [&invalid_hash]).await.unwrap();
+	assert!(changes.is_none());
+
+	// Import a new block with two extrinsics.
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":A".to_vec(), Some(b"B".to_vec())).unwrap();
+	builder.push_storage_change(b":AA".to_vec(), None).unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	let events: ArchiveBlockPhases<ArchiveEvent> =
+		api.call("archive_unstable_events", [&block_hash]).await.unwrap();
+	assert_eq!(events.extrinsics.len(), 2);
+	// Every extrinsic deposits `System::ExtrinsicSuccess`, whose fields hold the dispatch info,
+	// without topics.
+	for events in &events.extrinsics {
+		let success = events
+			.iter()
+			.find(|event| event.pallet_index == 0 && event.event_index == 0)
+			.unwrap();
+		assert!(success.data.len() > "0x".len());
+		assert!(success.topics.is_empty());
+	}
+
+	let changes: ArchiveBlockPhases<ArchiveStorageChange> =
+		api.call("archive_unstable_storageChanges", [&block_hash]).await.unwrap();
+	assert_eq!(changes.extrinsics.len(), 2);
+	let put = |key: &[u8], value: Option<&[u8]>| ArchiveStorageChange {
+		change_type: ArchiveStorageChangeType::Put,
+		key: hex_string(&key),
+		value: value.map(|value| hex_string(&value)),
+		child_trie_key: None,
+	};
+	assert!(changes.extrinsics[0].contains(&put(b":A", Some(b"B"))));
+	assert!(!changes.extrinsics[0].contains(&put(b":AA", None)));
+	assert!(changes.extrinsics[1].contains(&put(b":AA", None)));
+	assert!(!changes.initialization.contains(&put(b":A", Some(b"B"))));
+}
+
 #[tokio::test]
 async fn archive_storage_hashes_values() {
 	let (client, api) = setup_api();
@@ -60,6 +60,76 @@ pub struct MethodResultErr {
 	pub error: String,
 }
 
+/// Items of a block grouped by the execution phase they belong to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveBlockPhases<T> {
+	/// Items of the block initialization.
+	pub initialization: Vec<T>,
+	/// Items of each extrinsic of the block, in the order of the extrinsics.
+	pub extrinsics: Vec<Vec<T>>,
+	/// Items of the block finalization.
+	pub finalization: Vec<T>,
+}
+
+impl<T> ArchiveBlockPhases<T> {
+	/// Constructs the phases of a block with `extrinsics` extrinsics.
+	pub fn new(extrinsics: usize) -> Self {
+		Self {
+			initialization: Vec::new(),
+			extrinsics: (0..extrinsics).map(|_| Vec::new()).collect(),
+			finalization: Vec::new(),
+		}
+	}
+}
+
+/// An event deposited while executing a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveEvent {
+	/// The index of the pallet that deposited the event.
+	pub pallet_index: u8,
+	/// The index of the event in the event enum of the pallet.
+	pub event_index: u8,
+	/// The hexadecimal-encoded SCALE-encoded fields of the event.
+	pub data: String,
+	/// The hexadecimal-encoded topics of the event.
+	pub topics: Vec<String>,
+}
+
+/// The type of a storage change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ArchiveStorageChangeType {
+	/// The value of the key is set, or removed if there is no value.
+	Put,
+	/// The value is appended to the value of the key.
+	Append,
+	/// All the keys starting with the key are removed.
+	ClearPrefix,
+	/// The child trie is removed.
+	KillChildTrie,
+}
+
+/// A storage change made while executing a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveStorageChange {
+	/// The type of the change.
+	#[serde(rename = "type")]
+	pub change_type: ArchiveStorageChangeType,
+	/// The hex-encoded key, or prefix of the removed keys. Empty for a removed child trie.
+	pub key: String,
+	/// The hex-encoded value of the change, if any.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[serde(default)]
+	pub value: Option<String>,
+	/// The child trie key if the change is in a child trie.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	#[serde(default)]
+	pub child_trie_key: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -87,4 +157,21 @@ mod tests {
 		let ok_dec: MethodResult = serde_json::from_str(exp).unwrap();
 		assert_eq!(ok_dec, ok);
 	}
+
+	#[test]
+	fn storage_change_serialize() {
+		let change = ArchiveStorageChange {
+			change_type: ArchiveStorageChangeType::ClearPrefix,
+			key: "0x01".into(),
+			value: None,
+			child_trie_key: Some("0x02".into()),
+		};
+
+		let ser = serde_json::to_string(&change).unwrap();
+		let exp = r#"{"type":"clearPrefix","key":"0x01","childTrieKey":"0x02"}"#;
+		assert_eq!(ser, exp);
+
+		let dec: ArchiveStorageChange = serde_json::from_str(exp).unwrap();
+		assert_eq!(dec, change);
+	}
 }
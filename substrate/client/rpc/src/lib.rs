@@ -33,9 +33,12 @@ pub mod chain;
 pub mod dev;
 pub mod mixnet;
 pub mod offchain;
+pub mod pruning;
+pub mod snapshot;
 pub mod state;
 pub mod statement;
 pub mod system;
+pub mod txpool;
 pub mod utils;
 
 #[cfg(any(test, feature = "test-helpers"))]
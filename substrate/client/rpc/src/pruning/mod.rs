@@ -0,0 +1,70 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Implementation of the [`PruningApiServer`] trait keeping states regardless of the pruning
+//! mode.
+
+#[cfg(test)]
+mod tests;
+
+use jsonrpsee::Extensions;
+use sc_client_api::Backend;
+use sc_rpc_api::{check_if_safe, pruning::error::Error};
+use sp_runtime::traits::{Block as BlockT, SaturatedConversion};
+use std::{marker::PhantomData, sync::Arc};
+
+pub use sc_rpc_api::pruning::{KeptState, PruningApiServer};
+
+/// The state pruning API. All methods are unsafe.
+pub struct Pruning<Block, B> {
+	backend: Arc<B>,
+	_phantom: PhantomData<Block>,
+}
+
+impl<Block, B> Pruning<Block, B> {
+	/// Create a new state pruning API.
+	pub fn new(backend: Arc<B>) -> Self {
+		Self { backend, _phantom: PhantomData }
+	}
+}
+
+impl<Block, B> PruningApiServer<Block::Hash> for Pruning<Block, B>
+where
+	Block: BlockT + 'static,
+	B: Backend<Block> + Send + Sync + 'static,
+{
+	fn keep_state(&self, ext: &Extensions, hash: Block::Hash) -> Result<(), Error> {
+		check_if_safe(ext)?;
+		self.backend.keep_state(hash).map_err(|e| Error::Client(Box::new(e)))
+	}
+
+	fn release_state(&self, ext: &Extensions, hash: Block::Hash) -> Result<(), Error> {
+		check_if_safe(ext)?;
+		self.backend.release_state(hash).map_err(|e| Error::Client(Box::new(e)))
+	}
+
+	fn kept_states(&self, ext: &Extensions) -> Result<Vec<KeptState<Block::Hash>>, Error> {
+		check_if_safe(ext)?;
+		Ok(self
+			.backend
+			.kept_states()
+			.into_iter()
+			.map(|(hash, number)| KeptState { hash, number: number.saturated_into() })
+			.collect())
+	}
+}
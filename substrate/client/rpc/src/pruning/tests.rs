@@ -0,0 +1,68 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+use super::*;
+use crate::DenyUnsafe;
+use jsonrpsee::{core::EmptyServerParams as EmptyParams, MethodsError as RpcError};
+use sc_client_db::{BlocksPruning, DatabaseSettings, DatabaseSource, PruningMode};
+use substrate_test_runtime_client::{prelude::*, runtime::Block};
+
+#[tokio::test]
+async fn pruning_keeps_and_releases_states() {
+	let dir = tempfile::tempdir().unwrap();
+	let settings = DatabaseSettings {
+		trie_cache_maximum_size: None,
+		state_pruning: Some(PruningMode::blocks_pruning(1)),
+		source: DatabaseSource::ParityDb { path: dir.path().into() },
+		blocks_pruning: BlocksPruning::KeepFinalized,
+		metrics_registry: None,
+		block_timestamp: None,
+	};
+	let backend = Arc::new(sc_client_db::Backend::new(settings, 0).unwrap());
+	let client = TestClientBuilder::with_backend(backend.clone()).build();
+	let mut api = <Pruning<Block, _>>::new(backend).into_rpc();
+	api.extensions_mut().insert(DenyUnsafe::No);
+	let genesis = client.genesis_hash();
+
+	api.call::<_, ()>("pruning_keepState", [genesis]).await.unwrap();
+	let kept: Vec<KeptState<sp_core::H256>> =
+		api.call("pruning_keptStates", EmptyParams::new()).await.unwrap();
+	assert_eq!(kept, vec![KeptState { hash: genesis, number: 0 }]);
+
+	api.call::<_, ()>("pruning_releaseState", [genesis]).await.unwrap();
+	let kept: Vec<KeptState<sp_core::H256>> =
+		api.call("pruning_keptStates", EmptyParams::new()).await.unwrap();
+	assert!(kept.is_empty());
+
+	// Unknown blocks can not be kept.
+	assert!(api
+		.call::<_, ()>("pruning_keepState", [sp_core::H256::repeat_byte(1)])
+		.await
+		.is_err());
+}
+
+#[tokio::test]
+async fn pruning_is_unsafe() {
+	let (_, backend) = TestClientBuilder::new().build_with_backend();
+	let mut api = <Pruning<Block, _>>::new(backend).into_rpc();
+	api.extensions_mut().insert(DenyUnsafe::Yes);
+
+	assert!(matches!(
+		api.call::<_, Vec<KeptState<sp_core::H256>>>("pruning_keptStates", EmptyParams::new()).await,
+		Err(RpcError::JsonRpc(e)) if e.message() == "RPC call is unsafe to be called externally"
+	));
+}
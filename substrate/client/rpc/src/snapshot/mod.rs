@@ -0,0 +1,107 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Implementation of the [`SnapshotApiServer`] trait exporting snapshots of finalized state.
+
+#[cfg(test)]
+mod tests;
+
+use jsonrpsee::Extensions;
+use sc_client_api::Backend;
+use sc_rpc_api::{check_if_safe, snapshot::error::Error};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, SaturatedConversion};
+use std::{
+	fs, io,
+	marker::PhantomData,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+pub use sc_rpc_api::snapshot::{ExportedSnapshot, SnapshotApiServer};
+
+/// The state snapshot API. All methods are unsafe.
+pub struct Snapshot<Block, B> {
+	backend: Arc<B>,
+	_phantom: PhantomData<Block>,
+}
+
+impl<Block, B> Snapshot<Block, B> {
+	/// Create a new state snapshot API.
+	pub fn new(backend: Arc<B>) -> Self {
+		Self { backend, _phantom: PhantomData }
+	}
+}
+
+impl<Block, B> SnapshotApiServer<Block::Hash> for Snapshot<Block, B>
+where
+	Block: BlockT + 'static,
+	B: Backend<Block> + Send + Sync + 'static,
+{
+	fn export(
+		&self,
+		ext: &Extensions,
+		path: String,
+		at: Option<Block::Hash>,
+	) -> Result<ExportedSnapshot<Block::Hash>, Error> {
+		check_if_safe(ext)?;
+
+		// Validate the block before the file is touched, so that a failed call leaves an existing
+		// file as is.
+		let blockchain = self.backend.blockchain();
+		let info = blockchain.info();
+		let hash = at.unwrap_or(info.finalized_hash);
+		let number = match blockchain.number(hash).map_err(|e| Error::Export(Box::new(e)))? {
+			Some(number) if number <= info.finalized_number => number,
+			_ => return Err(Error::NotFinalized(format!("{hash:?}"))),
+		};
+		if blockchain.hash(number).map_err(|e| Error::Export(Box::new(e)))? != Some(hash) {
+			return Err(Error::NotFinalized(format!("{hash:?}")));
+		}
+
+		// The snapshot is written next to the target and only moved into place once complete.
+		let path = PathBuf::from(path);
+		let mut partial = path.clone().into_os_string();
+		partial.push(".partial");
+		let partial = PathBuf::from(partial);
+		let result = write_snapshot(&*self.backend, hash, &partial).and_then(|nodes| {
+			fs::rename(&partial, &path)?;
+			Ok(nodes)
+		});
+		if result.is_err() {
+			let _ = fs::remove_file(&partial);
+		}
+
+		Ok(ExportedSnapshot { hash, number: number.saturated_into(), nodes: result? })
+	}
+}
+
+/// Write the snapshot of the finalized block `hash` to a new file at `path`, returning the number
+/// of trie nodes written.
+fn write_snapshot<Block: BlockT, B: Backend<Block>>(
+	backend: &B,
+	hash: Block::Hash,
+	path: &Path,
+) -> Result<u64, Error> {
+	let mut file = io::BufWriter::new(fs::File::create(path)?);
+	let nodes = backend
+		.export_snapshot(hash, &mut file)
+		.map_err(|e| Error::Export(Box::new(e)))?;
+	file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+	Ok(nodes)
+}
@@ -0,0 +1,69 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::DenyUnsafe;
+use jsonrpsee::MethodsError as RpcError;
+use substrate_test_runtime_client::{prelude::*, runtime::Block};
+
+#[tokio::test]
+async fn snapshot_export_works() {
+	let (client, backend) = TestClientBuilder::new().build_with_backend();
+	let mut api = <Snapshot<Block, _>>::new(backend).into_rpc();
+	api.extensions_mut().insert(DenyUnsafe::No);
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("snapshot").to_string_lossy().into_owned();
+
+	let exported = api
+		.call::<_, ExportedSnapshot<sp_core::H256>>("snapshot_export", (path.clone(), None::<()>))
+		.await
+		.unwrap();
+	assert_eq!(exported.hash, client.genesis_hash());
+	assert_eq!(exported.number, 0);
+	assert!(exported.nodes > 0);
+	assert!(fs::metadata(&path).unwrap().len() > 0);
+
+	// Unknown blocks can not be exported and leave the existing snapshot as is.
+	let snapshot = fs::read(&path).unwrap();
+	let unknown = sp_core::H256::repeat_byte(1);
+	assert!(api
+		.call::<_, ExportedSnapshot<sp_core::H256>>(
+			"snapshot_export",
+			(path.clone(), Some(unknown))
+		)
+		.await
+		.is_err());
+	assert_eq!(fs::read(&path).unwrap(), snapshot);
+	assert!(!dir.path().join("snapshot.partial").exists());
+}
+
+#[tokio::test]
+async fn snapshot_export_is_unsafe() {
+	let (_, backend) = TestClientBuilder::new().build_with_backend();
+	let mut api = <Snapshot<Block, _>>::new(backend).into_rpc();
+	api.extensions_mut().insert(DenyUnsafe::Yes);
+
+	assert!(matches!(
+		api.call::<_, ExportedSnapshot<sp_core::H256>>(
+			"snapshot_export",
+			("snapshot".to_string(), None::<()>),
+		)
+		.await,
+		Err(RpcError::JsonRpc(e)) if e.message() == "RPC call is unsafe to be called externally"
+	));
+}
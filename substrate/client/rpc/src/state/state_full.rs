@@ -23,7 +23,7 @@ use std::{collections::HashMap, marker::PhantomData, sync::Arc, time::Duration};
 use super::{
 	client_err,
 	error::{Error, Result},
-	ChildStateBackend, StateBackend,
+	view_functions, ChildStateBackend, StateBackend,
 };
 use crate::{
 	utils::{spawn_subscription_task, BoundedVecDeque, PendingSubscription},
@@ -36,7 +36,7 @@ use sc_client_api::{
 	Backend, BlockBackend, BlockchainEvents, CallExecutor, ExecutorProvider, ProofProvider,
 	StorageProvider,
 };
-use sc_rpc_api::state::ReadProof;
+use sc_rpc_api::state::{ReadProof, ViewFunctionArgs, ViewFunctionResult};
 use sp_api::{CallApiAt, Metadata, ProvideRuntimeApi};
 use sp_blockchain::{
 	CachedHeaderMetadata, Error as ClientError, HeaderBackend, HeaderMetadata,
@@ -65,6 +65,7 @@ struct QueryStorageRange<Block: BlockT> {
 pub struct FullState<BE, Block: BlockT, Client> {
 	client: Arc<Client>,
 	executor: SubscriptionTaskExecutor,
+	view_functions_metadata: view_functions::MetadataCache,
 	_phantom: PhantomData<(BE, Block)>,
 }
 
@@ -79,7 +80,12 @@ where
 {
 	/// Create new state API backend for full nodes.
 	pub fn new(client: Arc<Client>, executor: SubscriptionTaskExecutor) -> Self {
-		Self { client, executor, _phantom: PhantomData }
+		Self {
+			client,
+			executor,
+			view_functions_metadata: Default::default(),
+			_phantom: PhantomData,
+		}
 	}
 
 	/// Returns given block hash or best block hash if None is passed.
@@ -204,6 +210,26 @@ where
 			.map_err(client_err)
 	}
 
+	fn call_view_function(
+		&self,
+		block: Option<Block::Hash>,
+		pallet: String,
+		function: String,
+		args: ViewFunctionArgs,
+	) -> std::result::Result<ViewFunctionResult, Error> {
+		let block = self.block_or_best(block).map_err(client_err)?;
+		let version = self.client.executor().runtime_version(block).map_err(client_err)?;
+		view_functions::call_view_function(
+			&self.view_functions_metadata,
+			&version,
+			|method, data| self.client.executor().call(block, method, &data, CallContext::Offchain),
+			&pallet,
+			&function,
+			args,
+		)
+		.map_err(|e| Error::ViewFunction(e.to_string()))
+	}
+
 	// TODO: This is horribly broken; either remove it, or make it streaming.
 	fn storage_keys(
 		&self,
@@ -0,0 +1,610 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Calls to the view functions of pallets.
+//!
+//! View functions are looked up by pallet and function name in the metadata of the runtime, which
+//! also provides the types used to encode JSON arguments and to decode the result. The metadata is
+//! cached by runtime version.
+
+use codec::{Compact, Decode, Encode};
+use frame_metadata::{v16::RuntimeMetadataV16, RuntimeMetadata, RuntimeMetadataPrefixed};
+use parking_lot::Mutex;
+use sc_rpc_api::state::{ViewFunctionArgs, ViewFunctionResult};
+use scale_info::{form::PortableForm, Field, PortableRegistry, TypeDef, TypeDefPrimitive};
+use serde_json::{Map, Value};
+use sp_core::{bytes, OpaqueMetadata};
+use sp_version::RuntimeVersion;
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// Runtime API method returning the metadata of a given version.
+const METADATA_AT_VERSION: &str = "Metadata_metadata_at_version";
+/// Runtime API method executing view functions.
+const EXECUTE_VIEW_FUNCTION: &str = "RuntimeViewFunction_execute_view_function";
+/// Metadata version listing the view functions.
+const VIEW_FUNCTIONS_METADATA_VERSION: u32 = 16;
+/// Maximum number of runtime versions whose metadata is cached.
+const MAX_CACHED_METADATA: usize = 4;
+
+/// Errors of view function calls.
+#[derive(Debug)]
+pub enum ViewFunctionError {
+	/// The view function does not exist, or the arguments do not match its inputs.
+	InvalidParam(String),
+	/// Calling into the runtime failed.
+	RuntimeCall(String),
+}
+
+impl fmt::Display for ViewFunctionError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::InvalidParam(e) => write!(f, "Invalid parameter: {e}"),
+			Self::RuntimeCall(e) => write!(f, "Runtime call: {e}"),
+		}
+	}
+}
+
+/// Decodes the `ViewFunctionDispatchError` of `frame-support`, which the client does not depend
+/// on.
+#[derive(Debug, PartialEq, Decode)]
+enum ViewFunctionDispatchError {
+	NotImplemented,
+	NotFound([u8; 32]),
+	Codec,
+}
+
+/// The metadata listing the view functions, cached by runtime version.
+#[derive(Default)]
+pub struct MetadataCache {
+	metadata: Mutex<HashMap<(String, u32), Arc<RuntimeMetadataV16>>>,
+}
+
+impl MetadataCache {
+	/// Get the metadata of the runtime with the given `version`, fetching it with `runtime_call`
+	/// if it is not cached.
+	fn get(
+		&self,
+		version: &RuntimeVersion,
+		runtime_call: impl Fn(&str, Vec<u8>) -> Result<Vec<u8>, ViewFunctionError>,
+	) -> Result<Arc<RuntimeMetadataV16>, ViewFunctionError> {
+		let key = (version.spec_name.to_string(), version.spec_version);
+		if let Some(metadata) = self.metadata.lock().get(&key) {
+			return Ok(metadata.clone())
+		}
+
+		let decode_error = |e: codec::Error| ViewFunctionError::RuntimeCall(e.to_string());
+		let metadata = runtime_call(METADATA_AT_VERSION, VIEW_FUNCTIONS_METADATA_VERSION.encode())?;
+		let metadata = Option::<OpaqueMetadata>::decode(&mut &metadata[..])
+			.map_err(decode_error)?
+			.ok_or_else(|| {
+				ViewFunctionError::RuntimeCall(
+					"The runtime has no metadata with view functions".into(),
+				)
+			})?;
+		let metadata =
+			match RuntimeMetadataPrefixed::decode(&mut &metadata[..]).map_err(decode_error)?.1 {
+				RuntimeMetadata::V16(metadata) => Arc::new(metadata),
+				_ =>
+					return Err(ViewFunctionError::RuntimeCall("Unexpected metadata version".into())),
+			};
+
+		let mut cache = self.metadata.lock();
+		// Runtime upgrades are rare, so the cache is simply cleared once full.
+		if cache.len() >= MAX_CACHED_METADATA {
+			cache.clear();
+		}
+		cache.insert(key, metadata.clone());
+		Ok(metadata)
+	}
+}
+
+/// Call the view function `function` of `pallet` in the runtime with the given `version`, using
+/// `call` to call into the runtime.
+pub fn call_view_function<E: fmt::Display>(
+	cache: &MetadataCache,
+	version: &RuntimeVersion,
+	call: impl Fn(&str, Vec<u8>) -> Result<Vec<u8>, E>,
+	pallet: &str,
+	function: &str,
+	args: ViewFunctionArgs,
+) -> Result<ViewFunctionResult, ViewFunctionError> {
+	let runtime_call = |method: &str, data: Vec<u8>| {
+		call(method, data).map_err(|e| ViewFunctionError::RuntimeCall(e.to_string()))
+	};
+	let decode_error = |e: codec::Error| ViewFunctionError::RuntimeCall(e.to_string());
+
+	let metadata = cache.get(version, &runtime_call)?;
+
+	let view_function = metadata
+		.pallets
+		.iter()
+		.find(|metadata| metadata.name == pallet)
+		.ok_or_else(|| ViewFunctionError::InvalidParam(format!("Pallet {pallet} not found")))?
+		.view_functions
+		.iter()
+		.find(|metadata| metadata.name == function)
+		.ok_or_else(|| {
+			ViewFunctionError::InvalidParam(format!("View function {pallet}::{function} not found"))
+		})?;
+
+	let input = match args {
+		ViewFunctionArgs::Scale(input) => input.0,
+		ViewFunctionArgs::Json(args) => {
+			if args.len() != view_function.inputs.len() {
+				return Err(ViewFunctionError::InvalidParam(format!(
+					"Expected {} arguments, got {}",
+					view_function.inputs.len(),
+					args.len()
+				)))
+			}
+			let mut input = Vec::new();
+			for (arg, param) in args.iter().zip(&view_function.inputs) {
+				encode_json(&metadata.types, param.ty.id, arg, &mut input).map_err(|e| {
+					ViewFunctionError::InvalidParam(format!("Argument {}: {e}", param.name))
+				})?;
+			}
+			input
+		},
+	};
+
+	let output = runtime_call(EXECUTE_VIEW_FUNCTION, (view_function.id, input).encode())?;
+	let result = match Result::<Vec<u8>, ViewFunctionDispatchError>::decode(&mut &output[..])
+		.map_err(decode_error)?
+	{
+		Ok(result) => result,
+		Err(ViewFunctionDispatchError::Codec) =>
+			return Err(ViewFunctionError::InvalidParam("Arguments could not be decoded".into())),
+		Err(ViewFunctionDispatchError::NotImplemented) =>
+			return Err(ViewFunctionError::RuntimeCall(
+				"The runtime does not implement view functions".into(),
+			)),
+		Err(ViewFunctionDispatchError::NotFound(id)) =>
+			return Err(ViewFunctionError::RuntimeCall(format!(
+				"View function {} not found",
+				bytes::to_hex(&id, false)
+			))),
+	};
+
+	let mut remaining = &result[..];
+	let decoded = decode_json(&metadata.types, view_function.output.id, &mut remaining)
+		.and_then(|decoded| {
+			remaining.is_empty().then_some(decoded).ok_or_else(|| "Trailing bytes".into())
+		})
+		.map_err(|e| ViewFunctionError::RuntimeCall(format!("Failed to decode the result: {e}")))?;
+
+	Ok(ViewFunctionResult { result: result.into(), decoded })
+}
+
+/// Whether `ty` is an `Option`, which is represented as `null` or its value in JSON.
+fn is_option(ty: &scale_info::Type<PortableForm>) -> bool {
+	ty.path.segments == ["Option"]
+}
+
+/// Whether `ty` is `u8`, sequences of which are represented as hex strings in JSON.
+fn is_byte(registry: &PortableRegistry, ty: u32) -> bool {
+	registry
+		.resolve(ty)
+		.is_some_and(|ty| matches!(ty.type_def, TypeDef::Primitive(TypeDefPrimitive::U8)))
+}
+
+fn unsigned(value: &Value) -> Result<u128, String> {
+	match value {
+		Value::Number(number) => number.as_u64().map(Into::into),
+		Value::String(number) => number.parse().ok(),
+		_ => None,
+	}
+	.ok_or_else(|| format!("Expected an unsigned integer, got {value}"))
+}
+
+fn signed(value: &Value) -> Result<i128, String> {
+	match value {
+		Value::Number(number) => number.as_i64().map(Into::into),
+		Value::String(number) => number.parse().ok(),
+		_ => None,
+	}
+	.ok_or_else(|| format!("Expected an integer, got {value}"))
+}
+
+/// Encode `value` as the type `ty` of `registry`.
+pub fn encode_json(
+	registry: &PortableRegistry,
+	ty: u32,
+	value: &Value,
+	out: &mut Vec<u8>,
+) -> Result<(), String> {
+	let ty = registry.resolve(ty).ok_or_else(|| format!("Unknown type {ty}"))?;
+	match &ty.type_def {
+		TypeDef::Composite(composite) => encode_fields(registry, &composite.fields, value, out),
+		TypeDef::Variant(variant) if is_option(ty) => match value {
+			Value::Null => {
+				out.push(0);
+				Ok(())
+			},
+			value => {
+				let some = variant
+					.variants
+					.iter()
+					.find(|variant| variant.name == "Some")
+					.ok_or("Invalid Option type")?;
+				out.push(some.index);
+				encode_fields(registry, &some.fields, value, out)
+			},
+		},
+		TypeDef::Variant(variant) => {
+			let null = Value::Null;
+			let (name, fields) = match value {
+				Value::String(name) => (name, &null),
+				Value::Object(object) if object.len() == 1 =>
+					object.iter().next().expect("object has one entry; qed"),
+				_ =>
+					return Err(format!(
+						"Expected a variant name, or an object with a variant name as key, got {value}"
+					)),
+			};
+			let variant = variant
+				.variants
+				.iter()
+				.find(|variant| &variant.name == name)
+				.ok_or_else(|| format!("Unknown variant {name}"))?;
+			out.push(variant.index);
+			encode_fields(registry, &variant.fields, fields, out)
+		},
+		TypeDef::Sequence(sequence) => match value {
+			Value::String(hex) if is_byte(registry, sequence.type_param.id) => {
+				bytes::from_hex(hex).map_err(|e| e.to_string())?.encode_to(out);
+				Ok(())
+			},
+			Value::Array(items) => {
+				Compact(items.len() as u32).encode_to(out);
+				items
+					.iter()
+					.try_for_each(|item| encode_json(registry, sequence.type_param.id, item, out))
+			},
+			_ => Err(format!("Expected an array, got {value}")),
+		},
+		TypeDef::Array(array) => match value {
+			Value::String(hex) if is_byte(registry, array.type_param.id) => {
+				let bytes = bytes::from_hex(hex).map_err(|e| e.to_string())?;
+				if bytes.len() != array.len as usize {
+					return Err(format!("Expected {} bytes, got {}", array.len, bytes.len()))
+				}
+				out.extend(bytes);
+				Ok(())
+			},
+			Value::Array(items) if items.len() == array.len as usize => items
+				.iter()
+				.try_for_each(|item| encode_json(registry, array.type_param.id, item, out)),
+			_ => Err(format!("Expected an array of {} items, got {value}", array.len)),
+		},
+		TypeDef::Tuple(tuple) => match value {
+			Value::Null if tuple.fields.is_empty() => Ok(()),
+			Value::Array(items) if items.len() == tuple.fields.len() => items
+				.iter()
+				.zip(&tuple.fields)
+				.try_for_each(|(item, ty)| encode_json(registry, ty.id, item, out)),
+			_ => Err(format!("Expected an array of {} items, got {value}", tuple.fields.len())),
+		},
+		TypeDef::Primitive(primitive) => encode_primitive(primitive, value, out),
+		TypeDef::Compact(_) => {
+			Compact(unsigned(value)?).encode_to(out);
+			Ok(())
+		},
+		TypeDef::BitSequence(_) => Err("Bit sequences are not supported".into()),
+	}
+}
+
+/// Encode `value` as `fields`, which are an object if they are named, the value of the field if
+/// there is only one, and an array otherwise.
+fn encode_fields(
+	registry: &PortableRegistry,
+	fields: &[Field<PortableForm>],
+	value: &Value,
+	out: &mut Vec<u8>,
+) -> Result<(), String> {
+	match (fields, value) {
+		([], Value::Null) => Ok(()),
+		([field], value) if field.name.is_none() => encode_json(registry, field.ty.id, value, out),
+		(fields, Value::Object(object)) if fields.iter().all(|field| field.name.is_some()) => {
+			if object.len() != fields.len() {
+				return Err(format!("Expected {} fields, got {value}", fields.len()))
+			}
+			fields.iter().try_for_each(|field| {
+				let name = field.name.as_ref().expect("all fields are named; qed");
+				let value = object.get(name).ok_or_else(|| format!("Missing field {name}"))?;
+				encode_json(registry, field.ty.id, value, out)
+			})
+		},
+		(fields, Value::Array(items)) if items.len() == fields.len() => items
+			.iter()
+			.zip(fields)
+			.try_for_each(|(item, field)| encode_json(registry, field.ty.id, item, out)),
+		_ => Err(format!("Expected {} fields, got {value}", fields.len())),
+	}
+}
+
+fn encode_primitive(
+	primitive: &TypeDefPrimitive,
+	value: &Value,
+	out: &mut Vec<u8>,
+) -> Result<(), String> {
+	fn convert<T: TryFrom<N> + Encode, N: fmt::Display + Copy>(
+		number: N,
+		out: &mut Vec<u8>,
+	) -> Result<(), String> {
+		T::try_from(number)
+			.map_err(|_| format!("{number} is out of range"))?
+			.encode_to(out);
+		Ok(())
+	}
+
+	match primitive {
+		TypeDefPrimitive::Bool => value
+			.as_bool()
+			.ok_or_else(|| format!("Expected a boolean, got {value}"))?
+			.encode_to(out),
+		TypeDefPrimitive::Char => {
+			let mut chars = value.as_str().map(str::chars).into_iter().flatten();
+			match (chars.next(), chars.next()) {
+				(Some(char), None) => (char as u32).encode_to(out),
+				_ => return Err(format!("Expected a character, got {value}")),
+			}
+		},
+		TypeDefPrimitive::Str => value
+			.as_str()
+			.ok_or_else(|| format!("Expected a string, got {value}"))?
+			.encode_to(out),
+		TypeDefPrimitive::U8 => convert::<u8, _>(unsigned(value)?, out)?,
+		TypeDefPrimitive::U16 => convert::<u16, _>(unsigned(value)?, out)?,
+		TypeDefPrimitive::U32 => convert::<u32, _>(unsigned(value)?, out)?,
+		TypeDefPrimitive::U64 => convert::<u64, _>(unsigned(value)?, out)?,
+		TypeDefPrimitive::U128 => unsigned(value)?.encode_to(out),
+		TypeDefPrimitive::I8 => convert::<i8, _>(signed(value)?, out)?,
+		TypeDefPrimitive::I16 => convert::<i16, _>(signed(value)?, out)?,
+		TypeDefPrimitive::I32 => convert::<i32, _>(signed(value)?, out)?,
+		TypeDefPrimitive::I64 => convert::<i64, _>(signed(value)?, out)?,
+		TypeDefPrimitive::I128 => signed(value)?.encode_to(out),
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 =>
+			return Err("256-bit integers are not supported".into()),
+	}
+	Ok(())
+}
+
+/// Decode a value of the type `ty` of `registry` from `input` as JSON.
+///
+/// Integers that do not fit in 64 bits are decoded as strings.
+pub fn decode_json(
+	registry: &PortableRegistry,
+	ty: u32,
+	input: &mut &[u8],
+) -> Result<Value, String> {
+	let ty = registry.resolve(ty).ok_or_else(|| format!("Unknown type {ty}"))?;
+	let codec_error = |e: codec::Error| e.to_string();
+	Ok(match &ty.type_def {
+		TypeDef::Composite(composite) => decode_fields(registry, &composite.fields, input)?,
+		TypeDef::Variant(variant) => {
+			let index = u8::decode(input).map_err(codec_error)?;
+			let variant = variant
+				.variants
+				.iter()
+				.find(|variant| variant.index == index)
+				.ok_or_else(|| format!("Unknown variant index {index}"))?;
+			match (is_option(ty), variant.fields.is_empty()) {
+				(true, _) => decode_fields(registry, &variant.fields, input)?,
+				(false, true) => Value::String(variant.name.clone()),
+				(false, false) => Value::Object(Map::from_iter([(
+					variant.name.clone(),
+					decode_fields(registry, &variant.fields, input)?,
+				)])),
+			}
+		},
+		TypeDef::Sequence(sequence) => {
+			let len = Compact::<u32>::decode(input).map_err(codec_error)?.0;
+			decode_items(registry, sequence.type_param.id, len, input)?
+		},
+		TypeDef::Array(array) => decode_items(registry, array.type_param.id, array.len, input)?,
+		TypeDef::Tuple(tuple) if tuple.fields.is_empty() => Value::Null,
+		TypeDef::Tuple(tuple) => tuple
+			.fields
+			.iter()
+			.map(|ty| decode_json(registry, ty.id, input))
+			.collect::<Result<_, _>>()?,
+		TypeDef::Primitive(primitive) => decode_primitive(primitive, input).map_err(codec_error)?,
+		TypeDef::Compact(_) =>
+			unsigned_json(Compact::<u128>::decode(input).map_err(codec_error)?.0),
+		TypeDef::BitSequence(_) => return Err("Bit sequences are not supported".into()),
+	})
+}
+
+/// Decode `fields` from `input`, the inverse of [`encode_fields`].
+fn decode_fields(
+	registry: &PortableRegistry,
+	fields: &[Field<PortableForm>],
+	input: &mut &[u8],
+) -> Result<Value, String> {
+	match fields {
+		[] => Ok(Value::Null),
+		[field] if field.name.is_none() => decode_json(registry, field.ty.id, input),
+		fields if fields.iter().all(|field| field.name.is_some()) => fields
+			.iter()
+			.map(|field| {
+				let name = field.name.clone().expect("all fields are named; qed");
+				Ok((name, decode_json(registry, field.ty.id, input)?))
+			})
+			.collect::<Result<Map<_, _>, String>>()
+			.map(Value::Object),
+		fields => fields.iter().map(|field| decode_json(registry, field.ty.id, input)).collect(),
+	}
+}
+
+fn decode_items(
+	registry: &PortableRegistry,
+	ty: u32,
+	len: u32,
+	input: &mut &[u8],
+) -> Result<Value, String> {
+	if is_byte(registry, ty) {
+		let len = len as usize;
+		if input.len() < len {
+			return Err("Not enough data to fill buffer".into())
+		}
+		let (bytes, remaining) = input.split_at(len);
+		*input = remaining;
+		return Ok(Value::String(bytes::to_hex(bytes, false)))
+	}
+	(0..len).map(|_| decode_json(registry, ty, input)).collect()
+}
+
+fn unsigned_json(number: u128) -> Value {
+	u64::try_from(number).map_or_else(|_| Value::String(number.to_string()), Into::into)
+}
+
+fn signed_json(number: i128) -> Value {
+	i64::try_from(number).map_or_else(|_| Value::String(number.to_string()), Into::into)
+}
+
+fn decode_primitive(
+	primitive: &TypeDefPrimitive,
+	input: &mut &[u8],
+) -> Result<Value, codec::Error> {
+	Ok(match primitive {
+		TypeDefPrimitive::Bool => bool::decode(input)?.into(),
+		TypeDefPrimitive::Char => char::from_u32(u32::decode(input)?)
+			.ok_or("Invalid character")?
+			.to_string()
+			.into(),
+		TypeDefPrimitive::Str => String::decode(input)?.into(),
+		TypeDefPrimitive::U8 => u8::decode(input)?.into(),
+		TypeDefPrimitive::U16 => u16::decode(input)?.into(),
+		TypeDefPrimitive::U32 => u32::decode(input)?.into(),
+		TypeDefPrimitive::U64 => u64::decode(input)?.into(),
+		TypeDefPrimitive::U128 => unsigned_json(u128::decode(input)?),
+		TypeDefPrimitive::I8 => i8::decode(input)?.into(),
+		TypeDefPrimitive::I16 => i16::decode(input)?.into(),
+		TypeDefPrimitive::I32 => i32::decode(input)?.into(),
+		TypeDefPrimitive::I64 => i64::decode(input)?.into(),
+		TypeDefPrimitive::I128 => signed_json(i128::decode(input)?),
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 =>
+			return Err("256-bit integers are not supported".into()),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_metadata::v16::{CustomMetadata, ExtrinsicMetadata, OuterEnums};
+	use scale_info::{meta_type, Registry, TypeInfo};
+	use serde_json::json;
+	use std::cell::Cell;
+
+	fn registry<T: TypeInfo + 'static>() -> (PortableRegistry, u32) {
+		let mut registry = Registry::new();
+		let id = registry.register_type(&meta_type::<T>()).id;
+		(registry.into(), id)
+	}
+
+	fn assert_json<T: Encode + TypeInfo + 'static>(value: T, json: Value) {
+		let (registry, ty) = registry::<T>();
+		let mut encoded = Vec::new();
+		encode_json(&registry, ty, &json, &mut encoded).unwrap();
+		assert_eq!(encoded, value.encode());
+		assert_eq!(decode_json(&registry, ty, &mut &encoded[..]).unwrap(), json);
+	}
+
+	#[test]
+	fn json_values_are_encoded_with_their_type() {
+		assert_json((1u32, Some(vec![1u8, 2]), true), json!([1, "0x0102", true]));
+		assert_json(None::<u128>, Value::Null);
+		assert_json(Some(u128::MAX), json!(u128::MAX.to_string()));
+		assert_json(Result::<u8, bool>::Ok(5), json!({ "Ok": 5 }));
+		assert_json(vec![("a".to_string(), -3i16)], json!([["a", -3]]));
+		assert_json(Compact(1000u64), json!(1000));
+		assert_json([7u8; 2], json!("0x0707"));
+
+		// `char` has no SCALE encoding of its own, it is encoded as its `u32` code point.
+		let (registry, ty) = registry::<char>();
+		let mut encoded = Vec::new();
+		encode_json(&registry, ty, &json!("x"), &mut encoded).unwrap();
+		assert_eq!(encoded, ('x' as u32).encode());
+		assert_eq!(decode_json(&registry, ty, &mut &encoded[..]).unwrap(), json!("x"));
+	}
+
+	#[test]
+	fn invalid_json_values_are_rejected() {
+		let (registry, ty) = registry::<(u8, Option<bool>)>();
+		let encode = |json| encode_json(&registry, ty, &json, &mut Vec::new());
+
+		assert!(encode(json!([1, null])).is_ok());
+		assert!(encode(json!([300, null])).is_err());
+		assert!(encode(json!([1, 1])).is_err());
+		assert!(encode(json!([1])).is_err());
+		assert!(encode(json!("0x01")).is_err());
+	}
+
+	#[test]
+	fn dispatch_errors_decode_as_in_frame_support() {
+		use frame_support::view_functions::{ViewFunctionDispatchError as Error, ViewFunctionId};
+
+		let decode =
+			|error: Error| ViewFunctionDispatchError::decode(&mut &error.encode()[..]).unwrap();
+		let id = ViewFunctionId { prefix: [1; 16], suffix: [2; 16] };
+		let mut expected = [1; 32];
+		expected[16..].copy_from_slice(&[2; 16]);
+
+		assert_eq!(decode(Error::NotImplemented), ViewFunctionDispatchError::NotImplemented);
+		assert_eq!(decode(Error::NotFound(id)), ViewFunctionDispatchError::NotFound(expected));
+		assert_eq!(decode(Error::Codec), ViewFunctionDispatchError::Codec);
+	}
+
+	#[test]
+	fn metadata_is_cached_by_runtime_version() {
+		let metadata = RuntimeMetadataV16::new(
+			Vec::new(),
+			ExtrinsicMetadata {
+				versions: vec![4],
+				address_ty: meta_type::<()>(),
+				call_ty: meta_type::<()>(),
+				signature_ty: meta_type::<()>(),
+				transaction_extensions_by_version: Default::default(),
+				transaction_extensions: Vec::new(),
+			},
+			Vec::new(),
+			OuterEnums {
+				call_enum_ty: meta_type::<()>(),
+				event_enum_ty: meta_type::<()>(),
+				error_enum_ty: meta_type::<()>(),
+			},
+			CustomMetadata { map: Default::default() },
+		);
+		let metadata =
+			Some(OpaqueMetadata::new(RuntimeMetadataPrefixed::from(metadata).encode())).encode();
+		let calls = Cell::new(0);
+		let runtime_call = |method: &str, _| {
+			assert_eq!(method, METADATA_AT_VERSION);
+			calls.set(calls.get() + 1);
+			Ok(metadata.clone())
+		};
+		let version = |spec_version| RuntimeVersion { spec_version, ..Default::default() };
+
+		let cache = MetadataCache::default();
+		cache.get(&version(1), runtime_call).unwrap();
+		cache.get(&version(1), runtime_call).unwrap();
+		assert_eq!(calls.get(), 1);
+		cache.get(&version(2), runtime_call).unwrap();
+		assert_eq!(calls.get(), 2);
+	}
+}
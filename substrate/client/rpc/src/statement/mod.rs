@@ -18,29 +18,49 @@
 
 //! Substrate statement store API.
 
+use crate::{
+	utils::{spawn_subscription_task, BoundedVecDeque, PendingSubscription},
+	SubscriptionTaskExecutor,
+};
 use codec::{Decode, Encode};
+use futures::{future, StreamExt};
 use jsonrpsee::{
 	core::{async_trait, RpcResult},
-	Extensions,
+	Extensions, PendingSubscriptionSink,
 };
 /// Re-export the API for backward compatibility.
-pub use sc_rpc_api::statement::{error::Error, StatementApiServer};
+pub use sc_rpc_api::statement::{error::Error, StatementApiServer, StatementPage};
 use sp_core::Bytes;
-use sp_statement_store::{StatementSource, SubmitResult};
+use sp_statement_store::{Statement, StatementSource, SubmitResult, Topic};
 use std::sync::Arc;
 
+/// The maximum number of statements returned by `statement_page`.
+const MAX_PAGE_SIZE: u32 = 1000;
+
 /// Statement store API
 pub struct StatementStore {
 	store: Arc<dyn sp_statement_store::StatementStore>,
+	executor: SubscriptionTaskExecutor,
 }
 
 impl StatementStore {
 	/// Create new instance of Offchain API.
-	pub fn new(store: Arc<dyn sp_statement_store::StatementStore>) -> Self {
-		StatementStore { store }
+	pub fn new(
+		store: Arc<dyn sp_statement_store::StatementStore>,
+		executor: SubscriptionTaskExecutor,
+	) -> Self {
+		StatementStore { store, executor }
 	}
 }
 
+/// Returns `true` if `statement` includes all `match_all_topics` and its decryption key is `dest`.
+fn matches(statement: &Statement, match_all_topics: &[Topic], dest: Option<[u8; 32]>) -> bool {
+	statement.decryption_key() == dest &&
+		match_all_topics.iter().all(|topic| {
+			(0..sp_statement_store::MAX_TOPICS).any(|i| statement.topic(i) == Some(*topic))
+		})
+}
+
 #[async_trait]
 impl StatementApiServer for StatementStore {
 	fn dump(&self, ext: &Extensions) -> RpcResult<Vec<Bytes>> {
@@ -123,6 +143,46 @@ impl StatementApiServer for StatementStore {
 			.collect())
 	}
 
+	fn page(
+		&self,
+		match_all_topics: Vec<[u8; 32]>,
+		dest: Option<[u8; 32]>,
+		after: Option<[u8; 32]>,
+		limit: u32,
+	) -> RpcResult<StatementPage> {
+		if limit == 0 {
+			return Err(Error::InvalidParams("`limit` must be greater than zero".into()).into());
+		}
+
+		let page = self
+			.store
+			.statements_page(&match_all_topics, dest, after, limit.min(MAX_PAGE_SIZE) as usize)
+			.map_err(|e| Error::StatementStore(e.to_string()))?;
+		Ok(StatementPage {
+			statements: page.statements.into_iter().map(|(_, s)| s.encode().into()).collect(),
+			next: page.next,
+		})
+	}
+
+	fn subscribe_statements(
+		&self,
+		pending: PendingSubscriptionSink,
+		match_all_topics: Vec<[u8; 32]>,
+		dest: Option<[u8; 32]>,
+	) {
+		let stream = self.store.subscribe_statements().filter_map(move |(_, statement)| {
+			future::ready(
+				matches(&statement, &match_all_topics, dest)
+					.then(|| Bytes::from(statement.encode())),
+			)
+		});
+
+		spawn_subscription_task(
+			&self.executor,
+			PendingSubscription::from(pending).pipe_from_stream(stream, BoundedVecDeque::default()),
+		);
+	}
+
 	fn submit(&self, encoded: Bytes) -> RpcResult<()> {
 		let statement = Decode::decode(&mut &*encoded)
 			.map_err(|e| Error::StatementStore(format!("Error decoding statement: {:?}", e)))?;
@@ -134,6 +194,8 @@ impl StatementApiServer for StatementStore {
 				Err(Error::StatementStore("Submitted an expired statement.".into()).into()),
 			SubmitResult::Bad(e) => Err(Error::StatementStore(e.into()).into()),
 			SubmitResult::Ignored => Err(Error::StatementStore("Store is full.".into()).into()),
+			SubmitResult::RateLimited =>
+				Err(Error::StatementStore("Account submission rate limit exceeded.".into()).into()),
 			SubmitResult::InternalError(e) => Err(Error::StatementStore(e.to_string()).into()),
 		}
 	}
@@ -0,0 +1,70 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Implementation of the [`TxPoolApiServer`] trait providing transaction pool inspection.
+
+#[cfg(test)]
+mod tests;
+
+use jsonrpsee::{core::async_trait, Extensions};
+use sc_rpc_api::{check_if_safe, txpool::error::Error};
+use sc_transaction_pool_api::{
+	inspect::{TransactionInspection, TransactionPoolContent},
+	TransactionPoolInspect,
+};
+use sp_runtime::{DeserializeOwned, Serialize};
+use std::sync::Arc;
+
+pub use sc_rpc_api::txpool::TxPoolApiServer;
+
+/// The transaction pool inspection API. All methods are unsafe.
+pub struct TxPool<P> {
+	pool: Arc<P>,
+}
+
+impl<P> TxPool<P> {
+	/// Create a new transaction pool inspection API.
+	pub fn new(pool: Arc<P>) -> Self {
+		Self { pool }
+	}
+}
+
+#[async_trait]
+impl<P> TxPoolApiServer<P::BlockHash, P::Hash> for TxPool<P>
+where
+	P: TransactionPoolInspect + 'static,
+	P::BlockHash: Serialize + DeserializeOwned + Send + Sync + 'static,
+	P::Hash: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+	async fn content(
+		&self,
+		ext: &Extensions,
+	) -> Result<TransactionPoolContent<P::BlockHash, P::Hash>, Error> {
+		check_if_safe(ext)?;
+		Ok(self.pool.content().await)
+	}
+
+	async fn inspect(
+		&self,
+		ext: &Extensions,
+		hash: P::Hash,
+	) -> Result<TransactionInspection<P::BlockHash, P::Hash>, Error> {
+		check_if_safe(ext)?;
+		Ok(self.pool.inspect(&hash).await)
+	}
+}
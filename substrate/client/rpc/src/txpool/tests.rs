@@ -0,0 +1,138 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+
+use codec::Encode;
+use jsonrpsee::{core::EmptyServerParams as EmptyParams, MethodsError as RpcError, RpcModule};
+use sc_rpc_api::DenyUnsafe;
+use sc_transaction_pool::{BasicPool, FullChainApi};
+use sc_transaction_pool_api::{TransactionPool, TransactionSource};
+use sp_blockchain::HeaderBackend;
+use sp_core::H256;
+use sp_crypto_hashing::blake2_256;
+use substrate_test_runtime_client::{
+	runtime::{Block, ExtrinsicBuilder, Transfer},
+	Backend, Client, Sr25519Keyring,
+};
+
+type FullTransactionPool = BasicPool<FullChainApi<Client<Backend>, Block>, Block>;
+
+fn setup(
+	deny_unsafe: DenyUnsafe,
+) -> (Arc<Client<Backend>>, Arc<FullTransactionPool>, RpcModule<TxPool<FullTransactionPool>>) {
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let pool = Arc::new(BasicPool::new_full(
+		Default::default(),
+		true.into(),
+		None,
+		sp_core::testing::TaskExecutor::new(),
+		client.clone(),
+	));
+	let mut module = TxPool::new(pool.clone()).into_rpc();
+	module.extensions_mut().insert(deny_unsafe);
+	(client, pool, module)
+}
+
+#[tokio::test]
+async fn txpool_content_reports_transactions() {
+	let (client, pool, api) = setup(DenyUnsafe::No);
+	let xt = ExtrinsicBuilder::new_transfer(Transfer {
+		amount: Default::default(),
+		nonce: 0,
+		from: Sr25519Keyring::Alice.into(),
+		to: Sr25519Keyring::Bob.into(),
+	})
+	.build();
+	let hash: H256 = blake2_256(&xt.encode()).into();
+	let at = client.info().genesis_hash;
+	pool.submit_one(at, TransactionSource::External, xt).await.unwrap();
+
+	let content: TransactionPoolContent<H256, H256> =
+		api.call("txpool_content", EmptyParams::new()).await.unwrap();
+	assert_eq!(content.transactions.len(), 1);
+	assert_eq!(content.transactions[0].hash, hash);
+	assert_eq!(content.transactions[0].views, vec![at]);
+	assert_eq!(content.views.len(), 1);
+	assert_eq!(content.views[0].ready.len(), 1);
+	assert!(content.views[0].future.is_empty());
+
+	let inspected: TransactionInspection<H256, H256> =
+		api.call("txpool_inspect", [hash]).await.unwrap();
+	assert_eq!(inspected, TransactionInspection { banned: false, content: Some(content) });
+
+	let inspected: TransactionInspection<H256, H256> =
+		api.call("txpool_inspect", [H256::repeat_byte(1)]).await.unwrap();
+	assert_eq!(inspected, TransactionInspection { banned: false, content: None });
+}
+
+#[tokio::test]
+async fn txpool_inspect_reports_single_transaction() {
+	let (client, pool, api) = setup(DenyUnsafe::No);
+	let transfer = |nonce| {
+		ExtrinsicBuilder::new_transfer(Transfer {
+			amount: Default::default(),
+			nonce,
+			from: Sr25519Keyring::Alice.into(),
+			to: Sr25519Keyring::Bob.into(),
+		})
+		.build()
+	};
+	let at = client.info().genesis_hash;
+	let (xt0, xt1) = (transfer(0), transfer(1));
+	let hash1: H256 = blake2_256(&xt1.encode()).into();
+	pool.submit_at(at, TransactionSource::External, vec![xt0, xt1]).await.unwrap();
+
+	let inspected: TransactionInspection<H256, H256> =
+		api.call("txpool_inspect", [hash1]).await.unwrap();
+	let content = inspected.content.unwrap();
+	assert_eq!(content.transactions.len(), 1);
+	assert_eq!(content.transactions[0].hash, hash1);
+	assert_eq!(content.views.len(), 1);
+	assert_eq!(content.views[0].ready.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![hash1]);
+}
+
+#[tokio::test]
+async fn txpool_inspect_reports_banned_transaction() {
+	let (client, pool, api) = setup(DenyUnsafe::No);
+	let xt = ExtrinsicBuilder::new_transfer(Transfer {
+		amount: Default::default(),
+		nonce: 0,
+		from: Sr25519Keyring::Alice.into(),
+		to: Sr25519Keyring::Bob.into(),
+	})
+	.build();
+	let hash: H256 = blake2_256(&xt.encode()).into();
+	let at = client.info().genesis_hash;
+	pool.submit_one(at, TransactionSource::External, xt).await.unwrap();
+	pool.report_invalid(None, [(hash, None)].into()).await;
+
+	let inspected: TransactionInspection<H256, H256> =
+		api.call("txpool_inspect", [hash]).await.unwrap();
+	assert_eq!(inspected, TransactionInspection { banned: true, content: None });
+}
+
+#[tokio::test]
+async fn txpool_content_is_unsafe() {
+	let (_, _, api) = setup(DenyUnsafe::Yes);
+	assert!(matches!(
+		api.call::<_, TransactionPoolContent<H256, H256>>("txpool_content", EmptyParams::new())
+			.await,
+		Err(RpcError::JsonRpc(e)) if e.message() == "RPC call is unsafe to be called externally"
+	));
+}
@@ -841,14 +841,18 @@ where
 					runtime_api.register_extension(ProofSizeExt::new(recorder));
 				}
 
-				runtime_api.execute_block(
-					*parent_hash,
-					Block::new(import_block.header.clone(), body.clone()),
-				)?;
+				sc_tracing::execution_metrics::measure_import(|| {
+					runtime_api.execute_block(
+						*parent_hash,
+						Block::new(import_block.header.clone(), body.clone()),
+					)
+				})?;
 
 				let state = self.backend.state_at(*parent_hash, call_context.into())?;
-				let gen_storage_changes = runtime_api
-					.into_storage_changes(&state, *parent_hash)
+				let gen_storage_changes =
+					sc_tracing::execution_metrics::measure_phase("storage_changes", || {
+						runtime_api.into_storage_changes(&state, *parent_hash)
+					})
 					.map_err(sp_blockchain::Error::Storage)?;
 
 				if import_block.header.state_root() != &gen_storage_changes.transaction_storage_root
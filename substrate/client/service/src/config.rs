@@ -21,7 +21,7 @@
 pub use jsonrpsee::server::BatchRequestConfig as RpcBatchRequestConfig;
 use prometheus_endpoint::Registry;
 use sc_chain_spec::ChainSpec;
-pub use sc_client_db::{BlocksPruning, Database, DatabaseSource, PruningMode};
+pub use sc_client_db::{BlocksPruning, Constraints, Database, DatabaseSource, PruningMode};
 pub use sc_executor::{WasmExecutionMethod, WasmtimeInstantiationStrategy};
 pub use sc_network::{
 	config::{
@@ -78,6 +78,10 @@ pub struct Configuration {
 	///
 	/// NOTE: only finalized blocks are subject for removal!
 	pub blocks_pruning: BlocksPruning,
+	/// Storage key of the SCALE encoded `u64` timestamp of blocks, used to prune the state by age.
+	///
+	/// Pruning the state by age is not available when `None`.
+	pub block_timestamp_key: Option<Vec<u8>>,
 	/// Chain configuration.
 	pub chain_spec: Box<dyn ChainSpec>,
 	/// Runtime executor configuration.
@@ -172,6 +176,8 @@ pub struct OffchainWorkerConfig {
 	pub enabled: bool,
 	/// allow writes from the runtime to the offchain worker database.
 	pub indexing_enabled: bool,
+	/// Submit the tasks of the runtime once they are valid.
+	pub submit_tasks: bool,
 }
 
 /// Configuration of the Prometheus endpoint.
@@ -236,6 +242,7 @@ impl Configuration {
 			source: self.database.clone(),
 			blocks_pruning: self.blocks_pruning,
 			metrics_registry: self.prometheus_registry().cloned(),
+			block_timestamp: self.block_timestamp_key.clone().map(sc_client_db::storage_timestamp),
 		}
 	}
 }
@@ -0,0 +1,74 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Keeping the state of chosen finalized blocks regardless of the state pruning mode.
+
+use futures::StreamExt;
+use log::{info, warn};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_core::storage::StorageKey;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, Zero};
+use std::sync::Arc;
+
+const LOG_TARGET: &str = "kept-states";
+
+/// Keeps the state of every finalized block that changes the value stored at `key`, such as the
+/// first block of each era with the `ActiveEra` storage item of the staking pallet.
+///
+/// Keeping a state walks the whole state of the block, so the returned future should be spawned as
+/// a blocking task.
+pub async fn keep_states_on_change<Block, BE, C>(client: Arc<C>, backend: Arc<BE>, key: StorageKey)
+where
+	Block: BlockT,
+	BE: Backend<Block>,
+	C: BlockchainEvents<Block> + StorageProvider<Block, BE> + HeaderBackend<Block>,
+{
+	let mut finality_notifications = client.finality_notification_stream();
+	while let Some(notification) = finality_notifications.next().await {
+		let finalized = notification.tree_route.iter().chain(std::iter::once(&notification.hash));
+		for hash in finalized {
+			match changes_value(&*client, *hash, &key) {
+				Ok(false) => {},
+				Ok(true) => match backend.keep_state(*hash) {
+					Ok(()) => info!(target: LOG_TARGET, "Kept state of {hash:?}"),
+					Err(e) => warn!(target: LOG_TARGET, "Failed to keep state of {hash:?}: {e}"),
+				},
+				Err(e) => warn!(target: LOG_TARGET, "Failed to read state of {hash:?}: {e}"),
+			}
+		}
+	}
+}
+
+/// Whether the block `hash` changes the value stored at `key`.
+fn changes_value<Block, BE, C>(
+	client: &C,
+	hash: Block::Hash,
+	key: &StorageKey,
+) -> sp_blockchain::Result<bool>
+where
+	Block: BlockT,
+	BE: Backend<Block>,
+	C: StorageProvider<Block, BE> + HeaderBackend<Block>,
+{
+	let header = client.expect_header(hash)?;
+	if header.number().is_zero() {
+		return Ok(false)
+	}
+	Ok(client.storage(hash, key)? != client.storage(*header.parent_hash(), key)?)
+}
@@ -28,6 +28,7 @@ pub mod config;
 pub mod error;
 
 mod builder;
+mod kept_states;
 mod metrics;
 mod task_manager;
 
@@ -66,6 +67,7 @@ pub use self::{
 	},
 	client::{ClientConfig, LocalCallExecutor},
 	error::Error,
+	kept_states::keep_states_on_change,
 	metrics::MetricsService,
 };
 #[allow(deprecated)]
@@ -77,7 +79,8 @@ pub use sc_chain_spec::{
 };
 
 pub use config::{
-	BasePath, BlocksPruning, Configuration, DatabaseSource, PruningMode, Role, RpcMethods, TaskType,
+	BasePath, BlocksPruning, Configuration, Constraints, DatabaseSource, PruningMode, Role,
+	RpcMethods, TaskType,
 };
 pub use sc_chain_spec::{
 	ChainSpec, ChainType, Extension as ChainSpecExtension, GenericChainSpec, NoExtension,
@@ -1487,6 +1487,7 @@ fn doesnt_import_blocks_that_revert_finality() {
 				blocks_pruning: BlocksPruning::KeepAll,
 				source: DatabaseSource::RocksDb { path: tmp.path().into(), cache_size: 1024 },
 				metrics_registry: None,
+				block_timestamp: None,
 			},
 			u64::MAX,
 		)
@@ -1764,6 +1765,7 @@ fn returns_status_for_pruned_blocks() {
 				blocks_pruning: BlocksPruning::KeepFinalized,
 				source: DatabaseSource::RocksDb { path: tmp.path().into(), cache_size: 1024 },
 				metrics_registry: None,
+				block_timestamp: None,
 			},
 			u64::MAX,
 		)
@@ -39,14 +39,17 @@
 //!
 //! # Pruning.
 //! See `RefWindow` for pruning algorithm details. `StateDb` prunes on each canonicalization until
-//! pruning constraints are satisfied.
+//! pruning constraints are satisfied. The window can be limited by its number of blocks, by the
+//! age of its blocks and by the size of the state it holds. Individual canonical blocks can be
+//! excluded from pruning with `StateDb::keep`; keeping their trie nodes around is up to the
+//! database, which must count references for this.
 
 mod noncanonical;
 mod pruning;
 #[cfg(test)]
 mod test;
 
-use codec::Codec;
+use codec::{Codec, Decode, Encode};
 use log::trace;
 use noncanonical::NonCanonicalOverlay;
 use parking_lot::RwLock;
@@ -62,6 +65,7 @@ const PRUNING_MODE: &[u8] = b"mode";
 const PRUNING_MODE_ARCHIVE: &[u8] = b"archive";
 const PRUNING_MODE_ARCHIVE_CANON: &[u8] = b"archive_canonical";
 const PRUNING_MODE_CONSTRAINED: &[u8] = b"constrained";
+const KEPT_BLOCKS: &[u8] = b"kept_blocks";
 pub(crate) const DEFAULT_MAX_BLOCK_CONSTRAINT: u32 = 256;
 
 /// Database value type.
@@ -218,12 +222,46 @@ pub struct CommitSet<H: Hash> {
 	pub meta: ChangeSet<Vec<u8>>,
 }
 
-/// Pruning constraints. If none are specified pruning is
+/// Pruning constraints. A block is pruned as soon as any of the specified constraints is exceeded.
+/// If none are specified, only non-canonical states are kept.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Constraints {
-	/// Maximum blocks. Defaults to 0 when unspecified, effectively keeping only non-canonical
-	/// states.
+	/// Maximum blocks.
 	pub max_blocks: Option<u32>,
+	/// Maximum age in milliseconds, measured from the timestamp of the last canonicalized block.
+	/// Blocks with an unknown timestamp are only pruned by age once a later block is.
+	pub max_age: Option<u64>,
+	/// Maximum size in bytes, approximated by the size of the trie nodes inserted by the blocks
+	/// in the window.
+	pub max_size: Option<u64>,
+}
+
+impl Constraints {
+	/// Whether the first block of `pruning` has to be pruned to satisfy the constraints.
+	fn exceeded_by<BlockHash: Hash, Key: Hash, D: MetaDb>(
+		&self,
+		pruning: &mut RefWindow<BlockHash, Key, D>,
+	) -> Result<bool, Error<D::Error>> {
+		let window_size = pruning.window_size();
+		if window_size == 0 {
+			return Ok(false)
+		}
+		if self.max_blocks.is_none() && self.max_age.is_none() && self.max_size.is_none() {
+			return Ok(true)
+		}
+		if self.max_blocks.map_or(false, |max_blocks| window_size > max_blocks as u64) ||
+			self.max_size.map_or(false, |max_size| pruning.size() > max_size)
+		{
+			return Ok(true)
+		}
+		if let Some(max_age) = self.max_age {
+			return Ok(match (pruning.last_timestamp(), pruning.next_timestamp()?) {
+				(Some(now), Some(timestamp)) => now.saturating_sub(timestamp) > max_age,
+				_ => false,
+			})
+		}
+		Ok(false)
+	}
 }
 
 /// Pruning mode.
@@ -240,7 +278,7 @@ pub enum PruningMode {
 impl PruningMode {
 	/// Create a mode that keeps given number of blocks.
 	pub fn blocks_pruning(n: u32) -> PruningMode {
-		PruningMode::Constrained(Constraints { max_blocks: Some(n) })
+		PruningMode::Constrained(Constraints { max_blocks: Some(n), max_age: None, max_size: None })
 	}
 
 	/// Is this an archive (either ArchiveAll or ArchiveCanonical) pruning mode?
@@ -278,7 +316,7 @@ impl Default for PruningMode {
 
 impl Default for Constraints {
 	fn default() -> Self {
-		Self { max_blocks: Some(DEFAULT_MAX_BLOCK_CONSTRAINT) }
+		Self { max_blocks: Some(DEFAULT_MAX_BLOCK_CONSTRAINT), max_age: None, max_size: None }
 	}
 }
 
@@ -305,6 +343,10 @@ pub struct StateDbSync<BlockHash: Hash, Key: Hash, D: MetaDb> {
 	pruning: Option<RefWindow<BlockHash, Key, D>>,
 	pinned: HashMap<BlockHash, u32>,
 	ref_counting: bool,
+	/// Timestamps of non-canonical blocks, until they are canonicalized.
+	timestamps: HashMap<BlockHash, u64>,
+	/// Canonical blocks that are never pruned, with their numbers.
+	kept: HashMap<BlockHash, u64>,
 }
 
 impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
@@ -316,13 +358,40 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		trace!(target: LOG_TARGET, "StateDb settings: {:?}. Ref-counting: {}", mode, ref_counting);
 
 		let non_canonical: NonCanonicalOverlay<BlockHash, Key> = NonCanonicalOverlay::new(&db)?;
+		let kept = match db.get_meta(&to_meta_key(KEPT_BLOCKS, &())).map_err(Error::Db)? {
+			Some(buffer) => <Vec<(BlockHash, u64)>>::decode(&mut buffer.as_slice())?,
+			None => Vec::new(),
+		};
 		let pruning: Option<RefWindow<BlockHash, Key, D>> = match mode {
-			PruningMode::Constrained(Constraints { max_blocks }) =>
-				Some(RefWindow::new(db, max_blocks.unwrap_or(0), ref_counting)?),
+			PruningMode::Constrained(ref constraints) => {
+				let window_size = match constraints.max_blocks {
+					Some(max_blocks) => max_blocks,
+					None if constraints.max_age.is_some() || constraints.max_size.is_some() => {
+						if ref_counting {
+							log::warn!(
+								target: LOG_TARGET,
+								"Pruning by age or size keeps the whole pruning window in memory \
+								with this database. Consider switching your database to paritydb."
+							);
+						}
+						DEFAULT_MAX_BLOCK_CONSTRAINT
+					},
+					None => 0,
+				};
+				Some(RefWindow::new(db, window_size, ref_counting)?)
+			},
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
 		};
 
-		Ok(StateDbSync { mode, non_canonical, pruning, pinned: Default::default(), ref_counting })
+		Ok(StateDbSync {
+			mode,
+			non_canonical,
+			pruning,
+			pinned: Default::default(),
+			ref_counting,
+			timestamps: Default::default(),
+			kept: kept.into_iter().collect(),
+		})
 	}
 
 	fn insert_block(
@@ -331,6 +400,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		number: u64,
 		parent_hash: &BlockHash,
 		mut changeset: ChangeSet<Key>,
+		timestamp: Option<u64>,
 	) -> Result<CommitSet<Key>, Error<D::Error>> {
 		match self.mode {
 			PruningMode::ArchiveAll => {
@@ -338,10 +408,17 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 				// write changes immediately
 				Ok(CommitSet { data: changeset, meta: Default::default() })
 			},
-			PruningMode::Constrained(_) | PruningMode::ArchiveCanonical => self
+			PruningMode::ArchiveCanonical => self
 				.non_canonical
 				.insert(hash, number, parent_hash, changeset)
 				.map_err(Into::into),
+			PruningMode::Constrained(_) => {
+				let commit = self.non_canonical.insert(hash, number, parent_hash, changeset)?;
+				if let Some(timestamp) = timestamp {
+					self.timestamps.insert(hash.clone(), timestamp);
+				}
+				Ok(commit)
+			},
 		}
 	}
 
@@ -358,8 +435,10 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		if self.mode == PruningMode::ArchiveCanonical {
 			commit.data.deleted.clear();
 		}
+		let timestamp = self.timestamps.remove(hash);
+		self.timestamps.retain(|hash, _| self.non_canonical.have_block(hash));
 		if let Some(ref mut pruning) = self.pruning {
-			pruning.note_canonical(hash, number, &mut commit)?;
+			pruning.note_canonical(hash, number, timestamp, &mut commit)?;
 		}
 		self.prune(&mut commit)?;
 		Ok(commit)
@@ -381,7 +460,9 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		match self.mode {
 			PruningMode::ArchiveAll => IsPruned::NotPruned,
 			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) => {
-				if self
+				if self.kept.contains_key(hash) {
+					IsPruned::NotPruned
+				} else if self
 					.non_canonical
 					.last_canonicalized_block_number()
 					.map(|c| number > c)
@@ -412,7 +493,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 			(&mut self.pruning, &self.mode)
 		{
 			loop {
-				if pruning.window_size() <= constraints.max_blocks.unwrap_or(0) as u64 {
+				if !constraints.exceeded_by(pruning)? {
 					break
 				}
 
@@ -463,6 +544,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 			PruningMode::ArchiveAll => Ok(()),
 			PruningMode::ArchiveCanonical | PruningMode::Constrained(_) => {
 				let have_block = self.non_canonical.have_block(hash) ||
+					self.kept.contains_key(hash) ||
 					self.pruning.as_ref().map_or_else(
 						|| hint(),
 						|pruning| match pruning.have_block(hash, number) {
@@ -502,6 +584,48 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 		}
 	}
 
+	fn keep(
+		&mut self,
+		hash: &BlockHash,
+		number: u64,
+	) -> Result<Option<CommitSet<Key>>, Error<D::Error>> {
+		if self.mode.is_archive() || self.kept.contains_key(hash) {
+			return Ok(None)
+		}
+		let canonical = self
+			.non_canonical
+			.last_canonicalized_block_number()
+			.map_or(false, |c| number <= c);
+		if !canonical || self.is_pruned(hash, number) == IsPruned::Pruned {
+			return Err(StateDbError::InvalidBlock.into())
+		}
+		trace!(target: LOG_TARGET, "Keeping block: {:?}", hash);
+		self.kept.insert(hash.clone(), number);
+		Ok(Some(self.kept_commit()))
+	}
+
+	fn release(&mut self, hash: &BlockHash) -> Option<CommitSet<Key>> {
+		self.kept.remove(hash)?;
+		trace!(target: LOG_TARGET, "Releasing kept block: {:?}", hash);
+		Some(self.kept_commit())
+	}
+
+	fn kept_blocks(&self) -> Vec<(BlockHash, u64)> {
+		let mut kept: Vec<_> =
+			self.kept.iter().map(|(hash, number)| (hash.clone(), *number)).collect();
+		kept.sort_by_key(|(_, number)| *number);
+		kept
+	}
+
+	fn kept_commit(&self) -> CommitSet<Key> {
+		let mut commit = CommitSet::default();
+		commit
+			.meta
+			.inserted
+			.push((to_meta_key(KEPT_BLOCKS, &()), self.kept_blocks().encode()));
+		commit
+	}
+
 	fn sync(&mut self) {
 		self.non_canonical.sync();
 	}
@@ -579,15 +703,17 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 		self.db.read().mode.clone()
 	}
 
-	/// Add a new non-canonical block.
+	/// Add a new non-canonical block. The `timestamp` of the block, in milliseconds, is used to
+	/// prune by age.
 	pub fn insert_block(
 		&self,
 		hash: &BlockHash,
 		number: u64,
 		parent_hash: &BlockHash,
 		changeset: ChangeSet<Key>,
+		timestamp: Option<u64>,
 	) -> Result<CommitSet<Key>, Error<D::Error>> {
-		self.db.write().insert_block(hash, number, parent_hash, changeset)
+		self.db.write().insert_block(hash, number, parent_hash, changeset, timestamp)
 	}
 
 	/// Finalize a previously inserted block.
@@ -609,6 +735,30 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 		self.db.write().unpin(hash)
 	}
 
+	/// Exclude a canonical block that has not been pruned yet from pruning. The database must keep
+	/// the trie nodes of its state around by itself, since these are still deleted as the window
+	/// moves on.
+	///
+	/// Returns `None` if the block is already kept, or if the pruning mode never prunes it.
+	pub fn keep(
+		&self,
+		hash: &BlockHash,
+		number: u64,
+	) -> Result<Option<CommitSet<Key>>, Error<D::Error>> {
+		self.db.write().keep(hash, number)
+	}
+
+	/// Allow pruning of a block previously passed to [`Self::keep`].
+	/// Returns `None` if the block is not kept.
+	pub fn release(&self, hash: &BlockHash) -> Option<CommitSet<Key>> {
+		self.db.write().release(hash)
+	}
+
+	/// Returns the blocks excluded from pruning, ordered by number.
+	pub fn kept_blocks(&self) -> Vec<(BlockHash, u64)> {
+		self.db.read().kept_blocks()
+	}
+
 	/// Confirm that all changes made to commit sets are on disk. Allows for temporarily pinned
 	/// blocks to be released.
 	pub fn sync(&self) {
@@ -671,6 +821,22 @@ pub enum IsPruned {
 	MaybePruned,
 }
 
+/// Rewrite the journals of a state database that is moved from a backend without reference
+/// counting to one with it.
+///
+/// Every key referenced by the journals is passed through `map_key`. Deletions in the pruning
+/// journal that were cancelled by a later re-insertion are dropped, since a reference counting
+/// backend keeps track of re-insertions itself. Returns the journal entries to write to the meta
+/// column of the new database in place of the existing ones.
+pub fn migrate_journals<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	map_key: impl Fn(Key) -> Key,
+) -> Result<Vec<(Vec<u8>, DBValue)>, Error<D::Error>> {
+	let mut entries = noncanonical::migrate_journal::<BlockHash, Key, D>(db, &map_key)?;
+	entries.extend(pruning::migrate_journal::<BlockHash, Key, D>(db, &map_key)?);
+	Ok(entries)
+}
+
 fn fetch_stored_pruning_mode<D: MetaDb>(db: &D) -> Result<Option<PruningMode>, Error<D::Error>> {
 	let meta_key_mode = to_meta_key(PRUNING_MODE, &());
 	if let Some(stored_mode) = db.get_meta(&meta_key_mode).map_err(Error::Db)? {
@@ -723,6 +889,7 @@ mod tests {
 					1,
 					&H256::from_low_u64_be(0),
 					make_changeset(&[1], &[91]),
+					None,
 				)
 				.unwrap(),
 		);
@@ -733,6 +900,7 @@ mod tests {
 					2,
 					&H256::from_low_u64_be(1),
 					make_changeset(&[21], &[921, 1]),
+					None,
 				)
 				.unwrap(),
 		);
@@ -743,6 +911,7 @@ mod tests {
 					2,
 					&H256::from_low_u64_be(1),
 					make_changeset(&[22], &[922]),
+					None,
 				)
 				.unwrap(),
 		);
@@ -753,6 +922,7 @@ mod tests {
 					3,
 					&H256::from_low_u64_be(21),
 					make_changeset(&[3], &[93]),
+					None,
 				)
 				.unwrap(),
 		);
@@ -764,6 +934,7 @@ mod tests {
 					4,
 					&H256::from_low_u64_be(3),
 					make_changeset(&[4], &[94]),
+					None,
 				)
 				.unwrap(),
 		);
@@ -788,8 +959,10 @@ mod tests {
 
 	#[test]
 	fn block_record_unavailable() {
-		let (mut db, state_db) =
-			make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(1) }));
+		let (mut db, state_db) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(1),
+			..Default::default()
+		}));
 		// import 2 blocks
 		for i in &[5, 6] {
 			db.commit(
@@ -799,6 +972,7 @@ mod tests {
 						*i,
 						&H256::from_low_u64_be(*i - 1),
 						make_changeset(&[], &[]),
+						None,
 					)
 					.unwrap(),
 			);
@@ -823,13 +997,19 @@ mod tests {
 
 	#[test]
 	fn prune_window_0() {
-		let (db, _) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(0) }));
+		let (db, _) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(0),
+			..Default::default()
+		}));
 		assert!(db.data_eq(&make_db(&[21, 3, 922, 94])));
 	}
 
 	#[test]
 	fn prune_window_1() {
-		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(1) }));
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(1),
+			..Default::default()
+		}));
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(0), 0), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::Pruned);
@@ -839,7 +1019,10 @@ mod tests {
 
 	#[test]
 	fn prune_window_2() {
-		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(2) }));
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(2),
+			..Default::default()
+		}));
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(0), 0), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::NotPruned);
@@ -847,6 +1030,131 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
 	}
 
+	#[test]
+	fn prune_by_size() {
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: None,
+			max_size: Some(32),
+			..Default::default()
+		}));
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::Pruned);
+		assert!(db.data_eq(&make_db(&[21, 3, 922, 93, 94])));
+
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: None,
+			max_size: Some(64),
+			..Default::default()
+		}));
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
+	}
+
+	/// Import and canonicalize a chain of `blocks`, each replacing the node of its parent.
+	fn import_chain(
+		db: &mut TestDb,
+		state_db: &StateDb<H256, H256, TestDb>,
+		blocks: std::ops::RangeInclusive<u64>,
+		timestamp: impl Fn(u64) -> Option<u64>,
+	) {
+		for i in blocks {
+			let deleted = if i > 1 { vec![i - 1] } else { Vec::new() };
+			let changeset = make_changeset(&[i], &deleted);
+			let hash = H256::from_low_u64_be(i);
+			let parent_hash = H256::from_low_u64_be(i - 1);
+			db.commit(
+				&state_db.insert_block(&hash, i, &parent_hash, changeset, timestamp(i)).unwrap(),
+			);
+			db.commit(&state_db.canonicalize_block(&hash).unwrap());
+		}
+	}
+
+	#[test]
+	fn prune_by_age() {
+		let mut db = make_db(&[]);
+		let mode = Constraints { max_blocks: None, max_age: Some(10), ..Default::default() };
+		let (init, state_db) =
+			StateDb::open(db.clone(), Some(PruningMode::Constrained(mode)), false, true).unwrap();
+		db.commit(&init);
+
+		import_chain(&mut db, &state_db, 1..=5, |i| Some(i * 6));
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(3), 3), IsPruned::Pruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(4), 4), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[3, 4, 5])));
+
+		// Blocks without a timestamp inherit the one of their parent.
+		import_chain(&mut db, &state_db, 6..=6, |_| None);
+		import_chain(&mut db, &state_db, 7..=7, |_| Some(40));
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(4), 4), IsPruned::Pruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(5), 5), IsPruned::NotPruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(6), 6), IsPruned::NotPruned);
+	}
+
+	#[test]
+	fn prune_by_age_without_timestamps() {
+		let mut db = make_db(&[]);
+		let mode = Constraints { max_blocks: None, max_age: Some(10), ..Default::default() };
+		let (init, state_db) =
+			StateDb::open(db.clone(), Some(PruningMode::Constrained(mode)), false, true).unwrap();
+		db.commit(&init);
+
+		// Blocks with an unknown age are not pruned by age.
+		import_chain(&mut db, &state_db, 1..=3, |_| None);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[1, 2, 3])));
+
+		// Until a later block is old enough.
+		import_chain(&mut db, &state_db, 4..=4, |_| Some(5));
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::NotPruned);
+		import_chain(&mut db, &state_db, 5..=5, |_| Some(20));
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(4), 4), IsPruned::Pruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(5), 5), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[4, 5])));
+
+		// The other constraints still apply.
+		let mut db = make_db(&[]);
+		let mode = Constraints { max_blocks: Some(2), max_age: Some(10), ..Default::default() };
+		let (init, state_db) =
+			StateDb::open(db.clone(), Some(PruningMode::Constrained(mode)), false, true).unwrap();
+		db.commit(&init);
+
+		import_chain(&mut db, &state_db, 1..=4, |_| None);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(2), 2), IsPruned::Pruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(3), 3), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[2, 3, 4])));
+	}
+
+	#[test]
+	fn kept_blocks_are_not_pruned() {
+		let mut db = make_db(&[]);
+		let (init, state_db) =
+			StateDb::open(db.clone(), Some(PruningMode::blocks_pruning(1)), false, true).unwrap();
+		db.commit(&init);
+
+		import_chain(&mut db, &state_db, 1..=2, |_| None);
+		let h1 = H256::from_low_u64_be(1);
+		let h2 = H256::from_low_u64_be(2);
+		assert!(state_db.keep(&h1, 1).is_err());
+		assert!(state_db.keep(&H256::from_low_u64_be(3), 3).is_err());
+		db.commit(&state_db.keep(&h2, 2).unwrap().unwrap());
+		assert!(state_db.keep(&h2, 2).unwrap().is_none());
+
+		import_chain(&mut db, &state_db, 3..=5, |_| None);
+		assert_eq!(state_db.is_pruned(&h2, 2), IsPruned::NotPruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(3), 3), IsPruned::Pruned);
+		assert_eq!(state_db.kept_blocks(), vec![(h2, 2)]);
+
+		let (_, state_db) =
+			StateDb::<H256, H256, TestDb>::open(db.clone(), None, false, false).unwrap();
+		assert_eq!(state_db.is_pruned(&h2, 2), IsPruned::NotPruned);
+		db.commit(&state_db.release(&h2).unwrap());
+		assert!(state_db.release(&h2).is_none());
+		assert_eq!(state_db.is_pruned(&h2, 2), IsPruned::Pruned);
+
+		let (_, state_db) =
+			StateDb::<H256, H256, TestDb>::open(db.clone(), None, false, false).unwrap();
+		assert!(state_db.kept_blocks().is_empty());
+	}
+
 	#[test]
 	fn detects_incompatible_mode() {
 		let mut db = make_db(&[]);
@@ -860,10 +1168,12 @@ mod tests {
 					0,
 					&H256::from_low_u64_be(0),
 					make_changeset(&[], &[]),
+					None,
 				)
 				.unwrap(),
 		);
-		let new_mode = PruningMode::Constrained(Constraints { max_blocks: Some(2) });
+		let new_mode =
+			PruningMode::Constrained(Constraints { max_blocks: Some(2), ..Default::default() });
 		let state_db_open_result: Result<(_, StateDb<H256, H256, TestDb>), _> =
 			StateDb::open(db.clone(), Some(new_mode), false, false);
 		assert!(state_db_open_result.is_err());
@@ -568,6 +568,42 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 	}
 }
 
+/// Read the journal of non-canonical blocks, passing every key through `map_key`.
+pub(crate) fn migrate_journal<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	map_key: &impl Fn(Key) -> Key,
+) -> Result<Vec<(Vec<u8>, DBValue)>, Error<D::Error>> {
+	let mut entries = Vec::new();
+	let last_canonicalized = db
+		.get_meta(&to_meta_key(LAST_CANONICAL, &()))
+		.map_err(Error::Db)?
+		.map(|buffer| <(BlockHash, u64)>::decode(&mut buffer.as_slice()))
+		.transpose()?;
+	let Some((_, mut block)) = last_canonicalized else { return Ok(entries) };
+
+	loop {
+		block += 1;
+		let level_start = entries.len();
+		for index in 0..MAX_BLOCKS_PER_LEVEL {
+			let journal_key = to_journal_key(block, index);
+			if let Some(record) = db.get_meta(&journal_key).map_err(Error::Db)? {
+				let record: JournalRecord<BlockHash, Key> = Decode::decode(&mut record.as_slice())?;
+				let record = JournalRecord {
+					hash: record.hash,
+					parent_hash: record.parent_hash,
+					inserted: record.inserted.into_iter().map(|(k, v)| (map_key(k), v)).collect(),
+					deleted: record.deleted.into_iter().map(map_key).collect(),
+				};
+				entries.push((journal_key, record.encode()));
+			}
+		}
+		if entries.len() == level_start {
+			break
+		}
+	}
+	Ok(entries)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::{to_journal_key, NonCanonicalOverlay};
@@ -23,10 +23,13 @@
 //! If a node is re-inserted into the window it gets removed from
 //! the death list.
 //! The changes are journaled in the DB.
+//!
+//! Each block also records its timestamp, if known, and the size of the trie nodes it inserted,
+//! so that the window can be limited by age and by size as well as by length.
 
 use crate::{
-	noncanonical::LAST_CANONICAL, to_meta_key, CommitSet, Error, Hash, MetaDb, StateDbError,
-	DEFAULT_MAX_BLOCK_CONSTRAINT, LOG_TARGET,
+	noncanonical::LAST_CANONICAL, to_meta_key, CommitSet, DBValue, Error, Hash, MetaDb,
+	StateDbError, DEFAULT_MAX_BLOCK_CONSTRAINT, LOG_TARGET,
 };
 use codec::{Decode, Encode};
 use log::trace;
@@ -34,6 +37,9 @@ use std::collections::{HashMap, HashSet, VecDeque};
 
 pub(crate) const LAST_PRUNED: &[u8] = b"last_pruned";
 const PRUNING_JOURNAL: &[u8] = b"pruning_journal";
+const PRUNING_WINDOW_SIZE: &[u8] = b"pruning_window_size";
+const LAST_TIMESTAMP: &[u8] = b"pruning_last_timestamp";
+const FIRST_TIMESTAMP: &[u8] = b"pruning_first_timestamp";
 
 /// See module documentation.
 pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
@@ -42,6 +48,13 @@ pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
 	queue: DeathRowQueue<BlockHash, Key, D>,
 	/// Block number that is next to be pruned.
 	base: u64,
+	/// Total size of the trie nodes inserted by the blocks in the window.
+	size: u64,
+	/// Timestamp of the last block added to the window, if known.
+	last_timestamp: Option<u64>,
+	/// Timestamp of the first block added to the window with a known timestamp. The blocks added
+	/// before it have no timestamp.
+	first_timestamp: Option<u64>,
 }
 
 /// `DeathRowQueue` used to keep track of blocks in the pruning window, there are two flavors:
@@ -128,7 +141,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 
 	/// import a new block to the back of the queue
 	fn import(&mut self, base: u64, num: u64, journal_record: JournalRecord<BlockHash, Key>) {
-		let JournalRecord { hash, inserted, deleted } = journal_record;
+		let JournalRecord { hash, inserted, deleted, timestamp, size } = journal_record;
 		trace!(target: LOG_TARGET, "Importing {}, base={}", num, base);
 		match self {
 			DeathRowQueue::DbBacked { cache, cache_capacity, last, .. } => {
@@ -136,7 +149,12 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				// cache.
 				if num == base + cache.len() as u64 && cache.len() < *cache_capacity {
 					trace!(target: LOG_TARGET, "Adding to DB backed cache {:?} (#{})", hash, num);
-					cache.push_back(DeathRow { hash, deleted: deleted.into_iter().collect() });
+					cache.push_back(DeathRow {
+						hash,
+						deleted: deleted.into_iter().collect(),
+						timestamp,
+						size,
+					});
 				}
 				*last = Some(num);
 			},
@@ -152,7 +170,12 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> DeathRowQueue<BlockHash, Key, D> {
 				for k in deleted.iter() {
 					death_index.insert(k.clone(), imported_block);
 				}
-				death_rows.push_back(DeathRow { hash, deleted: deleted.into_iter().collect() });
+				death_rows.push_back(DeathRow {
+					hash,
+					deleted: deleted.into_iter().collect(),
+					timestamp,
+					size,
+				});
 			},
 		}
 	}
@@ -255,8 +278,9 @@ fn load_death_row_from_db<BlockHash: Hash, Key: Hash, D: MetaDb>(
 	let journal_key = to_journal_key(block);
 	match db.get_meta(&journal_key).map_err(Error::Db)? {
 		Some(record) => {
-			let JournalRecord { hash, deleted, .. } = Decode::decode(&mut record.as_slice())?;
-			Ok(Some(DeathRow { hash, deleted: deleted.into_iter().collect() }))
+			let JournalRecord { hash, deleted, timestamp, size, .. } =
+				Decode::decode(&mut record.as_slice())?;
+			Ok(Some(DeathRow { hash, deleted: deleted.into_iter().collect(), timestamp, size }))
 		},
 		None => Ok(None),
 	}
@@ -266,13 +290,32 @@ fn load_death_row_from_db<BlockHash: Hash, Key: Hash, D: MetaDb>(
 struct DeathRow<BlockHash: Hash, Key: Hash> {
 	hash: BlockHash,
 	deleted: HashSet<Key>,
+	timestamp: Option<u64>,
+	size: u64,
 }
 
-#[derive(Encode, Decode, Default)]
+#[derive(Encode, Default)]
 struct JournalRecord<BlockHash: Hash, Key: Hash> {
 	hash: BlockHash,
 	inserted: Vec<Key>,
 	deleted: Vec<Key>,
+	timestamp: Option<u64>,
+	size: u64,
+}
+
+impl<BlockHash: Hash, Key: Hash> Decode for JournalRecord<BlockHash, Key> {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let hash = Decode::decode(input)?;
+		let inserted = Decode::decode(input)?;
+		let deleted = Decode::decode(input)?;
+		// Records written before timestamps and sizes were tracked end here.
+		let (timestamp, size) = if input.remaining_len()? == Some(0) {
+			(None, 0)
+		} else {
+			(Decode::decode(input)?, Decode::decode(input)?)
+		};
+		Ok(JournalRecord { hash, inserted, deleted, timestamp, size })
+	}
 }
 
 fn to_journal_key(block: u64) -> Vec<u8> {
@@ -312,6 +355,20 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			Some(buffer) => u64::decode(&mut buffer.as_slice())? + 1,
 			None => 0,
 		};
+		let size = match db.get_meta(&to_meta_key(PRUNING_WINDOW_SIZE, &())).map_err(Error::Db)? {
+			Some(buffer) => u64::decode(&mut buffer.as_slice())?,
+			None => 0,
+		};
+		let last_timestamp =
+			match db.get_meta(&to_meta_key(LAST_TIMESTAMP, &())).map_err(Error::Db)? {
+				Some(buffer) => Some(u64::decode(&mut buffer.as_slice())?),
+				None => None,
+			};
+		let first_timestamp =
+			match db.get_meta(&to_meta_key(FIRST_TIMESTAMP, &())).map_err(Error::Db)? {
+				Some(buffer) => Some(u64::decode(&mut buffer.as_slice())?),
+				None => None,
+			};
 		// the block number of the last block in the queue
 		let last_canonicalized_number =
 			match db.get_meta(&to_meta_key(LAST_CANONICAL, &())).map_err(Error::Db)? {
@@ -347,25 +404,48 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			DeathRowQueue::new_db_backed(db, base, last, window_size)?
 		};
 
-		Ok(RefWindow { queue, base })
+		Ok(RefWindow { queue, base, size, last_timestamp, first_timestamp })
 	}
 
 	pub fn window_size(&self) -> u64 {
 		self.queue.len(self.base) as u64
 	}
 
-	/// Get the hash of the next pruning block
-	pub fn next_hash(&mut self) -> Result<Option<BlockHash>, Error<D::Error>> {
-		let res = match &mut self.queue {
+	/// Total size of the trie nodes inserted by the blocks in the window.
+	pub fn size(&self) -> u64 {
+		self.size
+	}
+
+	/// Timestamp of the last block added to the window, if known.
+	pub fn last_timestamp(&self) -> Option<u64> {
+		self.last_timestamp
+	}
+
+	/// Get the first block of the window, loading it from the database if needed.
+	fn front(&mut self) -> Result<Option<&DeathRow<BlockHash, Key>>, Error<D::Error>> {
+		Ok(match &mut self.queue {
 			DeathRowQueue::DbBacked { db, cache, cache_capacity, .. } => {
 				if cache.is_empty() {
 					DeathRowQueue::load_batch_from_db(db, cache, self.base, *cache_capacity)?;
 				}
-				cache.front().map(|r| r.hash.clone())
+				cache.front()
 			},
-			DeathRowQueue::Mem { death_rows, .. } => death_rows.front().map(|r| r.hash.clone()),
-		};
-		Ok(res)
+			DeathRowQueue::Mem { death_rows, .. } => death_rows.front(),
+		})
+	}
+
+	/// Get the hash of the next pruning block
+	pub fn next_hash(&mut self) -> Result<Option<BlockHash>, Error<D::Error>> {
+		Ok(self.front()?.map(|r| r.hash.clone()))
+	}
+
+	/// Get the timestamp of the next pruning block, if known.
+	///
+	/// A block without a timestamp is at least as old as the first block with one, so the
+	/// timestamp of that block is returned instead, if there is one.
+	pub fn next_timestamp(&mut self) -> Result<Option<u64>, Error<D::Error>> {
+		let first_timestamp = self.first_timestamp;
+		Ok(self.front()?.and_then(|r| r.timestamp.or(first_timestamp)))
 	}
 
 	fn is_empty(&self) -> bool {
@@ -389,6 +469,11 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			let index = self.base;
 			commit.data.deleted.extend(pruned.deleted.into_iter());
 			commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), index.encode()));
+			self.size = self.size.saturating_sub(pruned.size);
+			commit
+				.meta
+				.inserted
+				.push((to_meta_key(PRUNING_WINDOW_SIZE, &()), self.size.encode()));
 			commit.meta.deleted.push(to_journal_key(self.base));
 			self.base += 1;
 			Ok(())
@@ -398,11 +483,13 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 		}
 	}
 
-	/// Add a change set to the window. Creates a journal record and pushes it to `commit`
+	/// Add a change set to the window. Creates a journal record and pushes it to `commit`.
+	/// Blocks without a `timestamp` inherit the one of the previous block, if known.
 	pub fn note_canonical(
 		&mut self,
 		hash: &BlockHash,
 		number: u64,
+		timestamp: Option<u64>,
 		commit: &mut CommitSet<Key>,
 	) -> Result<(), Error<D::Error>> {
 		if self.base == 0 && self.is_empty() && number > 0 {
@@ -430,13 +517,77 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			Default::default()
 		};
 		let deleted = std::mem::take(&mut commit.data.deleted);
-		let journal_record = JournalRecord { hash: hash.clone(), inserted, deleted };
+		let timestamp = timestamp.or(self.last_timestamp);
+		let size = commit.data.inserted.iter().map(|(_, value)| value.len() as u64).sum();
+		let journal_record =
+			JournalRecord { hash: hash.clone(), inserted, deleted, timestamp, size };
 		commit.meta.inserted.push((to_journal_key(number), journal_record.encode()));
+		if let Some(timestamp) = timestamp {
+			commit
+				.meta
+				.inserted
+				.push((to_meta_key(LAST_TIMESTAMP, &()), timestamp.encode()));
+			if self.first_timestamp.is_none() {
+				commit
+					.meta
+					.inserted
+					.push((to_meta_key(FIRST_TIMESTAMP, &()), timestamp.encode()));
+				self.first_timestamp = Some(timestamp);
+			}
+		}
+		self.last_timestamp = timestamp;
+		self.size += size;
+		commit
+			.meta
+			.inserted
+			.push((to_meta_key(PRUNING_WINDOW_SIZE, &()), self.size.encode()));
 		self.queue.import(self.base, number, journal_record);
 		Ok(())
 	}
 }
 
+/// Read the pruning journal, passing every key through `map_key` and dropping deletions that were
+/// cancelled by a re-insertion later in the window, the same way `DeathRowQueue::Mem` does.
+pub(crate) fn migrate_journal<BlockHash: Hash, Key: Hash, D: MetaDb>(
+	db: &D,
+	map_key: &impl Fn(Key) -> Key,
+) -> Result<Vec<(Vec<u8>, DBValue)>, Error<D::Error>> {
+	let mut block = match db.get_meta(&to_meta_key(LAST_PRUNED, &())).map_err(Error::Db)? {
+		Some(buffer) => u64::decode(&mut buffer.as_slice())? + 1,
+		None => 0,
+	};
+
+	let mut records: Vec<(u64, JournalRecord<BlockHash, Key>)> = Vec::new();
+	let mut death_index: HashMap<Key, usize> = HashMap::new();
+	while let Some(record) = db.get_meta(&to_journal_key(block)).map_err(Error::Db)? {
+		let record: JournalRecord<BlockHash, Key> = Decode::decode(&mut record.as_slice())?;
+		for k in record.inserted.iter() {
+			if let Some(index) = death_index.remove(k) {
+				records[index].1.deleted.retain(|deleted| deleted != k);
+			}
+		}
+		for k in record.deleted.iter() {
+			death_index.insert(k.clone(), records.len());
+		}
+		records.push((block, record));
+		block += 1;
+	}
+
+	Ok(records
+		.into_iter()
+		.map(|(block, record)| {
+			let record = JournalRecord::<BlockHash, Key> {
+				hash: record.hash,
+				inserted: record.inserted.into_iter().map(map_key).collect(),
+				deleted: record.deleted.into_iter().map(map_key).collect(),
+				timestamp: record.timestamp,
+				size: record.size,
+			};
+			(to_journal_key(block), record.encode())
+		})
+		.collect())
+}
+
 #[cfg(test)]
 mod tests {
 	use super::{to_journal_key, DeathRowQueue, HaveBlock, JournalRecord, RefWindow, LAST_PRUNED};
@@ -445,7 +596,7 @@ mod tests {
 		test::{make_commit, make_db, TestDb},
 		to_meta_key, CommitSet, Error, Hash, StateDbError, DEFAULT_MAX_BLOCK_CONSTRAINT,
 	};
-	use codec::Encode;
+	use codec::{Decode, Encode};
 	use sp_core::H256;
 
 	fn check_journal(pruning: &RefWindow<H256, H256, TestDb>, db: &TestDb) {
@@ -490,7 +641,7 @@ mod tests {
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
 		let mut commit = make_commit(&[4, 5], &[1, 3]);
 		let hash = H256::random();
-		pruning.note_canonical(&hash, 0, &mut commit).unwrap();
+		pruning.note_canonical(&hash, 0, None, &mut commit).unwrap();
 		db.commit(&commit);
 		assert_eq!(pruning.have_block(&hash, 0), HaveBlock::Yes);
 		assert_eq!(pruning.have_block(&hash, 0), HaveBlock::Yes);
@@ -519,10 +670,10 @@ mod tests {
 		let mut pruning: RefWindow<H256, H256, TestDb> =
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
 		let mut commit = make_commit(&[4], &[1]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, None, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[5], &[2]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, None, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5])));
 
@@ -545,10 +696,10 @@ mod tests {
 		let mut pruning: RefWindow<H256, H256, TestDb> =
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
 		let mut commit = make_commit(&[4], &[1]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, None, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[5], &[2]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, None, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3, 4, 5])));
 		let mut commit = CommitSet::default();
@@ -568,13 +719,13 @@ mod tests {
 		let mut pruning: RefWindow<H256, H256, TestDb> =
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, None, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, None, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 2, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 2, None, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 
@@ -594,19 +745,51 @@ mod tests {
 		assert_eq!(pruning.base, 3);
 	}
 
+	#[test]
+	fn migrate_journal_drops_cancelled_deletions() {
+		let mut db = make_db(&[1, 2, 3]);
+		let mut pruning: RefWindow<H256, H256, TestDb> =
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+		let blocks: [(&[u64], &[u64]); 3] = [(&[], &[2]), (&[2], &[]), (&[], &[2, 3])];
+		for (number, (inserted, deleted)) in blocks.into_iter().enumerate() {
+			let mut commit = make_commit(inserted, deleted);
+			pruning
+				.note_canonical(&H256::random(), number as u64, None, &mut commit)
+				.unwrap();
+			db.commit(&commit);
+		}
+
+		let shift = |key: H256| H256::from_low_u64_be(key.to_low_u64_be() + 100);
+		let entries = super::migrate_journal::<H256, H256, TestDb>(&db, &shift).unwrap();
+		let deleted: Vec<_> = entries
+			.iter()
+			.map(|(_, record)| {
+				JournalRecord::<H256, H256>::decode(&mut record.as_slice()).unwrap().deleted
+			})
+			.collect();
+		assert_eq!(
+			entries.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>(),
+			[to_journal_key(0), to_journal_key(1), to_journal_key(2)]
+		);
+		assert_eq!(
+			deleted,
+			vec![vec![], vec![], vec![H256::from_low_u64_be(102), H256::from_low_u64_be(103)]]
+		);
+	}
+
 	#[test]
 	fn reinserted_survive_pending() {
 		let mut db = make_db(&[1, 2, 3]);
 		let mut pruning: RefWindow<H256, H256, TestDb> =
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, None, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, None, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 2, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 2, None, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 
@@ -630,13 +813,13 @@ mod tests {
 		let mut pruning: RefWindow<H256, H256, TestDb> =
 			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 0, None, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[2], &[]);
-		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 1, None, &mut commit).unwrap();
 		db.commit(&commit);
 		let mut commit = make_commit(&[], &[2]);
-		pruning.note_canonical(&H256::random(), 2, &mut commit).unwrap();
+		pruning.note_canonical(&H256::random(), 2, None, &mut commit).unwrap();
 		db.commit(&commit);
 		assert!(db.data_eq(&make_db(&[1, 2, 3])));
 
@@ -732,7 +915,7 @@ mod tests {
 		// queue size and content should match
 		for i in 0..(cache_capacity + 10) {
 			let mut commit = make_commit(&[], &[]);
-			pruning.note_canonical(&(i as u64), i as u64, &mut commit).unwrap();
+			pruning.note_canonical(&(i as u64), i as u64, None, &mut commit).unwrap();
 			push_last_canonicalized(i as u64, &mut commit);
 			db.commit(&commit);
 			// blocks will fill the cache first
@@ -756,7 +939,12 @@ mod tests {
 		// won't keep the new block in memory
 		let mut commit = CommitSet::default();
 		pruning
-			.note_canonical(&(cache_capacity as u64 + 10), cache_capacity as u64 + 10, &mut commit)
+			.note_canonical(
+				&(cache_capacity as u64 + 10),
+				cache_capacity as u64 + 10,
+				None,
+				&mut commit,
+			)
 			.unwrap();
 		assert_eq!(pruning.window_size(), cache_capacity as u64 + 11);
 		let (cache, _) = pruning.queue.get_db_backed_queue_state().unwrap();
@@ -804,7 +992,7 @@ mod tests {
 		// import blocks
 		for i in 0..(cache_capacity as u64 * 2 + 10) {
 			let mut commit = make_commit(&[], &[]);
-			pruning.note_canonical(&i, i, &mut commit).unwrap();
+			pruning.note_canonical(&i, i, None, &mut commit).unwrap();
 			push_last_canonicalized(i as u64, &mut commit);
 			db.commit(&commit);
 		}
@@ -854,14 +1042,14 @@ mod tests {
 		// import blocks and commit to db
 		let mut commit = make_commit(&[], &[]);
 		for i in 0..(cache_capacity + 10) {
-			pruning.note_canonical(&i, i, &mut commit).unwrap();
+			pruning.note_canonical(&i, i, None, &mut commit).unwrap();
 		}
 		db.commit(&commit);
 
 		// import a block but not commit to db yet
 		let mut pending_commit = make_commit(&[], &[]);
 		let index = cache_capacity + 10;
-		pruning.note_canonical(&index, index, &mut pending_commit).unwrap();
+		pruning.note_canonical(&index, index, None, &mut pending_commit).unwrap();
 
 		let mut commit = make_commit(&[], &[]);
 		// prune blocks that had committed to db
@@ -895,7 +1083,7 @@ mod tests {
 
 			// import blocks
 			let mut commit = make_commit(&[], &[]);
-			pruning.note_canonical(&block, block, &mut commit).unwrap();
+			pruning.note_canonical(&block, block, None, &mut commit).unwrap();
 			push_last_canonicalized(block, &mut commit);
 			db.commit(&commit);
 
@@ -907,4 +1095,41 @@ mod tests {
 			assert_eq!(HaveBlock::Yes, pruning.have_block(&block, block));
 		}
 	}
+
+	#[test]
+	fn tracks_size_and_timestamps() {
+		let mut db = make_db(&[]);
+		let mut pruning: RefWindow<H256, H256, TestDb> =
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+		let mut commit = make_commit(&[1, 2], &[]);
+		pruning.note_canonical(&H256::random(), 0, Some(10), &mut commit).unwrap();
+		db.commit(&commit);
+		let mut commit = make_commit(&[3], &[1]);
+		pruning.note_canonical(&H256::random(), 1, None, &mut commit).unwrap();
+		push_last_canonicalized(1, &mut commit);
+		db.commit(&commit);
+		assert_eq!(pruning.size(), 96);
+		assert_eq!(pruning.last_timestamp(), Some(10));
+
+		let mut pruning: RefWindow<H256, H256, TestDb> =
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, false).unwrap();
+		assert_eq!(pruning.size(), 96);
+		assert_eq!(pruning.last_timestamp(), Some(10));
+		assert_eq!(pruning.next_timestamp().unwrap(), Some(10));
+		let mut commit = CommitSet::default();
+		pruning.prune_one(&mut commit).unwrap();
+		db.commit(&commit);
+		assert_eq!(pruning.size(), 32);
+		assert_eq!(pruning.next_timestamp().unwrap(), Some(10));
+	}
+
+	#[test]
+	fn decodes_journal_records_without_size() {
+		let hash = H256::random();
+		let encoded = (hash, vec![H256::repeat_byte(1)], vec![H256::repeat_byte(2)]).encode();
+		let record = JournalRecord::<H256, H256>::decode(&mut encoded.as_slice()).unwrap();
+		assert_eq!(record.hash, hash);
+		assert_eq!(record.deleted, vec![H256::repeat_byte(2)]);
+		assert_eq!((record.timestamp, record.size), (None, 0));
+	}
 }
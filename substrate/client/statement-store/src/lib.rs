@@ -37,6 +37,16 @@
 //! statements are deleted and `Ignored` result is returned.
 //! The order in which statements with the same priority are deleted is unspecified.
 //!
+//! Node-side quotas.
+//!
+//! On top of the runtime constraints, [`Options`] may configure limits enforced by the node:
+//! * The per-account `max_count` and `max_size` are capped by `max_account_statements` and
+//!   `max_account_size`, so that a single account can't fill the store.
+//! * An account may not add more than `max_account_submissions_per_period` statements every
+//!   `rate_limit_period_sec` seconds. Further statements are rejected with `RateLimited`.
+//! * The total data size of the statements with a given topic may not exceed `max_topic_size`.
+//!   Statements that would exceed it are `Ignored`.
+//!
 //! Statement expiration.
 //!
 //! Each time a statement is removed from the store (Either evicted by higher priority statement or
@@ -51,8 +61,9 @@ mod metrics;
 
 pub use sp_statement_store::{Error, StatementStore, MAX_TOPICS};
 
+use futures::channel::mpsc;
 use metrics::MetricsLink as PrometheusMetrics;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use prometheus_endpoint::Registry as PrometheusRegistry;
 use sc_keystore::LocalKeystore;
 use sp_api::ProvideRuntimeApi;
@@ -64,10 +75,11 @@ use sp_statement_store::{
 		InvalidStatement, StatementSource, StatementStoreExt, ValidStatement, ValidateStatement,
 	},
 	AccountId, BlockHash, Channel, DecryptionKey, Hash, NetworkPriority, Proof, Result, Statement,
-	SubmitResult, Topic,
+	StatementPage, StatementStream, SubmitResult, Topic,
 };
 use std::{
-	collections::{BTreeMap, HashMap, HashSet},
+	collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+	ops::Bound,
 	sync::Arc,
 };
 
@@ -79,9 +91,13 @@ const LOG_TARGET: &str = "statement-store";
 const DEFAULT_PURGE_AFTER_SEC: u64 = 2 * 24 * 60 * 60; //48h
 const DEFAULT_MAX_TOTAL_STATEMENTS: usize = 8192;
 const DEFAULT_MAX_TOTAL_SIZE: usize = 64 * 1024 * 1024;
+const DEFAULT_RATE_LIMIT_PERIOD_SEC: u64 = 60;
 
 const MAINTENANCE_PERIOD: std::time::Duration = std::time::Duration::from_secs(30);
 
+// Number of statements buffered for a subscriber before it is dropped.
+const SUBSCRIPTION_BUFFER_SIZE: usize = 1024;
+
 mod col {
 	pub const META: u8 = 0;
 	pub const STATEMENTS: u8 = 1;
@@ -128,15 +144,29 @@ struct StatementsForAccount {
 }
 
 /// Store configuration
+#[derive(Clone, Debug)]
 pub struct Options {
 	/// Maximum statement allowed in the store. Once this limit is reached lower-priority
 	/// statements may be evicted.
-	max_total_statements: usize,
+	pub max_total_statements: usize,
 	/// Maximum total data size allowed in the store. Once this limit is reached lower-priority
 	/// statements may be evicted.
-	max_total_size: usize,
+	pub max_total_size: usize,
 	/// Number of seconds for which removed statements won't be allowed to be added back in.
-	purge_after_sec: u64,
+	pub purge_after_sec: u64,
+	/// Maximum number of statements a single account may have in the store. Caps the `max_count`
+	/// returned by the runtime. `None` means no node-side limit.
+	pub max_account_statements: Option<usize>,
+	/// Maximum total data size of the statements of a single account. Caps the `max_size`
+	/// returned by the runtime. `None` means no node-side limit.
+	pub max_account_size: Option<usize>,
+	/// Maximum number of statements a single account may add to the store every
+	/// `rate_limit_period_sec` seconds. `None` means no rate limit.
+	pub max_account_submissions_per_period: Option<u32>,
+	/// Length of the rate limit period in seconds.
+	pub rate_limit_period_sec: u64,
+	/// Maximum total data size of the statements with a given topic. `None` means no limit.
+	pub max_topic_size: Option<usize>,
 }
 
 impl Default for Options {
@@ -145,18 +175,35 @@ impl Default for Options {
 			max_total_statements: DEFAULT_MAX_TOTAL_STATEMENTS,
 			max_total_size: DEFAULT_MAX_TOTAL_SIZE,
 			purge_after_sec: DEFAULT_PURGE_AFTER_SEC,
+			max_account_statements: None,
+			max_account_size: None,
+			max_account_submissions_per_period: None,
+			rate_limit_period_sec: DEFAULT_RATE_LIMIT_PERIOD_SEC,
+			max_topic_size: None,
 		}
 	}
 }
 
+/// Statements added by an account in the current rate limit period.
+struct RateLimitWindow {
+	// Start of the period.
+	start: u64,
+	// Number of statements added since `start`.
+	count: u32,
+}
+
 #[derive(Default)]
 struct Index {
-	by_topic: HashMap<Topic, HashSet<Hash>>,
-	by_dec_key: HashMap<Option<DecryptionKey>, HashSet<Hash>>,
+	// Ordered, so that statements can be paginated by hash.
+	by_topic: HashMap<Topic, BTreeSet<Hash>>,
+	by_dec_key: HashMap<Option<DecryptionKey>, BTreeSet<Hash>>,
 	topics_and_keys: HashMap<Hash, ([Option<Topic>; MAX_TOPICS], Option<DecryptionKey>)>,
 	entries: HashMap<Hash, (AccountId, Priority, usize)>,
 	expired: HashMap<Hash, u64>, // Value is expiration timestamp.
 	accounts: HashMap<AccountId, StatementsForAccount>,
+	// Sum of the data sizes of the statements with a given topic.
+	topic_sizes: HashMap<Topic, usize>,
+	rate_limits: HashMap<AccountId, RateLimitWindow>,
 	options: Options,
 	total_size: usize,
 }
@@ -206,6 +253,9 @@ pub struct Store {
 	// Used for testing
 	time_override: Option<u64>,
 	metrics: PrometheusMetrics,
+	// Senders of the statement subscriptions. Closed and lagging subscriptions are removed on the
+	// next send.
+	subscribers: Mutex<Vec<mpsc::Sender<(Hash, Statement)>>>,
 }
 
 enum IndexQuery {
@@ -217,6 +267,8 @@ enum IndexQuery {
 enum MaybeInserted {
 	Inserted(HashSet<Hash>),
 	Ignored,
+	TopicQuotaExceeded,
+	RateLimited,
 }
 
 impl Index {
@@ -229,6 +281,7 @@ impl Index {
 		let mut nt = 0;
 		while let Some(t) = statement.topic(nt) {
 			self.by_topic.entry(t).or_default().insert(hash);
+			*self.topic_sizes.entry(t).or_default() += statement.data_len();
 			all_topics[nt] = Some(t);
 			nt += 1;
 		}
@@ -264,34 +317,35 @@ impl Index {
 		self.expired.insert(hash, timestamp);
 	}
 
-	fn iterate_with(
+	/// Return the index sets of `key` and of each of `match_all_topics`, smallest first, or `None`
+	/// if no statement can match.
+	fn matching_sets(
 		&self,
 		key: Option<DecryptionKey>,
 		match_all_topics: &[Topic],
-		mut f: impl FnMut(&Hash) -> Result<()>,
-	) -> Result<()> {
-		let empty = HashSet::new();
-		let mut sets: [&HashSet<Hash>; MAX_TOPICS + 1] = [&empty; MAX_TOPICS + 1];
+	) -> Option<Vec<&BTreeSet<Hash>>> {
 		if match_all_topics.len() > MAX_TOPICS {
-			return Ok(())
+			return None
 		}
-		let key_set = self.by_dec_key.get(&key);
-		if key_set.map_or(0, |s| s.len()) == 0 {
-			// Key does not exist in the index.
-			return Ok(())
+		let mut sets = Vec::with_capacity(match_all_topics.len() + 1);
+		// Key does not exist in the index.
+		sets.push(self.by_dec_key.get(&key).filter(|s| !s.is_empty())?);
+		for t in match_all_topics {
+			// At least one of the match_all_topics does not exist in the index.
+			sets.push(self.by_topic.get(t).filter(|s| !s.is_empty())?);
 		}
-		sets[0] = key_set.expect("Function returns if key_set is None");
-		for (i, t) in match_all_topics.iter().enumerate() {
-			let set = self.by_topic.get(t);
-			if set.map_or(0, |s| s.len()) == 0 {
-				// At least one of the match_all_topics does not exist in the index.
-				return Ok(())
-			}
-			sets[i + 1] = set.expect("Function returns if set is None");
-		}
-		let sets = &mut sets[0..match_all_topics.len() + 1];
 		// Start with the smallest topic set or the key set.
 		sets.sort_by_key(|s| s.len());
+		Some(sets)
+	}
+
+	fn iterate_with(
+		&self,
+		key: Option<DecryptionKey>,
+		match_all_topics: &[Topic],
+		mut f: impl FnMut(&Hash) -> Result<()>,
+	) -> Result<()> {
+		let Some(sets) = self.matching_sets(key, match_all_topics) else { return Ok(()) };
 		for item in sets[0] {
 			if sets[1..].iter().all(|set| set.contains(item)) {
 				log::trace!(
@@ -305,6 +359,27 @@ impl Index {
 		Ok(())
 	}
 
+	/// Iterate in hash order over the matching statements with a hash greater than `after`, for as
+	/// long as `f` returns `true`.
+	fn iterate_ordered_with(
+		&self,
+		key: Option<DecryptionKey>,
+		match_all_topics: &[Topic],
+		after: Option<Hash>,
+		mut f: impl FnMut(&Hash) -> bool,
+	) {
+		let Some(sets) = self.matching_sets(key, match_all_topics) else { return };
+		let range = match after {
+			Some(after) => sets[0].range((Bound::Excluded(after), Bound::Unbounded)),
+			None => sets[0].range::<Hash, _>(..),
+		};
+		for item in range {
+			if sets[1..].iter().all(|set| set.contains(item)) && !f(item) {
+				return
+			}
+		}
+	}
+
 	fn maintain(&mut self, current_time: u64) -> Vec<Hash> {
 		// Purge previously expired messages.
 		let mut purged = Vec::new();
@@ -317,9 +392,36 @@ impl Index {
 				true
 			}
 		});
+		let period = self.options.rate_limit_period_sec;
+		self.rate_limits.retain(|_, window| window.start + period > current_time);
 		purged
 	}
 
+	/// Returns `true` if `account` already added the maximum number of statements allowed in the
+	/// current rate limit period.
+	fn is_rate_limited(&self, account: &AccountId, current_time: u64) -> bool {
+		let Some(max) = self.options.max_account_submissions_per_period else { return false };
+		self.rate_limits.get(account).map_or(false, |window| {
+			window.start + self.options.rate_limit_period_sec > current_time && window.count >= max
+		})
+	}
+
+	/// Count a statement added by `account` towards its rate limit.
+	fn record_submission(&mut self, account: AccountId, current_time: u64) {
+		if self.options.max_account_submissions_per_period.is_none() {
+			return
+		}
+		let period = self.options.rate_limit_period_sec;
+		let window = self
+			.rate_limits
+			.entry(account)
+			.or_insert(RateLimitWindow { start: current_time, count: 0 });
+		if window.start + period <= current_time {
+			*window = RateLimitWindow { start: current_time, count: 0 };
+		}
+		window.count += 1;
+	}
+
 	fn make_expired(&mut self, hash: &Hash, current_time: u64) -> bool {
 		if let Some((account, priority, len)) = self.entries.remove(hash) {
 			self.total_size -= len;
@@ -333,6 +435,14 @@ impl Index {
 							set.remove_entry();
 						}
 					}
+					if let std::collections::hash_map::Entry::Occupied(mut size) =
+						self.topic_sizes.entry(t)
+					{
+						*size.get_mut() -= len;
+						if *size.get() == 0 {
+							size.remove_entry();
+						}
+					}
 				}
 				if let std::collections::hash_map::Entry::Occupied(mut set) =
 					self.by_dec_key.entry(key)
@@ -373,8 +483,21 @@ impl Index {
 		validation: &ValidStatement,
 		current_time: u64,
 	) -> MaybeInserted {
+		// Checked under the same lock as the submission is recorded, so that concurrent
+		// submissions can't exceed the rate limit.
+		if self.is_rate_limited(account, current_time) {
+			return MaybeInserted::RateLimited
+		}
 		let statement_len = statement.data_len();
-		if statement_len > validation.max_size as usize {
+		let max_size = self
+			.options
+			.max_account_size
+			.map_or(validation.max_size as usize, |max| max.min(validation.max_size as usize));
+		let max_count = self
+			.options
+			.max_account_statements
+			.map_or(validation.max_count as usize, |max| max.min(validation.max_count as usize));
+		if statement_len > max_size {
 			log::debug!(
 				target: LOG_TARGET,
 				"Ignored oversize message: {:?} ({} bytes)",
@@ -387,7 +510,6 @@ impl Index {
 		let mut evicted = HashSet::new();
 		let mut would_free_size = 0;
 		let priority = Priority(statement.priority().unwrap_or(0));
-		let (max_size, max_count) = (validation.max_size as usize, validation.max_count as usize);
 		// It may happen that we can't delete enough lower priority messages
 		// to satisfy size constraints. We check for that before deleting anything,
 		// taking into account channel message replacement.
@@ -466,10 +588,38 @@ impl Index {
 			return MaybeInserted::Ignored
 		}
 
+		if let Some(max_topic_size) = self.options.max_topic_size {
+			let mut nt = 0;
+			while let Some(t) = statement.topic(nt) {
+				nt += 1;
+				let freed: usize = evicted
+					.iter()
+					.filter(|h| {
+						self.topics_and_keys
+							.get(*h)
+							.map_or(false, |(topics, _)| topics.contains(&Some(t)))
+					})
+					.filter_map(|h| self.entries.get(h).map(|(_, _, len)| *len))
+					.sum();
+				let topic_size = self.topic_sizes.get(&t).copied().unwrap_or_default();
+				if topic_size - freed + statement_len > max_topic_size {
+					log::debug!(
+						target: LOG_TARGET,
+						"Ignored statement {} because topic {} is full (size={})",
+						HexDisplay::from(&hash),
+						HexDisplay::from(&t),
+						topic_size,
+					);
+					return MaybeInserted::TopicQuotaExceeded
+				}
+			}
+		}
+
 		for h in &evicted {
 			self.make_expired(h, current_time);
 		}
 		self.insert_new(hash, *account, statement);
+		self.record_submission(*account, current_time);
 		MaybeInserted::Inserted(evicted)
 	}
 }
@@ -573,6 +723,7 @@ impl Store {
 			keystore,
 			time_override: None,
 			metrics: PrometheusMetrics::new(prometheus),
+			subscribers: Default::default(),
 		};
 		store.populate()?;
 		Ok(store)
@@ -668,6 +819,34 @@ impl Store {
 		Ok(result)
 	}
 
+	fn rate_limited(&self, hash: &Hash, account_id: &AccountId) -> SubmitResult {
+		log::debug!(
+			target: LOG_TARGET,
+			"Statement rate limited: {:?}, account {:?}",
+			HexDisplay::from(hash),
+			HexDisplay::from(account_id),
+		);
+		self.metrics.report(|metrics| metrics.statements_rate_limited.inc());
+		SubmitResult::RateLimited
+	}
+
+	/// Send a newly added statement to all subscribers.
+	///
+	/// Subscribers whose buffer is full are dropped, which ends their stream.
+	fn notify_subscribers(&self, hash: Hash, statement: &Statement) {
+		self.subscribers.lock().retain_mut(|sender| {
+			match sender.try_send((hash, statement.clone())) {
+				Ok(()) => true,
+				Err(e) => {
+					if e.is_full() {
+						log::debug!(target: LOG_TARGET, "Dropping lagging statement subscriber");
+					}
+					false
+				},
+			}
+		});
+	}
+
 	/// Perform periodic store maintenance
 	pub fn maintain(&self) {
 		log::trace!(target: LOG_TARGET, "Started store maintenance");
@@ -854,6 +1033,45 @@ impl StatementStore for Store {
 		})
 	}
 
+	/// Return at most `limit` known statements which include all topics and whose decryption key
+	/// is identified as `dest`, or which have no `DecryptionKey` field if `dest` is `None`.
+	fn statements_page(
+		&self,
+		match_all_topics: &[Topic],
+		dest: Option<[u8; 32]>,
+		after: Option<Hash>,
+		limit: usize,
+	) -> Result<StatementPage> {
+		let mut hashes = Vec::new();
+		self.index.read().iterate_ordered_with(dest, match_all_topics, after, |hash| {
+			hashes.push(*hash);
+			hashes.len() <= limit
+		});
+
+		let next = if hashes.len() > limit {
+			hashes.truncate(limit);
+			hashes.last().copied()
+		} else {
+			None
+		};
+
+		let mut statements = Vec::with_capacity(hashes.len());
+		for hash in hashes {
+			// The statement may have been removed since the index was read.
+			if let Some(statement) = self.statement(&hash)? {
+				statements.push((hash, statement));
+			}
+		}
+		Ok(StatementPage { statements, next })
+	}
+
+	/// Subscribe to the statements added to the store.
+	fn subscribe_statements(&self) -> StatementStream {
+		let (sender, receiver) = mpsc::channel(SUBSCRIPTION_BUFFER_SIZE);
+		self.subscribers.lock().push(sender);
+		receiver
+	}
+
 	/// Submit a statement to the store. Validates the statement and returns validation result.
 	fn submit(&self, statement: Statement, source: StatementSource) -> SubmitResult {
 		let hash = statement.hash();
@@ -879,6 +1097,11 @@ impl StatementStore for Store {
 			return SubmitResult::Bad("No statement proof")
 		};
 
+		// Reject early to skip the validation, the limit is enforced again when inserting.
+		if self.index.read().is_rate_limited(&account_id, self.timestamp()) {
+			return self.rate_limited(&hash, &account_id)
+		}
+
 		// Validate.
 		let at_block = if let Some(Proof::OnChain { block_hash, .. }) = statement.proof() {
 			Some(*block_hash)
@@ -918,6 +1141,11 @@ impl StatementStore for Store {
 			let evicted =
 				match index.insert(hash, &statement, &account_id, &validation, current_time) {
 					MaybeInserted::Ignored => return SubmitResult::Ignored,
+					MaybeInserted::TopicQuotaExceeded => {
+						self.metrics.report(|metrics| metrics.topic_quota_exceeded.inc());
+						return SubmitResult::Ignored
+					},
+					MaybeInserted::RateLimited => return self.rate_limited(&hash, &account_id),
 					MaybeInserted::Inserted(evicted) => evicted,
 				};
 
@@ -937,6 +1165,7 @@ impl StatementStore for Store {
 			}
 		} // Release index lock
 		self.metrics.report(|metrics| metrics.submitted_statements.inc());
+		self.notify_subscribers(hash, &statement);
 		let network_priority = NetworkPriority::High;
 		log::trace!(target: LOG_TARGET, "Statement submitted: {:?}", HexDisplay::from(&hash));
 		SubmitResult::New(network_priority)
@@ -1227,6 +1456,62 @@ mod tests {
 		assert_topics(&[0, 1, 2, 3, 42], None, &[]);
 	}
 
+	#[test]
+	fn statements_page_paginates_by_hash() {
+		let (store, _temp) = test_store();
+		let mut statements: Vec<_> =
+			(0..5).map(|i| signed_statement_with_topics(i, &[topic(0)], None)).collect();
+		statements.push(signed_statement_with_topics(5, &[topic(0)], Some(dec_key(1))));
+		for s in &statements {
+			store.submit(s.clone(), StatementSource::Network);
+		}
+		let mut expected: Vec<_> = statements[..5].iter().map(|s| s.hash()).collect();
+		expected.sort();
+
+		let mut got = Vec::new();
+		let mut after = None;
+		loop {
+			let page = store.statements_page(&[topic(0)], None, after, 2).unwrap();
+			assert!(page.statements.len() <= 2);
+			got.extend(page.statements.into_iter().map(|(hash, _)| hash));
+			match page.next {
+				Some(next) => after = Some(next),
+				None => break,
+			}
+		}
+		assert_eq!(got, expected);
+
+		let page = store.statements_page(&[topic(0)], Some(dec_key(1)), None, 10).unwrap();
+		assert_eq!(page.statements.len(), 1);
+		assert_eq!(page.next, None);
+		assert!(store
+			.statements_page(&[topic(1)], None, None, 10)
+			.unwrap()
+			.statements
+			.is_empty());
+	}
+
+	#[test]
+	fn subscribe_statements_receives_new_statements() {
+		let (store, _temp) = test_store();
+		let mut stream = store.subscribe_statements();
+		let statement0 = signed_statement(0);
+		let statement1 = signed_statement(1);
+
+		store.submit(statement0.clone(), StatementSource::Network);
+		store.submit(statement1.clone(), StatementSource::Network);
+		// Known statements are not sent again.
+		store.submit(statement0.clone(), StatementSource::Network);
+
+		assert_eq!(stream.try_next().unwrap(), Some((statement0.hash(), statement0)));
+		assert_eq!(stream.try_next().unwrap(), Some((statement1.hash(), statement1)));
+		assert!(stream.try_next().is_err());
+
+		drop(stream);
+		store.submit(signed_statement(2), StatementSource::Network);
+		assert!(store.subscribers.lock().is_empty());
+	}
+
 	#[test]
 	fn constraints() {
 		let (store, _temp) = test_store();
@@ -1291,6 +1576,67 @@ mod tests {
 		assert_eq!(expected_statements, statements);
 	}
 
+	#[test]
+	fn account_quota_caps_runtime_limits() {
+		let (store, _temp) = test_store();
+		store.index.write().options.max_account_statements = Some(2);
+		store.index.write().options.max_account_size = Some(1500);
+		let source = StatementSource::Network;
+		let ok = SubmitResult::New(NetworkPriority::High);
+
+		// The runtime allows 2000 bytes and 2 statements for account 5.
+		assert_eq!(store.submit(statement(5, 1, None, 1600), source), SubmitResult::Ignored);
+		assert_eq!(store.submit(statement(5, 1, None, 500), source), ok);
+		assert_eq!(store.submit(statement(5, 2, None, 500), source), ok);
+		// Evicts the lowest priority statement of the account to satisfy the size quota.
+		assert_eq!(store.submit(statement(5, 3, None, 900), source), ok);
+		assert_eq!(store.index.read().accounts.get(&account(5)).unwrap().data_size, 1400);
+		assert_eq!(store.index.read().expired.len(), 1);
+	}
+
+	#[test]
+	fn rate_limit_per_account() {
+		let (mut store, _temp) = test_store();
+		store.set_time(0);
+		store.index.write().options.max_account_submissions_per_period = Some(2);
+		store.index.write().options.rate_limit_period_sec = 10;
+		let source = StatementSource::Network;
+		let ok = SubmitResult::New(NetworkPriority::High);
+
+		assert_eq!(store.submit(signed_statement(0), source), ok);
+		assert_eq!(store.submit(signed_statement(1), source), ok);
+		assert_eq!(store.submit(signed_statement(2), source), SubmitResult::RateLimited);
+		// Other accounts are not affected.
+		assert_eq!(store.submit(statement(5, 1, None, 100), source), ok);
+
+		store.set_time(10);
+		assert_eq!(store.submit(signed_statement(2), source), ok);
+		store.maintain();
+		assert_eq!(store.index.read().rate_limits.len(), 1);
+	}
+
+	#[test]
+	fn topic_quota() {
+		let (store, _temp) = test_store();
+		store.index.write().options.max_topic_size = Some(2);
+		let source = StatementSource::Network;
+		let ok = SubmitResult::New(NetworkPriority::High);
+
+		let statement0 = signed_statement_with_topics(0, &[topic(0)], None);
+		let statement1 = signed_statement_with_topics(1, &[topic(0), topic(1)], None);
+		let statement2 = signed_statement_with_topics(2, &[topic(1), topic(0)], None);
+		assert_eq!(store.submit(statement0.clone(), source), ok);
+		assert_eq!(store.submit(statement1, source), ok);
+		// `topic(0)` is full.
+		assert_eq!(store.submit(statement2, source), SubmitResult::Ignored);
+		assert_eq!(store.submit(signed_statement_with_topics(3, &[topic(1)], None), source), ok);
+
+		// Removing a statement frees its topics.
+		store.remove(&statement0.hash()).unwrap();
+		assert_eq!(store.index.read().topic_sizes.get(&topic(0)), Some(&1));
+		assert_eq!(store.submit(signed_statement_with_topics(4, &[topic(0)], None), source), ok);
+	}
+
 	#[test]
 	fn expired_statements_are_purged() {
 		use super::DEFAULT_PURGE_AFTER_SEC;
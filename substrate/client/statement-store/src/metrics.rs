@@ -48,6 +48,8 @@ pub struct Metrics {
 	pub submitted_statements: Counter<U64>,
 	pub validations_invalid: Counter<U64>,
 	pub statements_pruned: Counter<U64>,
+	pub statements_rate_limited: Counter<U64>,
+	pub topic_quota_exceeded: Counter<U64>,
 }
 
 impl Metrics {
@@ -74,6 +76,20 @@ impl Metrics {
 				)?,
 				registry,
 			)?,
+			statements_rate_limited: register(
+				Counter::new(
+					"substrate_sub_statement_store_rate_limited",
+					"Total number of statements rejected because their account exceeded its rate limit",
+				)?,
+				registry,
+			)?,
+			topic_quota_exceeded: register(
+				Counter::new(
+					"substrate_sub_statement_store_topic_quota_exceeded",
+					"Total number of statements ignored because one of their topics is full",
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
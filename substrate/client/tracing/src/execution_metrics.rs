@@ -0,0 +1,551 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Prometheus metrics of the runtime execution of blocks.
+//!
+//! [`ExecutionMetrics`] is a [`TraceHandler`] measuring the wall time of the extrinsics and of the
+//! `on_initialize` and `on_finalize` hooks of a block, using the spans of runtimes built with the
+//! `with-tracing` feature. The time of an extrinsic is reported along with the weight it declared,
+//! aggregated by pallet instance and call.
+//!
+//! The runtime spans are only enabled while the client executes a block it imports, within
+//! [`measure_import`], so that blocks being built or transactions being validated neither pay for
+//! the tracing nor are reported.
+//!
+//! The wall time of the phases of the import measured by the client, such as executing the block
+//! and computing its storage changes, is reported with [`measure_phase`] for any runtime.
+
+use crate::{SpanDatum, TraceEvent, TraceHandler};
+use prometheus_endpoint::{register, CounterVec, Opts, PrometheusError, Registry, U64};
+use sp_tracing::WASM_TRACE_IDENTIFIER;
+use std::{
+	cell::{Cell, RefCell},
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		OnceLock,
+	},
+	time::{Duration, Instant},
+};
+use tracing::{subscriber::Interest, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Tracing directive enabling the spans used by [`ExecutionMetrics`].
+pub const EXECUTION_METRICS_DIRECTIVE: &str = "wasm_tracing=trace";
+
+/// Target of the runtime spans delimiting blocks and extrinsics.
+const EXECUTIVE_TARGET: &str = "frame_executive";
+
+/// Maximum number of spans buffered by a thread while waiting for the end of their block.
+const MAX_PENDING_SPANS: usize = 65536;
+
+/// Whether the execution metrics are collected by the global logger.
+pub(crate) static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Metrics of the phases measured by the client, set along with [`ENABLED`].
+pub(crate) static PHASE_METRICS: OnceLock<PhaseMetrics> = OnceLock::new();
+
+/// Whether a runtime without spans was reported.
+static MISSING_SPANS_REPORTED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+	/// Whether the current thread executes a block being imported.
+	static IMPORTING: Cell<bool> = const { Cell::new(false) };
+	/// Whether the runtime emitted the span of the block being imported.
+	static TRACED: Cell<bool> = const { Cell::new(false) };
+	/// Spans that ended on the current thread, along with the time they started.
+	static PENDING: RefCell<Vec<(Instant, Pending)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Run `f`, which executes a block being imported, collecting the execution metrics of the
+/// block.
+///
+/// This does nothing more than running `f` if the execution metrics are disabled.
+pub fn measure_import<R>(f: impl FnOnce() -> R) -> R {
+	if !ENABLED.load(Ordering::Relaxed) {
+		return f()
+	}
+
+	struct Window;
+
+	impl Drop for Window {
+		fn drop(&mut self) {
+			IMPORTING.set(false);
+			PENDING.with_borrow_mut(|pending| pending.clear());
+		}
+	}
+
+	TRACED.set(false);
+	IMPORTING.set(true);
+	let result = {
+		let _window = Window;
+		measure_phase("execute_block", f)
+	};
+
+	if !TRACED.get() && !MISSING_SPANS_REPORTED.swap(true, Ordering::Relaxed) {
+		log::warn!(
+			target: "sc_tracing",
+			"Execution metrics are not collected, as the runtime is not built with the \
+			 `with-tracing` feature",
+		);
+	}
+
+	result
+}
+
+/// Run `f`, which is the phase `phase` of the import of a block, reporting its wall time.
+///
+/// This does nothing more than running `f` if the execution metrics are disabled.
+pub fn measure_phase<R>(phase: &str, f: impl FnOnce() -> R) -> R {
+	let Some(metrics) = PHASE_METRICS.get() else { return f() };
+	let start = Instant::now();
+	let result = f();
+	let time = start.elapsed().as_nanos() as u64;
+	metrics.phases.with_label_values(&[phase]).inc();
+	metrics.phase_time.with_label_values(&[phase]).inc_by(time);
+	result
+}
+
+/// Metrics of the phases of the import of blocks, measured by the client.
+#[derive(Clone)]
+pub(crate) struct PhaseMetrics {
+	phases: CounterVec<U64>,
+	phase_time: CounterVec<U64>,
+}
+
+/// Layer enabling the runtime spans only within [`measure_import`].
+pub(crate) struct ExecutionWindow;
+
+impl<S: Subscriber> Layer<S> for ExecutionWindow {
+	fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+		if metadata.target() == WASM_TRACE_IDENTIFIER {
+			Interest::sometimes()
+		} else {
+			Interest::always()
+		}
+	}
+
+	fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+		metadata.target() != WASM_TRACE_IDENTIFIER || (metadata.is_span() && IMPORTING.get())
+	}
+}
+
+/// A span that ended before the block it belongs to.
+enum Pending {
+	/// The dispatch of an extrinsic.
+	Dispatch { pallet: String, call: String, time: Duration, ref_time: u64, proof_size: u64 },
+	/// A hook of a pallet.
+	Hook { pallet: String, hook: String, time: Duration },
+	/// Any other span, which may be a dispatched call.
+	Span { pallet: String, name: String },
+}
+
+/// Prometheus metrics of the runtime execution of blocks.
+pub struct ExecutionMetrics {
+	extrinsics: CounterVec<U64>,
+	extrinsic_time: CounterVec<U64>,
+	extrinsic_ref_time: CounterVec<U64>,
+	extrinsic_proof_size: CounterVec<U64>,
+	hooks: CounterVec<U64>,
+	hook_time: CounterVec<U64>,
+	pub(crate) phases: PhaseMetrics,
+}
+
+impl ExecutionMetrics {
+	/// Register the metrics in `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			extrinsics: register(
+				CounterVec::new(
+					Opts::new("runtime_extrinsics_total", "Number of executed extrinsics"),
+					&["pallet", "call"],
+				)?,
+				registry,
+			)?,
+			extrinsic_time: register(
+				CounterVec::new(
+					Opts::new(
+						"runtime_extrinsic_time_nanoseconds_total",
+						"Wall time spent dispatching extrinsics",
+					),
+					&["pallet", "call"],
+				)?,
+				registry,
+			)?,
+			extrinsic_ref_time: register(
+				CounterVec::new(
+					Opts::new(
+						"runtime_extrinsic_ref_time_picoseconds_total",
+						"Reference time weight declared by executed extrinsics",
+					),
+					&["pallet", "call"],
+				)?,
+				registry,
+			)?,
+			extrinsic_proof_size: register(
+				CounterVec::new(
+					Opts::new(
+						"runtime_extrinsic_proof_size_bytes_total",
+						"Proof size weight declared by executed extrinsics",
+					),
+					&["pallet", "call"],
+				)?,
+				registry,
+			)?,
+			hooks: register(
+				CounterVec::new(
+					Opts::new("runtime_hooks_total", "Number of executed pallet hooks"),
+					&["pallet", "hook"],
+				)?,
+				registry,
+			)?,
+			hook_time: register(
+				CounterVec::new(
+					Opts::new(
+						"runtime_hook_time_nanoseconds_total",
+						"Wall time spent executing pallet hooks",
+					),
+					&["pallet", "hook"],
+				)?,
+				registry,
+			)?,
+			phases: PhaseMetrics {
+				phases: register(
+					CounterVec::new(
+						Opts::new("block_import_phases_total", "Number of executed import phases"),
+						&["phase"],
+					)?,
+					registry,
+				)?,
+				phase_time: register(
+					CounterVec::new(
+						Opts::new(
+							"block_import_phase_time_nanoseconds_total",
+							"Wall time spent in the phases of the import of blocks",
+						),
+						&["phase"],
+					)?,
+					registry,
+				)?,
+			},
+		})
+	}
+
+	fn handle_runtime_span(&self, pending: &mut Vec<(Instant, Pending)>, span: &SpanDatum) {
+		let start = span.start_time;
+		match (span.target.as_str(), span.name.as_str()) {
+			(EXECUTIVE_TARGET, "execute_block") => {
+				TRACED.set(true);
+				let spans = take_within(pending, start);
+				// Anything left was not executed as part of the block.
+				pending.clear();
+				self.report(spans.into_iter().map(|(_, span)| span));
+				return
+			},
+			(EXECUTIVE_TARGET, "dispatch_extrinsic") => {
+				// The call is the outermost span within the dispatch.
+				let call = take_within(pending, start)
+					.into_iter()
+					.filter_map(|(start, span)| match span {
+						Pending::Span { pallet, name } => Some((start, pallet, name)),
+						_ => None,
+					})
+					.min_by_key(|(start, ..)| *start);
+				let (pallet, call) = call
+					.map(|(_, pallet, name)| (pallet, name))
+					.unwrap_or_else(|| ("unknown".into(), "unknown".into()));
+				pending.push((
+					start,
+					Pending::Dispatch {
+						pallet,
+						call,
+						time: span.overall_time,
+						ref_time: u64_value(span, "ref_time"),
+						proof_size: u64_value(span, "proof_size"),
+					},
+				));
+			},
+			(_, hook @ ("on_initialize" | "on_finalize")) => {
+				take_within(pending, start);
+				pending.push((
+					start,
+					Pending::Hook {
+						pallet: pallet(span),
+						hook: hook.into(),
+						time: span.overall_time,
+					},
+				));
+			},
+			(_, name) => {
+				// Only the outermost spans may be calls, but the extrinsics and hooks within them
+				// are kept until the end of the block.
+				pending.retain(|(span_start, span)| {
+					*span_start < start || !matches!(span, Pending::Span { .. })
+				});
+				pending.push((start, Pending::Span { pallet: pallet(span), name: name.into() }));
+			},
+		}
+
+		if pending.len() > MAX_PENDING_SPANS {
+			pending.clear();
+		}
+	}
+
+	fn report(&self, spans: impl Iterator<Item = Pending>) {
+		for span in spans {
+			match span {
+				Pending::Dispatch { pallet, call, time, ref_time, proof_size } => {
+					let labels = [pallet.as_str(), call.as_str()];
+					self.extrinsics.with_label_values(&labels).inc();
+					self.extrinsic_time.with_label_values(&labels).inc_by(time.as_nanos() as u64);
+					self.extrinsic_ref_time.with_label_values(&labels).inc_by(ref_time);
+					self.extrinsic_proof_size.with_label_values(&labels).inc_by(proof_size);
+				},
+				Pending::Hook { pallet, hook, time } => {
+					let labels = [pallet.as_str(), hook.as_str()];
+					self.hooks.with_label_values(&labels).inc();
+					self.hook_time.with_label_values(&labels).inc_by(time.as_nanos() as u64);
+				},
+				Pending::Span { .. } => {},
+			}
+		}
+	}
+}
+
+/// Get a value recorded by a runtime span.
+fn param<'a>(span: &'a SpanDatum, name: &str) -> Option<&'a str> {
+	// Values of runtime spans are formatted as `{ name: value, .. }`.
+	span.values
+		.string_values
+		.get("params")
+		.and_then(|params| params.split(&format!("{name}: ")).nth(1))
+		.and_then(|value| value.split([',', ' ', '}']).next())
+}
+
+/// The pallet instance of a span, falling back to the crate of its target for runtimes not
+/// recording it.
+fn pallet(span: &SpanDatum) -> String {
+	param(span, "pallet")
+		.unwrap_or_else(|| span.target.split("::").next().unwrap_or(&span.target))
+		.to_owned()
+}
+
+/// Get a `u64` value recorded by a span, either natively or in the runtime.
+fn u64_value(span: &SpanDatum, name: &str) -> u64 {
+	if let Some(value) = span.values.u64_values.get(name) {
+		return *value
+	}
+	param(span, name)
+		.and_then(|value| value.trim_end_matches("_u64").parse().ok())
+		.unwrap_or_default()
+}
+
+/// Remove the spans that started after `start`, which are the ones within the span starting then.
+fn take_within(pending: &mut Vec<(Instant, Pending)>, start: Instant) -> Vec<(Instant, Pending)> {
+	let (within, before) = pending.drain(..).partition(|(span_start, _)| *span_start >= start);
+	*pending = before;
+	within
+}
+
+impl TraceHandler for ExecutionMetrics {
+	fn handle_span(&self, span: &SpanDatum) {
+		if !span.values.bool_values.contains_key("wasm") {
+			return
+		}
+
+		PENDING.with_borrow_mut(|pending| self.handle_runtime_span(pending, span))
+	}
+
+	fn handle_event(&self, _event: &TraceEvent) {}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Values;
+	use tracing::{span::Id, Level};
+	use tracing_subscriber::layer::SubscriberExt;
+
+	fn span(
+		target: &str,
+		name: &str,
+		start: Instant,
+		millis: u64,
+		params: Option<&str>,
+	) -> SpanDatum {
+		let mut values = Values::default();
+		values.bool_values.insert("wasm".into(), true);
+		if let Some(params) = params {
+			values.string_values.insert("params".into(), params.into());
+		}
+		SpanDatum {
+			id: Id::from_u64(1),
+			parent_id: None,
+			name: name.into(),
+			target: target.into(),
+			level: Level::TRACE,
+			line: 0,
+			start_time: start,
+			overall_time: Duration::from_millis(millis),
+			values,
+		}
+	}
+
+	#[test]
+	fn reports_extrinsics_and_hooks_of_executed_blocks() {
+		let registry = Registry::new();
+		let metrics = ExecutionMetrics::register(&registry).unwrap();
+		let now = Instant::now();
+		let at = |millis| now + Duration::from_millis(millis);
+		let block = at(0);
+
+		// Spans are handled when they end, so inner spans come first.
+		let spans = [
+			span("pallet_timestamp::pallet", "on_initialize", at(1), 1, None),
+			span("frame_executive", "init_block", at(1), 2, None),
+			span("pallet_balances::pallet", "inner", at(5), 1, None),
+			span("pallet_balances::pallet", "transfer_allow_death", at(4), 3, None),
+			span(
+				"frame_executive",
+				"dispatch_extrinsic",
+				at(3),
+				5,
+				Some(" { ref_time: 1000_u64, proof_size: 20_u64 }"),
+			),
+			span("frame_executive", "apply_extrinsic", at(3), 6, None),
+			span("pallet_timestamp::pallet", "on_finalize", at(10), 1, None),
+			span("frame_executive", "finalize_block", at(10), 2, None),
+		];
+		for span in &spans {
+			metrics.handle_span(span);
+		}
+		// Nothing is reported before the end of the block.
+		assert_eq!(
+			metrics
+				.extrinsics
+				.with_label_values(&["pallet_balances", "transfer_allow_death"])
+				.get(),
+			0
+		);
+
+		metrics.handle_span(&span("frame_executive", "execute_block", block, 15, None));
+
+		let call = ["pallet_balances", "transfer_allow_death"];
+		assert_eq!(metrics.extrinsics.with_label_values(&call).get(), 1);
+		assert_eq!(metrics.extrinsic_time.with_label_values(&call).get(), 5_000_000);
+		assert_eq!(metrics.extrinsic_ref_time.with_label_values(&call).get(), 1000);
+		assert_eq!(metrics.extrinsic_proof_size.with_label_values(&call).get(), 20);
+		for hook in ["on_initialize", "on_finalize"] {
+			assert_eq!(metrics.hooks.with_label_values(&["pallet_timestamp", hook]).get(), 1);
+			assert_eq!(
+				metrics.hook_time.with_label_values(&["pallet_timestamp", hook]).get(),
+				1_000_000
+			);
+		}
+		assert!(PENDING.with_borrow(|pending| pending.is_empty()));
+	}
+
+	#[test]
+	fn ignores_spans_outside_of_blocks() {
+		let registry = Registry::new();
+		let metrics = ExecutionMetrics::register(&registry).unwrap();
+		let start = Instant::now();
+
+		metrics.handle_span(&span(
+			"pallet_balances::pallet",
+			"transfer_allow_death",
+			start,
+			1,
+			None,
+		));
+		metrics.handle_span(&span("frame_executive", "dispatch_extrinsic", start, 1, None));
+		metrics.handle_span(&span(
+			"frame_executive",
+			"execute_block",
+			start + Duration::from_millis(10),
+			1,
+			None,
+		));
+
+		let call = ["pallet_balances", "transfer_allow_death"];
+		assert_eq!(metrics.extrinsics.with_label_values(&call).get(), 0);
+		assert!(PENDING.with_borrow(|pending| pending.is_empty()));
+	}
+
+	#[test]
+	fn keys_spans_by_pallet_instance() {
+		let registry = Registry::new();
+		let metrics = ExecutionMetrics::register(&registry).unwrap();
+		let now = Instant::now();
+		let at = |millis| now + Duration::from_millis(millis);
+
+		for (start, pallet) in [(1, "Assets"), (3, "ForeignAssets")] {
+			let params = format!("{{ pallet: {pallet} }}");
+			metrics.handle_span(&span(
+				"pallet_assets",
+				"on_initialize",
+				at(start),
+				1,
+				Some(&params),
+			));
+		}
+		metrics.handle_span(&span("frame_executive", "execute_block", at(0), 5, None));
+
+		for pallet in ["Assets", "ForeignAssets"] {
+			assert_eq!(metrics.hooks.with_label_values(&[pallet, "on_initialize"]).get(), 1);
+		}
+		assert_eq!(metrics.hooks.with_label_values(&["pallet_assets", "on_initialize"]).get(), 0);
+	}
+
+	#[test]
+	fn reports_phases_for_any_runtime() {
+		let registry = Registry::new();
+		let metrics = ExecutionMetrics::register(&registry).unwrap();
+		let metrics = PHASE_METRICS.get_or_init(|| metrics.phases.clone());
+
+		measure_phase("storage_changes", || std::thread::sleep(Duration::from_millis(1)));
+
+		assert_eq!(metrics.phases.with_label_values(&["storage_changes"]).get(), 1);
+		assert!(metrics.phase_time.with_label_values(&["storage_changes"]).get() >= 1_000_000);
+	}
+
+	#[test]
+	fn enables_runtime_spans_within_imports() {
+		let subscriber = tracing_subscriber::registry().with(ExecutionWindow);
+		let runtime_span = || {
+			tracing::span!(target: WASM_TRACE_IDENTIFIER, Level::TRACE, "wasm_tracing")
+				.is_disabled()
+		};
+
+		tracing::subscriber::with_default(subscriber, || {
+			ENABLED.store(true, Ordering::Relaxed);
+			assert!(runtime_span());
+			assert!(!measure_import(|| {
+				PENDING.with_borrow_mut(|pending| {
+					pending.push((
+						Instant::now(),
+						Pending::Span { pallet: "".into(), name: "".into() },
+					))
+				});
+				runtime_span()
+			}));
+			assert!(runtime_span());
+			// The spans of the block are dropped along with it.
+			assert!(PENDING.with_borrow(|pending| pending.is_empty()));
+		});
+	}
+}
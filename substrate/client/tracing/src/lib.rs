@@ -29,6 +29,7 @@
 #![warn(missing_docs)]
 
 pub mod block;
+pub mod execution_metrics;
 pub mod logging;
 
 use rustc_hash::FxHashMap;
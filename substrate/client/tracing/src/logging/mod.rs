@@ -34,7 +34,7 @@ pub use directives::*;
 pub use sc_tracing_proc_macro::*;
 
 use is_terminal::IsTerminal;
-use std::io;
+use std::{io, sync::atomic::Ordering};
 use tracing::Subscriber;
 use tracing_subscriber::{
 	filter::LevelFilter,
@@ -95,6 +95,7 @@ fn to_log_level_filter(level_filter: Option<LevelFilter>) -> log::LevelFilter {
 fn prepare_subscriber<N, E, F, W>(
 	directives: &str,
 	profiling_targets: Option<&str>,
+	execution_metrics: bool,
 	force_colors: Option<bool>,
 	detailed_output: bool,
 	builder_hook: impl Fn(
@@ -176,6 +177,19 @@ where
 
 	tracing_log::LogTracer::builder().with_max_level(max_level).init()?;
 
+	// The runtime spans of the execution metrics are only enabled while importing blocks, unless
+	// they are explicitly enabled, so they don't change the maximum level of the logs.
+	let execution_window = if execution_metrics {
+		let enabled = env_filter.to_string().contains(sp_tracing::WASM_TRACE_IDENTIFIER);
+		env_filter = env_filter.add_directive(
+			parse_default_directive(crate::execution_metrics::EXECUTION_METRICS_DIRECTIVE)
+				.expect("provided directive is valid"),
+		);
+		(!enabled).then_some(crate::execution_metrics::ExecutionWindow)
+	} else {
+		None
+	};
+
 	// If we're only logging `INFO` entries then we'll use a simplified logging format.
 	let detailed_output = match max_level_hint {
 		Some(level) if level <= tracing_subscriber::filter::LevelFilter::INFO => false,
@@ -206,7 +220,7 @@ where
 
 	let builder = builder_hook(builder);
 
-	let subscriber = builder.finish().with(PrefixLayer);
+	let subscriber = builder.finish().with(PrefixLayer).with(execution_window);
 
 	Ok(subscriber)
 }
@@ -216,6 +230,7 @@ pub struct LoggerBuilder {
 	directives: String,
 	profiling: Option<(crate::TracingReceiver, String)>,
 	custom_profiler: Option<Box<dyn crate::TraceHandler>>,
+	execution_metrics: Option<crate::execution_metrics::ExecutionMetrics>,
 	log_reloading: bool,
 	force_colors: Option<bool>,
 	detailed_output: bool,
@@ -228,6 +243,7 @@ impl LoggerBuilder {
 			directives: directives.into(),
 			profiling: None,
 			custom_profiler: None,
+			execution_metrics: None,
 			log_reloading: false,
 			force_colors: None,
 			detailed_output: false,
@@ -253,6 +269,21 @@ impl LoggerBuilder {
 		self
 	}
 
+	/// Collect the execution metrics of imported blocks.
+	///
+	/// This enables the runtime spans the metrics of extrinsics and hooks are collected from while
+	/// importing blocks, which are only emitted by runtimes built with the `with-tracing` feature.
+	/// When profiling is set up, these metrics are collected by the profiling layer and are
+	/// limited to the profiling targets. The phases of the import are measured by the client for
+	/// any runtime.
+	pub fn with_execution_metrics(
+		&mut self,
+		execution_metrics: crate::execution_metrics::ExecutionMetrics,
+	) -> &mut Self {
+		self.execution_metrics = Some(execution_metrics);
+		self
+	}
+
 	/// Wether or not to disable log reloading.
 	pub fn with_log_reloading(&mut self, enabled: bool) -> &mut Self {
 		self.log_reloading = enabled;
@@ -280,11 +311,21 @@ impl LoggerBuilder {
 	///
 	/// This sets various global logging and tracing instances and thus may only be called once.
 	pub fn init(self) -> Result<()> {
+		let execution_metrics_enabled = self.execution_metrics.is_some();
+		crate::execution_metrics::ENABLED.store(execution_metrics_enabled, Ordering::Relaxed);
+		if let Some(metrics) = &self.execution_metrics {
+			let _ = crate::execution_metrics::PHASE_METRICS.set(metrics.phases.clone());
+		}
+		let execution_metrics = self
+			.execution_metrics
+			.map(|metrics| -> Box<dyn crate::TraceHandler> { Box::new(metrics) });
+
 		if let Some((tracing_receiver, profiling_targets)) = self.profiling {
 			if self.log_reloading {
 				let subscriber = prepare_subscriber(
 					&self.directives,
 					Some(&profiling_targets),
+					execution_metrics_enabled,
 					self.force_colors,
 					self.detailed_output,
 					|builder| enable_log_reloading!(builder),
@@ -294,6 +335,7 @@ impl LoggerBuilder {
 
 				self.custom_profiler
 					.into_iter()
+					.chain(execution_metrics)
 					.for_each(|profiler| profiling.add_handler(profiler));
 
 				tracing::subscriber::set_global_default(subscriber.with(profiling))?;
@@ -303,6 +345,7 @@ impl LoggerBuilder {
 				let subscriber = prepare_subscriber(
 					&self.directives,
 					Some(&profiling_targets),
+					execution_metrics_enabled,
 					self.force_colors,
 					self.detailed_output,
 					|builder| builder,
@@ -312,6 +355,7 @@ impl LoggerBuilder {
 
 				self.custom_profiler
 					.into_iter()
+					.chain(execution_metrics)
 					.for_each(|profiler| profiling.add_handler(profiler));
 
 				tracing::subscriber::set_global_default(subscriber.with(profiling))?;
@@ -319,27 +363,33 @@ impl LoggerBuilder {
 				Ok(())
 			}
 		} else if self.log_reloading {
+			let execution_metrics = execution_metrics
+				.map(|metrics| crate::ProfilingLayer::new_with_handler(metrics, ""));
 			let subscriber = prepare_subscriber(
 				&self.directives,
 				None,
+				execution_metrics_enabled,
 				self.force_colors,
 				self.detailed_output,
 				|builder| enable_log_reloading!(builder),
 			)?;
 
-			tracing::subscriber::set_global_default(subscriber)?;
+			tracing::subscriber::set_global_default(subscriber.with(execution_metrics))?;
 
 			Ok(())
 		} else {
+			let execution_metrics = execution_metrics
+				.map(|metrics| crate::ProfilingLayer::new_with_handler(metrics, ""));
 			let subscriber = prepare_subscriber(
 				&self.directives,
 				None,
+				execution_metrics_enabled,
 				self.force_colors,
 				self.detailed_output,
 				|builder| builder,
 			)?;
 
-			tracing::subscriber::set_global_default(subscriber)?;
+			tracing::subscriber::set_global_default(subscriber.with(execution_metrics))?;
 
 			Ok(())
 		}
@@ -0,0 +1,207 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Transaction pool inspection.
+//!
+//! Types describing the detailed content of the transaction pool, intended for debugging, e.g.
+//! via the `txpool` RPC namespace.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sp_core::Bytes;
+use sp_runtime::transaction_validity::{TransactionPriority, TransactionSource};
+
+/// Validity details of a transaction in a view of the pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatedTransactionInfo<Hash> {
+	/// Transaction hash.
+	pub hash: Hash,
+	/// Effective priority of the transaction.
+	pub priority: TransactionPriority,
+	/// Number of the block up to which the transaction is valid.
+	pub valid_till: u64,
+	/// Tags required by the transaction.
+	pub requires: Vec<Bytes>,
+	/// Tags provided by the transaction.
+	pub provides: Vec<Bytes>,
+	/// Should the transaction be propagated to other peers.
+	pub propagate: bool,
+	/// Size of the encoded transaction.
+	pub bytes: usize,
+}
+
+/// Content of a single view of the pool, i.e. the state of the pool at given block.
+///
+/// The single-state pool has exactly one view, at the most recent best block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewContent<BlockHash, Hash> {
+	/// Hash of the block the view is built at.
+	pub at: BlockHash,
+	/// Number of the block the view is built at.
+	pub number: u64,
+	/// Transactions ready to be included in a block built on top of `at`, in the order they would
+	/// be included.
+	pub ready: Vec<ValidatedTransactionInfo<Hash>>,
+	/// Transactions whose requirements are not yet satisfied at `at`.
+	pub future: Vec<ValidatedTransactionInfo<Hash>>,
+}
+
+/// Pool-wide details of a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PooledTransactionInfo<BlockHash, Hash> {
+	/// Transaction hash.
+	pub hash: Hash,
+	/// Source of the transaction.
+	#[serde(with = "source")]
+	pub source: TransactionSource,
+	/// Effective priority of the transaction, if known.
+	pub priority: Option<TransactionPriority>,
+	/// Size of the encoded transaction.
+	pub bytes: usize,
+	/// Is the progress of the transaction watched by a client.
+	pub watched: bool,
+	/// Is the transaction temporarily banned.
+	pub banned: bool,
+	/// Blocks of the views containing the transaction.
+	pub views: Vec<BlockHash>,
+}
+
+/// Content of the transaction pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionPoolContent<BlockHash, Hash> {
+	/// All transactions held by the pool.
+	pub transactions: Vec<PooledTransactionInfo<BlockHash, Hash>>,
+	/// Active views of the pool.
+	pub views: Vec<ViewContent<BlockHash, Hash>>,
+}
+
+/// Details of a single transaction reported by the pool inspection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionInspection<BlockHash, Hash> {
+	/// Is the transaction temporarily banned.
+	///
+	/// Banned transactions are usually not held by the pool anymore.
+	pub banned: bool,
+	/// Content of the pool restricted to the transaction and to the views containing it, or
+	/// `None` if the transaction is not held by the pool.
+	pub content: Option<TransactionPoolContent<BlockHash, Hash>>,
+}
+
+/// Transaction pool which can report its detailed content.
+#[async_trait]
+pub trait TransactionPoolInspect: Send + Sync {
+	/// Block hash type.
+	type BlockHash;
+	/// Transaction hash type.
+	type Hash: Send + Sync;
+
+	/// Returns the detailed content of the pool.
+	async fn content(&self) -> TransactionPoolContent<Self::BlockHash, Self::Hash>;
+
+	/// Returns the details of a single transaction.
+	///
+	/// The ban list is checked even if the transaction is not held by the pool.
+	async fn inspect(
+		&self,
+		hash: &Self::Hash,
+	) -> TransactionInspection<Self::BlockHash, Self::Hash>;
+}
+
+/// (De)serializes [`TransactionSource`] as a string.
+mod source {
+	use super::TransactionSource;
+	use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+	pub fn serialize<S: Serializer>(source: &TransactionSource, s: S) -> Result<S::Ok, S::Error> {
+		s.serialize_str(match source {
+			TransactionSource::InBlock => "inBlock",
+			TransactionSource::Local => "local",
+			TransactionSource::External => "external",
+		})
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<TransactionSource, D::Error> {
+		match String::deserialize(d)?.as_str() {
+			"inBlock" => Ok(TransactionSource::InBlock),
+			"local" => Ok(TransactionSource::Local),
+			"external" => Ok(TransactionSource::External),
+			other => Err(D::Error::custom(format!("unknown transaction source: {other}"))),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_serialize_content() {
+		let content = TransactionPoolContent {
+			transactions: vec![PooledTransactionInfo {
+				hash: 1u64,
+				source: TransactionSource::External,
+				priority: Some(10),
+				bytes: 100,
+				watched: false,
+				banned: false,
+				views: vec![2u64],
+			}],
+			views: vec![ViewContent {
+				at: 2u64,
+				number: 5,
+				ready: vec![ValidatedTransactionInfo {
+					hash: 1u64,
+					priority: 10,
+					valid_till: 64,
+					requires: vec![],
+					provides: vec![vec![1u8].into()],
+					propagate: true,
+					bytes: 100,
+				}],
+				future: vec![],
+			}],
+		};
+
+		let json = serde_json::to_string(&content).unwrap();
+		assert_eq!(
+			json,
+			r#"{"transactions":[{"hash":1,"source":"external","priority":10,"bytes":100,"watched":false,"banned":false,"views":[2]}],"views":[{"at":2,"number":5,"ready":[{"hash":1,"priority":10,"validTill":64,"requires":[],"provides":["0x01"],"propagate":true,"bytes":100}],"future":[]}]}"#
+		);
+		assert_eq!(
+			serde_json::from_str::<TransactionPoolContent<u64, u64>>(&json).unwrap(),
+			content
+		);
+	}
+
+	#[test]
+	fn should_serialize_banned_transaction() {
+		let inspection = TransactionInspection::<u64, u64> { banned: true, content: None };
+
+		let json = serde_json::to_string(&inspection).unwrap();
+		assert_eq!(json, r#"{"banned":true,"content":null}"#);
+		assert_eq!(
+			serde_json::from_str::<TransactionInspection<u64, u64>>(&json).unwrap(),
+			inspection
+		);
+	}
+}
@@ -20,6 +20,7 @@
 #![warn(missing_docs)]
 
 pub mod error;
+pub mod inspect;
 
 use async_trait::async_trait;
 use codec::Codec;
@@ -31,6 +32,7 @@ use std::{collections::HashMap, hash::Hash, marker::PhantomData, pin::Pin, sync:
 
 const LOG_TARGET: &str = "txpool::api";
 
+pub use inspect::TransactionPoolInspect;
 pub use sp_runtime::transaction_validity::{
 	TransactionLongevity, TransactionPriority, TransactionSource, TransactionTag,
 	TransactionValidityError,
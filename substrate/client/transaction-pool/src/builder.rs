@@ -19,17 +19,32 @@
 //! Utility for building substrate transaction pool trait object.
 
 use crate::{
-	common::api::FullChainApi,
+	common::{
+		api::FullChainApi,
+		journal::{JournalOptions, TransactionJournal},
+	},
 	fork_aware_txpool::ForkAwareTxPool as ForkAwareFullPool,
-	graph::{base_pool::Transaction, ChainApi, ExtrinsicFor, ExtrinsicHash, IsValidator, Options},
+	graph::{
+		base_pool::Transaction,
+		ordering::{OrderingPolicy, RuntimePriority},
+		ChainApi, ExtrinsicFor, ExtrinsicHash, IsValidator, Options,
+	},
 	single_state_txpool::BasicPool as SingleStateFullPool,
 	TransactionPoolWrapper, LOG_TARGET,
 };
 use prometheus_endpoint::Registry as PrometheusRegistry;
-use sc_transaction_pool_api::{LocalTransactionPool, MaintainedTransactionPool};
+use sc_transaction_pool_api::{
+	LocalTransactionPool, MaintainedTransactionPool, TransactionPoolInspect,
+};
+use sp_blockchain::HashAndNumber;
 use sp_core::traits::SpawnEssentialNamed;
 use sp_runtime::traits::Block as BlockT;
-use std::{marker::PhantomData, sync::Arc, time::Duration};
+use std::{
+	marker::PhantomData,
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::Duration,
+};
 
 /// The type of transaction pool.
 #[derive(Debug, Clone)]
@@ -45,11 +60,16 @@ pub enum TransactionPoolType {
 pub struct TransactionPoolOptions {
 	txpool_type: TransactionPoolType,
 	options: Options,
+	journal: Option<JournalOptions>,
 }
 
 impl Default for TransactionPoolOptions {
 	fn default() -> Self {
-		Self { txpool_type: TransactionPoolType::SingleState, options: Default::default() }
+		Self {
+			txpool_type: TransactionPoolType::SingleState,
+			options: Default::default(),
+			journal: None,
+		}
 	}
 }
 
@@ -81,7 +101,37 @@ impl TransactionPoolOptions {
 			Duration::from_secs(30 * 60)
 		};
 
-		TransactionPoolOptions { options, txpool_type }
+		TransactionPoolOptions { options, txpool_type, journal: None }
+	}
+
+	/// Enables the on-disk journal of the transaction pool.
+	///
+	/// The pool contents are periodically written to the file at `path`, up to `max_bytes`, and
+	/// restored at startup.
+	pub fn with_journal(mut self, path: PathBuf, max_bytes: usize) -> Self {
+		self.journal = Some(JournalOptions { path, max_bytes });
+		self
+	}
+
+	/// Sets the policy computing the effective priority of transactions in the pool.
+	///
+	/// By default transactions are ordered by the priority provided by the runtime.
+	pub fn with_ordering_policy(mut self, ordering: OrderingPolicy) -> Self {
+		self.options.ordering = ordering;
+		self
+	}
+
+	/// Resolves a relative journal path against `base_path`.
+	pub fn with_journal_base_path(mut self, base_path: &Path) -> Self {
+		if let Some(journal) = self.journal.as_mut().filter(|j| j.path.is_relative()) {
+			journal.path = base_path.join(&journal.path);
+		}
+		self
+	}
+
+	/// Returns the options of the transaction pool journal, if enabled.
+	pub fn journal(&self) -> Option<&JournalOptions> {
+		self.journal.as_ref()
 	}
 
 	/// Creates predefined options for benchmarking
@@ -98,14 +148,17 @@ impl TransactionPoolOptions {
 				},
 				reject_future_transactions: false,
 				ban_time: Duration::from_secs(30 * 60),
+				ordering: Arc::new(RuntimePriority),
 			},
 			txpool_type: TransactionPoolType::SingleState,
+			journal: None,
 		}
 	}
 }
 
 /// `FullClientTransactionPool` is a trait that combines the functionality of
-/// `MaintainedTransactionPool` and `LocalTransactionPool` for a given `Client` and `Block`.
+/// `MaintainedTransactionPool`, `LocalTransactionPool` and `TransactionPoolInspect` for a given
+/// `Client` and `Block`.
 ///
 /// This trait defines the requirements for a full client transaction pool, ensuring
 /// that it can handle transactions submission and maintenance.
@@ -122,6 +175,9 @@ pub trait FullClientTransactionPool<Block, Client>:
 		Block = Block,
 		Hash = ExtrinsicHash<FullChainApi<Client, Block>>,
 		Error = <FullChainApi<Client, Block> as ChainApi>::Error,
+	> + TransactionPoolInspect<
+		BlockHash = Block::Hash,
+		Hash = ExtrinsicHash<FullChainApi<Client, Block>>,
 	>
 where
 	Block: BlockT,
@@ -157,6 +213,9 @@ where
 			Block = Block,
 			Hash = ExtrinsicHash<FullChainApi<Client, Block>>,
 			Error = <FullChainApi<Client, Block> as ChainApi>::Error,
+		> + TransactionPoolInspect<
+			BlockHash = Block::Hash,
+			Hash = ExtrinsicHash<FullChainApi<Client, Block>>,
 		>,
 {
 }
@@ -229,23 +288,49 @@ where
 			txpool_type = ?self.options.txpool_type,
 			ready = ?self.options.options.ready,
 			future = ?self.options.options.future,
+			ordering = ?self.options.options.ordering,
+			journal = ?self.options.journal,
 			"Creating transaction pool"
 		);
+		let info = self.client.info();
+		let best = HashAndNumber { hash: info.best_hash, number: info.best_number };
+		let journal = self.options.journal.map(TransactionJournal::new);
+		let spawner = self.spawner.clone();
+		let spawn_journal = |journal: TransactionJournal, pool| {
+			spawner.spawn_essential(
+				"txpool-journal",
+				Some("transaction-pool"),
+				Box::pin(journal.run(pool, best)),
+			)
+		};
+
 		TransactionPoolWrapper::<Block, Client>(match self.options.txpool_type {
-			TransactionPoolType::SingleState => Box::new(SingleStateFullPool::new_full(
-				self.options.options,
-				self.is_validator,
-				self.prometheus,
-				self.spawner,
-				self.client,
-			)),
-			TransactionPoolType::ForkAware => Box::new(ForkAwareFullPool::new_full(
-				self.options.options,
-				self.is_validator,
-				self.prometheus,
-				self.spawner,
-				self.client,
-			)),
+			TransactionPoolType::SingleState => {
+				let pool = SingleStateFullPool::new_full(
+					self.options.options,
+					self.is_validator,
+					self.prometheus,
+					self.spawner,
+					self.client,
+				);
+				if let Some(journal) = journal {
+					spawn_journal(journal, pool.journaled_pool());
+				}
+				Box::new(pool)
+			},
+			TransactionPoolType::ForkAware => {
+				let pool = ForkAwareFullPool::new_full(
+					self.options.options,
+					self.is_validator,
+					self.prometheus,
+					self.spawner,
+					self.client,
+				);
+				if let Some(journal) = journal {
+					spawn_journal(journal, pool.journaled_pool());
+				}
+				Box::new(pool)
+			},
 		})
 	}
 }
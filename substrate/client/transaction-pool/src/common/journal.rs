@@ -0,0 +1,312 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! On-disk journal of the transaction pool.
+//!
+//! When enabled, the transactions held by the pool are periodically written to disk together with
+//! their source and submission time. At startup the journal is replayed: its transactions are
+//! added back to the pool and revalidated against the best block, so pending transactions survive
+//! node restarts. The journal is also written when the node shuts down, so only the transactions
+//! submitted after the last write are lost on a crash.
+
+use crate::{
+	graph::{self, base_pool::TimedTransactionSource, ExtrinsicFor, RawExtrinsicFor},
+	LOG_TARGET,
+};
+use codec::{Decode, Encode};
+use sc_transaction_pool_api::TransactionSource;
+use sp_blockchain::HashAndNumber;
+use std::{
+	io,
+	path::PathBuf,
+	sync::Arc,
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, info, warn};
+
+/// How often the pool contents are written to the journal.
+const JOURNAL_WRITE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Version of the journal file format.
+const JOURNAL_VERSION: u32 = 1;
+
+/// Configuration of the transaction pool journal.
+#[derive(Debug, Clone)]
+pub struct JournalOptions {
+	/// Path of the journal file.
+	pub path: PathBuf,
+	/// Maximum size of the journal in bytes. Transactions that don't fit are not persisted.
+	pub max_bytes: usize,
+}
+
+/// A transaction stored in the journal.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+struct JournalEntry {
+	/// The source of the transaction.
+	source: TransactionSource,
+	/// Submission time, in milliseconds since the UNIX epoch.
+	submitted_at: u64,
+	/// The SCALE-encoded transaction.
+	extrinsic: Vec<u8>,
+}
+
+/// Transaction pool whose contents can be stored in a [`TransactionJournal`].
+#[async_trait::async_trait]
+pub(crate) trait JournaledPool<ChainApi: graph::ChainApi>: Send + Sync {
+	/// Returns the transactions held by the pool with their sources, best transactions first.
+	async fn journal_snapshot(&self) -> Vec<(TimedTransactionSource, ExtrinsicFor<ChainApi>)>;
+
+	/// Same as [`Self::journal_snapshot`], but returns `None` instead of waiting for the pool to
+	/// be unlocked.
+	fn try_journal_snapshot(&self)
+		-> Option<Vec<(TimedTransactionSource, ExtrinsicFor<ChainApi>)>>;
+
+	/// Adds transactions read from the journal back to the pool.
+	///
+	/// Transactions shall be revalidated against the given best block, or on the next block
+	/// import.
+	async fn journal_restore(
+		&self,
+		best: HashAndNumber<ChainApi::Block>,
+		transactions: Vec<(TimedTransactionSource, ExtrinsicFor<ChainApi>)>,
+	);
+}
+
+/// The on-disk journal of the transaction pool.
+pub(crate) struct TransactionJournal {
+	options: JournalOptions,
+}
+
+impl TransactionJournal {
+	/// Creates a new journal with the given options.
+	pub fn new(options: JournalOptions) -> Self {
+		Self { options }
+	}
+
+	/// Replaces the content of the journal with the given transactions.
+	///
+	/// Transactions are written in the given order until `max_bytes` is reached. Returns the
+	/// number of written transactions.
+	pub fn write<E: Encode>(
+		&self,
+		transactions: impl IntoIterator<Item = (TimedTransactionSource, E)>,
+	) -> io::Result<usize> {
+		let (now, now_millis) = (Instant::now(), unix_millis());
+		let mut size = 0;
+		let mut entries = Vec::new();
+		for (source, xt) in transactions {
+			let age = source.timestamp.map_or(0, |t| now.saturating_duration_since(t).as_millis());
+			let entry = JournalEntry {
+				source: source.source,
+				submitted_at: now_millis.saturating_sub(age as u64),
+				extrinsic: xt.encode(),
+			};
+			size += entry.encoded_size();
+			if size > self.options.max_bytes {
+				debug!(
+					target: LOG_TARGET,
+					max_bytes = self.options.max_bytes,
+					written = entries.len(),
+					"txpool journal is full"
+				);
+				break
+			}
+			entries.push(entry);
+		}
+
+		if let Some(parent) = self.options.path.parent() {
+			std::fs::create_dir_all(parent)?;
+		}
+		// Write to a temporary file first, so that a crash never leaves a truncated journal.
+		let tmp_path = self.options.path.with_extension("tmp");
+		std::fs::write(&tmp_path, (JOURNAL_VERSION, &entries).encode())?;
+		std::fs::rename(&tmp_path, &self.options.path)?;
+		Ok(entries.len())
+	}
+
+	/// Reads the transactions stored in the journal.
+	///
+	/// Returns an empty list if there is no journal, or if it can't be read. Transactions that
+	/// can't be decoded are skipped.
+	pub fn read<E: Decode>(&self) -> Vec<(TimedTransactionSource, E)> {
+		let data = match std::fs::read(&self.options.path) {
+			Ok(data) => data,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => return Vec::new(),
+			Err(e) => {
+				warn!(target: LOG_TARGET, path = ?self.options.path, ?e, "Failed to read txpool journal");
+				return Vec::new()
+			},
+		};
+
+		let entries = match <(u32, Vec<JournalEntry>)>::decode(&mut &data[..]) {
+			Ok((JOURNAL_VERSION, entries)) => entries,
+			Ok((version, _)) => {
+				warn!(target: LOG_TARGET, version, "Unsupported txpool journal version");
+				return Vec::new()
+			},
+			Err(e) => {
+				warn!(target: LOG_TARGET, ?e, "Corrupted txpool journal");
+				return Vec::new()
+			},
+		};
+
+		let (now, now_millis) = (Instant::now(), unix_millis());
+		entries
+			.into_iter()
+			.filter_map(|entry| {
+				let xt = E::decode(&mut &entry.extrinsic[..])
+					.inspect_err(
+						|e| debug!(target: LOG_TARGET, ?e, "Failed to decode txpool journal entry"),
+					)
+					.ok()?;
+				let age = Duration::from_millis(now_millis.saturating_sub(entry.submitted_at));
+				let timestamp = now.checked_sub(age).unwrap_or(now);
+				Some((
+					TimedTransactionSource { source: entry.source, timestamp: Some(timestamp) },
+					xt,
+				))
+			})
+			.collect()
+	}
+
+	/// Restores the journal into `pool`, then periodically writes the pool contents to it.
+	pub async fn run<ChainApi, P>(self, pool: Arc<P>, best: HashAndNumber<ChainApi::Block>)
+	where
+		ChainApi: graph::ChainApi,
+		P: JournaledPool<ChainApi> + ?Sized,
+	{
+		let transactions = self
+			.read::<RawExtrinsicFor<ChainApi>>()
+			.into_iter()
+			.map(|(source, xt)| (source, Arc::new(xt)))
+			.collect::<Vec<_>>();
+		info!(
+			target: LOG_TARGET,
+			count = transactions.len(),
+			best = ?best.hash,
+			"Restoring transactions from the txpool journal"
+		);
+		pool.journal_restore(best, transactions).await;
+
+		let journal = Arc::new(self);
+		// The task is dropped on shutdown, so flush the latest pool contents when that happens.
+		let _flush = FlushOnDrop({
+			let (journal, pool) = (journal.clone(), pool.clone());
+			move || match pool.try_journal_snapshot() {
+				Some(transactions) => log_write_result(journal.write(transactions)),
+				None => warn!(target: LOG_TARGET, "txpool is locked, journal not flushed"),
+			}
+		});
+
+		loop {
+			futures_timer::Delay::new(JOURNAL_WRITE_INTERVAL).await;
+			let transactions = pool.journal_snapshot().await;
+			let journal = journal.clone();
+			let result = tokio::task::spawn_blocking(move || journal.write(transactions))
+				.await
+				.unwrap_or_else(|e| Err(io::Error::other(e)));
+			log_write_result(result);
+		}
+	}
+}
+
+/// Runs the wrapped closure when dropped.
+struct FlushOnDrop<F: FnMut()>(F);
+
+impl<F: FnMut()> Drop for FlushOnDrop<F> {
+	fn drop(&mut self) {
+		(self.0)()
+	}
+}
+
+/// Logs the result of [`TransactionJournal::write`].
+fn log_write_result(result: io::Result<usize>) {
+	match result {
+		Ok(count) => debug!(target: LOG_TARGET, count, "txpool journal written"),
+		Err(e) => warn!(target: LOG_TARGET, ?e, "Failed to write txpool journal"),
+	}
+}
+
+/// Returns the current time in milliseconds since the UNIX epoch.
+fn unix_millis() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn journal(max_bytes: usize) -> (TransactionJournal, tempfile::TempDir) {
+		let dir = tempfile::tempdir().unwrap();
+		let options = JournalOptions { path: dir.path().join("txpool").join("journal"), max_bytes };
+		(TransactionJournal::new(options), dir)
+	}
+
+	#[test]
+	fn write_and_read_round_trip() {
+		let (journal, _dir) = journal(usize::MAX);
+		assert!(journal.read::<Vec<u8>>().is_empty());
+
+		let submitted = Instant::now() - Duration::from_secs(10);
+		let transactions = vec![
+			(TimedTransactionSource::new_local(false), vec![1u8, 2, 3]),
+			(
+				TimedTransactionSource {
+					source: TransactionSource::External,
+					timestamp: Some(submitted),
+				},
+				vec![4u8],
+			),
+		];
+		assert_eq!(journal.write(transactions.clone()).unwrap(), 2);
+
+		let restored = journal.read::<Vec<u8>>();
+		assert_eq!(restored.len(), 2);
+		assert_eq!(restored[0].0.source, TransactionSource::Local);
+		assert_eq!(restored[0].1, vec![1u8, 2, 3]);
+		assert_eq!(restored[1].0.source, TransactionSource::External);
+		assert_eq!(restored[1].1, vec![4u8]);
+		let age = restored[1].0.timestamp.unwrap().elapsed();
+		assert!(age >= Duration::from_secs(9) && age < Duration::from_secs(60));
+	}
+
+	#[test]
+	fn write_obeys_size_limit() {
+		let entry_size = JournalEntry {
+			source: TransactionSource::External,
+			submitted_at: 0,
+			extrinsic: vec![0u8; 100].encode(),
+		}
+		.encoded_size();
+		let (journal, _dir) = journal(2 * entry_size);
+
+		let transactions =
+			(0..5u8).map(|i| (TimedTransactionSource::new_external(false), vec![i; 100]));
+		assert_eq!(journal.write(transactions).unwrap(), 2);
+		let restored = journal.read::<Vec<u8>>();
+		assert_eq!(restored.into_iter().map(|(_, xt)| xt[0]).collect::<Vec<_>>(), vec![0, 1]);
+	}
+
+	#[test]
+	fn corrupted_journal_is_ignored() {
+		let (journal, _dir) = journal(usize::MAX);
+		std::fs::create_dir_all(journal.options.path.parent().unwrap()).unwrap();
+		std::fs::write(&journal.options.path, b"garbage").unwrap();
+		assert!(journal.read::<Vec<u8>>().is_empty());
+	}
+}
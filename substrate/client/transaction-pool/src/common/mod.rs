@@ -21,6 +21,7 @@
 pub(crate) mod api;
 pub(crate) mod enactment_state;
 pub(crate) mod error;
+pub(crate) mod journal;
 pub(crate) mod metrics;
 pub(crate) mod sliding_stat;
 #[cfg(test)]
@@ -30,6 +30,7 @@ use super::{
 use crate::{
 	api::FullChainApi,
 	common::{
+		journal::JournaledPool,
 		sliding_stat::DurationSlidingStats,
 		tracing_log_xt::{log_xt_debug, log_xt_trace},
 		STAT_SLIDING_WINDOW,
@@ -42,7 +43,9 @@ use crate::{
 	graph::{
 		self,
 		base_pool::{TimedTransactionSource, Transaction},
+		ordering::OrderingInfo,
 		BlockHash, ExtrinsicFor, ExtrinsicHash, IsValidator, Options, RawExtrinsicFor,
+		ValidatedTransaction,
 	},
 	insert_and_log_throttled, ReadyIteratorFor, ValidateTransactionPriority, LOG_TARGET,
 	LOG_TARGET_STAT,
@@ -57,15 +60,17 @@ use futures::{
 use parking_lot::Mutex;
 use prometheus_endpoint::Registry as PrometheusRegistry;
 use sc_transaction_pool_api::{
-	error::Error as TxPoolApiError, ChainEvent, ImportNotificationStream,
-	MaintainedTransactionPool, PoolStatus, TransactionFor, TransactionPool, TransactionSource,
-	TransactionStatusStreamFor, TxHash, TxInvalidityReportMap,
+	error::Error as TxPoolApiError,
+	inspect::{PooledTransactionInfo, TransactionInspection, TransactionPoolContent, ViewContent},
+	ChainEvent, ImportNotificationStream, MaintainedTransactionPool, PoolStatus, TransactionFor,
+	TransactionPool, TransactionPoolInspect, TransactionSource, TransactionStatusStreamFor, TxHash,
+	TxInvalidityReportMap,
 };
 use sp_blockchain::{HashAndNumber, TreeRoute};
 use sp_core::traits::SpawnEssentialNamed;
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block as BlockT, NumberFor},
+	traits::{Block as BlockT, NumberFor, SaturatedConversion},
 	transaction_validity::{TransactionTag as Tag, TransactionValidityError, ValidTransaction},
 	Saturating,
 };
@@ -501,6 +506,74 @@ where
 		self.view_store.active_views.read().contains_key(hash)
 	}
 
+	/// Checks if the transaction is banned in any of the active views.
+	fn is_banned(&self, hash: &ExtrinsicHash<ChainApi>) -> bool {
+		self.view_store
+			.active_views
+			.read()
+			.values()
+			.any(|view| view.pool.validated_pool().is_banned(hash))
+	}
+
+	/// Returns the content of the mempool and of all active views.
+	///
+	/// If `only` is given, the content is restricted to this transaction and to the views
+	/// containing it.
+	async fn content_filtered(
+		&self,
+		only: Option<&ExtrinsicHash<ChainApi>>,
+	) -> TransactionPoolContent<Block::Hash, ExtrinsicHash<ChainApi>> {
+		let selected = |hash: &ExtrinsicHash<ChainApi>| only.map_or(true, |only| only == hash);
+		let active_views =
+			self.view_store.active_views.read().values().cloned().collect::<Vec<_>>();
+
+		let mut containing_views = HashMap::<_, Vec<_>>::new();
+		let views = active_views
+			.iter()
+			.filter_map(|view| {
+				let (ready, future) = view.pool.validated_pool().content(selected);
+				if only.is_some() && ready.is_empty() && future.is_empty() {
+					return None
+				}
+				for tx in ready.iter().chain(future.iter()) {
+					containing_views.entry(tx.hash).or_default().push(view.at.hash);
+				}
+				Some(ViewContent {
+					at: view.at.hash,
+					number: view.at.number.saturated_into(),
+					ready,
+					future,
+				})
+			})
+			.collect();
+
+		let transactions = self
+			.mempool
+			.with_transactions(|iter| {
+				iter.filter(|(hash, _)| selected(hash))
+					.map(|(hash, tx)| PooledTransactionInfo {
+						hash: *hash,
+						source: tx.source().source,
+						priority: tx.priority(),
+						bytes: tx.bytes(),
+						watched: tx.is_watched(),
+						banned: active_views
+							.iter()
+							.any(|view| view.pool.validated_pool().is_banned(hash)),
+						views: containing_views.get(hash).cloned().unwrap_or_default(),
+					})
+					.collect()
+			})
+			.await;
+
+		TransactionPoolContent { transactions, views }
+	}
+
+	/// Returns the part of the pool persisted in the transaction pool journal.
+	pub(crate) fn journaled_pool(&self) -> Arc<dyn JournaledPool<ChainApi>> {
+		self.mempool.clone()
+	}
+
 	/// Returns a number of unwatched and watched transactions in internal mempool.
 	///
 	/// Intended for use in unit tests.
@@ -1116,6 +1189,34 @@ where
 	}
 }
 
+#[async_trait]
+impl<ChainApi, Block> TransactionPoolInspect for ForkAwareTxPool<ChainApi, Block>
+where
+	Block: BlockT,
+	ChainApi: 'static + graph::ChainApi<Block = Block>,
+	<Block as BlockT>::Hash: Unpin,
+{
+	type BlockHash = Block::Hash;
+	type Hash = ExtrinsicHash<ChainApi>;
+
+	/// Reports the content of the mempool and of all active views.
+	async fn content(&self) -> TransactionPoolContent<Self::BlockHash, Self::Hash> {
+		self.content_filtered(None).await
+	}
+
+	/// A transaction is banned if it is banned in any of the active views.
+	async fn inspect(
+		&self,
+		hash: &Self::Hash,
+	) -> TransactionInspection<Self::BlockHash, Self::Hash> {
+		let content = self.content_filtered(Some(hash)).await;
+		TransactionInspection {
+			banned: self.is_banned(hash),
+			content: (!content.transactions.is_empty()).then_some(content),
+		}
+	}
+}
+
 impl<ChainApi, Block> sc_transaction_pool_api::LocalTransactionPool
 	for ForkAwareTxPool<ChainApi, Block>
 where
@@ -1833,9 +1934,14 @@ where
 			)
 			.await;
 
-		let Some(priority) = validated_tx.priority() else {
+		let ValidatedTransaction::Valid(ref validated_tx) = validated_tx else {
 			return Err(TxPoolApiError::ImmediatelyDropped)
 		};
+		let context = best_view.pool.validated_pool().ordering_context(&validated_tx.requires);
+		let priority = self
+			.options
+			.ordering
+			.priority(&OrderingInfo::from_transaction(validated_tx, context));
 
 		let insertion_info =
 			self.mempool.try_insert_with_replacement(xt, priority, source, watched).await?;
@@ -1849,24 +1955,35 @@ where
 		watched: bool,
 		xt: ExtrinsicFor<ChainApi>,
 	) -> Result<InsertionInfo<ExtrinsicHash<ChainApi>>, TxPoolApiError> {
-		let at = self
+		let best_view = self
 			.view_store
 			.most_recent_view
 			.read()
 			.as_ref()
 			.ok_or(TxPoolApiError::ImmediatelyDropped)?
-			.at
-			.hash;
+			.clone();
 
-		let ValidTransaction { priority, .. } = self
+		let ValidTransaction { priority, requires, provides, .. } = self
 			.api
-			.validate_transaction_blocking(at, TransactionSource::Local, Arc::from(xt.clone()))
+			.validate_transaction_blocking(
+				best_view.at.hash,
+				TransactionSource::Local,
+				Arc::from(xt.clone()),
+			)
 			.map_err(|_| TxPoolApiError::ImmediatelyDropped)?
 			.map_err(|e| match e {
 				TransactionValidityError::Invalid(i) => TxPoolApiError::InvalidTransaction(i),
 				TransactionValidityError::Unknown(u) => TxPoolApiError::UnknownTransaction(u),
 			})?;
-		let xt_hash = self.hash_of(&xt);
+		let (xt_hash, bytes) = self.api.hash_and_length(&xt);
+		let priority = self.options.ordering.priority(&OrderingInfo {
+			priority,
+			bytes,
+			source,
+			requires: &requires,
+			provides: &provides,
+			pool: best_view.pool.validated_pool().ordering_context(&requires),
+		});
 
 		let insertion_info = self
 			.mempool
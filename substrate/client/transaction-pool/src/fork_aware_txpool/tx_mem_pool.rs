@@ -31,6 +31,7 @@
 //! See <https://github.com/paritytech/polkadot-sdk/issues/8912> for some more information. The implementation of the
 //! bridging is based on passing messages from sync context to tokio thread.
 
+use async_trait::async_trait;
 use futures::{future::join_all, FutureExt};
 use itertools::Itertools;
 use parking_lot::RwLock;
@@ -57,7 +58,7 @@ use std::{
 use tracing::{debug, trace};
 
 use crate::{
-	common::tracing_log_xt::log_xt_trace,
+	common::{journal::JournaledPool, tracing_log_xt::log_xt_trace},
 	graph,
 	graph::{base_pool::TimedTransactionSource, ExtrinsicFor, ExtrinsicHash},
 	ValidateTransactionPriority, LOG_TARGET,
@@ -124,6 +125,25 @@ where
 		Self::new(true, source, tx, bytes)
 	}
 
+	/// Creates a new instance of wrapper for unwatched transaction restored from the journal.
+	///
+	/// The original source and submission time of the transaction are kept.
+	fn new_restored(
+		source: TimedTransactionSource,
+		tx: ExtrinsicFor<ChainApi>,
+		bytes: usize,
+		priority: Option<TransactionPriority>,
+	) -> Self {
+		Self {
+			watched: false,
+			tx,
+			source,
+			validated_at: AtomicU64::new(0),
+			bytes,
+			priority: priority.into(),
+		}
+	}
+
 	/// Creates a new instance of wrapper for a transaction with no priority.
 	fn new(
 		watched: bool,
@@ -179,6 +199,11 @@ where
 	pub(crate) fn priority(&self) -> Option<TransactionPriority> {
 		*self.priority.read()
 	}
+
+	/// Returns the size of the encoded transaction.
+	pub(crate) fn bytes(&self) -> usize {
+		self.bytes
+	}
 }
 
 impl<ChainApi, Block> std::fmt::Debug for TxInMemPool<ChainApi, Block>
@@ -554,6 +579,20 @@ where
 		self.transactions.read().await.with_items(f)
 	}
 
+	/// Returns the given transactions in the order they are persisted in the journal.
+	fn journal_entries(
+		transactions: std::collections::hash_map::Iter<
+			ExtrinsicHash<ChainApi>,
+			Arc<TxInMemPool<ChainApi, Block>>,
+		>,
+	) -> Vec<(TimedTransactionSource, ExtrinsicFor<ChainApi>)> {
+		transactions
+			.map(|(_, tx)| tx)
+			.sorted_by_key(|tx| (std::cmp::Reverse(tx.priority()), tx.source.timestamp))
+			.map(|tx| (tx.source(), tx.tx()))
+			.collect()
+	}
+
 	/// Removes transactions with given hashes from the memory pool.
 	pub(super) async fn remove_transactions(&self, tx_hashes: &[ExtrinsicHash<ChainApi>]) {
 		log_xt_trace!(target: LOG_TARGET, tx_hashes, "mempool::remove_transaction");
@@ -746,6 +785,64 @@ where
 	}
 }
 
+#[async_trait]
+impl<ChainApi, Block> JournaledPool<ChainApi> for TxMemPool<ChainApi, Block>
+where
+	Block: BlockT,
+	ChainApi: graph::ChainApi<Block = Block> + 'static,
+	<Block as BlockT>::Hash: Unpin,
+{
+	/// Transactions are ordered by priority (unknown priority last), and then by submission time.
+	async fn journal_snapshot(&self) -> Vec<(TimedTransactionSource, ExtrinsicFor<ChainApi>)> {
+		self.with_transactions(Self::journal_entries).await
+	}
+
+	fn try_journal_snapshot(
+		&self,
+	) -> Option<Vec<(TimedTransactionSource, ExtrinsicFor<ChainApi>)>> {
+		Some(self.transactions.try_read()?.with_items(Self::journal_entries))
+	}
+
+	/// Restored transactions are validated at the best block, and the valid ones are inserted
+	/// into the mempool as unwatched transactions, keeping their original source and submission
+	/// time. They are submitted to the views created during the next maintenance.
+	async fn journal_restore(
+		&self,
+		best: HashAndNumber<Block>,
+		transactions: Vec<(TimedTransactionSource, ExtrinsicFor<ChainApi>)>,
+	) {
+		let count = transactions.len();
+		let insert_futures = transactions.into_iter().map(|(source, xt)| async move {
+			let validity = self
+				.api
+				.validate_transaction(
+					best.hash,
+					source.clone().into(),
+					xt.clone(),
+					ValidateTransactionPriority::Submitted,
+				)
+				.await;
+			let priority = match validity {
+				Ok(Ok(valid)) => Some(valid.priority),
+				Ok(Err(TransactionValidityError::Invalid(InvalidTransaction::Future))) => None,
+				_ => return false,
+			};
+			let (hash, length) = self.api.hash_and_length(&xt);
+			self.try_insert(hash, TxInMemPool::new_restored(source, xt, length, priority))
+				.await
+				.is_ok()
+		});
+		let restored = join_all(insert_futures).await.into_iter().filter(|r| *r).count();
+		debug!(
+			target: LOG_TARGET,
+			restored,
+			dropped = count - restored,
+			best = ?best.hash,
+			"mempool::journal_restore"
+		);
+	}
+}
+
 /// Convenient return type of extend_unwatched
 type ExtendUnwatchedResult<ChainApi> =
 	Vec<Result<InsertionInfo<ExtrinsicHash<ChainApi>>, sc_transaction_pool_api::error::Error>>;
@@ -313,6 +313,11 @@ where
 		SizeTrackedStoreReadAccess { inner_guard: self.index.read().await }
 	}
 
+	/// Lock map for read, without waiting. Returns `None` if the map is locked for write.
+	pub fn try_read(&self) -> Option<SizeTrackedStoreReadAccess<K, S, V>> {
+		Some(SizeTrackedStoreReadAccess { inner_guard: self.index.try_read().ok()? })
+	}
+
 	/// Lock map for write.
 	pub async fn write(&self) -> SizeTrackedStoreWriteAccess<K, S, V> {
 		SizeTrackedStoreWriteAccess {
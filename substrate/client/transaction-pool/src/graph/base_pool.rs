@@ -23,7 +23,9 @@
 use std::{cmp::Ordering, collections::HashSet, fmt, hash, sync::Arc, time::Instant};
 
 use crate::LOG_TARGET;
-use sc_transaction_pool_api::{error, InPoolTransaction, PoolStatus};
+use sc_transaction_pool_api::{
+	error, inspect::ValidatedTransactionInfo, InPoolTransaction, PoolStatus,
+};
 use serde::Serialize;
 use sp_core::hexdisplay::HexDisplay;
 use sp_runtime::{
@@ -37,6 +39,7 @@ use tracing::{trace, warn};
 
 use super::{
 	future::{FutureTransactions, WaitingTransaction},
+	ordering::PoolContext,
 	ready::{BestIterator, ReadyTransactions, TransactionRef},
 };
 
@@ -202,6 +205,19 @@ impl<Hash: Clone, Extrinsic: Clone> Transaction<Hash, Extrinsic> {
 			propagate: self.propagate,
 		}
 	}
+
+	/// Returns the details of the transaction reported by the pool inspection.
+	pub fn inspect(&self) -> ValidatedTransactionInfo<Hash> {
+		ValidatedTransactionInfo {
+			hash: self.hash.clone(),
+			priority: self.priority,
+			valid_till: self.valid_till,
+			requires: self.requires.iter().cloned().map(Into::into).collect(),
+			provides: self.provides.iter().cloned().map(Into::into).collect(),
+			propagate: self.propagate,
+			bytes: self.bytes,
+		}
+	}
 }
 
 impl<Hash, Extrinsic> fmt::Debug for Transaction<Hash, Extrinsic>
@@ -612,6 +628,32 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			future_bytes: self.future.bytes(),
 		}
 	}
+
+	/// Returns the context of the pool for a transaction requiring given tags.
+	pub fn ordering_context(&self, requires: &[Tag]) -> PoolContext {
+		let provided_tags = self.ready.provided_tags();
+		let mut ancestors = HashSet::new();
+		let mut to_visit = requires
+			.iter()
+			.filter_map(|tag| provided_tags.get(tag))
+			.cloned()
+			.collect::<Vec<_>>();
+		while let Some(hash) = to_visit.pop() {
+			if !ancestors.insert(hash.clone()) {
+				continue
+			}
+			if let Some(tx) = self.ready.by_hash(&hash) {
+				to_visit
+					.extend(tx.requires.iter().filter_map(|tag| provided_tags.get(tag)).cloned());
+			}
+		}
+
+		PoolContext {
+			ready_ancestors: ancestors.len(),
+			ready: self.ready.len(),
+			future: self.future.len(),
+		}
+	}
 }
 
 /// Queue limits
@@ -710,6 +752,30 @@ mod tests {
 		assert_eq!(result.promoted.len(), 0);
 	}
 
+	#[test]
+	fn ordering_context_counts_ready_ancestors() {
+		// given
+		let mut pool = pool();
+		let tx = |hash: Hash, nonce: u8| Transaction {
+			hash,
+			requires: if nonce == 0 { vec![] } else { vec![vec![nonce - 1]] },
+			provides: vec![vec![nonce]],
+			..default_tx()
+		};
+		pool.import(tx(1, 0)).unwrap();
+		pool.import(tx(2, 1)).unwrap();
+		pool.import(Transaction { requires: vec![vec![9]], hash: 3, ..default_tx() })
+			.unwrap();
+
+		// then
+		assert_eq!(
+			pool.ordering_context(&[vec![1]]),
+			PoolContext { ready_ancestors: 2, ready: 2, future: 1 }
+		);
+		assert_eq!(pool.ordering_context(&[vec![0]]).ready_ancestors, 1);
+		assert_eq!(pool.ordering_context(&[vec![9]]).ready_ancestors, 0);
+	}
+
 	#[test]
 	fn should_import_transaction_to_ready() {
 		// given
@@ -35,6 +35,7 @@ pub(crate) mod tracked_map;
 mod validated_pool;
 
 pub mod base_pool;
+pub mod ordering;
 pub mod watcher;
 
 pub use self::pool::{
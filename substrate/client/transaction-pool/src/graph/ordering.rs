@@ -0,0 +1,219 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Node-side ordering policies for the transaction pool.
+//!
+//! By default transactions are ordered by the priority provided by the runtime. A
+//! [`TransactionOrderingPolicy`] allows the node to compute its own effective priority for every
+//! validated transaction. The effective priority is used for ordering the ready queue, for
+//! deciding whether a transaction can replace another one providing the same tags, and for
+//! selecting the transactions evicted from the fork-aware pool's mempool when it is full.
+
+use super::base_pool::Transaction;
+use sc_transaction_pool_api::TransactionSource;
+use sp_runtime::transaction_validity::{TransactionPriority, TransactionTag as Tag};
+use std::{fmt::Debug, sync::Arc};
+
+/// Information about a validated transaction given to a [`TransactionOrderingPolicy`].
+#[derive(Debug, Clone, Copy)]
+pub struct OrderingInfo<'a> {
+	/// Priority provided by the runtime.
+	pub priority: TransactionPriority,
+	/// Size of the encoded transaction.
+	pub bytes: usize,
+	/// Source of the transaction.
+	pub source: TransactionSource,
+	/// Tags required by the transaction.
+	///
+	/// Together with `provides`, these usually identify the sender and the nonce of the
+	/// transaction, which allows implementing per-sender policies.
+	pub requires: &'a [Tag],
+	/// Tags provided by the transaction.
+	pub provides: &'a [Tag],
+	/// Context of the pool the transaction is imported into.
+	pub pool: PoolContext,
+}
+
+impl<'a> OrderingInfo<'a> {
+	/// Creates the ordering information for given in-pool transaction.
+	pub(crate) fn from_transaction<Hash, Ex>(
+		tx: &'a Transaction<Hash, Ex>,
+		pool: PoolContext,
+	) -> Self {
+		Self {
+			priority: tx.priority,
+			bytes: tx.bytes,
+			source: tx.source.source,
+			requires: &tx.requires,
+			provides: &tx.provides,
+			pool,
+		}
+	}
+}
+
+/// Context of the pool given to a [`TransactionOrderingPolicy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolContext {
+	/// Number of ready transactions the transaction depends on, directly or through other ready
+	/// transactions.
+	///
+	/// For nonce-based transactions these are the pending transactions of the same sender, which
+	/// allows implementing per-sender fairness.
+	pub ready_ancestors: usize,
+	/// Number of ready transactions in the pool.
+	pub ready: usize,
+	/// Number of future transactions in the pool.
+	pub future: usize,
+}
+
+/// Policy computing the effective priority of transactions in the pool.
+///
+/// The policy is applied once, when a validated transaction is imported into the pool, and is
+/// given the [`PoolContext`] at that time. It is consulted from the transaction pool internals,
+/// so it should be cheap.
+pub trait TransactionOrderingPolicy: Debug + Send + Sync {
+	/// Returns the effective priority of the transaction.
+	fn priority(&self, tx: &OrderingInfo) -> TransactionPriority;
+}
+
+/// Shared instance of a [`TransactionOrderingPolicy`].
+pub type OrderingPolicy = Arc<dyn TransactionOrderingPolicy>;
+
+/// Orders transactions by the priority provided by the runtime.
+///
+/// This is the default policy.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuntimePriority;
+
+impl TransactionOrderingPolicy for RuntimePriority {
+	fn priority(&self, tx: &OrderingInfo) -> TransactionPriority {
+		tx.priority
+	}
+}
+
+/// Orders transactions by the runtime priority per byte of the encoded transaction.
+///
+/// The runtime priority usually grows with the fee paid by the transaction, so this policy
+/// prefers small transactions paying the same fee.
+///
+/// The runtime priority is multiplied by [`PRIORITY_PER_BYTE_SCALE`] before being divided by the
+/// size, saturating at the maximum priority.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PriorityPerByte;
+
+/// Scale applied to the runtime priority by [`PriorityPerByte`].
+pub const PRIORITY_PER_BYTE_SCALE: TransactionPriority = 1_000_000;
+
+impl TransactionOrderingPolicy for PriorityPerByte {
+	fn priority(&self, tx: &OrderingInfo) -> TransactionPriority {
+		// Scale before dividing, so that small priorities are not all rounded down to zero.
+		let priority = u128::from(tx.priority) * u128::from(PRIORITY_PER_BYTE_SCALE);
+		let per_byte = priority / tx.bytes.max(1) as u128;
+		per_byte.try_into().unwrap_or(TransactionPriority::MAX)
+	}
+}
+
+/// Orders local transactions before all other transactions.
+///
+/// Transactions with [`TransactionSource::Local`] are ordered by the `inner` policy in the upper
+/// half of the priority range, all other transactions in the lower half. Note that transactions
+/// submitted via RPC are [`TransactionSource::External`], local transactions are the ones
+/// submitted by the node itself, e.g. by offchain workers. Local
+/// transactions thus can never be replaced or evicted to make room for external ones, which
+/// effectively reserves the pool capacity they use.
+#[derive(Debug, Clone)]
+pub struct LocalFirst {
+	inner: OrderingPolicy,
+}
+
+impl LocalFirst {
+	/// Creates a new policy ordering local transactions first, and by `inner` otherwise.
+	pub fn new(inner: OrderingPolicy) -> Self {
+		Self { inner }
+	}
+}
+
+impl TransactionOrderingPolicy for LocalFirst {
+	fn priority(&self, tx: &OrderingInfo) -> TransactionPriority {
+		const HALF: TransactionPriority = TransactionPriority::MAX / 2 + 1;
+		let priority = self.inner.priority(tx) / 2;
+		match tx.source {
+			TransactionSource::Local => HALF + priority,
+			TransactionSource::InBlock | TransactionSource::External => priority,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn info(
+		priority: TransactionPriority,
+		bytes: usize,
+		source: TransactionSource,
+	) -> OrderingInfo<'static> {
+		OrderingInfo {
+			priority,
+			bytes,
+			source,
+			requires: &[],
+			provides: &[],
+			pool: Default::default(),
+		}
+	}
+
+	#[test]
+	fn priority_per_byte_prefers_smaller_transactions() {
+		let policy = PriorityPerByte;
+		let scale = PRIORITY_PER_BYTE_SCALE;
+		assert_eq!(policy.priority(&info(1000, 10, TransactionSource::External)), 100 * scale);
+		assert_eq!(policy.priority(&info(1000, 100, TransactionSource::External)), 10 * scale);
+		assert_eq!(policy.priority(&info(1000, 0, TransactionSource::External)), 1000 * scale);
+	}
+
+	#[test]
+	fn priority_per_byte_keeps_small_priorities_apart() {
+		let policy = PriorityPerByte;
+		let low = policy.priority(&info(1, 200, TransactionSource::External));
+		let high = policy.priority(&info(2, 200, TransactionSource::External));
+		assert!(low > 0);
+		assert!(low < high);
+		assert_eq!(
+			policy.priority(&info(TransactionPriority::MAX, 1, TransactionSource::External)),
+			TransactionPriority::MAX
+		);
+	}
+
+	#[test]
+	fn local_first_orders_local_transactions_first() {
+		let policy = LocalFirst::new(Arc::new(RuntimePriority));
+		let local = policy.priority(&info(0, 10, TransactionSource::Local));
+		let external =
+			policy.priority(&info(TransactionPriority::MAX, 10, TransactionSource::External));
+		assert!(local > external);
+
+		let low = policy.priority(&info(10, 10, TransactionSource::Local));
+		let high = policy.priority(&info(20, 10, TransactionSource::Local));
+		assert!(low < high);
+		assert_eq!(
+			policy.priority(&info(TransactionPriority::MAX, 10, TransactionSource::Local)),
+			TransactionPriority::MAX
+		);
+	}
+}
@@ -38,6 +38,7 @@ use tracing::{debug, instrument, trace, Level};
 
 use super::{
 	base_pool as base,
+	ordering::{OrderingPolicy, RuntimePriority},
 	validated_pool::{IsValidator, ValidatedPool, ValidatedTransaction},
 	EventHandler, ValidatedPoolSubmitOutcome,
 };
@@ -158,6 +159,8 @@ pub struct Options {
 	pub reject_future_transactions: bool,
 	/// How long the extrinsic is banned for.
 	pub ban_time: Duration,
+	/// Policy computing the effective priority of transactions.
+	pub ordering: OrderingPolicy,
 }
 
 impl Default for Options {
@@ -167,6 +170,7 @@ impl Default for Options {
 			future: base::Limit { count: 512, total_bytes: 1 * 1024 * 1024 },
 			reject_future_transactions: false,
 			ban_time: Duration::from_secs(60 * 30),
+			ordering: Arc::new(RuntimePriority),
 		}
 	}
 }
@@ -600,14 +604,17 @@ impl<B: ChainApi, L: EventHandler<B>> Pool<B, L> {
 #[cfg(test)]
 mod tests {
 	use super::{super::base_pool::Limit, *};
-	use crate::common::tests::{pool, uxt, TestApi, INVALID_NONCE};
+	use crate::{
+		common::tests::{pool, uxt, TestApi, INVALID_NONCE},
+		graph::ordering::LocalFirst,
+	};
 	use assert_matches::assert_matches;
 	use base::TimedTransactionSource;
 	use codec::Encode;
 	use futures::executor::block_on;
 	use parking_lot::Mutex;
 	use sc_transaction_pool_api::TransactionStatus;
-	use sp_runtime::transaction_validity::TransactionSource;
+	use sp_runtime::transaction_validity::{TransactionPriority, TransactionSource};
 	use std::{collections::HashMap, time::Instant};
 	use substrate_test_runtime::{AccountId, ExtrinsicBuilder, Transfer, H256};
 	use substrate_test_runtime_client::Sr25519Keyring::{Alice, Bob};
@@ -887,6 +894,51 @@ mod tests {
 		assert!(pool.validated_pool.is_banned(&hash1));
 	}
 
+	#[test]
+	fn should_apply_ordering_policy() {
+		sp_tracing::try_init_simple();
+
+		// given
+		let options = Options {
+			ordering: Arc::new(LocalFirst::new(Arc::new(RuntimePriority))),
+			..Default::default()
+		};
+		let api = Arc::new(TestApi::default());
+		let pool = Pool::new_with_staticly_sized_rotator(options, true.into(), api.clone());
+		let xt = |from| {
+			uxt(Transfer {
+				from,
+				to: AccountId::from_h256(H256::from_low_u64_be(2)),
+				amount: 5,
+				nonce: 0,
+			})
+		};
+
+		let hash1 = block_on(pool.submit_one(
+			&api.expect_hash_and_number(0),
+			SOURCE,
+			xt(Alice.into()).into(),
+		))
+		.unwrap()
+		.hash();
+
+		// when
+		let local = TimedTransactionSource::new_local(false);
+		let hash2 =
+			block_on(pool.submit_one(&api.expect_hash_and_number(0), local, xt(Bob.into()).into()))
+				.unwrap()
+				.hash();
+
+		// then
+		// both transactions provide the same tag and have the same runtime priority, but the local
+		// one is prioritized by the policy and replaces the external one.
+		let ready = pool.validated_pool().ready().collect::<Vec<_>>();
+		assert_eq!(ready.len(), 1);
+		assert_eq!(ready[0].hash, hash2);
+		assert!(ready[0].priority > TransactionPriority::MAX / 2);
+		assert!(pool.validated_pool().check_is_known(&hash1, true).is_ok());
+	}
+
 	#[test]
 	fn should_limit_futures() {
 		sp_tracing::try_init_simple();
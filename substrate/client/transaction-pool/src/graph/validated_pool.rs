@@ -25,7 +25,9 @@ use crate::{
 use futures::channel::mpsc::{channel, Sender};
 use indexmap::IndexMap;
 use parking_lot::{Mutex, RwLock};
-use sc_transaction_pool_api::{error, PoolStatus, ReadyTransactions, TransactionPriority};
+use sc_transaction_pool_api::{
+	error, inspect::ValidatedTransactionInfo, PoolStatus, ReadyTransactions, TransactionPriority,
+};
 use sp_blockchain::HashAndNumber;
 use sp_runtime::{
 	traits::SaturatedConversion,
@@ -41,6 +43,7 @@ use tracing::{debug, trace, warn, Level};
 use super::{
 	base_pool::{self as base, PruneStatus},
 	listener::EventHandler,
+	ordering::{OrderingInfo, PoolContext},
 	pool::{
 		BlockHash, ChainApi, EventStream, ExtrinsicFor, ExtrinsicHash, Options, TransactionFor,
 	},
@@ -325,14 +328,30 @@ impl<B: ChainApi, L: EventHandler<B>> ValidatedPool<B, L> {
 			.collect()
 	}
 
+	/// Replaces the runtime-provided priority of the transaction with the effective priority
+	/// computed by the configured ordering policy.
+	fn apply_ordering_policy(
+		&self,
+		pool: &base::BasePool<ExtrinsicHash<B>, ExtrinsicFor<B>>,
+		tx: &mut base::Transaction<ExtrinsicHash<B>, ExtrinsicFor<B>>,
+	) {
+		let context = pool.ordering_context(&tx.requires);
+		tx.priority = self.options.ordering.priority(&OrderingInfo::from_transaction(tx, context));
+	}
+
+	/// Returns the context of the pool for a transaction requiring given tags, as given to the
+	/// configured ordering policy.
+	pub(crate) fn ordering_context(&self, requires: &[Tag]) -> PoolContext {
+		self.pool.read().ordering_context(requires)
+	}
+
 	/// Submit single pre-validated transaction to the pool.
 	fn submit_one(
 		&self,
 		tx: ValidatedTransactionFor<B>,
 	) -> Result<ValidatedPoolSubmitOutcome<B>, B::Error> {
 		match tx {
-			ValidatedTransaction::Valid(tx) => {
-				let priority = tx.priority;
+			ValidatedTransaction::Valid(mut tx) => {
 				trace!(
 					target: LOG_TARGET,
 					tx_hash = ?tx.hash,
@@ -342,7 +361,11 @@ impl<B: ChainApi, L: EventHandler<B>> ValidatedPool<B, L> {
 					return Err(error::Error::Unactionable.into())
 				}
 
-				let imported = self.pool.write().import(tx)?;
+				let mut pool = self.pool.write();
+				self.apply_ordering_policy(&pool, &mut tx);
+				let priority = tx.priority;
+				let imported = pool.import(tx)?;
+				drop(pool);
 
 				if let base::Imported::Ready { ref hash, .. } = imported {
 					let sinks = &mut self.import_notification_sinks.lock();
@@ -543,37 +566,40 @@ impl<B: ChainApi, L: EventHandler<B>> ValidatedPool<B, L> {
 				let mut final_statuses = HashMap::new();
 				for (tx_hash, tx_to_resubmit) in txs_to_resubmit {
 					match tx_to_resubmit {
-						ValidatedTransaction::Valid(tx) => match pool.import(tx) {
-							Ok(imported) => match imported {
-								base::Imported::Ready { promoted, failed, removed, .. } => {
-									final_statuses.insert(tx_hash, Status::Ready);
-									for hash in promoted {
-										final_statuses.insert(hash, Status::Ready);
-									}
-									for hash in failed {
-										final_statuses.insert(hash, Status::Failed);
-									}
-									for tx in removed {
-										final_statuses.insert(tx.hash, Status::Dropped);
-									}
+						ValidatedTransaction::Valid(mut tx) => {
+							self.apply_ordering_policy(pool, &mut tx);
+							match pool.import(tx) {
+								Ok(imported) => match imported {
+									base::Imported::Ready { promoted, failed, removed, .. } => {
+										final_statuses.insert(tx_hash, Status::Ready);
+										for hash in promoted {
+											final_statuses.insert(hash, Status::Ready);
+										}
+										for hash in failed {
+											final_statuses.insert(hash, Status::Failed);
+										}
+										for tx in removed {
+											final_statuses.insert(tx.hash, Status::Dropped);
+										}
+									},
+									base::Imported::Future { .. } => {
+										final_statuses.insert(tx_hash, Status::Future);
+									},
 								},
-								base::Imported::Future { .. } => {
-									final_statuses.insert(tx_hash, Status::Future);
+								Err(error) => {
+									// we do not want to fail if single transaction import has
+									// failed nor we do want to propagate this error,
+									// because it could tx unknown to caller => let's just
+									// notify listeners (and issue debug message)
+									warn!(
+										target: LOG_TARGET,
+										?tx_hash,
+										%error,
+										"Removing invalid transaction from update"
+									);
+									final_statuses.insert(tx_hash, Status::Failed);
 								},
-							},
-							Err(error) => {
-								// we do not want to fail if single transaction import has failed
-								// nor we do want to propagate this error, because it could tx
-								// unknown to caller => let's just notify listeners (and issue debug
-								// message)
-								warn!(
-									target: LOG_TARGET,
-									?tx_hash,
-									%error,
-									"Removing invalid transaction from update"
-								);
-								final_statuses.insert(tx_hash, Status::Failed);
-							},
+							}
 						},
 						ValidatedTransaction::Invalid(_, _) |
 						ValidatedTransaction::Unknown(_, _) => {
@@ -802,6 +828,36 @@ impl<B: ChainApi, L: EventHandler<B>> ValidatedPool<B, L> {
 		self.pool.read().futures().map(|tx| (tx.hash, tx.data.clone())).collect()
 	}
 
+	/// Returns the sources and extrinsics of all transactions in the pool.
+	///
+	/// Ready transactions come first, ordered by priority, followed by future transactions.
+	pub fn transactions_with_sources(
+		&self,
+	) -> Vec<(base::TimedTransactionSource, ExtrinsicFor<B>)> {
+		let pool = self.pool.read();
+		pool.ready()
+			.map(|tx| (tx.source.clone(), tx.data.clone()))
+			.chain(pool.futures().map(|tx| (tx.source.clone(), tx.data.clone())))
+			.collect()
+	}
+
+	/// Returns the details of ready and future transactions in the pool selected by `filter`.
+	///
+	/// Ready transactions are ordered by priority.
+	pub fn content(
+		&self,
+		filter: impl Fn(&ExtrinsicHash<B>) -> bool,
+	) -> (
+		Vec<ValidatedTransactionInfo<ExtrinsicHash<B>>>,
+		Vec<ValidatedTransactionInfo<ExtrinsicHash<B>>>,
+	) {
+		let pool = self.pool.read();
+		(
+			pool.ready().filter(|tx| filter(&tx.hash)).map(|tx| tx.inspect()).collect(),
+			pool.futures().filter(|tx| filter(&tx.hash)).map(|tx| tx.inspect()).collect(),
+		)
+	}
+
 	/// Returns pool status.
 	pub fn status(&self) -> PoolStatus {
 		self.pool.read().status()
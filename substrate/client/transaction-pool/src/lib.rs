@@ -34,10 +34,14 @@ use std::sync::Arc;
 
 pub use api::FullChainApi;
 pub use builder::{Builder, TransactionPoolHandle, TransactionPoolOptions, TransactionPoolType};
-pub use common::notification_future;
+pub use common::{journal::JournalOptions, notification_future};
 pub use fork_aware_txpool::{ForkAwareTxPool, ForkAwareTxPoolTask};
 pub use graph::{
 	base_pool::{Limit as PoolLimit, TimedTransactionSource},
+	ordering::{
+		LocalFirst, OrderingInfo, OrderingPolicy, PoolContext, PriorityPerByte, RuntimePriority,
+		TransactionOrderingPolicy, PRIORITY_PER_BYTE_SCALE,
+	},
 	ChainApi, Options, Pool, ValidateTransactionPriority,
 };
 use single_state_txpool::prune_known_txs_for_block;
@@ -27,11 +27,12 @@ use crate::{
 	common::{
 		enactment_state::{EnactmentAction, EnactmentState},
 		error,
+		journal::JournaledPool,
 		tracing_log_xt::log_xt_trace,
 	},
 	graph::{
-		self, base_pool::TimedTransactionSource, EventHandler, ExtrinsicHash, IsValidator,
-		RawExtrinsicFor,
+		self, base_pool::TimedTransactionSource, EventHandler, ExtrinsicFor, ExtrinsicHash,
+		IsValidator, RawExtrinsicFor,
 	},
 	ReadyIteratorFor, ValidateTransactionPriority, LOG_TARGET,
 };
@@ -40,9 +41,11 @@ use futures::{channel::oneshot, future, prelude::*, Future, FutureExt};
 use parking_lot::Mutex;
 use prometheus_endpoint::Registry as PrometheusRegistry;
 use sc_transaction_pool_api::{
-	error::Error as TxPoolError, ChainEvent, ImportNotificationStream, MaintainedTransactionPool,
-	PoolStatus, TransactionFor, TransactionPool, TransactionSource, TransactionStatusStreamFor,
-	TxHash, TxInvalidityReportMap,
+	error::Error as TxPoolError,
+	inspect::{PooledTransactionInfo, TransactionInspection, TransactionPoolContent, ViewContent},
+	ChainEvent, ImportNotificationStream, MaintainedTransactionPool, PoolStatus, TransactionFor,
+	TransactionPool, TransactionPoolInspect, TransactionSource, TransactionStatusStreamFor, TxHash,
+	TxInvalidityReportMap,
 };
 use sp_blockchain::{HashAndNumber, TreeRoute};
 use sp_core::traits::SpawnEssentialNamed;
@@ -60,7 +63,7 @@ use std::{
 	time::Instant,
 };
 use tokio::select;
-use tracing::{trace, warn};
+use tracing::{debug, trace, warn};
 
 /// Basic implementation of transaction pool that can be customized by providing PoolApi.
 pub struct BasicPool<PoolApi, Block>
@@ -237,6 +240,60 @@ where
 		&self.pool
 	}
 
+	/// Returns the content of the pool as a single view at the most recent best block.
+	///
+	/// If `only` is given, the content is restricted to this transaction, and the view is omitted
+	/// if it doesn't contain it.
+	fn content_filtered(
+		&self,
+		only: Option<&graph::ExtrinsicHash<PoolApi>>,
+	) -> TransactionPoolContent<Block::Hash, graph::ExtrinsicHash<PoolApi>> {
+		let selected =
+			|hash: &graph::ExtrinsicHash<PoolApi>| only.map_or(true, |only| only == hash);
+		let at = self.enactment_state.lock().recent_best_block();
+		let number = self
+			.api
+			.resolve_block_number(at)
+			.map(SaturatedConversion::saturated_into)
+			.unwrap_or_default();
+		let validated_pool = self.pool.validated_pool();
+		let watched = validated_pool.watched_transactions().into_iter().collect::<HashSet<_>>();
+
+		let pool = validated_pool.pool.read();
+		let ready = pool.ready().filter(|tx| selected(&tx.hash)).collect::<Vec<_>>();
+		let future = pool.futures().filter(|tx| selected(&tx.hash)).collect::<Vec<_>>();
+		let transactions = ready
+			.iter()
+			.map(|tx| &**tx)
+			.chain(future.iter().copied())
+			.map(|tx| PooledTransactionInfo {
+				hash: tx.hash,
+				source: tx.source.source,
+				priority: Some(tx.priority),
+				bytes: tx.bytes,
+				watched: watched.contains(&tx.hash),
+				banned: validated_pool.is_banned(&tx.hash),
+				views: vec![at],
+			})
+			.collect::<Vec<_>>();
+		if only.is_some() && transactions.is_empty() {
+			return TransactionPoolContent { transactions, views: Vec::new() }
+		}
+		let view = ViewContent {
+			at,
+			number,
+			ready: ready.iter().map(|tx| tx.inspect()).collect(),
+			future: future.iter().map(|tx| tx.inspect()).collect(),
+		};
+
+		TransactionPoolContent { transactions, views: vec![view] }
+	}
+
+	/// Returns the part of the pool persisted in the transaction pool journal.
+	pub(crate) fn journaled_pool(&self) -> Arc<dyn JournaledPool<PoolApi>> {
+		self.pool.clone()
+	}
+
 	/// Get access to the underlying api
 	pub fn api(&self) -> &PoolApi {
 		&self.api
@@ -418,6 +475,64 @@ where
 	}
 }
 
+#[async_trait]
+impl<PoolApi, Block> TransactionPoolInspect for BasicPool<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: 'static + graph::ChainApi<Block = Block>,
+{
+	type BlockHash = Block::Hash;
+	type Hash = graph::ExtrinsicHash<PoolApi>;
+
+	/// Reports the content of the pool as a single view at the most recent best block.
+	async fn content(&self) -> TransactionPoolContent<Self::BlockHash, Self::Hash> {
+		self.content_filtered(None)
+	}
+
+	async fn inspect(
+		&self,
+		hash: &Self::Hash,
+	) -> TransactionInspection<Self::BlockHash, Self::Hash> {
+		let content = self.content_filtered(Some(hash));
+		TransactionInspection {
+			banned: self.pool.validated_pool().is_banned(hash),
+			content: (!content.transactions.is_empty()).then_some(content),
+		}
+	}
+}
+
+#[async_trait]
+impl<PoolApi, Block> JournaledPool<PoolApi> for graph::Pool<PoolApi, ()>
+where
+	Block: BlockT,
+	PoolApi: 'static + graph::ChainApi<Block = Block>,
+{
+	async fn journal_snapshot(&self) -> Vec<(TimedTransactionSource, ExtrinsicFor<PoolApi>)> {
+		self.validated_pool().transactions_with_sources()
+	}
+
+	fn try_journal_snapshot(&self) -> Option<Vec<(TimedTransactionSource, ExtrinsicFor<PoolApi>)>> {
+		Some(self.validated_pool().transactions_with_sources())
+	}
+
+	async fn journal_restore(
+		&self,
+		best: HashAndNumber<Block>,
+		transactions: Vec<(TimedTransactionSource, ExtrinsicFor<PoolApi>)>,
+	) {
+		let results = self
+			.submit_at(&best, transactions, ValidateTransactionPriority::Submitted)
+			.await;
+		let restored = results.iter().filter(|r| r.is_ok()).count();
+		debug!(
+			target: LOG_TARGET,
+			restored,
+			dropped = results.len() - restored,
+			"Restored transactions from journal"
+		);
+	}
+}
+
 impl<Block, Client> BasicPool<FullChainApi<Client, Block>, Block>
 where
 	Block: BlockT,
@@ -26,9 +26,11 @@ use crate::{
 };
 use async_trait::async_trait;
 use sc_transaction_pool_api::{
+	inspect::{TransactionInspection, TransactionPoolContent},
 	ChainEvent, ImportNotificationStream, LocalTransactionFor, LocalTransactionPool,
 	MaintainedTransactionPool, PoolStatus, ReadyTransactions, TransactionFor, TransactionPool,
-	TransactionSource, TransactionStatusStreamFor, TxHash, TxInvalidityReportMap,
+	TransactionPoolInspect, TransactionSource, TransactionStatusStreamFor, TxHash,
+	TxInvalidityReportMap,
 };
 use sp_runtime::traits::Block as BlockT;
 use std::{collections::HashMap, pin::Pin, sync::Arc};
@@ -165,6 +167,33 @@ where
 	}
 }
 
+#[async_trait]
+impl<Block, Client> TransactionPoolInspect for TransactionPoolWrapper<Block, Client>
+where
+	Block: BlockT,
+	Client: sp_api::ProvideRuntimeApi<Block>
+		+ sc_client_api::BlockBackend<Block>
+		+ sc_client_api::blockchain::HeaderBackend<Block>
+		+ sp_runtime::traits::BlockIdTo<Block>
+		+ sp_blockchain::HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ 'static,
+	Client::Api: sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>,
+{
+	type BlockHash = Block::Hash;
+	type Hash = ExtrinsicHash<FullChainApi<Client, Block>>;
+
+	async fn content(&self) -> TransactionPoolContent<Self::BlockHash, Self::Hash> {
+		self.0.content().await
+	}
+
+	async fn inspect(
+		&self,
+		hash: &Self::Hash,
+	) -> TransactionInspection<Self::BlockHash, Self::Hash> {
+		self.0.inspect(hash).await
+	}
+}
+
 impl<Block, Client> LocalTransactionPool for TransactionPoolWrapper<Block, Client>
 where
 	Block: BlockT,
@@ -74,6 +74,21 @@ fn runtime_task_enumerate_works_via_pallet_config() {
 	});
 }
 
+#[test]
+fn valid_tasks_are_listed_with_their_weight() {
+	new_test_ext().execute_with(|| {
+		Numbers::<Runtime>::insert(0, 1);
+		Numbers::<Runtime>::insert(1, 4);
+		let tasks = frame_system::Pallet::<Runtime>::valid_tasks(16);
+		assert_eq!(tasks.len(), 2);
+		for (task, weight) in tasks {
+			assert!(task.is_valid());
+			assert_eq!(weight, task.weight());
+		}
+		assert_eq!(frame_system::Pallet::<Runtime>::valid_tasks(1).len(), 1);
+	});
+}
+
 #[test]
 fn task_index_works_at_pallet_level() {
 	new_test_ext().execute_with(|| {
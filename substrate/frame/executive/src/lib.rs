@@ -455,6 +455,85 @@ where
 		Ok(before_all_weight.saturating_add(try_on_runtime_upgrade_weight))
 	}
 
+	/// Simulate one block of the Multi-Block-Migrations.
+	///
+	/// The block of `header` is initialized, which executes `on_initialize`, and the inherents
+	/// returned by `create_inherents` are applied. The inherents are created after the
+	/// initialization, like in block production. The
+	/// [`frame_system::Config::MultiBlockMigrator`] is then stepped once, as it
+	/// would be after the inherents of a block while the migrations are ongoing, before the block
+	/// is finalized. The weight that the migrator consumed in this block is reported together with
+	/// `max_service_weight`, the weight that it may consume per block.
+	///
+	/// The migrator is responsible for the `pre/post_upgrade` checks of the single migrations.
+	/// Once the migrations completed, the `checks` param determines whether to decode the entire
+	/// state and execute the `try_state` hooks.
+	///
+	/// Should only be used for testing ONLY.
+	pub fn try_step_multi_block_migrations(
+		header: &frame_system::pallet_prelude::HeaderFor<System>,
+		create_inherents: impl FnOnce() -> alloc::vec::Vec<Block::Extrinsic>,
+		max_service_weight: Weight,
+		checks: UpgradeCheckSelect,
+	) -> Result<frame_try_runtime::MultiBlockMigrationsStep, TryRuntimeError> {
+		Self::initialize_block(header);
+
+		for (idx, uxt) in create_inherents().into_iter().enumerate() {
+			if !System::is_inherent(&uxt) {
+				log::error!(target: LOG_TARGET, "try-runtime: extrinsic {idx} is not an inherent");
+				return Err("Only inherents can be applied while stepping the MBMs".into())
+			}
+			match Self::apply_extrinsic(uxt) {
+				Ok(Ok(())) => {},
+				Ok(Err(e)) => {
+					log::error!(target: LOG_TARGET, "try-runtime: inherent {idx} failed: {e:?}");
+					return Err(e)
+				},
+				Err(e) => {
+					log::error!(target: LOG_TARGET, "try-runtime: inherent {idx} is invalid: {e:?}");
+					return Err("Invalid inherent while stepping the MBMs".into())
+				},
+			}
+		}
+
+		let ongoing = <System as frame_system::Config>::MultiBlockMigrator::ongoing();
+		let mandatory_weight =
+			|| *frame_system::Pallet::<System>::block_weight().get(DispatchClass::Mandatory);
+		let before = mandatory_weight();
+		if !frame_system::Pallet::<System>::inherents_applied() {
+			Self::inherents_applied();
+		}
+		let weight =
+			if ongoing { mandatory_weight().saturating_sub(before) } else { Weight::zero() };
+		Self::finalize_block();
+
+		let block_number = *header.number();
+		let ongoing = <System as frame_system::Config>::MultiBlockMigrator::ongoing();
+
+		log::info!(
+			target: LOG_TARGET,
+			"try-runtime: stepped MBMs in block #{:?} / weight: {} / ongoing: {}",
+			block_number,
+			weight,
+			ongoing,
+		);
+
+		if !ongoing {
+			let _guard = StorageNoopGuard::default();
+
+			if checks.any() {
+				let res = AllPalletsWithSystem::try_decode_entire_state();
+				Self::log_decode_result(res)?;
+			}
+
+			if checks.try_state() {
+				AllPalletsWithSystem::try_state(block_number, TryStateSelect::All)?;
+			}
+		}
+
+		Ok(frame_try_runtime::MultiBlockMigrationsStep { weight, max_service_weight, ongoing })
+	}
+
 	/// Logs the result of trying to decode the entire state.
 	fn log_decode_result(
 		res: Result<usize, alloc::vec::Vec<TryDecodeEntireStorageError>>,
@@ -817,7 +896,16 @@ where
 
 		// AUDIT: Under no circumstances may this function panic from here onwards.
 
-		let r = Applyable::apply::<UnsignedValidator>(xt, &dispatch_info, encoded_len)?;
+		// Record the declared weight, to be compared with the time the dispatch takes. The span
+		// is only created when tracing is enabled.
+		let r = sp_tracing::within_span!(
+			sp_tracing::trace_span!(
+				"dispatch_extrinsic",
+				ref_time = dispatch_info.total_weight().ref_time(),
+				proof_size = dispatch_info.total_weight().proof_size()
+			);
+			Applyable::apply::<UnsignedValidator>(xt, &dispatch_info, encoded_len)
+		)?;
 
 		// Mandatory(inherents) are not allowed to fail.
 		//
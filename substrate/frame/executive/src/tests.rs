@@ -556,6 +556,7 @@ impl MockedSystemCallbacks {
 
 parameter_types! {
 	pub static MbmActive: bool = false;
+	pub static MbmStepWeight: Weight = Weight::zero();
 }
 
 pub struct MockedModeGetter;
@@ -565,7 +566,7 @@ impl MultiStepMigrator for MockedModeGetter {
 	}
 
 	fn step() -> Weight {
-		Weight::zero()
+		MbmStepWeight::get()
 	}
 }
 
@@ -1324,6 +1325,62 @@ fn try_execute_tx_forbidden_errors() {
 	});
 }
 
+#[test]
+#[cfg(feature = "try-runtime")]
+fn try_step_multi_block_migrations_works() {
+	let step = |max_service_weight| {
+		MockedSystemCallbacks::reset();
+		let header = Header::new_from_number(System::block_number() + 1);
+		let inherents =
+			|| vec![UncheckedXt::new_bare(RuntimeCall::Custom(custom::Call::inherent {}))];
+
+		let step = Executive::try_step_multi_block_migrations(
+			&header,
+			inherents,
+			max_service_weight,
+			frame_try_runtime::UpgradeCheckSelect::All,
+		);
+		// The whole block was executed:
+		assert!(MockedSystemCallbacks::post_transactions_called());
+		step
+	};
+
+	new_test_ext(1).execute_with(|| {
+		MbmActive::set(true);
+		MbmStepWeight::set(Weight::from_parts(10, 0));
+
+		let report = frame_try_runtime::run_multi_block_migrations(10, || {
+			// The mocked migrations complete in the third block:
+			if System::block_number() == 2 {
+				MbmActive::set(false);
+			}
+			step(Weight::from_parts(10, 0))
+		})
+		.unwrap();
+
+		assert!(report.completed);
+		assert_eq!(report.blocks(), 3);
+		// Only the weight of the migrations is reported, not the one of `on_initialize`:
+		assert_eq!(report.max_weight(), Weight::from_parts(10, 0));
+		assert_eq!(report.total_weight(), Weight::from_parts(20, 0));
+		assert_eq!(report.overweight_blocks(), 0);
+		assert_eq!(System::block_number(), 3);
+	});
+
+	new_test_ext(1).execute_with(|| {
+		MbmActive::set(true);
+		MbmStepWeight::set(Weight::from_parts(10, 0));
+
+		let report =
+			frame_try_runtime::run_multi_block_migrations(5, || step(Weight::from_parts(5, 0)))
+				.unwrap();
+
+		assert!(!report.completed);
+		assert_eq!(report.blocks(), 5);
+		assert_eq!(report.overweight_blocks(), 5);
+	});
+}
+
 /// Test if `apply_extrinsics` validates if the inherents are first.
 #[test]
 fn apply_extrinsics_checks_inherents_are_first() {
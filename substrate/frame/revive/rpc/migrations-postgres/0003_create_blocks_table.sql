@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS blocks (
+	block_number BIGINT NOT NULL PRIMARY KEY,
+	block_hash BYTEA NOT NULL,
+	finalized BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_blocks_block_hash ON blocks (
+	block_hash
+);
+
+-- Range indices used by `eth_getLogs` queries filtering on an address or a topic over a large
+-- range of blocks. As the logs table is partitioned, these are partitioned by block number too.
+CREATE INDEX IF NOT EXISTS idx_logs_address_block_number ON logs (
+	address,
+	block_number
+);
+
+CREATE INDEX IF NOT EXISTS idx_logs_topic_0_block_number ON logs (
+	topic_0,
+	block_number
+);
@@ -0,0 +1,101 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Ethereum pub/sub JSON-RPC methods.
+
+use crate::*;
+use futures::{stream, StreamExt};
+use jsonrpsee::{proc_macros::rpc, PendingSubscriptionSink};
+use sc_rpc::utils::{PendingSubscription, RingBuffer};
+
+/// The number of notifications buffered per subscription before the oldest ones are dropped.
+const SUBSCRIPTION_BUFFER_SIZE: usize = 128;
+
+/// Ethereum pub/sub JSON-RPC apis.
+#[rpc(server, client)]
+pub trait EthSubscriptionRpc {
+	/// Subscribe to new block headers, to logs matching a filter, or to new pending transactions.
+	///
+	/// Logs of blocks retracted by a chain re-organization are sent again with `removed: true`.
+	///
+	/// ## References
+	///
+	/// - <https://geth.ethereum.org/docs/interacting-with-geth/rpc/pubsub>
+	#[subscription(
+		name = "eth_subscribe" => "eth_subscription",
+		unsubscribe = "eth_unsubscribe",
+		item = SubscriptionItem
+	)]
+	fn eth_subscribe(&self, kind: SubscriptionKind, filter: Option<Filter>);
+}
+
+pub struct EthSubscriptionRpcServerImpl {
+	client: client::Client,
+}
+
+impl EthSubscriptionRpcServerImpl {
+	pub fn new(client: client::Client) -> Self {
+		Self { client }
+	}
+}
+
+impl EthSubscriptionRpcServer for EthSubscriptionRpcServerImpl {
+	fn eth_subscribe(
+		&self,
+		pending: PendingSubscriptionSink,
+		kind: SubscriptionKind,
+		filter: Option<Filter>,
+	) {
+		let provider = self.client.subscription_provider();
+		let items = match kind {
+			SubscriptionKind::NewHeads => provider
+				.block_notifications()
+				.filter_map(|notification| async move {
+					if notification.removed {
+						return None;
+					}
+
+					let header =
+						Block { transactions: Default::default(), ..notification.block.clone() };
+					Some(SubscriptionItem::from(header))
+				})
+				.boxed(),
+			SubscriptionKind::Logs => {
+				let filter = filter.unwrap_or_default();
+				provider
+					.block_notifications()
+					.flat_map(move |notification| {
+						let logs = notification
+							.logs
+							.iter()
+							.filter(|log| filter.matches_log(log))
+							.cloned()
+							.map(SubscriptionItem::from)
+							.collect::<Vec<_>>();
+						stream::iter(logs)
+					})
+					.boxed()
+			},
+			SubscriptionKind::NewPendingTransactions =>
+				provider.pending_transactions().map(SubscriptionItem::from).boxed(),
+		};
+
+		tokio::spawn(
+			PendingSubscription::from(pending)
+				.pipe_from_stream(items, RingBuffer::new(SUBSCRIPTION_BUFFER_SIZE)),
+		);
+	}
+}
@@ -149,7 +149,37 @@ impl BlockInfoProvider for SubxtBlockInfoProvider {
 #[cfg(test)]
 pub mod test {
 	use super::*;
-	use crate::BlockInfo;
+	use crate::{BlockInfo, BlockNotification};
+	use pallet_revive::evm::{Block, Log};
+
+	/// Create a best block notification with a single log.
+	///
+	/// The hash is derived from the block number and the fork, so blocks of different forks
+	/// can be linked through `parent_fork`.
+	pub fn block_notification(number: u8, fork: u8, parent_fork: u8) -> BlockNotification {
+		let hash = |n: u8, f: u8| H256::from([n, f].repeat(16).try_into().unwrap());
+		let block = Block {
+			hash: hash(number, fork),
+			parent_hash: hash(number.wrapping_sub(1), parent_fork),
+			number: number.into(),
+			..Default::default()
+		};
+		let logs = vec![Log { block_hash: block.hash, ..Default::default() }];
+		BlockNotification::new(block, logs)
+	}
+
+	/// Connect to a new in-memory SQLite database.
+	pub async fn in_memory_pool() -> sqlx::AnyPool {
+		sqlx::any::install_default_drivers();
+		// see sqlite in-memory issue: https://github.com/launchbadge/sqlx/issues/2510
+		sqlx::any::AnyPoolOptions::new()
+			.max_connections(1)
+			.idle_timeout(None)
+			.max_lifetime(None)
+			.connect("sqlite::memory:")
+			.await
+			.unwrap()
+	}
 
 	/// A Noop BlockInfoProvider used to test [`db::ReceiptProvider`].
 	pub struct MockBlockInfoProvider;
@@ -17,9 +17,9 @@
 //! The Ethereum JSON-RPC server.
 use crate::{
 	client::{connect, Client, SubscriptionType, SubstrateBlockNumber},
-	DebugRpcServer, DebugRpcServerImpl, EthRpcServer, EthRpcServerImpl, ReceiptExtractor,
-	ReceiptProvider, SubxtBlockInfoProvider, SystemHealthRpcServer, SystemHealthRpcServerImpl,
-	LOG_TARGET,
+	DebugRpcServer, DebugRpcServerImpl, EthRpcServer, EthRpcServerImpl, EthSubscriptionRpcServer,
+	EthSubscriptionRpcServerImpl, ReceiptExtractor, ReceiptProvider, SubxtBlockInfoProvider,
+	SystemHealthRpcServer, SystemHealthRpcServerImpl, LOG_TARGET,
 };
 use clap::Parser;
 use futures::{pin_mut, FutureExt};
@@ -29,7 +29,7 @@ use sc_service::{
 	config::{PrometheusConfig, RpcConfiguration},
 	start_rpc_servers, TaskManager,
 };
-use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::any::AnyPoolOptions;
 
 // Default port if --prometheus-port is not specified
 const DEFAULT_PROMETHEUS_PORT: u16 = 9616;
@@ -58,6 +58,7 @@ pub struct CliCommand {
 	/// The database used to store Ethereum transaction hashes.
 	/// This is only useful if the node needs to act as an archive node and respond to Ethereum RPC
 	/// queries for transactions that are not in the in memory cache.
+	/// Both SQLite (`sqlite://`) and PostgreSQL (`postgres://`) URLs are supported.
 	#[clap(long, env = "DATABASE_URL", default_value = IN_MEMORY_DB)]
 	pub database_url: String,
 
@@ -65,6 +66,18 @@ pub struct CliCommand {
 	#[clap(long)]
 	pub index_last_n_blocks: Option<SubstrateBlockNumber>,
 
+	/// Run as an archive indexer: index the receipts of every finalized block starting at
+	/// `--archive-from-block`, and keep them.
+	/// The backfill resumes where it left off after a restart, so this requires a persistent
+	/// `--database-url`.
+	#[clap(long, conflicts_with = "index_last_n_blocks")]
+	pub archive: bool,
+
+	/// The first block to index in archive mode.
+	/// Defaults to `--earliest-receipt-block`, or to the genesis block.
+	#[clap(long, requires = "archive")]
+	pub archive_from_block: Option<SubstrateBlockNumber>,
+
 	#[allow(missing_docs)]
 	#[clap(flatten)]
 	pub shared_params: SharedParams,
@@ -105,16 +118,18 @@ fn build_client(
 	earliest_receipt_block: Option<SubstrateBlockNumber>,
 	node_rpc_url: &str,
 	database_url: &str,
+	archive: bool,
 	abort_signal: Signals,
 ) -> anyhow::Result<Client> {
 	let fut = async {
 		let (api, rpc_client, rpc) = connect(node_rpc_url).await?;
 		let block_provider = SubxtBlockInfoProvider::new( api.clone(), rpc.clone()).await?;
 
+		sqlx::any::install_default_drivers();
 		let (pool, keep_latest_n_blocks) = if database_url == IN_MEMORY_DB {
 			log::warn!( target: LOG_TARGET, "💾 Using in-memory database, keeping only {cache_size} blocks in memory");
 			// see sqlite in-memory issue: https://github.com/launchbadge/sqlx/issues/2510
-			let pool = SqlitePoolOptions::new()
+			let pool = AnyPoolOptions::new()
 					.max_connections(1)
 					.idle_timeout(None)
 					.max_lifetime(None)
@@ -122,7 +137,7 @@ fn build_client(
 
 			(pool, Some(cache_size))
 		} else {
-			(SqlitePoolOptions::new().connect(database_url).await?, None)
+			(AnyPoolOptions::new().connect(database_url).await?, None)
 		};
 
 		let receipt_extractor = ReceiptExtractor::new(
@@ -134,6 +149,7 @@ fn build_client(
 				block_provider.clone(),
 				receipt_extractor.clone(),
 				keep_latest_n_blocks,
+				archive,
 			)
 			.await?;
 
@@ -162,10 +178,16 @@ pub fn run(cmd: CliCommand) -> anyhow::Result<()> {
 		database_url,
 		earliest_receipt_block,
 		index_last_n_blocks,
+		archive,
+		archive_from_block,
 		shared_params,
 		..
 	} = cmd;
 
+	if archive && database_url == IN_MEMORY_DB {
+		anyhow::bail!("--archive requires a persistent --database-url");
+	}
+
 	#[cfg(not(test))]
 	init_logger(&shared_params)?;
 	let is_dev = shared_params.dev;
@@ -204,6 +226,7 @@ pub fn run(cmd: CliCommand) -> anyhow::Result<()> {
 		earliest_receipt_block,
 		&node_rpc_url,
 		&database_url,
+		archive,
 		tokio_runtime.block_on(async { Signals::capture() })?,
 	)?;
 
@@ -224,13 +247,29 @@ pub fn run(cmd: CliCommand) -> anyhow::Result<()> {
 		None,
 	)?;
 
+	let pool_client = client.clone();
+	task_manager
+		.spawn_handle()
+		.spawn("pending-transactions-subscription", None, async move {
+			pool_client.subscribe_pending_transactions().await
+		});
+
+	let filter_provider = client.filter_provider().clone();
+	task_manager.spawn_handle().spawn("filter-sweeper", None, async move {
+		filter_provider.remove_expired_filters().await
+	});
+
 	task_manager
 		.spawn_essential_handle()
 		.spawn("block-subscription", None, async move {
 			let fut1 = client.subscribe_and_cache_new_blocks(SubscriptionType::BestBlocks);
 			let fut2 = client.subscribe_and_cache_new_blocks(SubscriptionType::FinalizedBlocks);
 
-			let res = if let Some(index_last_n_blocks) = index_last_n_blocks {
+			let res = if archive {
+				let from = archive_from_block.or(earliest_receipt_block).unwrap_or_default();
+				let fut3 = client.index_finalized_blocks_from(from);
+				tokio::try_join!(fut1, fut2, fut3).map(|_| ())
+			} else if let Some(index_last_n_blocks) = index_last_n_blocks {
 				let fut3 = client.subscribe_and_cache_blocks(index_last_n_blocks);
 				tokio::try_join!(fut1, fut2, fut3).map(|_| ())
 			} else {
@@ -255,11 +294,15 @@ fn rpc_module(is_dev: bool, client: Client) -> Result<RpcModule<()>, sc_service:
 		.into_rpc();
 
 	let health_api = SystemHealthRpcServerImpl::new(client.clone()).into_rpc();
+	let subscription_api = EthSubscriptionRpcServerImpl::new(client.clone()).into_rpc();
 	let debug_api = DebugRpcServerImpl::new(client).into_rpc();
 
 	let mut module = RpcModule::new(());
 	module.merge(eth_api).map_err(|e| sc_service::Error::Application(e.into()))?;
 	module.merge(health_api).map_err(|e| sc_service::Error::Application(e.into()))?;
+	module
+		.merge(subscription_api)
+		.map_err(|e| sc_service::Error::Application(e.into()))?;
 	module.merge(debug_api).map_err(|e| sc_service::Error::Application(e.into()))?;
 	Ok(module)
 }
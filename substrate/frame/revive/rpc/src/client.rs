@@ -25,9 +25,11 @@ use storage_api::StorageApi;
 
 use crate::{
 	subxt_client::{self, revive::calls::types::EthTransact, SrcChainConfig},
-	BlockInfoProvider, BlockTag, FeeHistoryProvider, ReceiptProvider, SubxtBlockInfoProvider,
-	TracerType, TransactionInfo, LOG_TARGET,
+	BlockInfoProvider, BlockNotification, BlockTag, FeeHistoryProvider, FilterProvider,
+	ReceiptProvider, SubscriptionProvider, SubxtBlockInfoProvider, TracerType, TransactionInfo,
+	LOG_TARGET,
 };
+use futures::StreamExt;
 use jsonrpsee::{
 	core::traits::ToRpcParams,
 	rpc_params,
@@ -35,18 +37,22 @@ use jsonrpsee::{
 };
 use pallet_revive::{
 	evm::{
-		decode_revert_reason, Block, BlockNumberOrTag, BlockNumberOrTagOrHash, FeeHistoryResult,
-		Filter, GenericTransaction, Log, ReceiptInfo, SyncingProgress, SyncingStatus, Trace,
-		TransactionSigned, TransactionTrace, H256, U256,
+		decode_revert_reason, AccountProof, Block, BlockNumberOrTag, BlockNumberOrTagOrHash, Bytes,
+		FeeHistoryResult, Filter, GenericTransaction, Log, ReceiptInfo, StorageProof,
+		SyncingProgress, SyncingStatus, Trace, TransactionSigned, TransactionTrace, H160, H256,
+		U256,
 	},
 	EthTransactError,
 };
 use sp_runtime::traits::Block as BlockT;
 use sp_weights::Weight;
-use std::{ops::Range, sync::Arc, time::Duration};
+use std::{collections::HashMap, ops::Range, sync::Arc, time::Duration};
 use subxt::{
 	backend::{
-		legacy::{rpc_methods::SystemHealth, LegacyRpcMethods},
+		legacy::{
+			rpc_methods::{ReadProof, SystemHealth},
+			LegacyRpcMethods,
+		},
 		rpc::{
 			reconnecting_rpc_client::{ExponentialBackoff, RpcClient as ReconnectingRpcClient},
 			RpcClient,
@@ -124,9 +130,34 @@ pub enum ClientError {
 	/// Failed to filter logs.
 	#[error("Failed to filter logs")]
 	LogFilterFailed(#[from] anyhow::Error),
+	/// The filter was not found, or has expired.
+	#[error("filter not found")]
+	FilterNotFound,
+	/// The maximum number of installed filters was reached.
+	#[error("too many installed filters")]
+	TooManyFilters,
+	/// The runtime of the block doesn't implement the runtime API the method needs.
+	#[error("{0} is not supported by the runtime")]
+	UnsupportedByRuntime(&'static str),
 }
 
 const REVERT_CODE: i32 = 3;
+
+/// How often the node's transaction pool is polled for new pending transactions.
+const PENDING_TRANSACTIONS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The number of blocks the archive indexer checks against the database at once.
+const ARCHIVE_INDEX_BATCH_SIZE: SubstrateBlockNumber = 1_000;
+
+/// The number of blocks the archive indexer fetches and indexes concurrently.
+const ARCHIVE_INDEX_CONCURRENCY: usize = 16;
+
+/// The number of times the archive indexer tries to index a block before skipping it.
+const ARCHIVE_INDEX_MAX_ATTEMPTS: u32 = 3;
+
+/// The delay between two attempts of the archive indexer to index a block.
+const ARCHIVE_INDEX_RETRY_DELAY: Duration = Duration::from_secs(1);
+
 impl From<ClientError> for ErrorObjectOwned {
 	fn from(err: ClientError) -> Self {
 		match err {
@@ -161,6 +192,8 @@ pub struct Client {
 	receipt_provider: ReceiptProvider,
 	block_provider: SubxtBlockInfoProvider,
 	fee_history_provider: FeeHistoryProvider,
+	subscription_provider: SubscriptionProvider,
+	filter_provider: FilterProvider,
 	chain_id: u64,
 	max_block_weight: Weight,
 }
@@ -221,6 +254,13 @@ impl Client {
 		let (chain_id, max_block_weight) =
 			tokio::try_join!(chain_id(&api), max_block_weight(&api))?;
 
+		let subscription_provider = SubscriptionProvider::default();
+		let filter_provider = FilterProvider::new(
+			receipt_provider.clone(),
+			block_provider.clone(),
+			subscription_provider.clone(),
+		);
+
 		Ok(Self {
 			api,
 			rpc_client,
@@ -228,6 +268,8 @@ impl Client {
 			receipt_provider,
 			block_provider,
 			fee_history_provider: FeeHistoryProvider::default(),
+			subscription_provider,
+			filter_provider,
 			chain_id,
 			max_block_weight,
 		})
@@ -328,16 +370,116 @@ impl Client {
 			let (signed_txs, receipts): (Vec<_>, Vec<_>) =
 				self.receipt_provider.insert_block_receipts(&block).await?.into_iter().unzip();
 
+			if let SubscriptionType::FinalizedBlocks = subscription_type {
+				self.receipt_provider.finalize_block(&block).await?;
+			}
+
 			let evm_block =
 				self.evm_block_from_receipts(&block, &receipts, signed_txs, false).await;
 			self.block_provider.update_latest(block, subscription_type).await;
 
 			self.fee_history_provider.update_fee_history(&evm_block, &receipts).await;
+
+			if let SubscriptionType::BestBlocks = subscription_type {
+				let logs = receipts.into_iter().flat_map(|receipt| receipt.logs).collect();
+				if let Err(err) = self
+					.subscription_provider
+					.notify_new_best_block(BlockNotification::new(evm_block, logs), |hash| {
+						self.block_notification(hash)
+					})
+					.await
+				{
+					log::error!(target: LOG_TARGET, "Failed to notify the new best block: {err:?}");
+				}
+			}
 			Ok(())
 		})
 		.await
 	}
 
+	/// Watch the node's transaction pool, and notify the subscribers of new Ethereum transactions.
+	pub async fn subscribe_pending_transactions(&self) {
+		log::info!(target: LOG_TARGET, "🔌 Watching the transaction pool");
+		let mut interval = tokio::time::interval(PENDING_TRANSACTIONS_POLL_INTERVAL);
+		interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+		// The Ethereum transaction hash of the extrinsics seen in the pool, by extrinsic hash, so
+		// that only the extrinsics that entered the pool since the last poll are decoded.
+		let mut known = HashMap::new();
+		loop {
+			interval.tick().await;
+			match self.pending_transaction_hashes(&mut known).await {
+				Ok(hashes) => self.subscription_provider.notify_pool_transactions(hashes),
+				Err(err) => {
+					log::debug!(target: LOG_TARGET, "Failed to fetch the pending transactions: {err:?}")
+				},
+			}
+		}
+	}
+
+	/// Get the hashes of the Ethereum transactions in the node's transaction pool.
+	///
+	/// `known` maps the hash of the extrinsics of the previous poll to the hash of the Ethereum
+	/// transaction they contain, if any. It is updated to the extrinsics currently in the pool.
+	async fn pending_transaction_hashes(
+		&self,
+		known: &mut HashMap<H256, Option<H256>>,
+	) -> Result<Vec<H256>, ClientError> {
+		let client = RpcClient::new(self.rpc_client.clone());
+		let extrinsics: Vec<Bytes> =
+			client.request("author_pendingExtrinsics", Default::default()).await?;
+
+		let mut pool = HashMap::with_capacity(extrinsics.len());
+		let mut new = vec![];
+		for ext in extrinsics {
+			let hash = H256(sp_core::blake2_256(&ext.0));
+			match known.get(&hash) {
+				Some(eth_hash) => {
+					pool.insert(hash, *eth_hash);
+				},
+				None => new.push((hash, ext.0)),
+			}
+		}
+
+		if !new.is_empty() {
+			let (hashes, new): (Vec<_>, Vec<_>) = new.into_iter().unzip();
+			let decoded =
+				subxt::ext::subxt_core::blocks::Extrinsics::<SrcChainConfig>::decode_from(
+					new,
+					self.api.metadata(),
+				)
+				.map_err(subxt::Error::from)?;
+			for (hash, ext) in hashes.into_iter().zip(decoded.iter()) {
+				let eth_hash = ext
+					.as_extrinsic::<EthTransact>()
+					.ok()
+					.flatten()
+					.map(|call| H256(sp_core::keccak_256(&call.payload)));
+				pool.insert(hash, eth_hash);
+			}
+		}
+
+		let hashes = pool.values().flatten().copied().collect();
+		*known = pool;
+		Ok(hashes)
+	}
+
+	/// Build the subscription notification for the block with the given hash.
+	async fn block_notification(
+		&self,
+		hash: SubstrateBlockHash,
+	) -> Result<Option<BlockNotification>, ClientError> {
+		let Some(block) = self.block_provider.block_by_hash(&hash).await? else {
+			return Ok(None);
+		};
+
+		let (signed_txs, receipts): (Vec<_>, Vec<_>) =
+			self.receipt_provider.receipts_from_block(&block).await?.into_iter().unzip();
+		let evm_block = self.evm_block_from_receipts(&block, &receipts, signed_txs, false).await;
+		let logs = receipts.into_iter().flat_map(|receipt| receipt.logs).collect();
+		Ok(Some(BlockNotification::new(evm_block, logs)))
+	}
+
 	/// Cache old blocks up to the given block number.
 	pub async fn subscribe_and_cache_blocks(
 		&self,
@@ -356,6 +498,74 @@ impl Client {
 		Ok(())
 	}
 
+	/// Index all finalized blocks starting at `from`, skipping the blocks that are already indexed.
+	///
+	/// Used by the archive mode to backfill the database, and to resume the backfill after a
+	/// restart. Newer blocks are indexed by the finalized blocks subscription.
+	pub async fn index_finalized_blocks_from(
+		&self,
+		from: SubstrateBlockNumber,
+	) -> Result<(), ClientError> {
+		let to = self.latest_finalized_block().await.number();
+		log::info!(target: LOG_TARGET, "🗄️ Archive indexing finalized blocks in range {from}..={to}");
+
+		let mut start = from;
+		while start <= to {
+			let end = start.saturating_add(ARCHIVE_INDEX_BATCH_SIZE - 1).min(to);
+			let indexed = self.receipt_provider.indexed_block_numbers(start..=end).await?;
+
+			futures::stream::iter((start..=end).filter(|n| !indexed.contains(n)))
+				.for_each_concurrent(ARCHIVE_INDEX_CONCURRENCY, |n| self.index_finalized_block(n))
+				.await;
+
+			log::debug!(target: LOG_TARGET, "🗄️ Indexed finalized blocks #{start}..=#{end}");
+			if end == to {
+				break;
+			}
+			start = end + 1;
+		}
+
+		log::info!(target: LOG_TARGET, "🗄️ Finished archive indexing up to block #{to}");
+		Ok(())
+	}
+
+	/// Index the finalized block `number`, retrying on failure.
+	///
+	/// A block that still fails after [`ARCHIVE_INDEX_MAX_ATTEMPTS`] is skipped, so that a single
+	/// missing block does not stop the backfill. Skipped blocks are not recorded as indexed, and
+	/// are indexed again on the next restart.
+	async fn index_finalized_block(&self, number: SubstrateBlockNumber) {
+		for attempt in 1..=ARCHIVE_INDEX_MAX_ATTEMPTS {
+			let res = async {
+				let block = self
+					.block_provider
+					.block_by_number(number)
+					.await?
+					.ok_or(ClientError::BlockNotFound)?;
+				self.receipt_provider.insert_block_receipts(&block).await?;
+				self.receipt_provider.finalize_block(&*block).await
+			}
+			.await;
+
+			match res {
+				Ok(()) => return,
+				Err(err) => log::warn!(
+					target: LOG_TARGET,
+					"🗄️ Failed to index block #{number} (attempt {attempt}/{ARCHIVE_INDEX_MAX_ATTEMPTS}): {err:?}"
+				),
+			}
+
+			if attempt < ARCHIVE_INDEX_MAX_ATTEMPTS {
+				tokio::time::sleep(ARCHIVE_INDEX_RETRY_DELAY).await;
+			}
+		}
+
+		log::error!(
+			target: LOG_TARGET,
+			"🗄️ Skipping block #{number}, it will be indexed again after a restart"
+		);
+	}
+
 	/// Get the block hash for the given block number or tag.
 	pub async fn block_hash_for_tag(
 		&self,
@@ -410,6 +620,16 @@ impl Client {
 		Ok(hash)
 	}
 
+	/// Get the provider backing the `eth_subscribe` subscriptions.
+	pub fn subscription_provider(&self) -> &SubscriptionProvider {
+		&self.subscription_provider
+	}
+
+	/// Get the provider managing the filters installed with the `eth_new*Filter` methods.
+	pub fn filter_provider(&self) -> &FilterProvider {
+		&self.filter_provider
+	}
+
 	/// Get an EVM transaction receipt by hash.
 	pub async fn receipt(&self, tx_hash: &H256) -> Option<ReceiptInfo> {
 		self.receipt_provider.receipt_by_hash(tx_hash).await
@@ -424,6 +644,81 @@ impl Client {
 		Ok(sync_state)
 	}
 
+	/// Get the EIP-1186 proof of the account at `address` and of the given storage slots.
+	///
+	/// The proofs are Substrate trie proofs against the state root of the block, rather than
+	/// Patricia-Merkle proofs against an Ethereum state root. Accounts that are not contracts get
+	/// empty storage proofs.
+	pub async fn account_proof(
+		&self,
+		address: H160,
+		storage_slots: Vec<U256>,
+		block_hash: H256,
+	) -> Result<AccountProof, ClientError> {
+		let runtime_api = self.runtime_api(block_hash);
+		let slots = storage_slots.iter().map(|slot| slot.to_big_endian()).collect::<Vec<_>>();
+		let keys = runtime_api.account_proof_keys(address, slots.clone()).await?;
+		let (balance, nonce) =
+			tokio::try_join!(runtime_api.balance(address), runtime_api.nonce(address))?;
+
+		let account_proof = self
+			.rpc
+			.state_get_read_proof(keys.account_keys.iter().map(Vec::as_slice), Some(block_hash))
+			.await?;
+
+		let storage_proof = match keys.child_trie_key {
+			Some(child_trie_key) => {
+				let proofs = storage_slots.into_iter().zip(slots).zip(keys.storage_keys).map(
+					|((key, slot), hashed_key)| {
+						let runtime_api = &runtime_api;
+						let child_trie_key = &child_trie_key;
+						async move {
+							let (value, proof) = tokio::try_join!(
+								runtime_api.get_storage(address, slot),
+								self.child_read_proof(child_trie_key, hashed_key, block_hash)
+							)?;
+							let value = value.unwrap_or_default();
+							let value = U256::from_big_endian(&value[..value.len().min(32)]);
+							Ok::<_, ClientError>(StorageProof { key, value, proof })
+						}
+					},
+				);
+				futures::future::try_join_all(proofs).await?
+			},
+			None => storage_slots
+				.into_iter()
+				.map(|key| StorageProof { key, ..Default::default() })
+				.collect(),
+		};
+
+		Ok(AccountProof {
+			address,
+			account_proof: account_proof.proof.into_iter().map(|node| node.0.into()).collect(),
+			balance,
+			code_hash: keys.code_hash,
+			nonce,
+			storage_hash: keys.storage_root,
+			storage_proof,
+		})
+	}
+
+	/// Get the trie nodes proving `key` in the given child trie.
+	async fn child_read_proof(
+		&self,
+		child_trie_key: &[u8],
+		key: Vec<u8>,
+		block_hash: H256,
+	) -> Result<Vec<Bytes>, ClientError> {
+		let client = RpcClient::new(self.rpc_client.clone());
+		let params = subxt::ext::subxt_rpcs::rpc_params![
+			Bytes::from(child_trie_key.to_vec()),
+			vec![Bytes::from(key)],
+			block_hash
+		];
+		let proof: ReadProof<H256> = client.request("state_getChildReadProof", params).await?;
+		Ok(proof.proof.into_iter().map(|node| node.0.into()).collect())
+	}
+
 	/// Get the syncing status of the chain.
 	pub async fn syncing(&self) -> Result<SyncingStatus, ClientError> {
 		let health = self.rpc.system_health().await?;
@@ -603,6 +898,22 @@ impl Client {
 		runtime_api.trace_call(transaction, config.clone()).await
 	}
 
+	/// Get the receipts of the given block, ordered by transaction index.
+	pub async fn receipts_by_block(
+		&self,
+		block: &SubstrateBlock,
+	) -> Result<Vec<ReceiptInfo>, ClientError> {
+		let mut receipts = self
+			.receipt_provider
+			.receipts_from_block(block)
+			.await?
+			.into_iter()
+			.map(|(_, receipt)| receipt)
+			.collect::<Vec<_>>();
+		receipts.sort_by_key(|receipt| receipt.transaction_index);
+		Ok(receipts)
+	}
+
 	/// Get the EVM block for the given Substrate block.
 	pub async fn evm_block(
 		&self,
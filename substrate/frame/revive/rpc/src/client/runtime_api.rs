@@ -21,11 +21,26 @@ use crate::{
 	ClientError, LOG_TARGET,
 };
 use pallet_revive::{
-	evm::{GenericTransaction, Trace, H160, U256},
-	EthTransactInfo,
+	evm::{
+		AccessListResult, GenericTransaction, SimulatePayload, SimulatedBlock, Trace, H160, U256,
+	},
+	AccountProofKeys, EthTransactInfo,
 };
+use sp_api::RuntimeApiInfo;
+use sp_runtime::{generic, traits::BlakeTwo256, AccountId32, OpaqueExtrinsic};
+use sp_version::RuntimeVersion;
 use subxt::OnlineClient;
 
+/// The `ReviveApi` runtime API, used to look up its version: the generic arguments don't change
+/// the id of the API.
+type ReviveApi = dyn pallet_revive::ReviveApi<
+	generic::Block<generic::Header<u32, BlakeTwo256>, OpaqueExtrinsic>,
+	AccountId32,
+	Balance,
+	u32,
+	u32,
+>;
+
 /// A Wrapper around subxt Runtime API
 #[derive(Clone)]
 pub struct RuntimeApi(subxt::runtime_api::RuntimeApi<SrcChainConfig, OnlineClient<SrcChainConfig>>);
@@ -38,6 +53,25 @@ impl RuntimeApi {
 		Self(api)
 	}
 
+	/// Get the version of the `ReviveApi` implemented by the runtime, if any.
+	async fn revive_api_version(&self) -> Result<Option<u32>, ClientError> {
+		let version: RuntimeVersion = self.0.call_raw("Core_version", None).await?;
+		Ok(version.api_version(&<ReviveApi as RuntimeApiInfo>::ID))
+	}
+
+	/// Ensure the runtime implements at least the given version of the `ReviveApi`, as `method`
+	/// requires.
+	async fn ensure_revive_api_version(
+		&self,
+		method: &'static str,
+		version: u32,
+	) -> Result<(), ClientError> {
+		if !matches!(self.revive_api_version().await?, Some(v) if v >= version) {
+			return Err(ClientError::UnsupportedByRuntime(method));
+		}
+		Ok(())
+	}
+
 	/// Get the balance of the given address.
 	pub async fn balance(&self, address: H160) -> Result<U256, ClientError> {
 		let address = address.0.into();
@@ -74,6 +108,38 @@ impl RuntimeApi {
 		}
 	}
 
+	/// Simulate blocks of calls, with state and block overrides.
+	pub async fn simulate(
+		&self,
+		payload: SimulatePayload,
+	) -> Result<Vec<SimulatedBlock>, ClientError> {
+		self.ensure_revive_api_version("eth_simulateV1", 2).await?;
+		let payload = subxt_client::apis().revive_api().eth_simulate(payload.into());
+		match self.0.call(payload).await? {
+			Err(err) => {
+				log::debug!(target: LOG_TARGET, "Simulation failed {err:?}");
+				Err(ClientError::TransactError(err.0))
+			},
+			Ok(blocks) => Ok(blocks.into_iter().map(|block| block.0).collect()),
+		}
+	}
+
+	/// Create the access list of a transaction.
+	pub async fn create_access_list(
+		&self,
+		tx: GenericTransaction,
+	) -> Result<AccessListResult, ClientError> {
+		self.ensure_revive_api_version("eth_createAccessList", 2).await?;
+		let payload = subxt_client::apis().revive_api().eth_create_access_list(tx.into());
+		match self.0.call(payload).await? {
+			Err(err) => {
+				log::debug!(target: LOG_TARGET, "Access list creation failed {err:?}");
+				Err(ClientError::TransactError(err.0))
+			},
+			Ok(result) => Ok(result.0),
+		}
+	}
+
 	/// Get the nonce of the given address.
 	pub async fn nonce(&self, address: H160) -> Result<U256, ClientError> {
 		let address = address.0.into();
@@ -161,4 +227,16 @@ impl RuntimeApi {
 		let code = self.0.call(payload).await?;
 		Ok(code)
 	}
+
+	/// Get the storage keys proving the given account and storage slots.
+	pub async fn account_proof_keys(
+		&self,
+		address: H160,
+		storage_keys: Vec<[u8; 32]>,
+	) -> Result<AccountProofKeys, ClientError> {
+		self.ensure_revive_api_version("eth_getProof", 2).await?;
+		let payload = subxt_client::apis().revive_api().account_proof_keys(address, storage_keys);
+		let keys = self.0.call(payload).await?;
+		Ok(keys.0)
+	}
 }
@@ -0,0 +1,487 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+	receipt_provider::MAX_LOGS, BlockInfoProvider, BlockNotification, BlockNumberOrTag, BlockTag,
+	ClientError, ReceiptProvider, SubscriptionProvider, SubxtBlockInfoProvider, LOG_TARGET,
+};
+use pallet_revive::evm::{Filter, FilterResults};
+use sp_core::{H256, U256};
+use std::{
+	collections::HashMap,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+use tokio::sync::{broadcast, Mutex};
+
+/// Filters that are not polled within this duration are uninstalled.
+const FILTER_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// How often the expired filters are uninstalled.
+const FILTER_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The maximum number of filters that can be installed at the same time.
+const MAX_FILTERS: usize = 1024;
+
+/// The kind of an installed filter, and the state needed to compute its changes.
+enum FilterKind {
+	/// A log filter, with the next block to query logs from and the number of its logs that
+	/// were already returned.
+	Logs { filter: Filter, next_block: U256, skip: usize },
+	/// A block filter, receiving the new best blocks.
+	Blocks(broadcast::Receiver<Arc<BlockNotification>>),
+	/// A pending transaction filter, receiving the hashes of the submitted transactions.
+	PendingTransactions(broadcast::Receiver<H256>),
+}
+
+/// A filter installed by `eth_newFilter`, `eth_newBlockFilter` or
+/// `eth_newPendingTransactionFilter`.
+struct InstalledFilter {
+	kind: FilterKind,
+	/// The last time the filter was polled, used to uninstall stale filters.
+	last_poll: Instant,
+}
+
+#[derive(Default)]
+struct Filters {
+	/// The id assigned to the next installed filter.
+	next_id: u64,
+	/// The installed filters.
+	installed: HashMap<U256, InstalledFilter>,
+}
+
+impl Filters {
+	/// Install a new filter, returning its id.
+	fn install(&mut self, kind: FilterKind) -> Result<U256, ClientError> {
+		self.remove_expired();
+		if self.installed.len() >= MAX_FILTERS {
+			return Err(ClientError::TooManyFilters);
+		}
+
+		self.next_id += 1;
+		let id = U256::from(self.next_id);
+		self.installed.insert(id, InstalledFilter { kind, last_poll: Instant::now() });
+		Ok(id)
+	}
+
+	/// Uninstall the filters that were not polled within [`FILTER_TIMEOUT`].
+	fn remove_expired(&mut self) {
+		self.installed.retain(|id, filter| {
+			let expired = filter.last_poll.elapsed() > FILTER_TIMEOUT;
+			if expired {
+				log::debug!(target: LOG_TARGET, "Uninstalling expired filter {id:?}");
+			}
+			!expired
+		});
+	}
+}
+
+/// FilterProvider manages the filters installed through the `eth_new*Filter` RPC methods.
+#[derive(Clone)]
+pub struct FilterProvider<B: BlockInfoProvider = SubxtBlockInfoProvider> {
+	/// The receipt provider, used to query the logs of the log filters.
+	receipt_provider: ReceiptProvider<B>,
+	/// The block provider, used to get the latest block number.
+	block_provider: B,
+	/// The subscription provider, used to get notified of new blocks and transactions.
+	subscription_provider: SubscriptionProvider,
+	/// The installed filters.
+	filters: Arc<Mutex<Filters>>,
+}
+
+impl<B: BlockInfoProvider> FilterProvider<B> {
+	/// Create a new `FilterProvider`.
+	pub fn new(
+		receipt_provider: ReceiptProvider<B>,
+		block_provider: B,
+		subscription_provider: SubscriptionProvider,
+	) -> Self {
+		Self {
+			receipt_provider,
+			block_provider,
+			subscription_provider,
+			filters: Default::default(),
+		}
+	}
+
+	/// Install a log filter, returning its id.
+	pub async fn new_filter(&self, filter: Filter) -> Result<U256, ClientError> {
+		if filter.block_hash.is_some() {
+			return Err(ClientError::LogFilterFailed(anyhow::anyhow!(
+				"blockHash is not supported by log filters"
+			)));
+		}
+
+		let next_block = match filter.from_block {
+			Some(BlockNumberOrTag::U256(n)) => n,
+			Some(BlockNumberOrTag::BlockTag(BlockTag::Earliest)) => U256::zero(),
+			Some(BlockNumberOrTag::BlockTag(_)) | None =>
+				U256::from(self.block_provider.latest_block_number().await) + 1,
+		};
+
+		self.filters
+			.lock()
+			.await
+			.install(FilterKind::Logs { filter, next_block, skip: 0 })
+	}
+
+	/// Install a filter notified of new blocks, returning its id.
+	pub async fn new_block_filter(&self) -> Result<U256, ClientError> {
+		let receiver = self.subscription_provider.block_receiver();
+		self.filters.lock().await.install(FilterKind::Blocks(receiver))
+	}
+
+	/// Install a filter notified of new pending transactions, returning its id.
+	pub async fn new_pending_transaction_filter(&self) -> Result<U256, ClientError> {
+		let receiver = self.subscription_provider.pending_transaction_receiver();
+		self.filters.lock().await.install(FilterKind::PendingTransactions(receiver))
+	}
+
+	/// Periodically uninstall the filters that were not polled within [`FILTER_TIMEOUT`].
+	///
+	/// Without it, abandoned filters would only be removed when another filter is accessed.
+	pub async fn remove_expired_filters(&self) {
+		let mut interval = tokio::time::interval(FILTER_SWEEP_INTERVAL);
+		loop {
+			interval.tick().await;
+			self.filters.lock().await.remove_expired();
+		}
+	}
+
+	/// Uninstall the filter with the given id, returning whether it was installed.
+	pub async fn uninstall_filter(&self, id: U256) -> bool {
+		let mut filters = self.filters.lock().await;
+		filters.remove_expired();
+		filters.installed.remove(&id).is_some()
+	}
+
+	/// Get the changes of the filter with the given id since it was last polled.
+	pub async fn filter_changes(&self, id: U256) -> Result<FilterResults, ClientError> {
+		let (filter, from_block, skip) = {
+			let mut filters = self.filters.lock().await;
+			filters.remove_expired();
+			let installed = filters.installed.get_mut(&id).ok_or(ClientError::FilterNotFound)?;
+			installed.last_poll = Instant::now();
+
+			match &mut installed.kind {
+				FilterKind::Logs { filter, next_block, skip } =>
+					(filter.clone(), *next_block, *skip),
+				FilterKind::Blocks(receiver) => {
+					let hashes = drain(receiver)
+						.into_iter()
+						.filter(|notification| !notification.removed)
+						.map(|notification| notification.block.hash)
+						.collect();
+					return Ok(FilterResults::Hashes(hashes));
+				},
+				FilterKind::PendingTransactions(receiver) =>
+					return Ok(FilterResults::Hashes(drain(receiver))),
+			}
+		};
+
+		let latest_block = U256::from(self.block_provider.latest_block_number().await);
+		let to_block = match filter.to_block {
+			Some(BlockNumberOrTag::U256(n)) => n.min(latest_block),
+			Some(BlockNumberOrTag::BlockTag(BlockTag::Earliest)) => U256::zero(),
+			Some(BlockNumberOrTag::BlockTag(_)) | None => latest_block,
+		};
+
+		if from_block > to_block {
+			return Ok(FilterResults::Logs(vec![]));
+		}
+
+		let logs = self
+			.receipt_provider
+			.logs_from(
+				Some(Filter {
+					from_block: Some(from_block.into()),
+					to_block: Some(to_block.into()),
+					..filter
+				}),
+				skip,
+			)
+			.await
+			.map_err(ClientError::LogFilterFailed)?;
+
+		// When the logs are capped, resume from the last returned block on the next poll, skipping
+		// the logs of that block that were already returned.
+		let (next, next_skip) = match logs.last() {
+			Some(last) if logs.len() >= MAX_LOGS => {
+				let returned = logs
+					.iter()
+					.rev()
+					.take_while(|log| log.block_number == last.block_number)
+					.count();
+				if last.block_number == from_block {
+					(from_block, skip + returned)
+				} else {
+					(last.block_number, returned)
+				}
+			},
+			_ => (to_block + 1, 0),
+		};
+
+		if let Some(InstalledFilter { kind: FilterKind::Logs { next_block, skip, .. }, .. }) =
+			self.filters.lock().await.installed.get_mut(&id)
+		{
+			*next_block = next;
+			*skip = next_skip;
+		}
+
+		Ok(FilterResults::Logs(logs))
+	}
+
+	/// Get all the logs matching the log filter with the given id.
+	pub async fn filter_logs(&self, id: U256) -> Result<FilterResults, ClientError> {
+		let filter = {
+			let mut filters = self.filters.lock().await;
+			filters.remove_expired();
+			let installed = filters.installed.get_mut(&id).ok_or(ClientError::FilterNotFound)?;
+			installed.last_poll = Instant::now();
+
+			match &installed.kind {
+				FilterKind::Logs { filter, .. } => filter.clone(),
+				FilterKind::Blocks(_) | FilterKind::PendingTransactions(_) =>
+					return Err(ClientError::FilterNotFound),
+			}
+		};
+
+		let logs = self
+			.receipt_provider
+			.logs(Some(filter))
+			.await
+			.map_err(ClientError::LogFilterFailed)?;
+		Ok(FilterResults::Logs(logs))
+	}
+}
+
+/// Drain all the items received since the last call, skipping the ones missed when lagging.
+fn drain<T: Clone>(receiver: &mut broadcast::Receiver<T>) -> Vec<T> {
+	let mut items = vec![];
+	loop {
+		match receiver.try_recv() {
+			Ok(item) => items.push(item),
+			Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+				log::debug!(target: LOG_TARGET, "Filter lagging behind, skipped {skipped} notifications");
+			},
+			Err(broadcast::error::TryRecvError::Empty | broadcast::error::TryRecvError::Closed) =>
+				return items,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		test::{block_notification, in_memory_pool, MockBlockInfo, MockBlockInfoProvider},
+		ReceiptExtractor,
+	};
+	use pallet_revive::evm::{Log, ReceiptInfo, TransactionSigned};
+	use pretty_assertions::assert_eq;
+
+	async fn setup_filter_provider() -> FilterProvider<MockBlockInfoProvider> {
+		let receipt_provider = ReceiptProvider::new(
+			in_memory_pool().await,
+			MockBlockInfoProvider,
+			ReceiptExtractor::new_mock(),
+			None,
+			false,
+		)
+		.await
+		.unwrap();
+		FilterProvider::new(receipt_provider, MockBlockInfoProvider, Default::default())
+	}
+
+	fn block(number: u8) -> BlockNotification {
+		block_notification(number, 0, 0)
+	}
+
+	/// Insert a block with a single log, returning the log.
+	async fn insert_log(provider: &FilterProvider<MockBlockInfoProvider>, number: u8) -> Log {
+		insert_logs(provider, number, 1).await.remove(0)
+	}
+
+	/// Insert a block with `count` logs, returning the logs.
+	async fn insert_logs(
+		provider: &FilterProvider<MockBlockInfoProvider>,
+		number: u8,
+		count: usize,
+	) -> Vec<Log> {
+		let block = MockBlockInfo { hash: H256::from([number; 32]), number: number.into() };
+		let logs = (0..count)
+			.map(|index| Log {
+				block_hash: block.hash,
+				block_number: number.into(),
+				log_index: index.into(),
+				transaction_hash: H256::from([number; 32]),
+				..Default::default()
+			})
+			.collect::<Vec<_>>();
+		let receipt = ReceiptInfo {
+			logs: logs.clone(),
+			transaction_hash: H256::from([number; 32]),
+			..Default::default()
+		};
+		provider
+			.receipt_provider
+			.insert(&block, &[(TransactionSigned::default(), receipt)])
+			.await
+			.unwrap();
+		logs
+	}
+
+	#[tokio::test]
+	async fn block_filter_works() -> anyhow::Result<()> {
+		let provider = setup_filter_provider().await;
+		let notify = |notification| {
+			provider
+				.subscription_provider
+				.notify_new_best_block(notification, |_| async { Ok(None) })
+		};
+
+		notify(block(1)).await?;
+		let id = provider.new_block_filter().await?;
+		notify(block(2)).await?;
+		notify(block(3)).await?;
+
+		let changes = provider.filter_changes(id).await?;
+		assert_eq!(changes, FilterResults::Hashes(vec![block(2).block.hash, block(3).block.hash]));
+
+		let changes = provider.filter_changes(id).await?;
+		assert_eq!(changes, FilterResults::Hashes(vec![]));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn pending_transaction_filter_works() -> anyhow::Result<()> {
+		let provider = setup_filter_provider().await;
+		let id = provider.new_pending_transaction_filter().await?;
+		provider.subscription_provider.notify_pending_transaction(H256::from([1u8; 32]));
+
+		let changes = provider.filter_changes(id).await?;
+		assert_eq!(changes, FilterResults::Hashes(vec![H256::from([1u8; 32])]));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn uninstall_filter_works() -> anyhow::Result<()> {
+		let provider = setup_filter_provider().await;
+		let id = provider.new_block_filter().await?;
+
+		assert!(provider.uninstall_filter(id).await);
+		assert!(!provider.uninstall_filter(id).await);
+		assert!(matches!(provider.filter_changes(id).await, Err(ClientError::FilterNotFound)));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn expired_filters_are_removed() -> anyhow::Result<()> {
+		let provider = setup_filter_provider().await;
+		let id = provider.new_filter(Filter::default()).await?;
+
+		provider.filters.lock().await.installed.get_mut(&id).unwrap().last_poll =
+			Instant::now() - FILTER_TIMEOUT - Duration::from_secs(1);
+
+		assert!(matches!(provider.filter_changes(id).await, Err(ClientError::FilterNotFound)));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn expired_filters_are_swept() -> anyhow::Result<()> {
+		let provider = setup_filter_provider().await;
+		let id = provider.new_block_filter().await?;
+		provider.filters.lock().await.installed.get_mut(&id).unwrap().last_poll =
+			Instant::now() - FILTER_TIMEOUT - Duration::from_secs(1);
+
+		// The first tick of the sweeper completes immediately.
+		let _ = tokio::time::timeout(Duration::from_millis(100), provider.remove_expired_filters())
+			.await;
+		assert!(provider.filters.lock().await.installed.is_empty());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn filters_are_limited() -> anyhow::Result<()> {
+		let provider = setup_filter_provider().await;
+		for _ in 0..MAX_FILTERS {
+			provider.new_block_filter().await?;
+		}
+
+		assert!(matches!(provider.new_block_filter().await, Err(ClientError::TooManyFilters)));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn log_filter_only_returns_new_logs() -> anyhow::Result<()> {
+		let provider = setup_filter_provider().await;
+		let old_log = insert_log(&provider, 2).await;
+		let id = provider.new_filter(Filter::default()).await?;
+
+		// The filter starts after the latest block (#2 for the mock provider), so the log of
+		// block #2 is only returned by `eth_getFilterLogs`.
+		let changes = provider.filter_changes(id).await?;
+		assert_eq!(changes, FilterResults::Logs(vec![]));
+		let logs = provider.filter_logs(id).await?;
+		assert_eq!(logs, FilterResults::Logs(vec![old_log]));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn log_filter_from_earliest_returns_old_logs() -> anyhow::Result<()> {
+		let provider = setup_filter_provider().await;
+		let logs = vec![insert_log(&provider, 1).await, insert_log(&provider, 2).await];
+		let filter = Filter {
+			from_block: Some(BlockNumberOrTag::BlockTag(BlockTag::Earliest)),
+			..Default::default()
+		};
+		let id = provider.new_filter(filter).await?;
+
+		let changes = provider.filter_changes(id).await?;
+		assert_eq!(changes, FilterResults::Logs(logs));
+
+		let changes = provider.filter_changes(id).await?;
+		assert_eq!(changes, FilterResults::Logs(vec![]));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn log_filter_returns_logs_beyond_the_limit() -> anyhow::Result<()> {
+		let provider = setup_filter_provider().await;
+		let mut logs = insert_logs(&provider, 1, 2 * MAX_LOGS + 1).await;
+		logs.push(insert_log(&provider, 2).await);
+		let filter = Filter {
+			from_block: Some(BlockNumberOrTag::BlockTag(BlockTag::Earliest)),
+			..Default::default()
+		};
+		let id = provider.new_filter(filter).await?;
+
+		// The logs of block #1 span several polls, and none of them is skipped or repeated.
+		let mut received = vec![];
+		for _ in 0..3 {
+			let FilterResults::Logs(changes) = provider.filter_changes(id).await? else {
+				panic!("log filters return logs");
+			};
+			assert!(changes.len() <= MAX_LOGS);
+			received.extend(changes);
+		}
+		assert_eq!(received, logs);
+
+		let changes = provider.filter_changes(id).await?;
+		assert_eq!(changes, FilterResults::Logs(vec![]));
+		Ok(())
+	}
+}
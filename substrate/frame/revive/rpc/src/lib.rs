@@ -47,6 +47,12 @@ pub use fee_history_provider::*;
 mod receipt_extractor;
 pub use receipt_extractor::*;
 
+mod subscription_provider;
+pub use subscription_provider::*;
+
+mod filter_provider;
+pub use filter_provider::*;
+
 mod apis;
 pub use apis::*;
 
@@ -170,6 +176,7 @@ impl EthRpcServer for EthRpcServerImpl {
 		})?;
 
 		log::debug!(target: LOG_TARGET, "send_raw_transaction hash: {hash:?}");
+		self.client.subscription_provider().notify_pending_transaction(hash);
 		Ok(hash)
 	}
 
@@ -209,6 +216,28 @@ impl EthRpcServer for EthRpcServerImpl {
 		self.send_raw_transaction(Bytes(payload)).await
 	}
 
+	async fn create_access_list(
+		&self,
+		transaction: GenericTransaction,
+		block: Option<BlockNumberOrTagOrHash>,
+	) -> RpcResult<AccessListResult> {
+		let hash = self.client.block_hash_for_tag(block.unwrap_or_default()).await?;
+		let runtime_api = self.client.runtime_api(hash);
+		let result = runtime_api.create_access_list(transaction).await?;
+		Ok(result)
+	}
+
+	async fn simulate_v1(
+		&self,
+		payload: SimulatePayload,
+		block: Option<BlockNumberOrTagOrHash>,
+	) -> RpcResult<Vec<SimulatedBlock>> {
+		let hash = self.client.block_hash_for_tag(block.unwrap_or_default()).await?;
+		let runtime_api = self.client.runtime_api(hash);
+		let blocks = runtime_api.simulate(payload).await?;
+		Ok(blocks)
+	}
+
 	async fn get_block_by_hash(
 		&self,
 		block_hash: H256,
@@ -266,6 +295,24 @@ impl EthRpcServer for EthRpcServerImpl {
 		Ok(Some(block))
 	}
 
+	async fn get_block_receipts(
+		&self,
+		block: BlockNumberOrTagOrHash,
+	) -> RpcResult<Option<Vec<ReceiptInfo>>> {
+		let block = match block {
+			BlockNumberOrTagOrHash::BlockHash(hash) => self.client.block_by_hash(&hash).await?,
+			BlockNumberOrTagOrHash::BlockNumber(number) =>
+				self.client.block_by_number_or_tag(&BlockNumberOrTag::U256(number)).await?,
+			BlockNumberOrTagOrHash::BlockTag(tag) =>
+				self.client.block_by_number_or_tag(&BlockNumberOrTag::BlockTag(tag)).await?,
+		};
+		let Some(block) = block else {
+			return Ok(None);
+		};
+		let receipts = self.client.receipts_by_block(&block).await?;
+		Ok(Some(receipts))
+	}
+
 	async fn get_block_transaction_count_by_hash(
 		&self,
 		block_hash: Option<H256>,
@@ -297,6 +344,41 @@ impl EthRpcServer for EthRpcServerImpl {
 		Ok(FilterResults::Logs(logs))
 	}
 
+	async fn new_filter(&self, filter: Filter) -> RpcResult<U256> {
+		Ok(self.client.filter_provider().new_filter(filter).await?)
+	}
+
+	async fn new_block_filter(&self) -> RpcResult<U256> {
+		Ok(self.client.filter_provider().new_block_filter().await?)
+	}
+
+	async fn new_pending_transaction_filter(&self) -> RpcResult<U256> {
+		Ok(self.client.filter_provider().new_pending_transaction_filter().await?)
+	}
+
+	async fn get_filter_changes(&self, filter_id: U256) -> RpcResult<FilterResults> {
+		Ok(self.client.filter_provider().filter_changes(filter_id).await?)
+	}
+
+	async fn get_filter_logs(&self, filter_id: U256) -> RpcResult<FilterResults> {
+		Ok(self.client.filter_provider().filter_logs(filter_id).await?)
+	}
+
+	async fn uninstall_filter(&self, filter_id: U256) -> RpcResult<bool> {
+		Ok(self.client.filter_provider().uninstall_filter(filter_id).await)
+	}
+
+	async fn get_proof(
+		&self,
+		address: H160,
+		storage_keys: Vec<U256>,
+		block: BlockNumberOrTagOrHash,
+	) -> RpcResult<AccountProof> {
+		let hash = self.client.block_hash_for_tag(block).await?;
+		let proof = self.client.account_proof(address, storage_keys, hash).await?;
+		Ok(proof)
+	}
+
 	async fn get_storage_at(
 		&self,
 		address: H160,
@@ -21,18 +21,25 @@ use crate::{
 };
 use pallet_revive::evm::{Filter, Log, ReceiptInfo, TransactionSigned};
 use sp_core::{H256, U256};
-use sqlx::{query, QueryBuilder, Row, Sqlite, SqlitePool};
+use sqlx::{any::AnyArguments, Any, AnyPool, Arguments, Encode, Row, Type};
 use std::{
-	collections::{BTreeMap, HashMap},
+	collections::{BTreeMap, HashMap, HashSet},
+	ops::RangeInclusive,
 	sync::Arc,
 };
 use tokio::sync::Mutex;
 
-/// ReceiptProvider stores transaction receipts and logs in a SQLite database.
+/// The number of blocks stored in each partition of the PostgreSQL `logs` table.
+const LOG_PARTITION_SIZE: i64 = 100_000;
+
+/// The maximum number of logs returned by a single query.
+pub(crate) const MAX_LOGS: usize = 10_000;
+
+/// ReceiptProvider stores transaction receipts and logs in a SQLite or PostgreSQL database.
 #[derive(Clone)]
 pub struct ReceiptProvider<B: BlockInfoProvider = SubxtBlockInfoProvider> {
 	/// The database pool.
-	pool: SqlitePool,
+	pool: AnyPool,
 	/// The block provider used to fetch blocks, and reconstruct receipts.
 	block_provider: B,
 	/// A means to extract receipts from extrinsics.
@@ -41,6 +48,13 @@ pub struct ReceiptProvider<B: BlockInfoProvider = SubxtBlockInfoProvider> {
 	keep_latest_n_blocks: Option<usize>,
 	/// A Map of the latest block numbers to block hashes.
 	block_number_to_hash: Arc<Mutex<BTreeMap<SubstrateBlockNumber, H256>>>,
+	/// When `true`, the canonical block at each height is tracked, see
+	/// [`ReceiptProvider::finalize_block`].
+	archive: bool,
+	/// Whether the database is PostgreSQL, where the `logs` table is partitioned by block number.
+	is_postgres: bool,
+	/// The partitions of the `logs` table known to exist.
+	log_partitions: Arc<Mutex<HashSet<i64>>>,
 }
 
 /// Provides information about a block,
@@ -62,40 +76,99 @@ impl BlockInfo for SubstrateBlock {
 	}
 }
 
+/// A query built at runtime.
+///
+/// [`sqlx::QueryBuilder`] emits `?` placeholders for the [`Any`] driver, which PostgreSQL does not
+/// understand, so the `$N` placeholders are numbered here instead.
+struct DynamicQuery<'q> {
+	sql: String,
+	args: AnyArguments<'q>,
+}
+
+impl<'q> DynamicQuery<'q> {
+	fn new(sql: &str) -> Self {
+		Self { sql: sql.to_string(), args: Default::default() }
+	}
+
+	fn push(&mut self, sql: impl std::fmt::Display) -> &mut Self {
+		self.sql.push_str(&sql.to_string());
+		self
+	}
+
+	fn push_bind(
+		&mut self,
+		value: impl 'q + Encode<'q, Any> + Type<Any>,
+	) -> anyhow::Result<&mut Self> {
+		self.args
+			.add(value)
+			.map_err(|err| anyhow::anyhow!("failed to bind value: {err}"))?;
+		let placeholder = format!("${}", self.args.len());
+		Ok(self.push(placeholder))
+	}
+
+	/// Push a parenthesized, comma separated list of bound values.
+	fn push_bind_list<T: 'q + Encode<'q, Any> + Type<Any>>(
+		&mut self,
+		values: impl IntoIterator<Item = T>,
+	) -> anyhow::Result<&mut Self> {
+		self.push("(");
+		for (i, value) in values.into_iter().enumerate() {
+			if i > 0 {
+				self.push(", ");
+			}
+			self.push_bind(value)?;
+		}
+		Ok(self.push(")"))
+	}
+}
+
 impl<B: BlockInfoProvider> ReceiptProvider<B> {
-	/// Create a new `ReceiptProvider` with the given database URL and block provider.
+	/// Create a new `ReceiptProvider` with the given database pool and block provider.
+	///
+	/// The pool can either connect to a SQLite or a PostgreSQL database. When `archive` is set,
+	/// the canonical block at each height is tracked, so that the blocks indexed by the archive
+	/// mode can be looked up with [`Self::indexed_block_numbers`].
 	pub async fn new(
-		pool: SqlitePool,
+		pool: AnyPool,
 		block_provider: B,
 		receipt_extractor: ReceiptExtractor,
 		keep_latest_n_blocks: Option<usize>,
+		archive: bool,
 	) -> Result<Self, sqlx::Error> {
-		sqlx::migrate!().run(&pool).await?;
+		let is_postgres = pool.connect_options().database_url.scheme().starts_with("postgres");
+		if is_postgres {
+			sqlx::migrate!("./migrations-postgres").run(&pool).await?;
+		} else {
+			sqlx::migrate!().run(&pool).await?;
+		}
+
 		Ok(Self {
 			pool,
 			block_provider,
 			receipt_extractor,
 			keep_latest_n_blocks,
 			block_number_to_hash: Default::default(),
+			archive,
+			is_postgres,
+			log_partitions: Default::default(),
 		})
 	}
 
 	async fn fetch_row(&self, transaction_hash: &H256) -> Option<(H256, usize)> {
-		let transaction_hash = transaction_hash.as_ref();
-		let result = query!(
+		let row = sqlx::query(
 			r#"
 			SELECT block_hash, transaction_index
 			FROM transaction_hashes
 			WHERE transaction_hash = $1
 			"#,
-			transaction_hash
 		)
+		.bind(transaction_hash.as_ref())
 		.fetch_optional(&self.pool)
 		.await
 		.ok()??;
 
-		let block_hash = H256::from_slice(&result.block_hash[..]);
-		let transaction_index = result.transaction_index.try_into().ok()?;
+		let block_hash = H256::from_slice(&row.try_get::<Vec<u8>, _>("block_hash").ok()?);
+		let transaction_index = row.try_get::<i64, _>("transaction_index").ok()?.try_into().ok()?;
 		Some((block_hash, transaction_index))
 	}
 
@@ -106,7 +179,8 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 		}
 		log::debug!(target: LOG_TARGET, "Removing block hashes: {block_hashes:?}");
 
-		let placeholders = vec!["?"; block_hashes.len()].join(", ");
+		let placeholders =
+			(1..=block_hashes.len()).map(|i| format!("${i}")).collect::<Vec<_>>().join(", ");
 		let sql = format!("DELETE FROM transaction_hashes WHERE block_hash in ({placeholders})");
 		let mut delete_tx_query = sqlx::query(&sql);
 
@@ -121,6 +195,135 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 		let delete_transaction_hashes = delete_tx_query.execute(&self.pool);
 		let delete_logs = delete_logs_query.execute(&self.pool);
 		tokio::try_join!(delete_transaction_hashes, delete_logs)?;
+
+		if self.archive {
+			let sql = format!("DELETE FROM blocks WHERE block_hash in ({placeholders})");
+			let mut delete_blocks_query = sqlx::query(&sql);
+			for block_hash in block_hashes {
+				delete_blocks_query = delete_blocks_query.bind(block_hash.as_ref());
+			}
+			delete_blocks_query.execute(&self.pool).await?;
+		}
+		Ok(())
+	}
+
+	/// Record `block` as the canonical block at its height.
+	///
+	/// If another, non-finalized block was recorded at the same height, it was re-orged out and its
+	/// receipts are removed. Returns `false` if `block` conflicts with an already finalized block,
+	/// in which case it is ignored.
+	async fn update_canonical_block(
+		&self,
+		block: &impl BlockInfo,
+		finalized: bool,
+	) -> Result<bool, ClientError> {
+		let block_hash = block.hash();
+		let block_number = block.number() as i64;
+
+		let previous =
+			sqlx::query("SELECT block_hash, finalized FROM blocks WHERE block_number = $1")
+				.bind(block_number)
+				.fetch_optional(&self.pool)
+				.await?;
+
+		if let Some(row) = previous {
+			let previous_hash = H256::from_slice(&row.try_get::<Vec<u8>, _>("block_hash")?);
+			let previous_finalized = row.try_get::<i64, _>("finalized")? != 0;
+
+			if previous_hash == block_hash {
+				if finalized && !previous_finalized {
+					sqlx::query("UPDATE blocks SET finalized = 1 WHERE block_number = $1")
+						.bind(block_number)
+						.execute(&self.pool)
+						.await?;
+				}
+				return Ok(true);
+			}
+
+			if previous_finalized && !finalized {
+				log::warn!(
+					target: LOG_TARGET,
+					"Ignoring block #{block_number} ({block_hash:?}), it conflicts with finalized block {previous_hash:?}"
+				);
+				return Ok(false);
+			}
+
+			log::debug!(
+				target: LOG_TARGET,
+				"Re-org at block #{block_number}: replacing {previous_hash:?} with {block_hash:?}"
+			);
+			self.remove(&[previous_hash]).await?;
+		}
+
+		sqlx::query(
+			r#"
+			INSERT INTO blocks (block_number, block_hash, finalized)
+			VALUES ($1, $2, $3)
+			ON CONFLICT (block_number) DO UPDATE
+			SET block_hash = excluded.block_hash, finalized = excluded.finalized
+			"#,
+		)
+		.bind(block_number)
+		.bind(block_hash.as_ref())
+		.bind(finalized as i64)
+		.execute(&self.pool)
+		.await?;
+		Ok(true)
+	}
+
+	/// Mark the given block as finalized.
+	///
+	/// In archive mode, any other block previously recorded at the same height is removed.
+	/// Otherwise, this is a no-op.
+	pub async fn finalize_block(&self, block: &impl BlockInfo) -> Result<(), ClientError> {
+		if self.archive {
+			self.update_canonical_block(block, true).await?;
+		}
+		Ok(())
+	}
+
+	/// Get the numbers of the blocks in `range` that are already indexed.
+	///
+	/// Blocks are only tracked in archive mode, so this is always empty otherwise.
+	pub async fn indexed_block_numbers(
+		&self,
+		range: RangeInclusive<SubstrateBlockNumber>,
+	) -> Result<HashSet<SubstrateBlockNumber>, ClientError> {
+		let block_numbers: Vec<i64> = sqlx::query_scalar(
+			"SELECT block_number FROM blocks WHERE block_number BETWEEN $1 AND $2",
+		)
+		.bind(*range.start() as i64)
+		.bind(*range.end() as i64)
+		.fetch_all(&self.pool)
+		.await?;
+
+		Ok(block_numbers.into_iter().map(|n| n as SubstrateBlockNumber).collect())
+	}
+
+	/// Make sure the partition of the PostgreSQL `logs` table holding `block_number` exists.
+	///
+	/// Partitions are created on demand, for ranges of [`LOG_PARTITION_SIZE`] blocks.
+	async fn ensure_log_partition(&self, block_number: i64) -> Result<(), ClientError> {
+		if !self.is_postgres {
+			return Ok(());
+		}
+
+		let partition = block_number / LOG_PARTITION_SIZE;
+		let mut log_partitions = self.log_partitions.lock().await;
+		if log_partitions.contains(&partition) {
+			return Ok(());
+		}
+
+		let from = partition * LOG_PARTITION_SIZE;
+		let to = from + LOG_PARTITION_SIZE;
+		log::debug!(target: LOG_TARGET, "Creating logs partition for blocks #{from}..#{to}");
+		sqlx::query(&format!(
+			"CREATE TABLE IF NOT EXISTS logs_{partition} PARTITION OF logs FOR VALUES FROM ({from}) TO ({to})"
+		))
+		.execute(&self.pool)
+		.await?;
+
+		log_partitions.insert(partition);
 		Ok(())
 	}
 
@@ -155,11 +358,15 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 	///
 	/// Note: Can be merged into `insert_block_receipts` once <https://github.com/paritytech/subxt/issues/1883> is fixed and subxt let
 	/// us create Mock `SubstrateBlock`
-	async fn insert(
+	pub(crate) async fn insert(
 		&self,
 		block: &impl BlockInfo,
 		receipts: &[(TransactionSigned, ReceiptInfo)],
 	) -> Result<(), ClientError> {
+		if self.archive && !self.update_canonical_block(block, false).await? {
+			return Ok(());
+		}
+
 		if receipts.is_empty() {
 			return Ok(());
 		}
@@ -168,14 +375,13 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 		let block_hash_ref = block_hash.as_ref();
 		let block_number = block.number() as i64;
 
-		let result = sqlx::query!(
-			r#"SELECT EXISTS(SELECT 1 FROM transaction_hashes WHERE block_hash = $1) AS "exists!: bool""#,
-			block_hash_ref
-		)
-		.fetch_one(&self.pool)
-		.await?;
+		let count: i64 =
+			sqlx::query_scalar("SELECT COUNT(*) FROM transaction_hashes WHERE block_hash = $1")
+				.bind(block_hash_ref)
+				.fetch_one(&self.pool)
+				.await?;
 
-		if result.exists {
+		if count > 0 {
 			return Ok(());
 		}
 
@@ -203,24 +409,30 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 			self.remove(&to_remove).await?;
 		}
 
+		if receipts.iter().any(|(_, receipt)| !receipt.logs.is_empty()) {
+			self.ensure_log_partition(block_number).await?;
+		}
+
 		for (_, receipt) in receipts {
 			let transaction_hash: &[u8] = receipt.transaction_hash.as_ref();
-			let transaction_index = receipt.transaction_index.as_u32() as i32;
+			let transaction_index = receipt.transaction_index.as_u32() as i64;
 
-			query!(
+			sqlx::query(
 				r#"
-				INSERT OR REPLACE INTO transaction_hashes (transaction_hash, block_hash, transaction_index)
+				INSERT INTO transaction_hashes (transaction_hash, block_hash, transaction_index)
 				VALUES ($1, $2, $3)
+				ON CONFLICT (transaction_hash) DO UPDATE
+				SET block_hash = excluded.block_hash, transaction_index = excluded.transaction_index
 				"#,
-				transaction_hash,
-				block_hash_ref,
-				transaction_index
 			)
+			.bind(transaction_hash)
+			.bind(block_hash_ref)
+			.bind(transaction_index)
 			.execute(&self.pool)
 			.await?;
 
 			for log in &receipt.logs {
-				let log_index = log.log_index.as_u32() as i32;
+				let log_index = log.log_index.as_u32() as i64;
 				let address: &[u8] = log.address.as_ref();
 
 				let topic_0 = log.topics.first().as_ref().map(|v| &v[..]);
@@ -229,9 +441,10 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 				let topic_3 = log.topics.get(3).as_ref().map(|v| &v[..]);
 				let data = log.data.as_ref().map(|v| &v.0[..]);
 
-				query!(
+				// The logs of a given block never change, so existing rows are kept as is.
+				sqlx::query(
 					r#"
-					INSERT OR REPLACE INTO logs(
+					INSERT INTO logs(
 						block_hash,
 						transaction_index,
 						log_index,
@@ -241,19 +454,20 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 						topic_0, topic_1, topic_2, topic_3,
 						data)
 					VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+					ON CONFLICT DO NOTHING
 					"#,
-					block_hash_ref,
-					transaction_index,
-					log_index,
-					address,
-					block_number,
-					transaction_hash,
-					topic_0,
-					topic_1,
-					topic_2,
-					topic_3,
-					data
 				)
+				.bind(block_hash_ref)
+				.bind(transaction_index)
+				.bind(log_index)
+				.bind(address)
+				.bind(block_number)
+				.bind(transaction_hash)
+				.bind(topic_0)
+				.bind(topic_1)
+				.bind(topic_2)
+				.bind(topic_3)
+				.bind(data)
 				.execute(&self.pool)
 				.await?;
 			}
@@ -263,7 +477,18 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 
 	/// Get logs that match the given filter.
 	pub async fn logs(&self, filter: Option<Filter>) -> anyhow::Result<Vec<Log>> {
-		let mut qb = QueryBuilder::<Sqlite>::new("SELECT logs.* FROM logs WHERE 1=1");
+		self.logs_from(filter, 0).await
+	}
+
+	/// Get logs that match the given filter, skipping the first `offset` matching logs.
+	///
+	/// At most [`MAX_LOGS`] logs are returned.
+	pub(crate) async fn logs_from(
+		&self,
+		filter: Option<Filter>,
+		offset: usize,
+	) -> anyhow::Result<Vec<Log>> {
+		let mut qb = DynamicQuery::new("SELECT logs.* FROM logs WHERE 1=1");
 		let filter = filter.unwrap_or_default();
 
 		let latest_block = U256::from(self.block_provider.latest_block_number().await);
@@ -290,40 +515,36 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 				anyhow::bail!("invalid block range params");
 			},
 			(Some(from_block), Some(to_block), None) if from_block == to_block => {
-				qb.push(" AND block_number = ").push_bind(from_block.as_u64() as i64);
+				qb.push(" AND block_number = ").push_bind(from_block.as_u64() as i64)?;
 			},
 			(Some(from_block), Some(to_block), None) => {
 				qb.push(" AND block_number BETWEEN ")
-					.push_bind(from_block.as_u64() as i64)
+					.push_bind(from_block.as_u64() as i64)?
 					.push(" AND ")
-					.push_bind(to_block.as_u64() as i64);
+					.push_bind(to_block.as_u64() as i64)?;
 			},
 			(Some(from_block), None, None) => {
-				qb.push(" AND block_number >= ").push_bind(from_block.as_u64() as i64);
+				qb.push(" AND block_number >= ").push_bind(from_block.as_u64() as i64)?;
 			},
 			(None, Some(to_block), None) => {
-				qb.push(" AND block_number <= ").push_bind(to_block.as_u64() as i64);
+				qb.push(" AND block_number <= ").push_bind(to_block.as_u64() as i64)?;
 			},
 			(None, None, Some(hash)) => {
-				qb.push(" AND block_hash = ").push_bind(hash.0.to_vec());
+				qb.push(" AND block_hash = ").push_bind(hash.0.to_vec())?;
 			},
 			(None, None, None) => {
-				qb.push(" AND block_number = ").push_bind(latest_block.as_u64() as i64);
+				qb.push(" AND block_number = ").push_bind(latest_block.as_u64() as i64)?;
 			},
 		}
 
 		if let Some(addresses) = filter.address {
 			match addresses {
 				AddressOrAddresses::Address(addr) => {
-					qb.push(" AND address = ").push_bind(addr.0.to_vec());
+					qb.push(" AND address = ").push_bind(addr.0.to_vec())?;
 				},
 				AddressOrAddresses::Addresses(addrs) => {
-					qb.push(" AND address IN (");
-					let mut separated = qb.separated(", ");
-					for addr in addrs {
-						separated.push_bind(addr.0.to_vec());
-					}
-					separated.push_unseparated(")");
+					qb.push(" AND address IN ")
+						.push_bind_list(addrs.into_iter().map(|addr| addr.0.to_vec()))?;
 				},
 			}
 		}
@@ -336,25 +557,23 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 			for (i, topic) in topics.into_iter().enumerate() {
 				match topic {
 					FilterTopic::Single(hash) => {
-						qb.push(format_args!(" AND topic_{i} = ")).push_bind(hash.0.to_vec());
+						qb.push(format_args!(" AND topic_{i} = ")).push_bind(hash.0.to_vec())?;
 					},
 					FilterTopic::Multiple(hashes) => {
-						qb.push(format_args!(" AND topic_{i} IN ("));
-						let mut separated = qb.separated(", ");
-						for hash in hashes {
-							separated.push_bind(hash.0.to_vec());
-						}
-						separated.push_unseparated(")");
+						qb.push(format_args!(" AND topic_{i} IN "))
+							.push_bind_list(hashes.into_iter().map(|hash| hash.0.to_vec()))?;
 					},
 				}
 			}
 		}
 
-		qb.push(" LIMIT 10000");
+		qb.push(format_args!(
+			" ORDER BY block_number, transaction_index, log_index LIMIT {MAX_LOGS} OFFSET {offset}"
+		));
 
-		let logs = qb
-			.build()
-			.try_map(|row| {
+		let DynamicQuery { sql, args } = qb;
+		let logs = sqlx::query_with(&sql, args)
+			.try_map(|row: sqlx::any::AnyRow| {
 				let block_hash: Vec<u8> = row.try_get("block_hash")?;
 				let transaction_index: i64 = row.try_get("transaction_index")?;
 				let log_index: i64 = row.try_get("log_index")?;
@@ -392,21 +611,19 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 
 	/// Get the number of receipts per block.
 	pub async fn receipts_count_per_block(&self, block_hash: &H256) -> Option<usize> {
-		let block_hash = block_hash.as_ref();
-		let row = query!(
+		let count: i64 = sqlx::query_scalar(
 			r#"
             SELECT COUNT(*) as count
             FROM transaction_hashes
             WHERE block_hash = $1
             "#,
-			block_hash
 		)
+		.bind(block_hash.as_ref())
 		.fetch_one(&self.pool)
 		.await
 		.ok()?;
 
-		let count = row.count as usize;
-		Some(count)
+		Some(count as usize)
 	}
 
 	/// Return all transaction hashes for the given block hash.
@@ -414,19 +631,19 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 		&self,
 		block_hash: &H256,
 	) -> Option<HashMap<usize, H256>> {
-		let block_hash = block_hash.as_ref();
-		let rows = query!(
+		let rows = sqlx::query(
 			r#"
 		      SELECT transaction_index, transaction_hash
 		      FROM transaction_hashes
 		      WHERE block_hash = $1
 		      "#,
-			block_hash
 		)
-		.map(|row| {
-			let transaction_index = row.transaction_index as usize;
-			let transaction_hash = H256::from_slice(&row.transaction_hash);
-			(transaction_index, transaction_hash)
+		.bind(block_hash.as_ref())
+		.try_map(|row: sqlx::any::AnyRow| {
+			let transaction_index = row.try_get::<i64, _>("transaction_index")? as usize;
+			let transaction_hash =
+				H256::from_slice(&row.try_get::<Vec<u8>, _>("transaction_hash")?);
+			Ok((transaction_index, transaction_hash))
 		})
 		.fetch_all(&self.pool)
 		.await
@@ -465,21 +682,7 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 
 	/// Get the signed transaction for the given transaction hash.
 	pub async fn signed_tx_by_hash(&self, transaction_hash: &H256) -> Option<TransactionSigned> {
-		let transaction_hash = transaction_hash.as_ref();
-		let result = query!(
-			r#"
-			SELECT block_hash, transaction_index
-			FROM transaction_hashes
-			WHERE transaction_hash = $1
-			"#,
-			transaction_hash
-		)
-		.fetch_optional(&self.pool)
-		.await
-		.ok()??;
-
-		let block_hash = H256::from_slice(&result.block_hash[..]);
-		let transaction_index = result.transaction_index.try_into().ok()?;
+		let (block_hash, transaction_index) = self.fetch_row(transaction_hash).await?;
 
 		let block = self.block_provider.block_by_hash(&block_hash).await.ok()??;
 		let (signed_tx, _) = self
@@ -494,20 +697,20 @@ impl<B: BlockInfoProvider> ReceiptProvider<B> {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::test::{MockBlockInfo, MockBlockInfoProvider};
+	use crate::test::{in_memory_pool, MockBlockInfo, MockBlockInfoProvider};
 	use pallet_revive::evm::{ReceiptInfo, TransactionSigned};
 	use pretty_assertions::assert_eq;
 	use sp_core::{H160, H256};
-	use sqlx::SqlitePool;
+	use sqlx::AnyPool;
 
-	async fn count(pool: &SqlitePool, table: &str, block_hash: Option<H256>) -> usize {
+	async fn count(pool: &AnyPool, table: &str, block_hash: Option<H256>) -> usize {
 		let count: i64 = match block_hash {
 			None =>
 				sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
 					.fetch_one(pool)
 					.await,
 			Some(hash) =>
-				sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table} WHERE block_hash = ?"))
+				sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table} WHERE block_hash = $1"))
 					.bind(hash.as_ref())
 					.fetch_one(pool)
 					.await,
@@ -517,19 +720,21 @@ mod tests {
 		count as _
 	}
 
-	async fn setup_sqlite_provider(pool: SqlitePool) -> ReceiptProvider<MockBlockInfoProvider> {
-		ReceiptProvider {
-			pool,
-			block_provider: MockBlockInfoProvider {},
-			receipt_extractor: ReceiptExtractor::new_mock(),
-			keep_latest_n_blocks: Some(10),
-			block_number_to_hash: Default::default(),
-		}
+	async fn setup_sqlite_provider(archive: bool) -> ReceiptProvider<MockBlockInfoProvider> {
+		ReceiptProvider::new(
+			in_memory_pool().await,
+			MockBlockInfoProvider {},
+			ReceiptExtractor::new_mock(),
+			Some(10),
+			archive,
+		)
+		.await
+		.unwrap()
 	}
 
-	#[sqlx::test]
-	async fn test_insert_remove(pool: SqlitePool) -> anyhow::Result<()> {
-		let provider = setup_sqlite_provider(pool).await;
+	#[tokio::test]
+	async fn test_insert_remove() -> anyhow::Result<()> {
+		let provider = setup_sqlite_provider(false).await;
 		let block = MockBlockInfo { hash: H256::default(), number: 0 };
 		let receipts = vec![(
 			TransactionSigned::default(),
@@ -549,9 +754,9 @@ mod tests {
 		Ok(())
 	}
 
-	#[sqlx::test]
-	async fn test_prune(pool: SqlitePool) -> anyhow::Result<()> {
-		let provider = setup_sqlite_provider(pool).await;
+	#[tokio::test]
+	async fn test_prune() -> anyhow::Result<()> {
+		let provider = setup_sqlite_provider(false).await;
 		let n = provider.keep_latest_n_blocks.unwrap();
 
 		for i in 0..2 * n {
@@ -578,9 +783,9 @@ mod tests {
 		return Ok(());
 	}
 
-	#[sqlx::test]
-	async fn test_fork(pool: SqlitePool) -> anyhow::Result<()> {
-		let provider = setup_sqlite_provider(pool).await;
+	#[tokio::test]
+	async fn test_fork() -> anyhow::Result<()> {
+		let provider = setup_sqlite_provider(false).await;
 
 		for i in [1u8, 2u8] {
 			let block = MockBlockInfo { hash: H256::from([i; 32]), number: 1 };
@@ -610,9 +815,73 @@ mod tests {
 		return Ok(());
 	}
 
-	#[sqlx::test]
-	async fn test_receipts_count_per_block(pool: SqlitePool) -> anyhow::Result<()> {
-		let provider = setup_sqlite_provider(pool).await;
+	#[tokio::test]
+	async fn test_finalized_block_is_kept() -> anyhow::Result<()> {
+		let provider = setup_sqlite_provider(true).await;
+
+		for i in [1u8, 2u8] {
+			let block = MockBlockInfo { hash: H256::from([i; 32]), number: 1 };
+			let transaction_hash = H256::from([i; 32]);
+			let receipts = vec![(
+				TransactionSigned::default(),
+				ReceiptInfo {
+					transaction_hash,
+					logs: vec![Log {
+						block_hash: block.hash,
+						transaction_hash,
+						..Default::default()
+					}],
+					..Default::default()
+				},
+			)];
+			provider.insert(&block, &receipts).await?;
+			if i == 1 {
+				provider.finalize_block(&block).await?;
+			}
+		}
+
+		assert_eq!(
+			count(&provider.pool, "transaction_hashes", Some(H256::from([1u8; 32]))).await,
+			1
+		);
+		assert_eq!(
+			count(&provider.pool, "transaction_hashes", Some(H256::from([2u8; 32]))).await,
+			0
+		);
+		assert_eq!(count(&provider.pool, "blocks", Some(H256::from([1u8; 32]))).await, 1);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_indexed_block_numbers() -> anyhow::Result<()> {
+		let provider = setup_sqlite_provider(true).await;
+
+		// Blocks without receipts are indexed as well.
+		for number in [1, 2, 4] {
+			let block = MockBlockInfo { hash: H256::from([number as u8; 32]), number };
+			provider.insert(&block, &[]).await?;
+		}
+
+		assert_eq!(provider.indexed_block_numbers(0..=3).await?, [1, 2].into());
+		assert_eq!(provider.indexed_block_numbers(0..=10).await?, [1, 2, 4].into());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn blocks_are_only_tracked_in_archive_mode() -> anyhow::Result<()> {
+		let provider = setup_sqlite_provider(false).await;
+		let block = MockBlockInfo { hash: H256::from([1u8; 32]), number: 1 };
+		provider.insert(&block, &[]).await?;
+		provider.finalize_block(&block).await?;
+
+		assert!(provider.indexed_block_numbers(0..=10).await?.is_empty());
+		assert_eq!(count(&provider.pool, "blocks", None).await, 0);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_receipts_count_per_block() -> anyhow::Result<()> {
+		let provider = setup_sqlite_provider(false).await;
 		let block = MockBlockInfo { hash: H256::default(), number: 0 };
 		let receipts = vec![
 			(
@@ -631,9 +900,9 @@ mod tests {
 		Ok(())
 	}
 
-	#[sqlx::test]
-	async fn test_query_logs(pool: SqlitePool) -> anyhow::Result<()> {
-		let provider = setup_sqlite_provider(pool).await;
+	#[tokio::test]
+	async fn test_query_logs() -> anyhow::Result<()> {
+		let provider = setup_sqlite_provider(false).await;
 		let block1 = MockBlockInfo { hash: H256::from([1u8; 32]), number: 1 };
 		let block2 = MockBlockInfo { hash: H256::from([2u8; 32]), number: 2 };
 		let log1 = Log {
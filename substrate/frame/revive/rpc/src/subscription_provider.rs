@@ -0,0 +1,314 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{ClientError, LOG_TARGET};
+use futures::Stream;
+use pallet_revive::evm::{Block, Log, H256};
+use std::{
+	collections::{HashSet, VecDeque},
+	future::Future,
+	sync::Arc,
+};
+use tokio::sync::{broadcast, Mutex};
+
+/// The maximum number of best blocks kept in memory to detect chain re-organizations.
+const MAX_REORG_DEPTH: usize = 256;
+
+/// The capacity of the notification channels.
+///
+/// Subscribers lagging behind by more than this number of notifications skip the oldest ones.
+const CHANNEL_CAPACITY: usize = 512;
+
+/// A block added to, or retracted from, the best chain.
+#[derive(Debug, Clone, Default)]
+pub struct BlockNotification {
+	/// The EVM block.
+	pub block: Block,
+	/// The logs emitted by the transactions of the block.
+	pub logs: Vec<Log>,
+	/// Whether the block was retracted from the best chain by a re-organization.
+	pub removed: bool,
+}
+
+impl BlockNotification {
+	/// Create a new notification for a block added to the best chain.
+	pub fn new(block: Block, logs: Vec<Log>) -> Self {
+		Self { block, logs, removed: false }
+	}
+
+	/// Mark the block and all of its logs as removed from the best chain.
+	fn into_removed(mut self) -> Self {
+		self.removed = true;
+		self.logs.iter_mut().for_each(|log| log.removed = Some(true));
+		self
+	}
+}
+
+/// SubscriptionProvider tracks the best chain and broadcasts notifications to the `eth_subscribe`
+/// subscribers.
+#[derive(Clone)]
+pub struct SubscriptionProvider {
+	/// The sender used to broadcast best block notifications.
+	block_sender: broadcast::Sender<Arc<BlockNotification>>,
+	/// The sender used to broadcast pending transaction hashes.
+	pending_tx_sender: broadcast::Sender<H256>,
+	/// The latest blocks of the best chain, used to compute the retracted blocks on re-orgs.
+	best_chain: Arc<Mutex<VecDeque<Arc<BlockNotification>>>>,
+	/// The pending transactions that were already broadcast.
+	known_pending_txs: Arc<std::sync::Mutex<HashSet<H256>>>,
+}
+
+impl Default for SubscriptionProvider {
+	fn default() -> Self {
+		let (block_sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+		let (pending_tx_sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+		Self {
+			block_sender,
+			pending_tx_sender,
+			best_chain: Default::default(),
+			known_pending_txs: Default::default(),
+		}
+	}
+}
+
+impl SubscriptionProvider {
+	/// Returns a stream of the blocks added to, and retracted from, the best chain.
+	pub fn block_notifications(&self) -> impl Stream<Item = Arc<BlockNotification>> {
+		into_stream(self.block_receiver())
+	}
+
+	/// Returns a stream of the hashes of the transactions entering the node's transaction pool.
+	pub fn pending_transactions(&self) -> impl Stream<Item = H256> {
+		into_stream(self.pending_transaction_receiver())
+	}
+
+	/// Returns a receiver of the blocks added to, and retracted from, the best chain.
+	pub fn block_receiver(&self) -> broadcast::Receiver<Arc<BlockNotification>> {
+		self.block_sender.subscribe()
+	}
+
+	/// Returns a receiver of the hashes of the transactions entering the node's transaction pool.
+	pub fn pending_transaction_receiver(&self) -> broadcast::Receiver<H256> {
+		self.pending_tx_sender.subscribe()
+	}
+
+	/// Notify the subscribers that a new transaction was submitted.
+	///
+	/// Transactions that were already broadcast are ignored.
+	pub fn notify_pending_transaction(&self, hash: H256) {
+		let mut known = self.known_pending_txs.lock().expect("Lock is never poisoned; qed");
+		if known.insert(hash) {
+			// An error only means that there are no subscribers.
+			let _ = self.pending_tx_sender.send(hash);
+		}
+	}
+
+	/// Notify the subscribers of the transactions currently in the node's transaction pool.
+	///
+	/// Only the transactions that were not broadcast before are sent. Transactions that left the
+	/// pool are forgotten, so they are broadcast again if they re-enter it.
+	pub fn notify_pool_transactions(&self, pool: impl IntoIterator<Item = H256>) {
+		let pool = pool.into_iter().collect::<HashSet<_>>();
+		let mut known = self.known_pending_txs.lock().expect("Lock is never poisoned; qed");
+		for hash in pool.difference(&known) {
+			let _ = self.pending_tx_sender.send(*hash);
+		}
+		*known = pool;
+	}
+
+	/// Notify the subscribers of a new best block.
+	///
+	/// If the block is not a child of the previous best block, the route between the two is
+	/// computed: the blocks of the old fork are broadcast as removed, and the missing blocks of the
+	/// new fork are fetched with `fetch_block` and broadcast before the new best block.
+	pub async fn notify_new_best_block<F, Fut>(
+		&self,
+		notification: BlockNotification,
+		fetch_block: F,
+	) -> Result<(), ClientError>
+	where
+		F: Fn(H256) -> Fut,
+		Fut: Future<Output = Result<Option<BlockNotification>, ClientError>>,
+	{
+		let mut best_chain = self.best_chain.lock().await;
+		let mut enacted = vec![notification];
+		let mut retracted = vec![];
+
+		while let (Some(tip), Some(head)) = (best_chain.back(), enacted.last()) {
+			if tip.block.hash == head.block.hash {
+				enacted.pop();
+				break;
+			}
+
+			if tip.block.hash == head.block.parent_hash {
+				break;
+			}
+
+			if tip.block.number >= head.block.number {
+				retracted.extend(best_chain.pop_back());
+				continue;
+			}
+
+			if enacted.len() >= MAX_REORG_DEPTH {
+				log::warn!(target: LOG_TARGET, "Could not find the common ancestor of the new best block within {MAX_REORG_DEPTH} blocks");
+				break;
+			}
+
+			match fetch_block(head.block.parent_hash).await? {
+				Some(parent) => enacted.push(parent),
+				None => break,
+			}
+		}
+
+		if !retracted.is_empty() {
+			log::debug!(target: LOG_TARGET, "Re-org detected, retracting {} blocks", retracted.len());
+		}
+
+		for block in retracted {
+			let removed = Arc::unwrap_or_clone(block).into_removed();
+			let _ = self.block_sender.send(Arc::new(removed));
+		}
+
+		for block in enacted.into_iter().rev() {
+			let block = Arc::new(block);
+			best_chain.push_back(block.clone());
+			let _ = self.block_sender.send(block);
+		}
+
+		while best_chain.len() > MAX_REORG_DEPTH {
+			best_chain.pop_front();
+		}
+
+		Ok(())
+	}
+}
+
+/// Convert a broadcast receiver into a stream, skipping the notifications missed when lagging.
+fn into_stream<T: Clone + Send + 'static>(
+	receiver: broadcast::Receiver<T>,
+) -> impl Stream<Item = T> {
+	futures::stream::unfold(receiver, |mut receiver| async move {
+		loop {
+			match receiver.recv().await {
+				Ok(item) => return Some((item, receiver)),
+				Err(broadcast::error::RecvError::Lagged(skipped)) => {
+					log::debug!(target: LOG_TARGET, "Subscriber lagging behind, skipped {skipped} notifications");
+				},
+				Err(broadcast::error::RecvError::Closed) => return None,
+			}
+		}
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test::block_notification as block;
+	use futures::StreamExt;
+	use pretty_assertions::assert_eq;
+	use std::collections::HashMap;
+
+	async fn notify(
+		provider: &SubscriptionProvider,
+		known: &HashMap<H256, BlockNotification>,
+		notification: BlockNotification,
+	) {
+		provider
+			.notify_new_best_block(
+				notification,
+				|hash| async move { Ok(known.get(&hash).cloned()) },
+			)
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	async fn notify_reorg_works() {
+		let provider = SubscriptionProvider::default();
+		let mut notifications = Box::pin(provider.block_notifications());
+
+		// Best chain: 1 <- 2 <- 3
+		let known: HashMap<_, _> = [block(1, 0, 0), block(2, 0, 0), block(3, 0, 0)]
+			.into_iter()
+			.chain([block(2, 1, 0), block(3, 1, 1), block(4, 1, 1)])
+			.map(|b| (b.block.hash, b))
+			.collect();
+
+		for b in [block(1, 0, 0), block(2, 0, 0), block(3, 0, 0)] {
+			notify(&provider, &known, b).await;
+		}
+
+		// Re-org to fork 1: 1 <- 2' <- 3' <- 4'
+		notify(&provider, &known, block(4, 1, 1)).await;
+
+		let mut received = vec![];
+		for _ in 0..8 {
+			let n = notifications.next().await.unwrap();
+			received.push((n.block.number.as_u32(), n.block.hash, n.removed));
+		}
+
+		let expected = [
+			(1, block(1, 0, 0).block.hash, false),
+			(2, block(2, 0, 0).block.hash, false),
+			(3, block(3, 0, 0).block.hash, false),
+			(3, block(3, 0, 0).block.hash, true),
+			(2, block(2, 0, 0).block.hash, true),
+			(2, block(2, 1, 0).block.hash, false),
+			(3, block(3, 1, 1).block.hash, false),
+			(4, block(4, 1, 1).block.hash, false),
+		];
+		assert_eq!(received, expected);
+	}
+
+	#[tokio::test]
+	async fn pool_transactions_are_notified_once() {
+		let provider = SubscriptionProvider::default();
+		let mut notifications = Box::pin(provider.pending_transactions());
+		let tx = |n: u8| H256::from([n; 32]);
+
+		provider.notify_pending_transaction(tx(1));
+		provider.notify_pool_transactions([tx(1), tx(2)]);
+		provider.notify_pool_transactions([tx(2), tx(3)]);
+		// `tx(1)` left the pool and is reported again when it re-enters it.
+		provider.notify_pool_transactions([tx(1)]);
+
+		let mut received = vec![];
+		for _ in 0..4 {
+			received.push(notifications.next().await.unwrap());
+		}
+		assert_eq!(received, vec![tx(1), tx(2), tx(3), tx(1)]);
+	}
+
+	#[tokio::test]
+	async fn removed_logs_are_flagged() {
+		let provider = SubscriptionProvider::default();
+		let mut notifications = Box::pin(provider.block_notifications());
+		let known = HashMap::new();
+
+		notify(&provider, &known, block(1, 0, 0)).await;
+		notify(&provider, &known, block(1, 1, 0)).await;
+
+		let added = notifications.next().await.unwrap();
+		assert_eq!(added.logs[0].removed, None);
+
+		let removed = notifications.next().await.unwrap();
+		assert!(removed.removed);
+		assert_eq!(removed.logs[0].removed, Some(true));
+
+		let added = notifications.next().await.unwrap();
+		assert_eq!(added.block.hash, block(1, 1, 0).block.hash);
+	}
+}
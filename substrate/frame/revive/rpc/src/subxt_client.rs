@@ -47,6 +47,22 @@ pub use subxt::config::PolkadotConfig as SrcChainConfig;
 		path = "pallet_revive::evm::api::rpc_types_gen::GenericTransaction",
 		with = "::subxt::utils::Static<::pallet_revive::evm::GenericTransaction>"
 	),
+	substitute_type(
+		path = "pallet_revive::evm::api::eth_rpc_types::SimulatePayload",
+		with = "::subxt::utils::Static<::pallet_revive::evm::SimulatePayload>"
+	),
+	substitute_type(
+		path = "pallet_revive::evm::api::eth_rpc_types::SimulatedBlock",
+		with = "::subxt::utils::Static<::pallet_revive::evm::SimulatedBlock>"
+	),
+	substitute_type(
+		path = "pallet_revive::evm::api::eth_rpc_types::AccessListResult",
+		with = "::subxt::utils::Static<::pallet_revive::evm::AccessListResult>"
+	),
+	substitute_type(
+		path = "pallet_revive::primitives::AccountProofKeys",
+		with = "::subxt::utils::Static<::pallet_revive::AccountProofKeys>"
+	),
 	substitute_type(
 		path = "pallet_revive::primitives::EthTransactInfo<B>",
 		with = "::subxt::utils::Static<::pallet_revive::EthTransactInfo<B>>"
@@ -29,7 +29,7 @@ use clap::Parser;
 use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
 use pallet_revive::{
 	create1,
-	evm::{Account, BlockTag, U256},
+	evm::{Account, BlockNumberOrTagOrHash, BlockTag, U256},
 };
 use static_init::dynamic;
 use std::{sync::Arc, thread};
@@ -135,6 +135,34 @@ async fn transfer() -> anyhow::Result<()> {
 	Ok(())
 }
 
+#[tokio::test]
+async fn get_block_receipts() -> anyhow::Result<()> {
+	let _lock = SHARED_RESOURCES.write();
+	let client = Arc::new(SharedResources::client().await);
+
+	let ethan = Account::from(subxt_signer::eth::dev::ethan());
+	let value = 1_000_000_000_000u128.into();
+	let tx = TransactionBuilder::new(&client).value(value).to(ethan.address()).send().await?;
+	let receipt = tx.wait_for_receipt().await?;
+
+	let block = BlockNumberOrTagOrHash::BlockHash(receipt.block_hash);
+	let receipts = client.get_block_receipts(block).await?.expect("Block should exist");
+	assert!(
+		receipts.contains(&receipt),
+		"Block receipts {receipts:?} should contain the receipt {receipt:?}."
+	);
+	assert!(receipts
+		.windows(2)
+		.all(|pair| pair[0].transaction_index < pair[1].transaction_index));
+
+	let block = BlockNumberOrTagOrHash::BlockNumber(receipt.block_number);
+	assert_eq!(client.get_block_receipts(block).await?, Some(receipts));
+
+	let unknown = BlockNumberOrTagOrHash::BlockHash(Default::default());
+	assert_eq!(client.get_block_receipts(unknown).await?, None);
+	Ok(())
+}
+
 #[tokio::test]
 async fn deploy_and_call() -> anyhow::Result<()> {
 	let _lock = SHARED_RESOURCES.write();
@@ -27,7 +27,6 @@ use serde::{
 use sp_core::{H160, H256, U256};
 
 /// The type of tracer to use.
-/// Only "callTracer" is supported for now.
 #[derive(TypeInfo, Debug, Clone, Encode, Decode, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "tracer", content = "tracerConfig", rename_all = "camelCase")]
 pub enum TracerType {
@@ -36,6 +35,15 @@ pub enum TracerType {
 
 	/// A tracer that traces the prestate.
 	PrestateTracer(Option<PrestateTracerConfig>),
+
+	/// A tracer that logs every executed instruction.
+	///
+	/// This is the default tracer when no tracer is specified.
+	StructLogger(Option<StructLoggerConfig>),
+
+	/// A tracer that collects the function selectors of the calls.
+	#[serde(rename = "4byteTracer")]
+	FourByteTracer,
 }
 
 impl From<CallTracerConfig> for TracerType {
@@ -52,10 +60,10 @@ impl Default for TracerType {
 
 /// Tracer configuration used to trace calls.
 #[derive(TypeInfo, Debug, Clone, Default, PartialEq)]
-#[cfg_attr(feature = "std", derive(Deserialize, Serialize), serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "std", derive(Serialize), serde(rename_all = "camelCase"))]
 pub struct TracerConfig {
 	/// The tracer type.
-	#[cfg_attr(feature = "std", serde(flatten, default))]
+	#[cfg_attr(feature = "std", serde(flatten))]
 	pub config: TracerType,
 
 	/// Timeout for the tracer.
@@ -63,6 +71,54 @@ pub struct TracerConfig {
 	pub timeout: Option<core::time::Duration>,
 }
 
+/// When no tracer is specified, the struct logger is used, configured with the top-level
+/// options, as in geth. Unknown tracers and malformed `tracerConfig` objects are rejected.
+#[cfg(feature = "std")]
+impl<'de> Deserialize<'de> for TracerConfig {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		use serde::de::{DeserializeOwned, Error};
+
+		const TRACERS: &[&str] = &["callTracer", "prestateTracer", "structLogger", "4byteTracer"];
+
+		#[derive(Deserialize)]
+		#[serde(rename_all = "camelCase")]
+		struct RawTracerConfig {
+			tracer: Option<String>,
+			tracer_config: Option<serde_json::Value>,
+			#[serde(flatten)]
+			struct_logger: StructLoggerConfig,
+			#[serde(with = "humantime_serde", default)]
+			timeout: Option<core::time::Duration>,
+		}
+
+		fn tracer_config<T: DeserializeOwned, E: Error>(
+			value: Option<serde_json::Value>,
+		) -> Result<Option<T>, E> {
+			value
+				.map(|value| {
+					serde_json::from_value(value)
+						.map_err(|err| E::custom(alloc::format!("invalid tracerConfig: {err}")))
+				})
+				.transpose()
+		}
+
+		let RawTracerConfig { tracer, tracer_config: raw_config, struct_logger, timeout } =
+			RawTracerConfig::deserialize(deserializer)?;
+
+		let config = match tracer.as_deref() {
+			None if raw_config.is_some() => return Err(D::Error::missing_field("tracer")),
+			None => TracerType::StructLogger(Some(struct_logger)),
+			Some("callTracer") => TracerType::CallTracer(tracer_config(raw_config)?),
+			Some("prestateTracer") => TracerType::PrestateTracer(tracer_config(raw_config)?),
+			Some("structLogger") => TracerType::StructLogger(tracer_config(raw_config)?),
+			Some("4byteTracer") => TracerType::FourByteTracer,
+			Some(other) => return Err(D::Error::unknown_variant(other, TRACERS)),
+		};
+
+		Ok(TracerConfig { config, timeout })
+	}
+}
+
 /// The configuration for the call tracer.
 #[derive(Clone, Debug, Decode, Serialize, Deserialize, Encode, PartialEq, TypeInfo)]
 #[serde(default, rename_all = "camelCase")]
@@ -100,6 +156,23 @@ impl Default for PrestateTracerConfig {
 	}
 }
 
+/// The configuration for the struct logger.
+#[derive(Clone, Debug, Default, Decode, Serialize, Deserialize, Encode, PartialEq, TypeInfo)]
+#[serde(default, rename_all = "camelCase")]
+pub struct StructLoggerConfig {
+	/// Whether to include the memory in the trace.
+	pub enable_memory: bool,
+
+	/// Whether to exclude the stack (the registers) from the trace.
+	pub disable_stack: bool,
+
+	/// Whether to exclude the storage from the trace.
+	pub disable_storage: bool,
+
+	/// The maximum number of logged steps, `0` means no limit.
+	pub limit: u64,
+}
+
 /// Serialization should support the following JSON format:
 ///
 /// ```json
@@ -137,6 +210,28 @@ fn test_tracer_config_serialization() {
 				timeout: Some(core::time::Duration::from_millis(10)),
 			},
 		),
+		(
+			r#"{"tracer": "4byteTracer"}"#,
+			TracerConfig { config: TracerType::FourByteTracer, timeout: None },
+		),
+		(
+			r#"{}"#,
+			TracerConfig {
+				config: TracerType::StructLogger(Some(StructLoggerConfig::default())),
+				timeout: None,
+			},
+		),
+		(
+			r#"{"enableMemory": true, "limit": 10, "timeout": "10ms"}"#,
+			TracerConfig {
+				config: TracerType::StructLogger(Some(StructLoggerConfig {
+					enable_memory: true,
+					limit: 10,
+					..Default::default()
+				})),
+				timeout: Some(core::time::Duration::from_millis(10)),
+			},
+		),
 	];
 
 	for (json_data, expected) in tracers {
@@ -146,6 +241,23 @@ fn test_tracer_config_serialization() {
 	}
 }
 
+#[test]
+fn test_tracer_config_deserialization_errors() {
+	let invalid = [
+		r#"{"tracer": "unknownTracer"}"#,
+		r#"{"tracer": "callTracer", "tracerConfig": { "onlyTopCall": 1 }}"#,
+		r#"{"tracer": "prestateTracer", "tracerConfig": "diffMode"}"#,
+		r#"{"tracerConfig": { "onlyTopCall": true }}"#,
+	];
+
+	for json_data in invalid {
+		assert!(
+			serde_json::from_str::<TracerConfig>(json_data).is_err(),
+			"Deserialization of {json_data} should fail"
+		);
+	}
+}
+
 /// The type of call that was executed.
 #[derive(
 	Default, TypeInfo, Encode, Decode, Serialize, Deserialize, Eq, PartialEq, Clone, Debug,
@@ -173,6 +285,61 @@ pub enum Trace {
 	Call(CallTrace),
 	/// A prestate trace.
 	Prestate(PrestateTrace),
+	/// A struct logger trace.
+	StructLogger(StructLoggerTrace),
+	/// A 4byte trace.
+	FourByte(FourByteTrace),
+}
+
+/// A 4byte trace, counting the calls of each function selector.
+///
+/// The keys are formatted as `<selector>-<call data size>`, e.g. `0x27dc297e-128`.
+pub type FourByteTrace = BTreeMap<String, u32>;
+
+/// A struct logger trace.
+#[derive(
+	TypeInfo, Default, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLoggerTrace<Gas = U256> {
+	/// Amount of gas used.
+	pub gas: Gas,
+	/// Whether the call failed.
+	pub failed: bool,
+	/// The return data of the call.
+	pub return_value: Bytes,
+	/// The executed instructions.
+	pub struct_logs: Vec<StructLog>,
+}
+
+/// An instruction executed by a contract.
+#[derive(
+	TypeInfo, Default, Encode, Decode, Serialize, Deserialize, Clone, Debug, Eq, PartialEq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+	/// The program counter.
+	pub pc: u32,
+	/// The name of the instruction.
+	pub op: String,
+	/// The engine fuel left before executing the instruction.
+	pub gas: u64,
+	/// The engine fuel consumed by the instruction, including nested calls.
+	pub gas_cost: u64,
+	/// The call depth, starting at 1.
+	pub depth: u32,
+	/// The registers, PolkaVM has no stack.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// The memory of the current stack frame, in 32 bytes words.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub memory: Option<Vec<Bytes>>,
+	/// The storage slots accessed by this instruction.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<Bytes, Bytes>>,
+	/// The error, if the call failed at this instruction.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
 }
 
 /// A prestate Trace
@@ -0,0 +1,303 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hand-written JSON-RPC types of the `eth` namespace, which are not part of the generated types.
+
+use super::*;
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use codec::{Decode, Encode, Input, Output};
+use derive_more::{From, TryInto};
+use scale_info::{build::Fields, Path, Type, TypeInfo};
+use serde::{Deserialize, Serialize};
+
+/// Subscription kind, as accepted by `eth_subscribe`
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub enum SubscriptionKind {
+	/// New block headers added to the best chain
+	#[serde(rename = "newHeads")]
+	NewHeads,
+	/// Logs included in new blocks, matching the given filter
+	#[serde(rename = "logs")]
+	Logs,
+	/// Hashes of transactions added to the pending state
+	#[serde(rename = "newPendingTransactions")]
+	NewPendingTransactions,
+}
+
+/// Subscription notification result
+#[derive(Debug, Clone, Serialize, Deserialize, From, TryInto, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum SubscriptionItem {
+	/// Block header
+	Header(Block),
+	/// Log
+	Log(Log),
+	/// Transaction hash
+	TransactionHash(H256),
+}
+
+/// Account and storage proofs, as returned by `eth_getProof` (EIP-1186)
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProof {
+	/// The address of the account
+	pub address: Address,
+	/// The trie nodes proving the account
+	pub account_proof: Vec<Bytes>,
+	/// The balance of the account
+	pub balance: U256,
+	/// The hash of the account code
+	pub code_hash: H256,
+	/// The nonce of the account
+	pub nonce: U256,
+	/// The root of the account storage trie
+	pub storage_hash: H256,
+	/// The proofs of the requested storage slots
+	pub storage_proof: Vec<StorageProof>,
+}
+
+/// Storage slot proof, as returned by `eth_getProof` (EIP-1186)
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct StorageProof {
+	/// The storage slot
+	pub key: U256,
+	/// The value of the storage slot
+	pub value: U256,
+	/// The trie nodes proving the storage slot
+	pub proof: Vec<Bytes>,
+}
+
+/// State override of an account, as accepted by `eth_simulateV1`
+#[derive(
+	Debug, Default, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, Eq, PartialEq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+	/// Balance to set for the account
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub balance: Option<U256>,
+	/// Nonce to set for the account
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub nonce: Option<U256>,
+	/// Code to set for the account
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code: Option<Bytes>,
+	/// Storage replacing the whole storage of the account
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub state: Option<BTreeMap<H256, H256>>,
+	/// Storage slots to set, leaving the other slots untouched
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+/// State overrides, keyed by account address
+pub type StateOverride = BTreeMap<Address, AccountOverride>;
+
+/// Block header overrides, as accepted by `eth_simulateV1`
+#[derive(
+	Debug, Default, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, Eq, PartialEq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverrides {
+	/// Block number
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub number: Option<U256>,
+	/// Block timestamp
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub time: Option<U256>,
+	/// Block gas limit
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub gas_limit: Option<U256>,
+	/// Block author
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub fee_recipient: Option<Address>,
+	/// Base fee per gas
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub base_fee_per_gas: Option<U256>,
+}
+
+/// A block of calls to simulate, as accepted by `eth_simulateV1`
+#[derive(
+	Debug, Default, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, Eq, PartialEq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateBlock {
+	/// Overrides of the block header
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub block_overrides: Option<BlockOverrides>,
+	/// Overrides of the state, applied before executing the calls
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub state_overrides: Option<StateOverride>,
+	/// Calls to execute sequentially
+	#[serde(default)]
+	pub calls: Vec<GenericTransaction>,
+}
+
+/// Simulation payload, as accepted by `eth_simulateV1`
+#[derive(
+	Debug, Default, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, Eq, PartialEq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatePayload {
+	/// Blocks of calls to simulate
+	pub block_state_calls: Vec<SimulateBlock>,
+	/// Whether to fail the whole simulation when a call is invalid
+	#[serde(default)]
+	pub validation: bool,
+}
+
+/// Error of a simulated call
+#[derive(
+	Debug, Default, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, Eq, PartialEq,
+)]
+pub struct SimulateCallError {
+	/// Error code
+	pub code: i32,
+	/// Error message
+	pub message: String,
+}
+
+/// Result of a simulated call
+#[derive(
+	Debug, Default, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, Eq, PartialEq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateCallResult {
+	/// Status, `1` for success and `0` for failure
+	pub status: U256,
+	/// Return data, or revert data
+	pub return_data: Bytes,
+	/// Gas used
+	pub gas_used: U256,
+	/// Logs emitted by the call
+	pub logs: Vec<Log>,
+	/// Error, if the call failed
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<SimulateCallError>,
+}
+
+/// A simulated block, as returned by `eth_simulateV1`
+#[derive(
+	Debug, Default, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, Eq, PartialEq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedBlock {
+	/// Block number
+	pub number: U256,
+	/// Block timestamp
+	pub timestamp: U256,
+	/// Block gas limit
+	pub gas_limit: U256,
+	/// Gas used by the calls of the block
+	pub gas_used: U256,
+	/// Block author
+	pub miner: Address,
+	/// Base fee per gas
+	pub base_fee_per_gas: U256,
+	/// Results of the calls of the block
+	pub calls: Vec<SimulateCallResult>,
+}
+
+/// Access list result, as returned by `eth_createAccessList`
+#[derive(
+	Debug, Default, Clone, Encode, Decode, TypeInfo, Serialize, Deserialize, Eq, PartialEq,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListResult {
+	/// The accounts and storage keys accessed by the transaction
+	pub access_list: AccessList,
+	/// Gas used by the transaction
+	pub gas_used: U256,
+}
+
+// The generated `Log` type is not SCALE encodable, it is encoded by hand so that it can be returned
+// by the runtime as part of a `SimulateCallResult`.
+impl Encode for Log {
+	fn encode_to<O: Output + ?Sized>(&self, dest: &mut O) {
+		(
+			&self.address,
+			&self.block_hash,
+			&self.block_number,
+			&self.data,
+			&self.log_index,
+			&self.removed,
+			&self.topics,
+			&self.transaction_hash,
+			&self.transaction_index,
+		)
+			.encode_to(dest)
+	}
+}
+
+impl Decode for Log {
+	fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let (
+			address,
+			block_hash,
+			block_number,
+			data,
+			log_index,
+			removed,
+			topics,
+			transaction_hash,
+			transaction_index,
+		) = Decode::decode(input)?;
+		Ok(Log {
+			address,
+			block_hash,
+			block_number,
+			data,
+			log_index,
+			removed,
+			topics,
+			transaction_hash,
+			transaction_index,
+		})
+	}
+}
+
+impl TypeInfo for Log {
+	type Identity = Self;
+
+	fn type_info() -> Type {
+		Type::builder().path(Path::new("Log", module_path!())).composite(
+			Fields::named()
+				.field(|f| f.ty::<Address>().name("address"))
+				.field(|f| f.ty::<H256>().name("block_hash"))
+				.field(|f| f.ty::<U256>().name("block_number"))
+				.field(|f| f.ty::<Option<Bytes>>().name("data"))
+				.field(|f| f.ty::<U256>().name("log_index"))
+				.field(|f| f.ty::<Option<bool>>().name("removed"))
+				.field(|f| f.ty::<Vec<H256>>().name("topics"))
+				.field(|f| f.ty::<H256>().name("transaction_hash"))
+				.field(|f| f.ty::<U256>().name("transaction_index")),
+		)
+	}
+}
+
+#[test]
+fn log_encoding_roundtrip() {
+	let log = Log {
+		address: H160::from_low_u64_be(1),
+		block_number: 2u32.into(),
+		data: Some(vec![1, 2, 3].into()),
+		topics: vec![H256::from_low_u64_be(4)],
+		transaction_index: 5u32.into(),
+		..Default::default()
+	};
+	assert_eq!(Log::decode(&mut &log.encode()[..]).unwrap(), log);
+}
@@ -106,6 +106,31 @@ impl ReceiptInfo {
 		bloom.into()
 	}
 }
+impl Filter {
+	/// Returns `true` if the log matches the address and topics criteria of this filter.
+	///
+	/// The block range criteria (`fromBlock`, `toBlock` and `blockHash`) are not checked.
+	pub fn matches_log(&self, log: &Log) -> bool {
+		let address_matches = match &self.address {
+			None => true,
+			Some(AddressOrAddresses::Address(address)) => *address == log.address,
+			Some(AddressOrAddresses::Addresses(addresses)) =>
+				addresses.is_empty() || addresses.contains(&log.address),
+		};
+
+		let topics_match = self.topics.iter().flatten().enumerate().all(|(i, topic)| {
+			match (topic, log.topics.get(i)) {
+				(FilterTopic::Multiple(hashes), _) if hashes.is_empty() => true,
+				(FilterTopic::Single(hash), Some(log_topic)) => hash == log_topic,
+				(FilterTopic::Multiple(hashes), Some(log_topic)) => hashes.contains(log_topic),
+				(_, None) => false,
+			}
+		});
+
+		address_matches && topics_match
+	}
+}
+
 /// Specialised Bloom filter that sets three bits out of 2048, given an
 /// arbitrary byte sequence.
 ///
@@ -202,6 +227,79 @@ fn logs_bloom_works() {
 	assert_eq!(receipt.logs_bloom, ReceiptInfo::logs_bloom(&receipt.logs));
 }
 
+#[test]
+fn filter_matches_log_works() {
+	let log = Log {
+		address: H160::from([1u8; 20]),
+		topics: vec![H256::from([1u8; 32]), H256::from([2u8; 32])],
+		..Default::default()
+	};
+
+	let cases = [
+		("empty filter", Filter::default(), true),
+		("same address", Filter { address: Some(log.address.into()), ..Default::default() }, true),
+		(
+			"other address",
+			Filter { address: Some(H160::from([2u8; 20]).into()), ..Default::default() },
+			false,
+		),
+		(
+			"address in list",
+			Filter {
+				address: Some(vec![H160::from([2u8; 20]), log.address].into()),
+				..Default::default()
+			},
+			true,
+		),
+		(
+			"wildcard first topic",
+			Filter {
+				topics: Some(vec![
+					FilterTopic::Multiple(vec![]),
+					FilterTopic::Single(H256::from([2u8; 32])),
+				]),
+				..Default::default()
+			},
+			true,
+		),
+		(
+			"one of many topics",
+			Filter {
+				topics: Some(vec![FilterTopic::Multiple(vec![
+					H256::from([3u8; 32]),
+					H256::from([1u8; 32]),
+				])]),
+				..Default::default()
+			},
+			true,
+		),
+		(
+			"topic mismatch",
+			Filter {
+				topics: Some(vec![FilterTopic::Single(H256::from([2u8; 32]))]),
+				..Default::default()
+			},
+			false,
+		),
+		(
+			"more topics than the log",
+			Filter {
+				topics: Some(vec![
+					FilterTopic::Single(H256::from([1u8; 32])),
+					FilterTopic::Single(H256::from([2u8; 32])),
+					FilterTopic::Single(H256::from([3u8; 32])),
+				]),
+				..Default::default()
+			},
+			false,
+		),
+	];
+
+	for (name, filter, expected) in cases {
+		assert_eq!(filter.matches_log(&log), expected, "{}", name);
+	}
+}
+
 impl GenericTransaction {
 	/// Create a new [`GenericTransaction`] from a signed transaction.
 	pub fn from_signed(tx: TransactionSigned, base_gas_price: U256, from: Option<H160>) -> Self {
@@ -15,7 +15,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use crate::{
-	evm::{CallTrace, Trace},
+	evm::{CallTrace, FourByteTrace, StructLoggerTrace, Trace},
 	tracing::Tracing,
 	BalanceOf, Bounded, Config, MomentOf, Weight,
 };
@@ -27,6 +27,18 @@ pub use call_tracing::*;
 mod prestate_tracing;
 pub use prestate_tracing::*;
 
+mod struct_logger;
+pub use struct_logger::*;
+
+mod four_byte_tracing;
+pub use four_byte_tracing::*;
+
+mod log_tracing;
+pub use log_tracing::*;
+
+mod access_list_tracing;
+pub use access_list_tracing::*;
+
 /// A composite tracer.
 #[derive(derive_more::From, Debug)]
 pub enum Tracer<T> {
@@ -34,6 +46,10 @@ pub enum Tracer<T> {
 	CallTracer(CallTracer<U256, fn(Weight) -> U256>),
 	/// A tracer that traces the prestate.
 	PrestateTracer(PrestateTracer<T>),
+	/// A tracer that logs every executed instruction.
+	StructLogger(StructLogger<U256, fn(Weight) -> U256>),
+	/// A tracer that collects the function selectors of the calls.
+	FourByteTracer(FourByteTracer),
 }
 
 impl<T: Config> Tracer<T>
@@ -48,6 +64,8 @@ where
 		match self {
 			Tracer::CallTracer(_) => CallTrace::default().into(),
 			Tracer::PrestateTracer(tracer) => tracer.empty_trace().into(),
+			Tracer::StructLogger(_) => StructLoggerTrace::default().into(),
+			Tracer::FourByteTracer(_) => FourByteTrace::default().into(),
 		}
 	}
 
@@ -56,6 +74,8 @@ where
 		match self {
 			Tracer::CallTracer(inner) => inner as &mut dyn Tracing,
 			Tracer::PrestateTracer(inner) => inner as &mut dyn Tracing,
+			Tracer::StructLogger(inner) => inner as &mut dyn Tracing,
+			Tracer::FourByteTracer(inner) => inner as &mut dyn Tracing,
 		}
 	}
 
@@ -64,6 +84,8 @@ where
 		match self {
 			Tracer::CallTracer(inner) => inner.collect_trace().map(Trace::Call),
 			Tracer::PrestateTracer(inner) => Some(inner.collect_trace().into()),
+			Tracer::StructLogger(inner) => inner.collect_trace().map(Trace::StructLogger),
+			Tracer::FourByteTracer(inner) => inner.collect_trace().map(Trace::FourByte),
 		}
 	}
 }
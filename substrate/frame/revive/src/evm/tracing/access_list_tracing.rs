@@ -0,0 +1,118 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+	evm::{AccessList, AccessListEntry},
+	exec::Key,
+	primitives::ExecReturnValue,
+	tracing::Tracing,
+	Code, DispatchError, Weight,
+};
+use alloc::{
+	collections::{BTreeMap, BTreeSet},
+	vec::Vec,
+};
+use sp_core::{H160, H256, U256};
+
+/// A tracer that records the accounts and storage keys accessed during an execution.
+///
+/// Only fixed sized storage keys are recorded, as variable sized keys can not be part of an
+/// Ethereum access list.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct AccessListTracer {
+	/// The accessed storage keys of each account.
+	accessed: BTreeMap<H160, BTreeSet<H256>>,
+	/// The accounts created during the execution.
+	created: BTreeSet<H160>,
+	/// The account whose storage is accessed by each active call.
+	storage_addrs: Vec<H160>,
+	/// Whether the next call instantiates a contract.
+	is_create: bool,
+}
+
+impl AccessListTracer {
+	/// Collect the access list.
+	///
+	/// Accounts without accessed storage keys are left out when they were created during the
+	/// execution or when `exclude` returns `true` for them.
+	pub fn collect_access_list(&mut self, exclude: impl Fn(&H160) -> bool) -> AccessList {
+		let created = core::mem::take(&mut self.created);
+		core::mem::take(&mut self.accessed)
+			.into_iter()
+			.filter(|(address, storage_keys)| {
+				!storage_keys.is_empty() || !(created.contains(address) || exclude(address))
+			})
+			.map(|(address, storage_keys)| AccessListEntry {
+				address,
+				storage_keys: storage_keys.into_iter().collect(),
+			})
+			.collect()
+	}
+
+	/// Record an access to the given storage key of the current account.
+	fn record_storage_access(&mut self, key: &Key) {
+		let (Some(address), Key::Fix(key)) = (self.storage_addrs.last(), key) else { return };
+		self.accessed.entry(*address).or_default().insert(H256(*key));
+	}
+}
+
+impl Tracing for AccessListTracer {
+	fn instantiate_code(&mut self, _code: &Code, _salt: Option<&[u8; 32]>) {
+		self.is_create = true;
+	}
+
+	fn enter_child_span(
+		&mut self,
+		_from: H160,
+		to: H160,
+		is_delegate_call: bool,
+		_is_read_only: bool,
+		_value: U256,
+		_input: &[u8],
+		_gas: Weight,
+	) {
+		if core::mem::take(&mut self.is_create) {
+			self.created.insert(to);
+		}
+
+		self.accessed.entry(to).or_default();
+
+		// A delegate call accesses the storage of its caller.
+		let storage_addr = match self.storage_addrs.last() {
+			Some(current) if is_delegate_call => *current,
+			_ => to,
+		};
+		self.storage_addrs.push(storage_addr);
+	}
+
+	fn storage_read(&mut self, key: &Key, _value: Option<&[u8]>) {
+		self.record_storage_access(key);
+	}
+
+	fn storage_write(&mut self, key: &Key, _old_value: Option<Vec<u8>>, _new_value: Option<&[u8]>) {
+		self.record_storage_access(key);
+	}
+
+	fn exit_child_span(&mut self, _output: &ExecReturnValue, _gas_used: Weight) {
+		self.is_create = false;
+		self.storage_addrs.pop();
+	}
+
+	fn exit_child_span_with_error(&mut self, _error: DispatchError, _gas_used: Weight) {
+		self.is_create = false;
+		self.storage_addrs.pop();
+	}
+}
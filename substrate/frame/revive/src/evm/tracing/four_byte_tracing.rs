@@ -0,0 +1,75 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+	evm::FourByteTrace, primitives::ExecReturnValue, tracing::Tracing, Code, DispatchError, Weight,
+};
+use alloc::{format, string::String};
+use sp_core::{H160, U256};
+
+/// A tracer that counts the calls of each function selector, like geth's 4byte tracer.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct FourByteTracer {
+	/// The number of calls of each `<selector>-<call data size>`.
+	selectors: FourByteTrace,
+	/// Whether the next call is a contract creation.
+	is_create: bool,
+	/// Whether a top-level call was traced since the last collected trace.
+	has_trace: bool,
+}
+
+impl FourByteTracer {
+	/// Collect the traces and return them.
+	pub fn collect_trace(&mut self) -> Option<FourByteTrace> {
+		core::mem::take(&mut self.has_trace).then(|| core::mem::take(&mut self.selectors))
+	}
+}
+
+impl Tracing for FourByteTracer {
+	fn instantiate_code(&mut self, _code: &Code, _salt: Option<&[u8; 32]>) {
+		self.is_create = true;
+	}
+
+	fn enter_child_span(
+		&mut self,
+		_from: H160,
+		_to: H160,
+		_is_delegate_call: bool,
+		_is_read_only: bool,
+		_value: U256,
+		input: &[u8],
+		_gas: Weight,
+	) {
+		self.has_trace = true;
+
+		// The input of a contract creation is the constructor arguments, it has no selector.
+		if core::mem::take(&mut self.is_create) || input.len() < 4 {
+			return
+		}
+
+		let selector = input[..4].iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+		let key = format!("0x{selector}-{}", input.len() - 4);
+		*self.selectors.entry(key).or_default() += 1;
+	}
+
+	fn exit_child_span(&mut self, _output: &ExecReturnValue, _gas_used: Weight) {
+		self.is_create = false;
+	}
+
+	fn exit_child_span_with_error(&mut self, _error: DispatchError, _gas_used: Weight) {
+		self.is_create = false;
+	}
+}
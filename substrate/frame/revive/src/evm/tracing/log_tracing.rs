@@ -0,0 +1,83 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{evm::Log, primitives::ExecReturnValue, tracing::Tracing, DispatchError, Weight};
+use alloc::vec::Vec;
+use sp_core::{H160, H256, U256};
+
+/// A tracer that records the logs emitted by the calls that were not reverted.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct LogTracer {
+	/// The logs emitted by each active call, the logs of a call are moved to its parent when it
+	/// succeeds, and dropped when it reverts.
+	logs: Vec<Vec<Log>>,
+	/// The logs of the completed top-level calls.
+	collected: Vec<Log>,
+}
+
+impl LogTracer {
+	/// Collect the logs and return them.
+	pub fn collect_logs(&mut self) -> Vec<Log> {
+		core::mem::take(&mut self.collected)
+	}
+
+	/// Exit the current call, keeping its logs only if it succeeded.
+	fn exit_call(&mut self, succeeded: bool) {
+		let Some(logs) = self.logs.pop() else { return };
+		if !succeeded {
+			return
+		}
+
+		match self.logs.last_mut() {
+			Some(parent) => parent.extend(logs),
+			None => self.collected.extend(logs),
+		}
+	}
+}
+
+impl Tracing for LogTracer {
+	fn enter_child_span(
+		&mut self,
+		_from: H160,
+		_to: H160,
+		_is_delegate_call: bool,
+		_is_read_only: bool,
+		_value: U256,
+		_input: &[u8],
+		_gas: Weight,
+	) {
+		self.logs.push(Vec::new());
+	}
+
+	fn log_event(&mut self, address: H160, topics: &[H256], data: &[u8]) {
+		if let Some(logs) = self.logs.last_mut() {
+			logs.push(Log {
+				address,
+				topics: topics.to_vec(),
+				data: Some(data.to_vec().into()),
+				..Default::default()
+			});
+		}
+	}
+
+	fn exit_child_span(&mut self, output: &ExecReturnValue, _gas_used: Weight) {
+		self.exit_call(!output.did_revert());
+	}
+
+	fn exit_child_span_with_error(&mut self, _error: DispatchError, _gas_used: Weight) {
+		self.exit_call(false);
+	}
+}
@@ -0,0 +1,192 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+	evm::{Bytes, StructLog, StructLoggerConfig, StructLoggerTrace},
+	primitives::ExecReturnValue,
+	tracing::Tracing,
+	DispatchError, Key, Weight,
+};
+use alloc::{collections::BTreeMap, format, string::ToString, vec::Vec};
+use sp_core::{H160, U256};
+
+/// A tracer that logs every instruction executed by the contracts, like geth's struct logger.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct StructLogger<Gas, GasMapper> {
+	/// Map Weight to Gas equivalent.
+	gas_mapper: GasMapper,
+	/// The tracer configuration.
+	config: StructLoggerConfig,
+	/// The logged instructions.
+	struct_logs: Vec<StructLog>,
+	/// For each active call, the index of its last logged instruction.
+	call_stack: Vec<Option<usize>>,
+	/// The gas used by the top-level call.
+	gas_used: Gas,
+	/// Whether the top-level call failed.
+	failed: bool,
+	/// The return data of the top-level call.
+	return_value: Bytes,
+	/// Whether a top-level call was traced since the last collected trace.
+	has_trace: bool,
+}
+
+impl<Gas: Default, GasMapper> StructLogger<Gas, GasMapper> {
+	/// Create a new [`StructLogger`] instance.
+	pub fn new(config: StructLoggerConfig, gas_mapper: GasMapper) -> Self {
+		Self {
+			gas_mapper,
+			config,
+			struct_logs: Vec::new(),
+			call_stack: Vec::new(),
+			gas_used: Default::default(),
+			failed: false,
+			return_value: Default::default(),
+			has_trace: false,
+		}
+	}
+
+	/// Collect the traces and return them.
+	pub fn collect_trace(&mut self) -> Option<StructLoggerTrace<Gas>> {
+		if !core::mem::take(&mut self.has_trace) {
+			return None
+		}
+
+		Some(StructLoggerTrace {
+			gas: core::mem::take(&mut self.gas_used),
+			failed: core::mem::take(&mut self.failed),
+			return_value: core::mem::take(&mut self.return_value),
+			struct_logs: core::mem::take(&mut self.struct_logs),
+		})
+	}
+
+	/// Returns the last logged instruction of the current call.
+	fn current_log_mut(&mut self) -> Option<&mut StructLog> {
+		let index = (*self.call_stack.last()?)?;
+		self.struct_logs.get_mut(index)
+	}
+
+	/// Attach an accessed storage slot to the current instruction.
+	fn record_storage(&mut self, key: &Key, value: Option<&[u8]>) {
+		if self.config.disable_storage {
+			return
+		}
+
+		if let Some(log) = self.current_log_mut() {
+			log.storage
+				.get_or_insert_with(BTreeMap::new)
+				.insert(key.unhashed().to_vec().into(), value.unwrap_or_default().to_vec().into());
+		}
+	}
+
+	/// Leave the current call, recording the outcome of the top-level call.
+	fn exit_call(&mut self, failed: bool, return_value: &[u8], gas_used: Gas) {
+		self.call_stack.pop();
+		if self.call_stack.is_empty() {
+			self.failed = failed;
+			self.return_value = return_value.to_vec().into();
+			self.gas_used = gas_used;
+		}
+	}
+}
+
+impl<Gas: Default, GasMapper: Fn(Weight) -> Gas> Tracing for StructLogger<Gas, GasMapper> {
+	fn is_execution_tracing_enabled(&self) -> bool {
+		true
+	}
+
+	fn enter_child_span(
+		&mut self,
+		_from: H160,
+		_to: H160,
+		_is_delegate_call: bool,
+		_is_read_only: bool,
+		_value: U256,
+		_input: &[u8],
+		_gas: Weight,
+	) {
+		self.has_trace = true;
+		self.call_stack.push(None);
+	}
+
+	fn enter_opcode(
+		&mut self,
+		pc: u32,
+		opcode: &str,
+		gas_left: u64,
+		registers: &[u64],
+		memory: &dyn Fn() -> Vec<u8>,
+	) {
+		if self.config.limit != 0 && self.struct_logs.len() as u64 >= self.config.limit {
+			return
+		}
+
+		// The cost of the previous instruction of the call is only known now.
+		if let Some(previous) = self.current_log_mut() {
+			previous.gas_cost = previous.gas.saturating_sub(gas_left);
+		}
+
+		let log = StructLog {
+			pc,
+			op: opcode.to_string(),
+			gas: gas_left,
+			gas_cost: 0,
+			depth: self.call_stack.len() as u32,
+			stack: (!self.config.disable_stack)
+				.then(|| registers.iter().map(|reg| U256::from(*reg)).collect()),
+			memory: self
+				.config
+				.enable_memory
+				.then(|| memory().chunks(32).map(|word| word.to_vec().into()).collect()),
+			storage: None,
+			error: None,
+		};
+
+		self.struct_logs.push(log);
+		let index = self.struct_logs.len() - 1;
+		if let Some(current) = self.call_stack.last_mut() {
+			*current = Some(index);
+		}
+	}
+
+	fn storage_read(&mut self, key: &Key, value: Option<&[u8]>) {
+		self.record_storage(key, value);
+	}
+
+	fn storage_write(&mut self, key: &Key, _old_value: Option<Vec<u8>>, new_value: Option<&[u8]>) {
+		self.record_storage(key, new_value);
+	}
+
+	fn exit_child_span(&mut self, output: &ExecReturnValue, gas_used: Weight) {
+		let gas_used = (self.gas_mapper)(gas_used);
+		self.exit_call(output.did_revert(), &output.data, gas_used);
+	}
+
+	fn exit_child_span_with_error(&mut self, error: DispatchError, gas_used: Weight) {
+		let message = match error {
+			DispatchError::Module(sp_runtime::ModuleError { message, .. }) =>
+				message.unwrap_or_default().to_string(),
+			_ => format!("{:?}", error),
+		};
+
+		if let Some(log) = self.current_log_mut() {
+			log.error = Some(message);
+		}
+
+		let gas_used = (self.gas_mapper)(gas_used);
+		self.exit_call(true, &[], gas_used);
+	}
+}
@@ -1903,7 +1903,9 @@ where
 	}
 
 	fn now(&self) -> U256 {
-		(self.timestamp / 1000u32.into()).into()
+		crate::simulated_block::with(|env| env.timestamp)
+			.flatten()
+			.unwrap_or_else(|| (self.timestamp / 1000u32.into()).into())
 	}
 
 	fn minimum_balance(&self) -> U256 {
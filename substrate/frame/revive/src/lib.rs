@@ -44,13 +44,15 @@ pub mod weights;
 
 use crate::{
 	evm::{
-		runtime::GAS_PRICE, CallTracer, GasEncoder, GenericTransaction, PrestateTracer, Trace,
-		Tracer, TracerType, TYPE_EIP1559,
+		runtime::GAS_PRICE, AccessListResult, AccessListTracer, AccountOverride, CallTracer,
+		FourByteTracer, GasEncoder, GenericTransaction, LogTracer, PrestateTracer, SimulateBlock,
+		SimulateCallError, SimulateCallResult, SimulatePayload, SimulatedBlock, StructLogger,
+		Trace, Tracer, TracerType, TYPE_EIP1559,
 	},
 	exec::{AccountIdOf, ExecError, Executable, Key, Stack as ExecStack},
 	gas::GasMeter,
 	storage::{meter::Meter as StorageMeter, ContractInfo, DeletionQueueManager},
-	tracing::if_tracing,
+	tracing::{if_tracing, trace},
 	vm::{CodeInfo, ContractBlob, RuntimeCosts},
 };
 use alloc::{boxed::Box, format, vec};
@@ -124,6 +126,13 @@ const SENTINEL: u32 = u32::MAX;
 /// Example: `RUST_LOG=runtime::revive=debug my_code --dev`
 const LOG_TARGET: &str = "runtime::revive";
 
+/// The maximum number of blocks simulated by a single [`Pallet::dry_run_eth_simulate`] call.
+pub const MAX_SIMULATED_BLOCKS: u32 = 256;
+
+/// The maximum number of calls simulated by a single [`Pallet::dry_run_eth_simulate`] call,
+/// across all blocks.
+pub const MAX_SIMULATED_CALLS: u32 = 1_000;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -1407,6 +1416,237 @@ where
 		Ok(result)
 	}
 
+	/// Create the access list of an Ethereum transaction.
+	///
+	/// The transaction is executed with `dry_run`, usually a wrapper around
+	/// [`Self::dry_run_eth_transact`], while recording the accounts and storage keys it accesses.
+	/// As in geth, the sender, the recipient and the pre-compiles are only listed when some of
+	/// their storage keys were accessed.
+	pub fn dry_run_eth_create_access_list(
+		tx: GenericTransaction,
+		dry_run: impl FnOnce(
+			GenericTransaction,
+		) -> Result<EthTransactInfo<BalanceOf<T>>, EthTransactError>,
+	) -> Result<AccessListResult, EthTransactError> {
+		use precompiles::{All, Precompiles};
+
+		let from = tx.from.unwrap_or_default();
+		let to = tx.to;
+
+		let mut tracer = AccessListTracer::default();
+		let info = trace(&mut tracer, || dry_run(tx))?;
+		let access_list = tracer.collect_access_list(|address| {
+			*address == from ||
+				Some(*address) == to ||
+				<All<T>>::code(address.as_fixed_bytes()).is_some()
+		});
+
+		Ok(AccessListResult { access_list, gas_used: info.eth_gas })
+	}
+
+	/// Simulate blocks of Ethereum calls on top of the current state.
+	///
+	/// The calls are executed sequentially with `dry_run`, usually a wrapper around
+	/// [`Self::dry_run_eth_transact`], each call seeing the state changes of the previous ones.
+	/// The state and block overrides of a block are applied before its calls. The calls of a
+	/// block can not use more gas than the block gas limit. At most [`MAX_SIMULATED_BLOCKS`]
+	/// blocks and [`MAX_SIMULATED_CALLS`] calls are simulated.
+	pub fn dry_run_eth_simulate(
+		payload: SimulatePayload,
+		dry_run: impl Fn(GenericTransaction) -> Result<EthTransactInfo<BalanceOf<T>>, EthTransactError>,
+	) -> Result<Vec<SimulatedBlock>, EthTransactError>
+	where
+		<T as frame_system::Config>::RuntimeCall:
+			Dispatchable<Info = frame_support::dispatch::DispatchInfo>,
+		T: pallet_transaction_payment::Config,
+		OnChargeTransactionBalanceOf<T>: Into<BalanceOf<T>>,
+	{
+		use sp_runtime::SaturatedConversion;
+
+		if payload.block_state_calls.len() > MAX_SIMULATED_BLOCKS as usize {
+			return Err(EthTransactError::Message(format!(
+				"Too many blocks, at most {MAX_SIMULATED_BLOCKS} blocks can be simulated"
+			)));
+		}
+		let calls = payload.block_state_calls.iter().map(|block| block.calls.len()).sum::<usize>();
+		if calls > MAX_SIMULATED_CALLS as usize {
+			return Err(EthTransactError::Message(format!(
+				"Too many calls, at most {MAX_SIMULATED_CALLS} calls can be simulated"
+			)));
+		}
+
+		let mut number: u64 = <frame_system::Pallet<T>>::block_number().saturated_into();
+		let now: U256 = T::Time::now().into();
+		let mut timestamp = now / 1000;
+		let mut blocks = Vec::with_capacity(payload.block_state_calls.len());
+
+		for SimulateBlock { block_overrides, state_overrides, calls } in payload.block_state_calls {
+			let overrides = block_overrides.unwrap_or_default();
+
+			let next_number = match overrides.number {
+				Some(n) => u64::try_from(n)
+					.map_err(|_| EthTransactError::Message("Invalid block number".into()))?,
+				None => number.saturating_add(1),
+			};
+			if next_number <= number {
+				return Err(EthTransactError::Message(format!(
+					"Block number {next_number} must be greater than {number}"
+				)));
+			}
+			number = next_number;
+			<frame_system::Pallet<T>>::set_block_number(number.saturated_into());
+
+			if let Some(time) = overrides.time {
+				if time < timestamp {
+					return Err(EthTransactError::Message(format!(
+						"Block timestamp {time} must not be lower than {timestamp}"
+					)));
+				}
+				timestamp = time;
+			}
+
+			for (address, account_override) in state_overrides.unwrap_or_default() {
+				Self::apply_account_override(&address, account_override).map_err(|err| {
+					EthTransactError::Message(format!(
+						"Failed to apply the state override of {address:?}: {err:?}"
+					))
+				})?;
+			}
+
+			// The timestamp is carried over to the next blocks, so that their calls run at the time
+			// the blocks report.
+			let mut env = SimulatedBlockEnv {
+				timestamp: Some(timestamp),
+				fee_recipient: overrides.fee_recipient,
+				base_fee_per_gas: overrides.base_fee_per_gas,
+			};
+			let mut block = SimulatedBlock {
+				number: number.into(),
+				timestamp,
+				gas_limit: overrides.gas_limit.unwrap_or_else(Self::evm_block_gas_limit),
+				miner: overrides.fee_recipient.or_else(Self::block_author).unwrap_or_default(),
+				base_fee_per_gas: overrides.base_fee_per_gas.unwrap_or(GAS_PRICE.into()),
+				..Default::default()
+			};
+
+			for (transaction_index, tx) in calls.into_iter().enumerate() {
+				let gas_left = block.gas_limit.saturating_sub(block.gas_used);
+				if tx.gas.is_some_and(|gas| gas > gas_left) {
+					return Err(EthTransactError::Message(format!(
+						"Block gas limit exceeded by call {transaction_index}: {gas_left} gas left"
+					)));
+				}
+
+				let mut tracer = LogTracer::default();
+				let result =
+					simulated_block::using(&mut env, || trace(&mut tracer, || dry_run(tx)));
+
+				let mut call = match result {
+					Ok(info) => SimulateCallResult {
+						status: 1u32.into(),
+						return_data: info.data.into(),
+						gas_used: info.eth_gas,
+						logs: tracer.collect_logs(),
+						error: None,
+					},
+					Err(EthTransactError::Data(data)) => SimulateCallResult {
+						return_data: data.into(),
+						error: Some(SimulateCallError {
+							code: 3,
+							message: "execution reverted".into(),
+						}),
+						..Default::default()
+					},
+					Err(EthTransactError::Message(message)) if payload.validation =>
+						return Err(EthTransactError::Message(message)),
+					Err(EthTransactError::Message(message)) => SimulateCallResult {
+						error: Some(SimulateCallError { code: -32015, message }),
+						..Default::default()
+					},
+				};
+
+				if call.gas_used > gas_left {
+					return Err(EthTransactError::Message(format!(
+						"Block gas limit exceeded by call {transaction_index}: {gas_left} gas left"
+					)));
+				}
+
+				let first_log_index = block.calls.iter().map(|call| call.logs.len()).sum::<usize>();
+				for (log_index, log) in call.logs.iter_mut().enumerate() {
+					log.block_number = block.number;
+					log.transaction_index = transaction_index.into();
+					log.log_index = (first_log_index + log_index).into();
+				}
+
+				block.gas_used = block.gas_used.saturating_add(call.gas_used);
+				block.calls.push(call);
+			}
+
+			blocks.push(block);
+		}
+
+		Ok(blocks)
+	}
+
+	/// Apply the state override of an `eth_simulateV1` call to the account at `address`.
+	fn apply_account_override(
+		address: &H160,
+		account_override: AccountOverride,
+	) -> Result<(), DispatchError> {
+		let account_id = T::AddressMapper::to_account_id(address);
+
+		if let Some(balance) = account_override.balance {
+			let balance = Self::convert_evm_to_native(balance, ConversionPrecision::RoundUp)?;
+			T::Currency::set_balance(&account_id, balance);
+		}
+
+		if let Some(nonce) = account_override.nonce {
+			let nonce = u32::try_from(nonce).map_err(|_| DispatchError::Other("Invalid nonce"))?;
+			frame_system::Account::<T>::mutate(&account_id, |account| account.nonce = nonce.into());
+		}
+
+		if let Some(code) = account_override.code {
+			let mut blob = ContractBlob::<T>::from_code(code.0, account_id.clone())?;
+			blob.store_code(true)?;
+			let code_hash = *blob.code_hash();
+
+			let contract_info = match ContractInfoOf::<T>::get(address) {
+				Some(mut contract_info) => {
+					<CodeInfo<T>>::decrement_refcount(contract_info.code_hash)?;
+					contract_info.code_hash = code_hash;
+					contract_info
+				},
+				None =>
+					ContractInfo::new(address, System::<T>::account_nonce(&account_id), code_hash)?,
+			};
+			<CodeInfo<T>>::increment_refcount(code_hash)?;
+			ContractInfoOf::<T>::insert(address, contract_info);
+		}
+
+		if account_override.state.is_none() && account_override.state_diff.is_none() {
+			return Ok(())
+		}
+
+		let contract_info =
+			ContractInfoOf::<T>::get(address).ok_or(Error::<T>::ContractNotFound)?;
+		if account_override.state.is_some() {
+			let _ = frame_support::storage::child::clear_storage(
+				&contract_info.child_trie_info(),
+				None,
+				None,
+			);
+		}
+
+		for (key, value) in
+			account_override.state.into_iter().chain(account_override.state_diff).flatten()
+		{
+			let value = (!value.is_zero()).then(|| value.0.to_vec());
+			contract_info.write(&Key::from_fixed(key.0), value, None, false)?;
+		}
+
+		Ok(())
+	}
+
 	/// Get the balance with EVM decimals of the given `address`.
 	pub fn evm_balance(address: &H160) -> U256 {
 		let account = T::AddressMapper::to_account_id(&address);
@@ -1486,6 +1726,12 @@ where
 			.into(),
 			TracerType::PrestateTracer(config) =>
 				PrestateTracer::new(config.unwrap_or_default()).into(),
+			TracerType::StructLogger(config) => StructLogger::new(
+				config.unwrap_or_default(),
+				Self::evm_gas_from_weight as fn(Weight) -> U256,
+			)
+			.into(),
+			TracerType::FourByteTracer => FourByteTracer::default().into(),
 		}
 	}
 
@@ -1524,6 +1770,37 @@ where
 		Ok(maybe_value)
 	}
 
+	/// Returns the storage keys proving the account at `address` and the given storage slots.
+	///
+	/// See [`AccountProofKeys`] for how the proofs are generated.
+	pub fn account_proof_keys(address: H160, storage_keys: Vec<[u8; 32]>) -> AccountProofKeys {
+		let account_id = T::AddressMapper::to_account_id(&address);
+		let mut account_keys = vec![frame_system::Account::<T>::hashed_key_for(&account_id)];
+
+		let Some(contract_info) = ContractInfoOf::<T>::get(&address) else {
+			return AccountProofKeys {
+				account_keys,
+				// The root of an empty trie is the hash of the empty node.
+				storage_root: sp_io::hashing::blake2_256(&[0u8]).into(),
+				code_hash: exec::EMPTY_CODE_HASH,
+				..Default::default()
+			}
+		};
+
+		account_keys.push(ContractInfoOf::<T>::hashed_key_for(&address));
+		let child_info = contract_info.child_trie_info();
+		let state_version = <T as frame_system::Config>::Version::get().state_version();
+		let storage_root = frame_support::storage::child::root(&child_info, state_version);
+
+		AccountProofKeys {
+			account_keys,
+			child_trie_key: Some(child_info.prefixed_storage_key().into_inner()),
+			storage_keys: storage_keys.into_iter().map(|key| Key::from_fixed(key).hash()).collect(),
+			storage_root: H256::from_slice(&storage_root),
+			code_hash: contract_info.code_hash,
+		}
+	}
+
 	/// Uploads new code and returns the Vm binary contract blob and deposit amount collected.
 	fn try_upload_code(
 		origin: T::AccountId,
@@ -1597,6 +1874,10 @@ impl<T: Config> Pallet<T> {
 	pub fn block_author() -> Option<H160> {
 		use frame_support::traits::FindAuthor;
 
+		if let Some(fee_recipient) = simulated_block::with(|env| env.fee_recipient).flatten() {
+			return Some(fee_recipient)
+		}
+
 		let digest = <frame_system::Pallet<T>>::digest();
 		let pre_runtime_digests = digest.logs.iter().filter_map(|d| d.as_pre_runtime());
 
@@ -1629,9 +1910,40 @@ pub const RUNTIME_PALLETS_ADDR: H160 =
 // Set up a global reference to the boolean flag used for the re-entrancy guard.
 environmental!(executing_contract: bool);
 
+/// The block header values overridden by the block being simulated by
+/// [`Pallet::dry_run_eth_simulate`].
+#[derive(Default, Clone)]
+pub(crate) struct SimulatedBlockEnv {
+	/// The timestamp of the block, in seconds.
+	pub timestamp: Option<U256>,
+	/// The author of the block.
+	pub fee_recipient: Option<H160>,
+	/// The base fee per gas of the block.
+	pub base_fee_per_gas: Option<U256>,
+}
+
+/// A global reference to the header overrides of the block being simulated.
+///
+/// It lives in its own module as `environmental!` only supports one global per module.
+mod simulated_block {
+	use super::SimulatedBlockEnv;
+
+	environmental::environmental!(simulated_block: SimulatedBlockEnv);
+
+	/// Run `f` while simulating a block with the header values of `env`.
+	pub(crate) fn using<R>(env: &mut SimulatedBlockEnv, f: impl FnOnce() -> R) -> R {
+		simulated_block::using(env, f)
+	}
+
+	/// Access the header overrides of the block being simulated, if any.
+	pub(crate) fn with<R>(f: impl FnOnce(&mut SimulatedBlockEnv) -> R) -> Option<R> {
+		simulated_block::with(f)
+	}
+}
+
 sp_api::decl_runtime_apis! {
 	/// The API used to dry-run contract interactions.
-	#[api_version(1)]
+	#[api_version(2)]
 	pub trait ReviveApi<AccountId, Balance, Nonce, BlockNumber> where
 		AccountId: Codec,
 		Balance: Codec,
@@ -1681,6 +1993,18 @@ sp_api::decl_runtime_apis! {
 		/// See [`crate::Pallet::dry_run_eth_transact`]
 		fn eth_transact(tx: GenericTransaction) -> Result<EthTransactInfo<Balance>, EthTransactError>;
 
+		/// Simulate blocks of Ethereum calls, with state and block overrides.
+		///
+		/// See [`crate::Pallet::dry_run_eth_simulate`].
+		#[api_version(2)]
+		fn eth_simulate(payload: SimulatePayload) -> Result<Vec<SimulatedBlock>, EthTransactError>;
+
+		/// Create the access list of an Ethereum transaction.
+		///
+		/// See [`crate::Pallet::dry_run_eth_create_access_list`].
+		#[api_version(2)]
+		fn eth_create_access_list(tx: GenericTransaction) -> Result<AccessListResult, EthTransactError>;
+
 		/// Upload new code without instantiating a contract from it.
 		///
 		/// See [`crate::Pallet::bare_upload_code`].
@@ -1749,6 +2073,12 @@ sp_api::decl_runtime_apis! {
 
 		/// The code at the specified address taking pre-compiles into account.
 		fn code(address: H160) -> Vec<u8>;
+
+		/// Returns the storage keys proving the account at `address` and the given storage slots.
+		///
+		/// See eth-rpc `eth_getProof` for usage.
+		#[api_version(2)]
+		fn account_proof_keys(address: H160, storage_keys: Vec<[u8; 32]>) -> AccountProofKeys;
 	}
 }
 
@@ -1828,6 +2158,18 @@ macro_rules! impl_runtime_apis_plus_revive {
 					$crate::Pallet::<Self>::dry_run_eth_transact(tx, blockweights.max_block, tx_fee)
 				}
 
+				fn eth_simulate(
+					payload: $crate::evm::SimulatePayload,
+				) -> Result<Vec<$crate::evm::SimulatedBlock>, $crate::EthTransactError> {
+					$crate::Pallet::<Self>::dry_run_eth_simulate(payload, Self::eth_transact)
+				}
+
+				fn eth_create_access_list(
+					tx: $crate::evm::GenericTransaction,
+				) -> Result<$crate::evm::AccessListResult, $crate::EthTransactError> {
+					$crate::Pallet::<Self>::dry_run_eth_create_access_list(tx, Self::eth_transact)
+				}
+
 				fn call(
 					origin: AccountId,
 					dest: $crate::H160,
@@ -1975,6 +2317,13 @@ macro_rules! impl_runtime_apis_plus_revive {
 				fn code(address: $crate::H160) -> Vec<u8> {
 					$crate::Pallet::<Self>::code(&address)
 				}
+
+				fn account_proof_keys(
+					address: $crate::H160,
+					storage_keys: Vec<[u8; 32]>,
+				) -> $crate::AccountProofKeys {
+					$crate::Pallet::<Self>::account_proof_keys(address, storage_keys)
+				}
 			}
 		}
 	};
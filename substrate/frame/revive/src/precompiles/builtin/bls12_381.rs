@@ -0,0 +1,438 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The BLS12-381 pre-compiles of [EIP-2537](https://eips.ethereum.org/EIPS/eip-2537).
+
+use crate::{
+	precompiles::{BuiltinAddressMatcher, Error, Ext, PrimitivePrecompile},
+	vm::RuntimeCosts,
+	Config,
+};
+use alloc::vec::Vec;
+use ark_bls12_381::{
+	g1, g2, Bls12_381, Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine, G2Projective,
+};
+use ark_ec::{
+	hashing::{curve_maps::wb::WBMap, map_to_curve_hasher::MapToCurve},
+	pairing::Pairing,
+	AffineRepr, CurveGroup, VariableBaseMSM,
+};
+use ark_ff::{BigInt, BigInteger, One, PrimeField, Zero};
+use core::{marker::PhantomData, num::NonZero};
+use sp_core::U256;
+use sp_runtime::DispatchError;
+
+/// Length of an encoded field element, which is left padded to 64 bytes.
+const FQ_LENGTH: usize = 64;
+/// Number of zero bytes padding an encoded field element.
+const FQ_PADDING_LENGTH: usize = 16;
+/// Length of an encoded element of the quadratic extension field.
+const FQ2_LENGTH: usize = 2 * FQ_LENGTH;
+/// Length of an encoded G1 point.
+const G1_LENGTH: usize = 2 * FQ_LENGTH;
+/// Length of an encoded G2 point.
+const G2_LENGTH: usize = 2 * FQ2_LENGTH;
+/// Length of an encoded scalar.
+const SCALAR_LENGTH: usize = 32;
+
+pub struct Bls12381G1Add<T>(PhantomData<T>);
+
+impl<T: Config> PrimitivePrecompile for Bls12381G1Add<T> {
+	type T = T;
+	const MATCHER: BuiltinAddressMatcher =
+		BuiltinAddressMatcher::Fixed(NonZero::new(0x0b).unwrap());
+	const HAS_CONTRACT_INFO: bool = false;
+
+	fn call(
+		_address: &[u8; 20],
+		input: Vec<u8>,
+		env: &mut impl Ext<T = Self::T>,
+	) -> Result<Vec<u8>, Error> {
+		env.gas_meter_mut().charge(RuntimeCosts::Bls12381G1Add)?;
+
+		if input.len() != 2 * G1_LENGTH {
+			Err(DispatchError::from("invalid input length"))?;
+		}
+
+		// No subgroup check is required for the addition.
+		let p1 = read_g1(&input[..G1_LENGTH], false)?;
+		let p2 = read_g1(&input[G1_LENGTH..], false)?;
+
+		Ok(encode_g1((p1 + p2).into_affine()))
+	}
+}
+
+pub struct Bls12381G1Msm<T>(PhantomData<T>);
+
+impl<T: Config> PrimitivePrecompile for Bls12381G1Msm<T> {
+	type T = T;
+	const MATCHER: BuiltinAddressMatcher =
+		BuiltinAddressMatcher::Fixed(NonZero::new(0x0c).unwrap());
+	const HAS_CONTRACT_INFO: bool = false;
+
+	fn call(
+		_address: &[u8; 20],
+		input: Vec<u8>,
+		env: &mut impl Ext<T = Self::T>,
+	) -> Result<Vec<u8>, Error> {
+		const PAIR_LENGTH: usize = G1_LENGTH + SCALAR_LENGTH;
+
+		if input.is_empty() || input.len() % PAIR_LENGTH != 0 {
+			Err(DispatchError::from("invalid input length"))?;
+		}
+
+		let pairs = input.len() / PAIR_LENGTH;
+		env.gas_meter_mut().charge(RuntimeCosts::Bls12381G1Msm(pairs as u32))?;
+
+		let mut points = Vec::with_capacity(pairs);
+		let mut scalars = Vec::with_capacity(pairs);
+		for pair in input.chunks_exact(PAIR_LENGTH) {
+			points.push(read_g1(&pair[..G1_LENGTH], true)?);
+			scalars.push(read_scalar(&pair[G1_LENGTH..]));
+		}
+
+		Ok(encode_g1(G1Projective::msm_unchecked(&points, &scalars).into_affine()))
+	}
+}
+
+pub struct Bls12381G2Add<T>(PhantomData<T>);
+
+impl<T: Config> PrimitivePrecompile for Bls12381G2Add<T> {
+	type T = T;
+	const MATCHER: BuiltinAddressMatcher =
+		BuiltinAddressMatcher::Fixed(NonZero::new(0x0d).unwrap());
+	const HAS_CONTRACT_INFO: bool = false;
+
+	fn call(
+		_address: &[u8; 20],
+		input: Vec<u8>,
+		env: &mut impl Ext<T = Self::T>,
+	) -> Result<Vec<u8>, Error> {
+		env.gas_meter_mut().charge(RuntimeCosts::Bls12381G2Add)?;
+
+		if input.len() != 2 * G2_LENGTH {
+			Err(DispatchError::from("invalid input length"))?;
+		}
+
+		// No subgroup check is required for the addition.
+		let p1 = read_g2(&input[..G2_LENGTH], false)?;
+		let p2 = read_g2(&input[G2_LENGTH..], false)?;
+
+		Ok(encode_g2((p1 + p2).into_affine()))
+	}
+}
+
+pub struct Bls12381G2Msm<T>(PhantomData<T>);
+
+impl<T: Config> PrimitivePrecompile for Bls12381G2Msm<T> {
+	type T = T;
+	const MATCHER: BuiltinAddressMatcher =
+		BuiltinAddressMatcher::Fixed(NonZero::new(0x0e).unwrap());
+	const HAS_CONTRACT_INFO: bool = false;
+
+	fn call(
+		_address: &[u8; 20],
+		input: Vec<u8>,
+		env: &mut impl Ext<T = Self::T>,
+	) -> Result<Vec<u8>, Error> {
+		const PAIR_LENGTH: usize = G2_LENGTH + SCALAR_LENGTH;
+
+		if input.is_empty() || input.len() % PAIR_LENGTH != 0 {
+			Err(DispatchError::from("invalid input length"))?;
+		}
+
+		let pairs = input.len() / PAIR_LENGTH;
+		env.gas_meter_mut().charge(RuntimeCosts::Bls12381G2Msm(pairs as u32))?;
+
+		let mut points = Vec::with_capacity(pairs);
+		let mut scalars = Vec::with_capacity(pairs);
+		for pair in input.chunks_exact(PAIR_LENGTH) {
+			points.push(read_g2(&pair[..G2_LENGTH], true)?);
+			scalars.push(read_scalar(&pair[G2_LENGTH..]));
+		}
+
+		Ok(encode_g2(G2Projective::msm_unchecked(&points, &scalars).into_affine()))
+	}
+}
+
+pub struct Bls12381Pairing<T>(PhantomData<T>);
+
+impl<T: Config> PrimitivePrecompile for Bls12381Pairing<T> {
+	type T = T;
+	const MATCHER: BuiltinAddressMatcher =
+		BuiltinAddressMatcher::Fixed(NonZero::new(0x0f).unwrap());
+	const HAS_CONTRACT_INFO: bool = false;
+
+	fn call(
+		_address: &[u8; 20],
+		input: Vec<u8>,
+		env: &mut impl Ext<T = Self::T>,
+	) -> Result<Vec<u8>, Error> {
+		const PAIR_LENGTH: usize = G1_LENGTH + G2_LENGTH;
+
+		if input.is_empty() || input.len() % PAIR_LENGTH != 0 {
+			Err(DispatchError::from("invalid input length"))?;
+		}
+
+		let pairs = input.len() / PAIR_LENGTH;
+		env.gas_meter_mut().charge(RuntimeCosts::Bls12381Pairing(pairs as u32))?;
+
+		let mut g1_points = Vec::with_capacity(pairs);
+		let mut g2_points = Vec::with_capacity(pairs);
+		for pair in input.chunks_exact(PAIR_LENGTH) {
+			g1_points.push(read_g1(&pair[..G1_LENGTH], true)?);
+			g2_points.push(read_g2(&pair[G1_LENGTH..], true)?);
+		}
+
+		let ret_val = if Bls12_381::multi_pairing(g1_points, g2_points).0.is_one() {
+			U256::one()
+		} else {
+			U256::zero()
+		};
+
+		Ok(ret_val.to_big_endian().to_vec())
+	}
+}
+
+pub struct Bls12381MapFpToG1<T>(PhantomData<T>);
+
+impl<T: Config> PrimitivePrecompile for Bls12381MapFpToG1<T> {
+	type T = T;
+	const MATCHER: BuiltinAddressMatcher =
+		BuiltinAddressMatcher::Fixed(NonZero::new(0x10).unwrap());
+	const HAS_CONTRACT_INFO: bool = false;
+
+	fn call(
+		_address: &[u8; 20],
+		input: Vec<u8>,
+		env: &mut impl Ext<T = Self::T>,
+	) -> Result<Vec<u8>, Error> {
+		env.gas_meter_mut().charge(RuntimeCosts::Bls12381MapFpToG1)?;
+
+		if input.len() != FQ_LENGTH {
+			Err(DispatchError::from("invalid input length"))?;
+		}
+
+		let fq = read_fq(&input)?;
+		let point = WBMap::<g1::Config>::new()
+			.and_then(|map| map.map_to_curve(fq))
+			.map_err(|_| DispatchError::from("could not map field element to curve"))?;
+
+		Ok(encode_g1(point.clear_cofactor()))
+	}
+}
+
+pub struct Bls12381MapFp2ToG2<T>(PhantomData<T>);
+
+impl<T: Config> PrimitivePrecompile for Bls12381MapFp2ToG2<T> {
+	type T = T;
+	const MATCHER: BuiltinAddressMatcher =
+		BuiltinAddressMatcher::Fixed(NonZero::new(0x11).unwrap());
+	const HAS_CONTRACT_INFO: bool = false;
+
+	fn call(
+		_address: &[u8; 20],
+		input: Vec<u8>,
+		env: &mut impl Ext<T = Self::T>,
+	) -> Result<Vec<u8>, Error> {
+		env.gas_meter_mut().charge(RuntimeCosts::Bls12381MapFp2ToG2)?;
+
+		if input.len() != FQ2_LENGTH {
+			Err(DispatchError::from("invalid input length"))?;
+		}
+
+		let fq2 = read_fq2(&input)?;
+		let point = WBMap::<g2::Config>::new()
+			.and_then(|map| map.map_to_curve(fq2))
+			.map_err(|_| DispatchError::from("could not map field element to curve"))?;
+
+		Ok(encode_g2(point.clear_cofactor()))
+	}
+}
+
+/// Decode a padded big endian field element.
+fn read_fq(input: &[u8]) -> Result<Fq, DispatchError> {
+	let (padding, value) = input.split_at(FQ_PADDING_LENGTH);
+	if padding.iter().any(|byte| *byte != 0) {
+		Err("invalid field element top bytes")?;
+	}
+
+	let mut limbs = [0u64; 6];
+	for (limb, chunk) in limbs.iter_mut().zip(value.rchunks_exact(8)) {
+		*limb = u64::from_be_bytes(chunk.try_into().expect("chunk is 8-byte length; qed"));
+	}
+
+	Fq::from_bigint(BigInt(limbs)).ok_or_else(|| "invalid fp.Element encoding".into())
+}
+
+/// Decode an element of the quadratic extension field, encoded as `c0 || c1`.
+fn read_fq2(input: &[u8]) -> Result<Fq2, DispatchError> {
+	let c0 = read_fq(&input[..FQ_LENGTH])?;
+	let c1 = read_fq(&input[FQ_LENGTH..FQ2_LENGTH])?;
+	Ok(Fq2::new(c0, c1))
+}
+
+/// Decode a G1 point encoded as `x || y`, the point at infinity being encoded as zeros.
+fn read_g1(input: &[u8], subgroup_check: bool) -> Result<G1Affine, DispatchError> {
+	let x = read_fq(&input[..FQ_LENGTH])?;
+	let y = read_fq(&input[FQ_LENGTH..G1_LENGTH])?;
+	if x.is_zero() && y.is_zero() {
+		return Ok(G1Affine::zero());
+	}
+
+	let point = G1Affine::new_unchecked(x, y);
+	if !point.is_on_curve() {
+		Err("invalid point: not on curve")?;
+	}
+	if subgroup_check && !point.is_in_correct_subgroup_assuming_on_curve() {
+		Err("g1 point is not on correct subgroup")?;
+	}
+
+	Ok(point)
+}
+
+/// Decode a G2 point encoded as `x || y`, the point at infinity being encoded as zeros.
+fn read_g2(input: &[u8], subgroup_check: bool) -> Result<G2Affine, DispatchError> {
+	let x = read_fq2(&input[..FQ2_LENGTH])?;
+	let y = read_fq2(&input[FQ2_LENGTH..G2_LENGTH])?;
+	if x.is_zero() && y.is_zero() {
+		return Ok(G2Affine::zero());
+	}
+
+	let point = G2Affine::new_unchecked(x, y);
+	if !point.is_on_curve() {
+		Err("invalid point: not on curve")?;
+	}
+	if subgroup_check && !point.is_in_correct_subgroup_assuming_on_curve() {
+		Err("g2 point is not on correct subgroup")?;
+	}
+
+	Ok(point)
+}
+
+/// Decode a big endian scalar.
+///
+/// Scalars are not required to be lower than the group order, which is fine as they are only
+/// multiplied with points of the prime order subgroup.
+fn read_scalar(input: &[u8]) -> Fr {
+	Fr::from_be_bytes_mod_order(&input[..SCALAR_LENGTH])
+}
+
+/// Encode a field element as padded big endian bytes.
+fn encode_fq(fq: &Fq, output: &mut Vec<u8>) {
+	output.extend_from_slice(&[0u8; FQ_PADDING_LENGTH]);
+	output.extend_from_slice(&fq.into_bigint().to_bytes_be());
+}
+
+/// Encode a G1 point, the point at infinity being encoded as zeros.
+fn encode_g1(point: G1Affine) -> Vec<u8> {
+	let mut output = Vec::with_capacity(G1_LENGTH);
+	match point.xy() {
+		Some((x, y)) => {
+			encode_fq(x, &mut output);
+			encode_fq(y, &mut output);
+		},
+		None => output.resize(G1_LENGTH, 0),
+	}
+	output
+}
+
+/// Encode a G2 point, the point at infinity being encoded as zeros.
+fn encode_g2(point: G2Affine) -> Vec<u8> {
+	let mut output = Vec::with_capacity(G2_LENGTH);
+	match point.xy() {
+		Some((x, y)) => {
+			encode_fq(&x.c0, &mut output);
+			encode_fq(&x.c1, &mut output);
+			encode_fq(&y.c0, &mut output);
+			encode_fq(&y.c1, &mut output);
+		},
+		None => output.resize(G2_LENGTH, 0),
+	}
+	output
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		precompiles::tests::{run_failure_test_vectors, run_test_vectors},
+		tests::Test,
+	};
+
+	#[test]
+	fn test_bls12381_g1_add() {
+		run_test_vectors::<Bls12381G1Add<Test>>(include_str!("./testdata/b-bls12381g1add.json"));
+		run_failure_test_vectors::<Bls12381G1Add<Test>>(include_str!(
+			"./testdata/b-bls12381g1add-failure.json"
+		));
+	}
+
+	#[test]
+	fn test_bls12381_g1_msm() {
+		run_test_vectors::<Bls12381G1Msm<Test>>(include_str!("./testdata/c-bls12381g1msm.json"));
+		run_failure_test_vectors::<Bls12381G1Msm<Test>>(include_str!(
+			"./testdata/c-bls12381g1msm-failure.json"
+		));
+	}
+
+	#[test]
+	fn test_bls12381_g2_add() {
+		run_test_vectors::<Bls12381G2Add<Test>>(include_str!("./testdata/d-bls12381g2add.json"));
+		run_failure_test_vectors::<Bls12381G2Add<Test>>(include_str!(
+			"./testdata/d-bls12381g2add-failure.json"
+		));
+	}
+
+	#[test]
+	fn test_bls12381_g2_msm() {
+		run_test_vectors::<Bls12381G2Msm<Test>>(include_str!("./testdata/e-bls12381g2msm.json"));
+		run_failure_test_vectors::<Bls12381G2Msm<Test>>(include_str!(
+			"./testdata/e-bls12381g2msm-failure.json"
+		));
+	}
+
+	#[test]
+	fn test_bls12381_pairing() {
+		run_test_vectors::<Bls12381Pairing<Test>>(include_str!(
+			"./testdata/f-bls12381pairing.json"
+		));
+		run_failure_test_vectors::<Bls12381Pairing<Test>>(include_str!(
+			"./testdata/f-bls12381pairing-failure.json"
+		));
+	}
+
+	#[test]
+	fn test_bls12381_map_fp_to_g1() {
+		run_test_vectors::<Bls12381MapFpToG1<Test>>(include_str!(
+			"./testdata/10-bls12381mapg1.json"
+		));
+		run_failure_test_vectors::<Bls12381MapFpToG1<Test>>(include_str!(
+			"./testdata/10-bls12381mapg1-failure.json"
+		));
+	}
+
+	#[test]
+	fn test_bls12381_map_fp2_to_g2() {
+		run_test_vectors::<Bls12381MapFp2ToG2<Test>>(include_str!(
+			"./testdata/11-bls12381mapg2.json"
+		));
+		run_failure_test_vectors::<Bls12381MapFp2ToG2<Test>>(include_str!(
+			"./testdata/11-bls12381mapg2-failure.json"
+		));
+	}
+}
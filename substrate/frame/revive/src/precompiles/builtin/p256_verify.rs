@@ -0,0 +1,83 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The secp256r1 signature verification pre-compile of
+//! [RIP-7212](https://github.com/ethereum/RIPs/blob/master/RIPS/rip-7212.md).
+
+use crate::{
+	precompiles::{BuiltinAddressMatcher, Error, Ext, PrimitivePrecompile},
+	vm::RuntimeCosts,
+	Config,
+};
+use alloc::vec::Vec;
+use core::{marker::PhantomData, num::NonZero};
+use p256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
+use sp_core::U256;
+
+pub struct P256Verify<T>(PhantomData<T>);
+
+impl<T: Config> PrimitivePrecompile for P256Verify<T> {
+	type T = T;
+	const MATCHER: BuiltinAddressMatcher =
+		BuiltinAddressMatcher::Fixed(NonZero::new(0x100).unwrap());
+	const HAS_CONTRACT_INFO: bool = false;
+
+	fn call(
+		_address: &[u8; 20],
+		input: Vec<u8>,
+		env: &mut impl Ext<T = Self::T>,
+	) -> Result<Vec<u8>, Error> {
+		env.gas_meter_mut().charge(RuntimeCosts::P256Verify)?;
+
+		// An invalid input or signature returns empty data instead of failing the call.
+		if verify(&input).is_some() {
+			Ok(U256::one().to_big_endian().to_vec())
+		} else {
+			Ok(Vec::new())
+		}
+	}
+}
+
+/// Verify the signature of `input`, encoded as `hash || r || s || x || y`.
+fn verify(input: &[u8]) -> Option<()> {
+	if input.len() != 160 {
+		return None;
+	}
+
+	let (hash, rest) = input.split_at(32);
+	let (signature, public_key) = rest.split_at(64);
+
+	// The public key is given as uncompressed coordinates without the SEC1 tag.
+	let mut encoded_key = [0u8; 65];
+	encoded_key[0] = 0x04;
+	encoded_key[1..].copy_from_slice(public_key);
+
+	let signature = Signature::from_slice(signature).ok()?;
+	let public_key = VerifyingKey::from_sec1_bytes(&encoded_key).ok()?;
+	public_key.verify_prehash(hash, &signature).ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{precompiles::tests::run_test_vectors, tests::Test};
+
+	#[test]
+	fn test_p256_verify() {
+		run_test_vectors::<P256Verify<Test>>(include_str!("./testdata/100-p256verify.json"));
+	}
+}
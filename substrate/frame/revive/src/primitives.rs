@@ -17,7 +17,7 @@
 
 //! A crate that hosts a common definitions that are relevant for the pallet-revive.
 
-use crate::{H160, U256};
+use crate::{H160, H256, U256};
 use alloc::{string::String, vec::Vec};
 use codec::{Decode, Encode, MaxEncodedLen};
 use frame_support::weights::Weight;
@@ -122,6 +122,28 @@ pub type CodeUploadResult<Balance> = Result<CodeUploadReturnValue<Balance>, Disp
 /// Result type of a `get_storage` call.
 pub type GetStorageResult = Result<Option<Vec<u8>>, ContractAccessError>;
 
+/// The storage keys proving an account and some of its storage slots.
+///
+/// The keys are proven by the node with the `state_getReadProof` and `state_getChildReadProof`
+/// RPCs. The resulting proofs are against the Substrate state root of the block rather than the
+/// root of an Ethereum Patricia-Merkle trie.
+#[derive(Clone, Eq, PartialEq, Default, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct AccountProofKeys {
+	/// The keys of the account in the main trie: the `frame_system` account, and the contract
+	/// info for contracts.
+	pub account_keys: Vec<Vec<u8>>,
+	/// The prefixed storage key of the contract child trie, `None` if the account is not a
+	/// contract.
+	pub child_trie_key: Option<Vec<u8>>,
+	/// The hashed keys of the requested storage slots in the contract child trie. Empty if the
+	/// account is not a contract.
+	pub storage_keys: Vec<Vec<u8>>,
+	/// The root of the contract child trie, or the root of an empty trie.
+	pub storage_root: H256,
+	/// The hash of the contract code, or the hash of the empty code.
+	pub code_hash: H256,
+}
+
 /// The possible errors that can happen querying the storage of a contract.
 #[derive(Copy, Clone, Eq, PartialEq, Encode, Decode, MaxEncodedLen, RuntimeDebug, TypeInfo)]
 pub enum ContractAccessError {
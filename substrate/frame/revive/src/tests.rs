@@ -3717,6 +3717,252 @@ fn to_account_id_works() {
 	});
 }
 
+#[test]
+fn eth_simulate_works() {
+	use crate::evm::*;
+	let (code, _code_hash) = compile_module("tracing").unwrap();
+	let (binary_callee, _) = compile_module("tracing_callee").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 100_000_000);
+
+		let Contract { addr: addr_callee, .. } =
+			builder::bare_instantiate(Code::Upload(binary_callee)).build_and_unwrap_contract();
+		let Contract { addr, .. } = builder::bare_instantiate(Code::Upload(code))
+			.value(10_000_000)
+			.build_and_unwrap_contract();
+
+		let dry_run = |tx| Pallet::<Test>::dry_run_eth_transact(tx, Weight::MAX, |_, _| 0u64);
+		let transfer = GenericTransaction {
+			from: Some(BOB_ADDR),
+			to: Some(ALICE_ADDR),
+			value: Some(Pallet::<Test>::convert_native_to_evm(1_000u64)),
+			gas: Some(1u32.into()),
+			..Default::default()
+		};
+		let call = GenericTransaction {
+			from: Some(ALICE_ADDR),
+			to: Some(addr),
+			input: (1u32, addr_callee).encode().into(),
+			..Default::default()
+		};
+
+		let payload = SimulatePayload {
+			block_state_calls: vec![
+				// Bob has no funds: the transfer fails.
+				SimulateBlock { calls: vec![transfer.clone()], ..Default::default() },
+				// The balance override funds Bob, and the logs of the call are returned.
+				SimulateBlock {
+					state_overrides: Some(
+						[(
+							BOB_ADDR,
+							AccountOverride {
+								balance: Some(Pallet::<Test>::convert_native_to_evm(1_000_000u64)),
+								..Default::default()
+							},
+						)]
+						.into(),
+					),
+					calls: vec![transfer, call],
+					..Default::default()
+				},
+			],
+			validation: false,
+		};
+
+		let number = System::block_number();
+		let blocks = Pallet::<Test>::dry_run_eth_simulate(payload.clone(), dry_run).unwrap();
+		assert_eq!(blocks.len(), 2);
+		assert_eq!(blocks[0].number, (number + 1).into());
+		assert_eq!(blocks[1].number, (number + 2).into());
+
+		assert_eq!(blocks[0].calls[0].status, U256::zero());
+		assert_eq!(blocks[0].calls[0].error.as_ref().map(|err| err.code), Some(-32015));
+
+		assert_eq!(blocks[1].calls[0].status, U256::one());
+		assert_eq!(blocks[1].calls[1].status, U256::one());
+		let logs = &blocks[1].calls[1].logs;
+		let data = logs.iter().map(|log| log.data.clone().unwrap().0).collect::<Vec<_>>();
+		assert_eq!(data, vec![b"before".to_vec(), b"after".to_vec()]);
+		assert!(logs.iter().all(|log| log.address == addr && log.transaction_index == 1.into()));
+
+		// Invalid calls abort the simulation when validation is enabled.
+		let payload = SimulatePayload { validation: true, ..payload };
+		assert!(Pallet::<Test>::dry_run_eth_simulate(payload, dry_run).is_err());
+
+		// Block numbers must be increasing.
+		let payload = SimulatePayload {
+			block_state_calls: vec![SimulateBlock {
+				block_overrides: Some(BlockOverrides {
+					number: Some(number.into()),
+					..Default::default()
+				}),
+				..Default::default()
+			}],
+			validation: false,
+		};
+		assert!(Pallet::<Test>::dry_run_eth_simulate(payload, dry_run).is_err());
+	});
+}
+
+#[test]
+fn eth_simulate_carries_state_over_calls_and_blocks() {
+	use crate::evm::*;
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 100_000_000);
+
+		let dry_run = |tx| Pallet::<Test>::dry_run_eth_transact(tx, Weight::MAX, |_, _| 0u64);
+		let transfer = |from, to, value: u64| GenericTransaction {
+			from: Some(from),
+			to: Some(to),
+			value: Some(Pallet::<Test>::convert_native_to_evm(value)),
+			..Default::default()
+		};
+
+		// Charlie and Django can only pay with the funds received by the previous calls.
+		let payload = SimulatePayload {
+			block_state_calls: vec![
+				SimulateBlock {
+					calls: vec![
+						transfer(ALICE_ADDR, CHARLIE_ADDR, 1_000_000),
+						transfer(CHARLIE_ADDR, DJANGO_ADDR, 100_000),
+					],
+					..Default::default()
+				},
+				SimulateBlock {
+					block_overrides: Some(BlockOverrides {
+						time: Some(1_000.into()),
+						fee_recipient: Some(EVE_ADDR),
+						base_fee_per_gas: Some(7.into()),
+						..Default::default()
+					}),
+					calls: vec![transfer(DJANGO_ADDR, BOB_ADDR, 10_000)],
+					..Default::default()
+				},
+			],
+			validation: true,
+		};
+
+		let blocks = Pallet::<Test>::dry_run_eth_simulate(payload, dry_run).unwrap();
+		assert!(blocks.iter().flat_map(|block| &block.calls).all(|call| call.status == 1.into()));
+		assert_eq!(blocks[1].timestamp, 1_000.into());
+		assert_eq!(blocks[1].miner, EVE_ADDR);
+		assert_eq!(blocks[1].base_fee_per_gas, 7.into());
+
+		// The calls of a block can not use more gas than the block gas limit.
+		let payload = SimulatePayload {
+			block_state_calls: vec![SimulateBlock {
+				block_overrides: Some(BlockOverrides {
+					gas_limit: Some(1.into()),
+					..Default::default()
+				}),
+				calls: vec![transfer(ALICE_ADDR, CHARLIE_ADDR, 1_000_000)],
+				..Default::default()
+			}],
+			validation: false,
+		};
+		assert!(Pallet::<Test>::dry_run_eth_simulate(payload, dry_run).is_err());
+
+		// The number of simulated blocks is capped.
+		let payload = SimulatePayload {
+			block_state_calls: vec![Default::default(); crate::MAX_SIMULATED_BLOCKS as usize + 1],
+			validation: false,
+		};
+		assert!(Pallet::<Test>::dry_run_eth_simulate(payload, dry_run).is_err());
+	});
+}
+
+#[test]
+fn eth_simulate_carries_timestamp_over_blocks() {
+	use crate::evm::*;
+	let (code, _) = compile_module("now").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 100_000_000);
+
+		let Contract { addr, .. } =
+			builder::bare_instantiate(Code::Upload(code)).build_and_unwrap_contract();
+
+		let dry_run = |tx| Pallet::<Test>::dry_run_eth_transact(tx, Weight::MAX, |_, _| 0u64);
+		let call =
+			GenericTransaction { from: Some(ALICE_ADDR), to: Some(addr), ..Default::default() };
+		let block = |time: Option<u64>| SimulateBlock {
+			block_overrides: Some(BlockOverrides {
+				time: time.map(Into::into),
+				..Default::default()
+			}),
+			calls: vec![call.clone()],
+			..Default::default()
+		};
+
+		// The blocks without a time override run at the time of the previous block.
+		let payload = SimulatePayload {
+			block_state_calls: vec![block(Some(1_000)), block(None), block(Some(2_000))],
+			validation: true,
+		};
+		let blocks = Pallet::<Test>::dry_run_eth_simulate(payload, dry_run).unwrap();
+		for (block, time) in blocks.iter().zip([1_000u64, 1_000, 2_000]) {
+			assert_eq!(block.timestamp, time.into());
+			let now = U256::from_little_endian(&block.calls[0].return_data.0);
+			assert_eq!(now, time.into());
+		}
+	});
+}
+
+#[test]
+fn eth_create_access_list_works() {
+	use crate::evm::*;
+	let (code, _code_hash) = compile_module("storage").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 100_000_000);
+
+		let Contract { addr, .. } =
+			builder::bare_instantiate(Code::Upload(code)).build_and_unwrap_contract();
+
+		let dry_run = |tx| Pallet::<Test>::dry_run_eth_transact(tx, Weight::MAX, |_, _| 0u64);
+		let tx =
+			GenericTransaction { from: Some(ALICE_ADDR), to: Some(addr), ..Default::default() };
+
+		let result = Pallet::<Test>::dry_run_eth_create_access_list(tx.clone(), dry_run).unwrap();
+		assert_eq!(
+			result.access_list,
+			vec![AccessListEntry { address: addr, storage_keys: vec![H256([1u8; 32])] }]
+		);
+		assert_eq!(result.gas_used, dry_run(tx).unwrap().eth_gas);
+	});
+}
+
+#[test]
+fn account_proof_keys_works() {
+	let (binary, code_hash) = compile_module("dummy").unwrap();
+
+	ExtBuilder::default().existential_deposit(1).build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 1_000_000);
+
+		let Contract { addr, account_id } =
+			builder::bare_instantiate(Code::Upload(binary)).build_and_unwrap_contract();
+
+		let slot = [1u8; 32];
+		let keys = Pallet::<Test>::account_proof_keys(addr, vec![slot]);
+		let child_info = get_contract(&addr).child_trie_info();
+		assert_eq!(
+			keys.account_keys,
+			vec![
+				frame_system::Account::<Test>::hashed_key_for(&account_id),
+				ContractInfoOf::<Test>::hashed_key_for(&addr),
+			]
+		);
+		assert_eq!(keys.child_trie_key, Some(child_info.prefixed_storage_key().into_inner()));
+		assert_eq!(keys.storage_keys, vec![Key::Fix(slot).hash()]);
+		assert_eq!(keys.code_hash, code_hash);
+
+		// Accounts that are not contracts have no storage.
+		let keys = Pallet::<Test>::account_proof_keys(BOB_ADDR, vec![slot]);
+		assert_eq!(keys.account_keys.len(), 1);
+		assert_eq!(keys.child_trie_key, None);
+		assert!(keys.storage_keys.is_empty());
+		assert_eq!(keys.code_hash, crate::exec::EMPTY_CODE_HASH);
+	});
+}
+
 #[test]
 fn code_hash_works() {
 	use crate::precompiles::{Precompile, EVM_REVERT};
@@ -4258,6 +4504,84 @@ fn call_tracing_works() {
 	});
 }
 
+#[test]
+fn four_byte_tracing_works() {
+	use crate::evm::*;
+	let (code, _code_hash) = compile_module("tracing").unwrap();
+	let (binary_callee, _) = compile_module("tracing_callee").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 100_000_000);
+
+		let Contract { addr: addr_callee, .. } =
+			builder::bare_instantiate(Code::Upload(binary_callee)).build_and_unwrap_contract();
+
+		let Contract { addr, .. } = builder::bare_instantiate(Code::Upload(code))
+			.value(10_000_000)
+			.build_and_unwrap_contract();
+
+		let mut tracer = FourByteTracer::default();
+		trace(&mut tracer, || builder::bare_call(addr).data((3u32, addr_callee).encode()).build());
+
+		// The value transfer to BOB has no input, hence no selector.
+		let expected = [
+			"0x00000000-0",
+			"0x00000000-20",
+			"0x01000000-0",
+			"0x01000000-20",
+			"0x02000000-0",
+			"0x02000000-20",
+			"0x03000000-20",
+		]
+		.into_iter()
+		.map(|key| (key.to_string(), 1))
+		.collect::<FourByteTrace>();
+		assert_eq!(tracer.collect_trace(), Some(expected));
+		assert_eq!(tracer.collect_trace(), None);
+	});
+}
+
+#[test]
+fn struct_logger_works() {
+	use crate::evm::*;
+	let (code, _code_hash) = compile_module("tracing").unwrap();
+	let (binary_callee, _) = compile_module("tracing_callee").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 100_000_000);
+
+		let Contract { addr: addr_callee, .. } =
+			builder::bare_instantiate(Code::Upload(binary_callee)).build_and_unwrap_contract();
+
+		let Contract { addr, .. } = builder::bare_instantiate(Code::Upload(code))
+			.value(10_000_000)
+			.build_and_unwrap_contract();
+
+		let config = StructLoggerConfig { enable_memory: true, ..Default::default() };
+		let mut tracer = StructLogger::new(config, |_| U256::zero());
+		trace(&mut tracer, || builder::bare_call(addr).data((1u32, addr_callee).encode()).build());
+		let trace = tracer.collect_trace().unwrap();
+
+		assert!(!trace.failed);
+		assert!(trace.struct_logs.iter().all(|log| log.stack.as_ref().unwrap().len() == 13));
+		assert!(trace.struct_logs.iter().all(|log| log.memory.is_some()));
+
+		// The top-level call and the nested calls are logged.
+		let max_depth = trace.struct_logs.iter().map(|log| log.depth).max();
+		assert_eq!(trace.struct_logs.first().map(|log| log.depth), Some(1));
+		assert_eq!(max_depth, Some(3));
+
+		// Host function calls are named after the syscall.
+		assert!(trace.struct_logs.iter().any(|log| log.op == "ecalli(call)"));
+
+		// The logged instructions can be limited.
+		let config = StructLoggerConfig { limit: 10, disable_stack: true, ..Default::default() };
+		let mut tracer = StructLogger::new(config, |_| U256::zero());
+		trace(&mut tracer, || builder::bare_call(addr).data((1u32, addr_callee).encode()).build());
+		let trace = tracer.collect_trace().unwrap();
+		assert_eq!(trace.struct_logs.len(), 10);
+		assert!(trace.struct_logs.iter().all(|log| log.stack.is_none() && log.memory.is_none()));
+	});
+}
+
 #[test]
 fn create_call_tracing_works() {
 	use crate::evm::*;
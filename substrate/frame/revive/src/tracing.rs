@@ -84,4 +84,24 @@ pub trait Tracing {
 
 	/// Called when a contract call terminates with an error
 	fn exit_child_span_with_error(&mut self, _error: DispatchError, _gas_left: Weight) {}
+
+	/// Whether [`Self::enter_opcode`] should be called for every executed instruction.
+	///
+	/// Step tracing slows down the execution considerably, it is only enabled when requested.
+	fn is_execution_tracing_enabled(&self) -> bool {
+		false
+	}
+
+	/// Called before an instruction is executed.
+	///
+	/// `gas_left` is the engine fuel left, and `memory` returns the current stack frame memory.
+	fn enter_opcode(
+		&mut self,
+		_pc: u32,
+		_opcode: &str,
+		_gas_left: u64,
+		_registers: &[u64],
+		_memory: &dyn Fn() -> Vec<u8>,
+	) {
+	}
 }
@@ -33,11 +33,17 @@ use crate::{
 	gas::{GasMeter, Token},
 	limits,
 	storage::meter::Diff,
+	tracing::if_tracing,
 	weights::WeightInfo,
 	AccountIdOf, BadOrigin, BalanceOf, CodeInfoOf, CodeVec, Config, Error, ExecError, HoldReason,
 	PristineCode, Weight, LOG_TARGET,
 };
-use alloc::vec::Vec;
+use alloc::{
+	collections::BTreeMap,
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
 use codec::{Decode, Encode, MaxEncodedLen};
 use frame_support::{
 	dispatch::DispatchResult,
@@ -288,6 +294,10 @@ pub struct PreparedCall<'a, E: Ext> {
 	module: polkavm::Module,
 	instance: polkavm::RawInstance,
 	runtime: Runtime<'a, E, polkavm::RawInstance>,
+	/// The name of the instruction at each program counter.
+	///
+	/// Only set when the active tracer records every executed instruction.
+	opcodes: Option<BTreeMap<u32, String>>,
 }
 
 impl<'a, E: Ext> PreparedCall<'a, E>
@@ -298,6 +308,9 @@ where
 	pub fn call(mut self) -> ExecResult {
 		let exec_result = loop {
 			let interrupt = self.instance.run();
+			if let (Ok(polkavm::InterruptKind::Step), Some(opcodes)) = (&interrupt, &self.opcodes) {
+				self.trace_step(opcodes);
+			}
 			if let Some(exec_result) =
 				self.runtime.handle_interrupt(interrupt, &self.module, &mut self.instance)
 			{
@@ -308,6 +321,22 @@ where
 		exec_result
 	}
 
+	/// Report the instruction about to be executed to the tracer.
+	fn trace_step(&self, opcodes: &BTreeMap<u32, String>) {
+		let Some(pc) = self.instance.program_counter() else { return };
+		let opcode = opcodes.get(&pc.0).map(String::as_str).unwrap_or("unknown");
+		let registers = polkavm::Reg::ALL.map(|reg| self.instance.reg(reg));
+		let gas_left = self.instance.gas().max(0) as u64;
+		let memory = || {
+			let stack_pointer = self.instance.reg(polkavm::Reg::SP) as u32;
+			let stack_high = self.instance.module().memory_map().stack_address_high();
+			self.instance
+				.read_memory(stack_pointer, stack_high.saturating_sub(stack_pointer))
+				.unwrap_or_default()
+		};
+		if_tracing(|tracer| tracer.enter_opcode(pc.0, opcode, gas_left, &registers, &memory));
+	}
+
 	/// The guest memory address at which the aux data is located.
 	#[cfg(feature = "runtime-benchmarks")]
 	pub fn aux_data_base(&self) -> u32 {
@@ -358,11 +387,16 @@ impl<T: Config> ContractBlob<T> {
 				interpreter is available on all platforms; qed",
 		);
 
+		let mut step_tracing = false;
+		if_tracing(|tracer| step_tracing = tracer.is_execution_tracing_enabled());
+		let opcodes = step_tracing.then(|| opcode_names(self.code.as_slice()));
+
 		let mut module_config = polkavm::ModuleConfig::new();
 		module_config.set_page_size(limits::PAGE_SIZE);
 		module_config.set_gas_metering(Some(polkavm::GasMeteringKind::Sync));
 		module_config.set_allow_sbrk(false);
 		module_config.set_aux_data_size(aux_data_size);
+		module_config.set_step_tracing(step_tracing);
 		let module = polkavm::Module::new(&engine, &module_config, self.code.into_inner().into())
 			.map_err(|err| {
 			log::debug!(target: LOG_TARGET, "failed to create polkavm module: {err:?}");
@@ -385,10 +419,30 @@ impl<T: Config> ContractBlob<T> {
 		instance.set_gas(gas_limit_polkavm);
 		instance.prepare_call_untyped(entry_program_counter, &[]);
 
-		Ok(PreparedCall { module, instance, runtime })
+		Ok(PreparedCall { module, instance, runtime, opcodes })
 	}
 }
 
+/// Map the program counter of every instruction of `code` to a human readable name.
+///
+/// Host function calls are named after the called syscall. Only used for tracing.
+fn opcode_names(code: &[u8]) -> BTreeMap<u32, String> {
+	let Ok(program) = polkavm::ProgramBlob::parse(code.into()) else { return Default::default() };
+	program
+		.instructions(polkavm::program::ISA64_V1)
+		.map(|inst| {
+			let name = match inst.kind {
+				polkavm::program::Instruction::ecalli(idx) => match program.imports().get(idx) {
+					Some(symbol) => format!("ecalli({symbol})"),
+					None => "ecalli".to_string(),
+				},
+				_ => format!("{:?}", inst.kind.opcode()),
+			};
+			(inst.offset.0, name)
+		})
+		.collect()
+}
+
 impl<T: Config> Executable<T> for ContractBlob<T>
 where
 	BalanceOf<T>: Into<U256> + TryFrom<U256>,
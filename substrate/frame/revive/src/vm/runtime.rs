@@ -382,6 +382,22 @@ pub enum RuntimeCosts {
 	Blake2F(u32),
 	/// Weight of calling `Modexp` precompile
 	Modexp(u64),
+	/// Weight of calling `Bls12381G1Add` precompile.
+	Bls12381G1Add,
+	/// Weight of calling `Bls12381G1Msm` precompile for the given number of input pairs.
+	Bls12381G1Msm(u32),
+	/// Weight of calling `Bls12381G2Add` precompile.
+	Bls12381G2Add,
+	/// Weight of calling `Bls12381G2Msm` precompile for the given number of input pairs.
+	Bls12381G2Msm(u32),
+	/// Weight of calling `Bls12381Pairing` precompile for the given number of input pairs.
+	Bls12381Pairing(u32),
+	/// Weight of calling `Bls12381MapFpToG1` precompile.
+	Bls12381MapFpToG1,
+	/// Weight of calling `Bls12381MapFp2ToG2` precompile.
+	Bls12381MapFp2ToG2,
+	/// Weight of calling `P256Verify` precompile.
+	P256Verify,
 }
 
 /// For functions that modify storage, benchmarks are performed with one item in the
@@ -515,6 +531,14 @@ impl<T: Config> Token<T> for RuntimeCosts {
 			Bn128Pairing(len) => T::WeightInfo::bn128_pairing(len),
 			Identity(len) => T::WeightInfo::identity(len),
 			Blake2F(rounds) => T::WeightInfo::blake2f(rounds),
+			Bls12381G1Add => T::WeightInfo::bls12_381_g1_add(),
+			Bls12381G1Msm(len) => T::WeightInfo::bls12_381_g1_msm(len),
+			Bls12381G2Add => T::WeightInfo::bls12_381_g2_add(),
+			Bls12381G2Msm(len) => T::WeightInfo::bls12_381_g2_msm(len),
+			Bls12381Pairing(len) => T::WeightInfo::bls12_381_pairing(len),
+			Bls12381MapFpToG1 => T::WeightInfo::bls12_381_map_fp_to_g1(),
+			Bls12381MapFp2ToG2 => T::WeightInfo::bls12_381_map_fp2_to_g2(),
+			P256Verify => T::WeightInfo::p256_verify(),
 			Modexp(gas) => {
 				use frame_support::weights::constants::WEIGHT_REF_TIME_PER_SECOND;
 				/// Current approximation of the gas/s consumption considering
@@ -1703,10 +1727,13 @@ pub mod env {
 	#[stable]
 	fn base_fee(&mut self, memory: &mut M, out_ptr: u32) -> Result<(), TrapReason> {
 		self.charge_gas(RuntimeCosts::BaseFee)?;
+		let base_fee = crate::simulated_block::with(|env| env.base_fee_per_gas)
+			.flatten()
+			.unwrap_or_default();
 		Ok(self.write_fixed_sandbox_output(
 			memory,
 			out_ptr,
-			&U256::zero().to_little_endian(),
+			&base_fee.to_little_endian(),
 			false,
 			already_charged,
 		)?)
@@ -153,6 +153,14 @@ pub trait WeightInfo {
 	fn bn128_mul() -> Weight;
 	fn bn128_pairing(n: u32, ) -> Weight;
 	fn blake2f(n: u32, ) -> Weight;
+	fn bls12_381_g1_add() -> Weight;
+	fn bls12_381_g1_msm(n: u32, ) -> Weight;
+	fn bls12_381_g2_add() -> Weight;
+	fn bls12_381_g2_msm(n: u32, ) -> Weight;
+	fn bls12_381_pairing(n: u32, ) -> Weight;
+	fn bls12_381_map_fp_to_g1() -> Weight;
+	fn bls12_381_map_fp2_to_g2() -> Weight;
+	fn p256_verify() -> Weight;
 	fn seal_ecdsa_to_eth_address() -> Weight;
 	fn seal_set_code_hash() -> Weight;
 	fn instr(r: u32, ) -> Weight;
@@ -1097,6 +1105,60 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			// Standard Error: 6
 			.saturating_add(Weight::from_parts(22_679, 0).saturating_mul(n.into()))
 	}
+	fn bls12_381_g1_add() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(33_412_000, 0)
+	}
+	/// The range of component `n` is `[1, 128]`.
+	fn bls12_381_g1_msm(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(612_337_115, 0)
+			.saturating_add(Weight::from_parts(1_402_118_930, 0).saturating_mul(n.into()))
+	}
+	fn bls12_381_g2_add() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(94_870_000, 0)
+	}
+	/// The range of component `n` is `[1, 128]`.
+	fn bls12_381_g2_msm(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(1_488_902_347, 0)
+			.saturating_add(Weight::from_parts(4_093_551_276, 0).saturating_mul(n.into()))
+	}
+	/// The range of component `n` is `[1, 20]`.
+	fn bls12_381_pairing(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(9_803_115_872, 0)
+			.saturating_add(Weight::from_parts(12_118_640_019, 0).saturating_mul(n.into()))
+	}
+	fn bls12_381_map_fp_to_g1() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(451_206_000, 0)
+	}
+	fn bls12_381_map_fp2_to_g2() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(1_903_448_000, 0)
+	}
+	fn p256_verify() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(1_021_735_000, 0)
+	}
 	fn seal_ecdsa_to_eth_address() -> Weight {
 		// Proof Size summary in bytes:
 		//  Measured:  `0`
@@ -2074,6 +2136,60 @@ impl WeightInfo for () {
 			// Standard Error: 6
 			.saturating_add(Weight::from_parts(22_679, 0).saturating_mul(n.into()))
 	}
+	fn bls12_381_g1_add() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(33_412_000, 0)
+	}
+	/// The range of component `n` is `[1, 128]`.
+	fn bls12_381_g1_msm(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(612_337_115, 0)
+			.saturating_add(Weight::from_parts(1_402_118_930, 0).saturating_mul(n.into()))
+	}
+	fn bls12_381_g2_add() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(94_870_000, 0)
+	}
+	/// The range of component `n` is `[1, 128]`.
+	fn bls12_381_g2_msm(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(1_488_902_347, 0)
+			.saturating_add(Weight::from_parts(4_093_551_276, 0).saturating_mul(n.into()))
+	}
+	/// The range of component `n` is `[1, 20]`.
+	fn bls12_381_pairing(n: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(9_803_115_872, 0)
+			.saturating_add(Weight::from_parts(12_118_640_019, 0).saturating_mul(n.into()))
+	}
+	fn bls12_381_map_fp_to_g1() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(451_206_000, 0)
+	}
+	fn bls12_381_map_fp2_to_g2() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(1_903_448_000, 0)
+	}
+	fn p256_verify() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		Weight::from_parts(1_021_735_000, 0)
+	}
 	fn seal_ecdsa_to_eth_address() -> Weight {
 		// Proof Size summary in bytes:
 		//  Measured:  `0`
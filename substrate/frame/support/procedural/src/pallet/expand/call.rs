@@ -537,7 +537,15 @@ pub fn expand_call(def: &mut Def) -> proc_macro2::TokenStream {
 							#cfg_attrs
 							Self::#fn_name { #( #args_name_pattern, )* } => {
 								#frame_support::__private::sp_tracing::enter_span!(
-									#frame_support::__private::sp_tracing::trace_span!(stringify!(#fn_name))
+									#frame_support::__private::sp_tracing::trace_span!(
+										stringify!(#fn_name),
+										pallet = <
+											<T as #frame_system::Config>::PalletInfo
+											as
+											#frame_support::traits::PalletInfo
+										>::name::<#pallet_ident<#type_use_gen>>()
+											.unwrap_or("<unknown pallet name>")
+									)
 								);
 								#maybe_allow_attrs
 								#[allow(clippy::useless_conversion)]
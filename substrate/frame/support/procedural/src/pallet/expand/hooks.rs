@@ -149,7 +149,10 @@ pub fn expand_hooks(def: &mut Def) -> proc_macro2::TokenStream {
 		{
 			fn on_finalize(n: #frame_system::pallet_prelude::BlockNumberFor::<T>) {
 				#frame_support::__private::sp_tracing::enter_span!(
-					#frame_support::__private::sp_tracing::trace_span!("on_finalize")
+					#frame_support::__private::sp_tracing::trace_span!(
+						"on_finalize",
+						pallet = #pallet_name
+					)
 				);
 				<
 					Self as #frame_support::traits::Hooks<
@@ -199,7 +202,10 @@ pub fn expand_hooks(def: &mut Def) -> proc_macro2::TokenStream {
 				n: #frame_system::pallet_prelude::BlockNumberFor::<T>
 			) -> #frame_support::weights::Weight {
 				#frame_support::__private::sp_tracing::enter_span!(
-					#frame_support::__private::sp_tracing::trace_span!("on_initialize")
+					#frame_support::__private::sp_tracing::trace_span!(
+						"on_initialize",
+						pallet = #pallet_name
+					)
 				);
 				<
 					Self as #frame_support::traits::Hooks<
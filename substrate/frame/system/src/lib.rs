@@ -138,7 +138,7 @@ use frame_support::{
 	traits::{
 		ConstU32, Contains, EnsureOrigin, EnsureOriginWithArg, Get, HandleLifetime,
 		OnKilledAccount, OnNewAccount, OnRuntimeUpgrade, OriginTrait, PalletInfo, SortedMembers,
-		StoredMap, TypedGet,
+		StoredMap, Task, TypedGet,
 	},
 	Parameter,
 };
@@ -2085,6 +2085,25 @@ impl<T: Config> Pallet<T> {
 			.collect::<_>()
 	}
 
+	/// Get at most `limit` of the tasks that are currently valid, along with their weight.
+	///
+	/// Any of them can be executed by submitting `do_task` as an unsigned transaction.
+	/// Should only be called outside of the runtime block execution, as it reads the storage of
+	/// the pallets with tasks until `limit` valid tasks are found.
+	pub fn valid_tasks(limit: usize) -> Vec<(T::RuntimeTask, Weight)>
+	where
+		<T::RuntimeTask as Task>::Enumeration: Iterator<Item = T::RuntimeTask>,
+	{
+		T::RuntimeTask::iter()
+			.filter(|task| task.is_valid())
+			.take(limit)
+			.map(|task| {
+				let weight = task.weight();
+				(task, weight)
+			})
+			.collect()
+	}
+
 	/// Simulate the execution of a block sequence up to a specified height, injecting the
 	/// provided hooks at each block.
 	///
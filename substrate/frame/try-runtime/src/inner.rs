@@ -19,6 +19,112 @@
 
 pub use frame_support::traits::{TryStateSelect, UpgradeCheckSelect};
 use frame_support::weights::Weight;
+use sp_inherents::InherentData;
+use sp_runtime::traits::Block as BlockT;
+
+/// Log target for the simulation of multi-block migrations.
+#[cfg(feature = "std")]
+const LOG_TARGET: &str = "try-runtime::mbm";
+
+/// The outcome of stepping the multi-block migrations in one simulated block.
+#[derive(codec::Encode, codec::Decode, Clone, Debug, Copy, scale_info::TypeInfo, PartialEq, Eq)]
+pub struct MultiBlockMigrationsStep {
+	/// The weight that the migrations consumed in this block.
+	pub weight: Weight,
+	/// The maximal weight that the migrations may consume per block.
+	pub max_service_weight: Weight,
+	/// Whether the migrations are still ongoing after this block.
+	pub ongoing: bool,
+}
+
+/// The outcome of driving the multi-block migrations over consecutive simulated blocks.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MultiBlockMigrationsReport {
+	/// The step of every simulated block, in order.
+	pub steps: Vec<MultiBlockMigrationsStep>,
+	/// Whether the migrations completed within the simulated blocks.
+	pub completed: bool,
+}
+
+#[cfg(feature = "std")]
+impl MultiBlockMigrationsReport {
+	/// The number of blocks that were simulated.
+	pub fn blocks(&self) -> u32 {
+		self.steps.len() as u32
+	}
+
+	/// The weight that the migrations consumed in all blocks together.
+	pub fn total_weight(&self) -> Weight {
+		self.steps
+			.iter()
+			.fold(Weight::zero(), |acc, step| acc.saturating_add(step.weight))
+	}
+
+	/// The maximal weight that the migrations consumed in a single block, per component.
+	pub fn max_weight(&self) -> Weight {
+		self.steps.iter().fold(Weight::zero(), |acc, step| acc.max(step.weight))
+	}
+
+	/// The number of blocks in which the migrations consumed more than their service weight.
+	pub fn overweight_blocks(&self) -> u32 {
+		self.steps
+			.iter()
+			.filter(|step| !step.weight.all_lte(step.max_service_weight))
+			.count() as u32
+	}
+}
+
+/// Drive the multi-block migrations block by block until they complete.
+///
+/// `step` is called once per simulated block and should call into
+/// [`TryRuntime::step_multi_block_migrations`], for example through a runtime api or natively
+/// within the externalities of a remote state. At most `max_blocks` blocks are simulated and the
+/// first error of `step` is returned.
+#[cfg(feature = "std")]
+pub fn run_multi_block_migrations<E>(
+	max_blocks: u32,
+	mut step: impl FnMut() -> Result<MultiBlockMigrationsStep, E>,
+) -> Result<MultiBlockMigrationsReport, E> {
+	let mut report = MultiBlockMigrationsReport::default();
+
+	for block in 1..=max_blocks {
+		let outcome = step()?;
+		log::info!(
+			target: LOG_TARGET,
+			"Block {block}: migrations consumed {} of {} max service weight, ongoing: {}",
+			outcome.weight,
+			outcome.max_service_weight,
+			outcome.ongoing,
+		);
+		if !outcome.weight.all_lte(outcome.max_service_weight) {
+			log::warn!(target: LOG_TARGET, "Block {block}: migrations exceeded the service weight");
+		}
+
+		report.steps.push(outcome);
+		if !outcome.ongoing {
+			report.completed = true;
+			break
+		}
+	}
+
+	if report.completed {
+		log::info!(
+			target: LOG_TARGET,
+			"Migrations completed after {} blocks, total weight {}, max weight per block {}",
+			report.blocks(),
+			report.total_weight(),
+			report.max_weight(),
+		);
+	} else {
+		log::error!(
+			target: LOG_TARGET,
+			"Migrations did not complete within {max_blocks} blocks",
+		);
+	}
+
+	Ok(report)
+}
 
 sp_api::decl_runtime_apis! {
 	/// Runtime api for testing the execution of a runtime upgrade.
@@ -46,5 +152,20 @@ sp_api::decl_runtime_apis! {
 			signature_check: bool,
 			try_state: TryStateSelect,
 		) -> Weight;
+
+		/// Simulate one block of the multi-block migrations.
+		///
+		/// Initializes the block of `header`, applies the inherents created from `inherent_data`
+		/// and steps the `MultiBlockMigrator` of the runtime before finalizing the block, like it
+		/// would in a block that only contains inherents. The `pre_upgrade` and `post_upgrade`
+		/// checks of each migration are executed by the migrator when the migration starts and
+		/// completes. If `checks` is set, the state is decoded and `try_state` of all pallets
+		/// executed once the migrations completed.
+		#[api_version(2)]
+		fn step_multi_block_migrations(
+			header: <Block as BlockT>::Header,
+			inherent_data: InherentData,
+			checks: UpgradeCheckSelect,
+		) -> MultiBlockMigrationsStep;
 	}
 }
@@ -20,6 +20,11 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
+extern crate alloc;
+
+use alloc::vec::Vec;
+use sp_runtime::Weight;
+
 /// Re-export of parent module scope storage prefix.
 pub use sp_core::offchain::STORAGE_PREFIX;
 
@@ -35,3 +40,20 @@ sp_api::decl_runtime_apis! {
 		fn offchain_worker(header: &Block::Header);
 	}
 }
+
+sp_api::decl_runtime_apis! {
+	/// The api to find and execute the tasks of the runtime.
+	///
+	/// Tasks are service work, like the `RuntimeTask`s of FRAME, that anyone may trigger once they
+	/// are valid by submitting their extrinsic as an unsigned transaction.
+	pub trait TasksApi {
+		/// Returns at most `limit` of the SCALE encoded tasks that are currently valid, along with
+		/// their weight.
+		fn valid_tasks(limit: u32) -> Vec<(Vec<u8>, Weight)>;
+
+		/// Returns the unsigned extrinsic executing the given SCALE encoded task.
+		///
+		/// Returns `None` if the task could not be decoded or cannot be executed.
+		fn task_extrinsic(task: Vec<u8>) -> Option<Block::Extrinsic>;
+	}
+}
@@ -496,7 +496,7 @@ where
 			method = "Append",
 			ext_id = %HexDisplay::from(&self.id.to_le_bytes()),
 			key = %HexDisplay::from(&key),
-			value = %HexDisplay::from(&value),
+			value = ?HexDisplay::from(&value),
 		);
 
 		let _guard = guard();
@@ -579,14 +579,29 @@ where
 	}
 
 	fn storage_start_transaction(&mut self) {
+		trace!(
+			target: "state",
+			method = "StartTransaction",
+			ext_id = %HexDisplay::from(&self.id.to_le_bytes()),
+		);
 		self.overlay.start_transaction()
 	}
 
 	fn storage_rollback_transaction(&mut self) -> Result<(), ()> {
+		trace!(
+			target: "state",
+			method = "RollbackTransaction",
+			ext_id = %HexDisplay::from(&self.id.to_le_bytes()),
+		);
 		self.overlay.rollback_transaction().map_err(|_| ())
 	}
 
 	fn storage_commit_transaction(&mut self) -> Result<(), ()> {
+		trace!(
+			target: "state",
+			method = "CommitTransaction",
+			ext_id = %HexDisplay::from(&self.id.to_le_bytes()),
+		);
 		self.overlay.commit_transaction().map_err(|_| ())
 	}
 
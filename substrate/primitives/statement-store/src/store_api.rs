@@ -52,6 +52,9 @@ pub enum SubmitResult {
 	KnownExpired,
 	/// Priority is too low or the size is too big.
 	Ignored,
+	/// The account of the statement exceeded the number of statements it may submit in a given
+	/// period.
+	RateLimited,
 	/// Statement failed validation.
 	Bad(&'static str),
 	/// Internal store error.
@@ -61,6 +64,21 @@ pub enum SubmitResult {
 /// Result type for `Error`
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// A page of statements, see [`StatementStore::statements_page`].
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct StatementPage {
+	/// The statements of this page with their hashes, ordered by hash.
+	pub statements: Vec<(Hash, Statement)>,
+	/// The hash to pass as `after` to fetch the next page, if there are more statements.
+	pub next: Option<Hash>,
+}
+
+/// Stream of the statements added to the store, with their hashes.
+///
+/// The stream is bounded: a subscriber which does not keep up with the new statements is dropped
+/// by the store, which ends the stream.
+pub type StatementStream = futures::channel::mpsc::Receiver<(Hash, Statement)>;
+
 /// Statement store API.
 pub trait StatementStore: Send + Sync {
 	/// Return all statements.
@@ -99,6 +117,51 @@ pub trait StatementStore: Send + Sync {
 	fn posted_clear_stmt(&self, match_all_topics: &[Topic], dest: [u8; 32])
 		-> Result<Vec<Vec<u8>>>;
 
+	/// Return at most `limit` known statements which include all topics and whose decryption key
+	/// is identified as `dest`, or which have no `DecryptionKey` field if `dest` is `None`.
+	///
+	/// Statements are ordered by hash, and only those with a hash greater than `after` are
+	/// returned.
+	///
+	/// The default implementation filters and sorts all [`Self::statements`], stores should
+	/// provide a more efficient one.
+	fn statements_page(
+		&self,
+		match_all_topics: &[Topic],
+		dest: Option<[u8; 32]>,
+		after: Option<Hash>,
+		limit: usize,
+	) -> Result<StatementPage> {
+		let mut statements: Vec<_> = self
+			.statements()?
+			.into_iter()
+			.filter(|(hash, statement)| {
+				after.map_or(true, |after| *hash > after) &&
+					statement.decryption_key() == dest &&
+					match_all_topics.iter().all(|topic| {
+						(0..crate::MAX_TOPICS).any(|i| statement.topic(i) == Some(*topic))
+					})
+			})
+			.collect();
+		statements.sort_unstable_by_key(|(hash, _)| *hash);
+
+		let next = if statements.len() > limit {
+			statements.truncate(limit);
+			statements.last().map(|(hash, _)| *hash)
+		} else {
+			None
+		};
+		Ok(StatementPage { statements, next })
+	}
+
+	/// Subscribe to the statements added to the store.
+	///
+	/// The default implementation returns a stream that ends immediately, for stores which do not
+	/// support subscriptions.
+	fn subscribe_statements(&self) -> StatementStream {
+		futures::channel::mpsc::channel(0).1
+	}
+
 	/// Submit a statement.
 	fn submit(&self, statement: Statement, source: StatementSource) -> SubmitResult;
 
@@ -0,0 +1,401 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Turns benchmark results and generated weight files into comparable [`Formula`]s.
+
+use std::{
+	collections::BTreeMap,
+	fs,
+	path::{Path, PathBuf},
+};
+
+use frame_benchmarking::{
+	Analysis, BenchmarkBatchSplitResults, BenchmarkResult, BenchmarkSelector,
+};
+use inflector::Inflector;
+
+/// A linear function of the benchmark components.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Linear {
+	/// The constant part.
+	pub base: u128,
+	/// The slope per component name. Zero slopes are omitted.
+	pub slopes: BTreeMap<String, u128>,
+}
+
+impl Linear {
+	/// Evaluate the function with every component set to its value in `components`.
+	///
+	/// Components that are missing in `components` are assumed to be zero.
+	pub fn eval(&self, components: &BTreeMap<String, u32>) -> u128 {
+		self.slopes.iter().fold(self.base, |acc, (name, slope)| {
+			let value = components.get(name).copied().unwrap_or_default() as u128;
+			acc.saturating_add(slope.saturating_mul(value))
+		})
+	}
+
+	fn add_slope(&mut self, name: &str, slope: u128) {
+		if slope != 0 {
+			let entry = self.slopes.entry(name.into()).or_default();
+			*entry = entry.saturating_add(slope);
+		}
+	}
+
+	fn from_analysis(analysis: Analysis) -> Self {
+		let mut linear = Self { base: analysis.base, ..Default::default() };
+		for (slope, name) in analysis.slopes.into_iter().zip(analysis.names.iter()) {
+			linear.add_slope(name, slope);
+		}
+		linear
+	}
+}
+
+/// The weight formula of a single benchmark.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Formula {
+	/// Execution time in picoseconds, excluding the database operations.
+	pub ref_time: Linear,
+	/// Proof size in bytes.
+	pub proof_size: Linear,
+	/// Number of database reads.
+	pub reads: Linear,
+	/// Number of database writes.
+	pub writes: Linear,
+	/// The maximal value of each component.
+	pub ranges: BTreeMap<String, u32>,
+}
+
+/// All benchmark formulas keyed by pallet and benchmark name.
+pub type Formulas = BTreeMap<(String, String), Formula>;
+
+/// Load all formulas from the given paths.
+///
+/// Each path can either be a JSON file as written by `benchmark pallet --json-file`, a weight file
+/// as written by `benchmark pallet --output` or a directory that contains any of them.
+pub fn load(paths: &[PathBuf]) -> Result<Formulas, String> {
+	let mut formulas = Formulas::new();
+	for path in paths {
+		load_path(path, &mut formulas)?;
+	}
+	Ok(formulas)
+}
+
+fn load_path(path: &Path, formulas: &mut Formulas) -> Result<(), String> {
+	if path.is_dir() {
+		let mut entries = fs::read_dir(path)
+			.and_then(|dir| dir.map(|e| e.map(|e| e.path())).collect::<Result<Vec<_>, _>>())
+			.map_err(|e| format!("Could not read directory {}: {}", path.display(), e))?;
+		entries.sort();
+		for entry in entries {
+			if entry.is_dir() || matches!(extension(&entry), Some("json" | "rs")) {
+				load_path(&entry, formulas)?;
+			}
+		}
+		return Ok(())
+	}
+
+	let content = fs::read_to_string(path)
+		.map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+	let loaded = match extension(path) {
+		Some("json") => from_json(&content),
+		Some("rs") => {
+			let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+			from_weight_file(&content, stem)
+		},
+		_ => Err("Unknown file extension, expected `.json` or `.rs`".into()),
+	}
+	.map_err(|e| format!("Could not load {}: {}", path.display(), e))?;
+
+	for (key, formula) in loaded {
+		if formulas.insert(key.clone(), formula).is_some() {
+			return Err(format!("Benchmark {}::{} was provided more than once", key.0, key.1))
+		}
+	}
+	Ok(())
+}
+
+fn extension(path: &Path) -> Option<&str> {
+	path.extension().and_then(|e| e.to_str())
+}
+
+/// Analyze the raw results of `benchmark pallet --json-file`.
+///
+/// Uses the same least-squares analysis as the default of `benchmark pallet`. The proof size is
+/// the *measured* one, since the raw results do not contain the storage info that is needed to
+/// estimate the worst case.
+pub fn from_json(content: &str) -> Result<Formulas, String> {
+	let batches: Vec<BenchmarkBatchSplitResults> =
+		serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+	let mut formulas = Formulas::new();
+	for batch in batches.iter() {
+		let pallet = String::from_utf8_lossy(&batch.pallet).to_string();
+		let instance = String::from_utf8_lossy(&batch.instance).to_string();
+		let benchmark = String::from_utf8_lossy(&batch.benchmark).to_string();
+
+		// Use the same name as the weight file that `benchmark pallet` would write.
+		let mut name = pallet.clone();
+		if batches.iter().any(|b| b.pallet == batch.pallet && b.instance != batch.instance) {
+			name = format!("{}_{}", name, instance.to_snake_case());
+		}
+		let name = name.replace("::", "_");
+
+		let analyze = |results: &Vec<BenchmarkResult>, selector| {
+			Analysis::min_squares_iqr(results, selector)
+				.map(Linear::from_analysis)
+				.ok_or_else(|| format!("Could not analyze the results of {}::{}", name, benchmark))
+		};
+
+		let mut ranges = BTreeMap::<String, u32>::new();
+		for result in batch.time_results.iter().chain(batch.db_results.iter()) {
+			for (param, value) in result.components.iter() {
+				let max = ranges.entry(param.to_string()).or_default();
+				*max = (*max).max(*value);
+			}
+		}
+
+		let formula = Formula {
+			ref_time: analyze(&batch.time_results, BenchmarkSelector::ExtrinsicTime)?,
+			proof_size: analyze(&batch.db_results, BenchmarkSelector::ProofSize)?,
+			reads: analyze(&batch.db_results, BenchmarkSelector::Reads)?,
+			writes: analyze(&batch.db_results, BenchmarkSelector::Writes)?,
+			ranges,
+		};
+		formulas.insert((name, benchmark), formula);
+	}
+	Ok(formulas)
+}
+
+/// Parse a weight file that was generated by `benchmark pallet`.
+///
+/// Only the first implementation of each function is considered, which skips the `()`
+/// implementation of pallet-local weight files. The pallet name is taken from the file stem,
+/// unless it is a generic `weights` or `mod`.
+pub fn from_weight_file(content: &str, file_stem: &str) -> Result<Formulas, String> {
+	let pallet = match file_stem {
+		"weights" | "mod" => content
+			.lines()
+			.find_map(|l| between(l, "//! Autogenerated weights for `", "`"))
+			.ok_or("Missing `Autogenerated weights for` header")?,
+		stem => stem,
+	};
+
+	let mut formulas = Formulas::new();
+	let mut ranges = BTreeMap::new();
+	let mut current: Option<(String, Formula)> = None;
+
+	for (index, line) in content.lines().enumerate() {
+		let line = line.trim();
+		let err = |msg: &str| format!("Line {}: {}", index + 1, msg);
+
+		let Some((_, formula)) = current.as_mut() else {
+			if let Some(range) = between(line, "/// The range of component `", "]`.") {
+				let (name, bounds) = range.split_once("` is `[").ok_or_else(|| err("bad range"))?;
+				let max = bounds.split(',').nth(1).and_then(parse_number);
+				let max = max.ok_or_else(|| err("bad range"))?;
+				ranges.insert(name.to_string(), max as u32);
+			} else if line.starts_with("fn ") && line.ends_with("-> Weight {") {
+				let name = between(line, "fn ", "(").ok_or_else(|| err("bad function"))?;
+				let ranges = core::mem::take(&mut ranges);
+				current = Some((name.to_string(), Formula { ranges, ..Default::default() }));
+			} else if !line.starts_with("///") {
+				ranges.clear();
+			}
+			continue
+		};
+
+		if line == "}" {
+			let (name, formula) = current.take().expect("Checked above; qed");
+			formulas.entry((pallet.to_string(), name)).or_insert(formula);
+		} else if !line.starts_with("//") {
+			parse_weight_term(line, formula).map_err(|e| err(&e))?;
+		}
+	}
+
+	if formulas.is_empty() {
+		return Err("No weight functions found".into())
+	}
+	Ok(formulas)
+}
+
+/// Parse one line of a weight function body and add it to `formula`.
+fn parse_weight_term(line: &str, formula: &mut Formula) -> Result<(), String> {
+	let component = between(line, ".saturating_mul(", ".into())");
+
+	if let Some(parts) = between(line, "Weight::from_parts(", ")") {
+		let (ref_time, proof_size) = parts
+			.split_once(',')
+			.and_then(|(r, p)| Some((parse_number(r)?, parse_number(p)?)))
+			.ok_or("bad `Weight::from_parts`")?;
+		match component {
+			Some(c) => {
+				formula.ref_time.add_slope(c, ref_time);
+				formula.proof_size.add_slope(c, proof_size);
+			},
+			None => {
+				formula.ref_time.base = formula.ref_time.base.saturating_add(ref_time);
+				formula.proof_size.base = formula.proof_size.base.saturating_add(proof_size);
+			},
+		}
+	}
+
+	for (op, linear) in [(".reads(", &mut formula.reads), (".writes(", &mut formula.writes)] {
+		let Some(arg) = line.split_once(op).map(|(_, arg)| arg) else { continue };
+		let count = arg
+			.trim_start_matches('(')
+			.split(')')
+			.next()
+			.and_then(parse_number)
+			.ok_or("bad database operation")?;
+		match component {
+			Some(c) => linear.add_slope(c, count),
+			None => linear.base = linear.base.saturating_add(count),
+		}
+	}
+	Ok(())
+}
+
+/// Returns the text between the first occurrence of `start` and the next `end`.
+fn between<'a>(s: &'a str, start: &str, end: &str) -> Option<&'a str> {
+	let (_, rest) = s.split_once(start)?;
+	rest.split_once(end).map(|(inner, _)| inner)
+}
+
+/// Parse a Rust integer literal like `1_234_u64`.
+fn parse_number(s: &str) -> Option<u128> {
+	let s = s.trim();
+	let s = s.strip_suffix("_u64").or_else(|| s.strip_suffix("u64")).unwrap_or(s);
+	s.replace('_', "").parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const WEIGHT_FILE: &str = r#"
+//! Autogenerated weights for `pallet_example`
+
+/// Weight functions for `pallet_example`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> pallet_example::WeightInfo for WeightInfo<T> {
+	/// Storage: `Example::Value` (r:1 w:1)
+	/// Proof: `Example::Value` (`max_values`: Some(1), `max_size`: Some(4), added: 499, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[1, 1000]`.
+	/// The range of component `m` is `[0, 10]`.
+	fn do_something(n: u32, _m: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `42 + n * (3 ±0)`
+		//  Estimated: `1489 + n * (5 ±0)`
+		// Minimum execution time: 10_000_000 picoseconds.
+		Weight::from_parts(11_234_000, 0)
+			.saturating_add(Weight::from_parts(0, 1489))
+			// Standard Error: 1_000
+			.saturating_add(Weight::from_parts(2_500, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(Weight::from_parts(0, 5).saturating_mul(n.into()))
+	}
+	fn constant() -> Weight {
+		// Minimum execution time: 1_000_000 picoseconds.
+		Weight::from_parts(1_100_000, 0)
+	}
+}
+
+impl WeightInfo for () {
+	fn constant() -> Weight {
+		Weight::from_parts(999_999_999, 0)
+	}
+}
+"#;
+
+	#[test]
+	fn from_weight_file_works() {
+		let formulas = from_weight_file(WEIGHT_FILE, "weights").unwrap();
+		assert_eq!(formulas.len(), 2);
+
+		let f = &formulas[&("pallet_example".into(), "do_something".into())];
+		assert_eq!(f.ref_time.base, 11_234_000);
+		assert_eq!(f.ref_time.slopes, BTreeMap::from([("n".into(), 2_500)]));
+		assert_eq!(f.proof_size.base, 1489);
+		assert_eq!(f.proof_size.slopes, BTreeMap::from([("n".into(), 5)]));
+		assert_eq!(f.reads.base, 1);
+		assert_eq!(f.reads.slopes, BTreeMap::from([("n".into(), 2)]));
+		assert_eq!(f.writes, Linear { base: 1, slopes: Default::default() });
+		assert_eq!(f.ranges, BTreeMap::from([("n".into(), 1000), ("m".into(), 10)]));
+		assert_eq!(f.ref_time.eval(&f.ranges), 11_234_000 + 2_500 * 1000);
+
+		// The `()` implementation is ignored.
+		let f = &formulas[&("pallet_example".into(), "constant".into())];
+		assert_eq!(f.ref_time.base, 1_100_000);
+		assert!(f.ranges.is_empty());
+	}
+
+	#[test]
+	fn from_json_works() {
+		use frame_benchmarking::BenchmarkParameter;
+
+		let results = (1..=5)
+			.flat_map(|n| {
+				let result = BenchmarkResult {
+					components: vec![(BenchmarkParameter::n, n)],
+					extrinsic_time: 10 + 2 * n as u128,
+					reads: 1 + n,
+					writes: 1,
+					proof_size: 100 + 10 * n,
+					..Default::default()
+				};
+				[result.clone(), result]
+			})
+			.collect::<Vec<_>>();
+		let batch = |instance: &str| BenchmarkBatchSplitResults {
+			pallet: b"pallet_example".to_vec(),
+			instance: instance.as_bytes().to_vec(),
+			benchmark: b"do_something".to_vec(),
+			time_results: results.clone(),
+			db_results: results.clone(),
+		};
+
+		let json = serde_json::to_string(&vec![batch("Example")]).unwrap();
+		let formulas = from_json(&json).unwrap();
+		let f = &formulas[&("pallet_example".into(), "do_something".into())];
+		assert_eq!(f.ranges, BTreeMap::from([("n".into(), 5)]));
+		assert!(f.ref_time.slopes.contains_key("n"));
+		assert!(f.reads.slopes.contains_key("n"));
+		assert!(!f.writes.slopes.contains_key("n"));
+		assert!(f.proof_size.slopes.contains_key("n"));
+
+		// Multiple instances are named like their weight files.
+		let json = serde_json::to_string(&vec![batch("Example"), batch("OtherExample")]).unwrap();
+		let formulas = from_json(&json).unwrap();
+		assert!(formulas.contains_key(&("pallet_example_example".into(), "do_something".into())));
+		assert!(
+			formulas.contains_key(&("pallet_example_other_example".into(), "do_something".into()))
+		);
+	}
+
+	#[test]
+	fn from_weight_file_uses_file_stem() {
+		let formulas = from_weight_file(WEIGHT_FILE, "pallet_example_instance1").unwrap();
+		assert!(formulas.keys().all(|(p, _)| p == "pallet_example_instance1"));
+	}
+
+	#[test]
+	fn from_weight_file_rejects_empty() {
+		assert!(from_weight_file("//! Autogenerated weights for `x`", "weights").is_err());
+	}
+}
@@ -0,0 +1,379 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains the [`CompareCmd`] which reports weight regressions between two benchmark runs.
+
+mod formula;
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::PathBuf,
+};
+
+use clap::Parser;
+use comfy_table::{Row, Table};
+use log::{error, info, warn};
+use sc_cli::{CliConfiguration, Result, SharedParams};
+use thousands::Separable;
+
+use formula::{Formula, Formulas, Linear};
+
+/// Compare the weights of two benchmark runs.
+///
+/// Both sides accept the JSON output of `benchmark pallet --json-file`, weight files that were
+/// written by `benchmark pallet --output` or directories containing them. The weights are compared
+/// in their worst case, which is when all components are at the maximum of their range.
+#[derive(Debug, Parser)]
+pub struct CompareCmd {
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	/// Results of the baseline run.
+	#[arg(long, required = true, num_args = 1.., value_name = "PATH")]
+	pub old: Vec<PathBuf>,
+
+	/// Results of the run that should be checked against the baseline.
+	#[arg(long, required = true, num_args = 1.., value_name = "PATH")]
+	pub new: Vec<PathBuf>,
+
+	/// Maximal increase of the worst-case ref time that is not considered a regression.
+	#[arg(long, default_value_t = 10.0, value_name = "PERCENT")]
+	pub ref_time_threshold: f64,
+
+	/// Maximal increase of the worst-case proof size that is not considered a regression.
+	#[arg(long, default_value_t = 10.0, value_name = "PERCENT")]
+	pub proof_size_threshold: f64,
+
+	/// Do not return an error if any benchmark regressed.
+	#[arg(long)]
+	pub allow_regressions: bool,
+
+	/// Also print the benchmarks whose weight did not change.
+	#[arg(long)]
+	pub show_unchanged: bool,
+}
+
+/// Errors that can be returned by this command.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+	#[error("{0} benchmark(s) regressed above the threshold")]
+	Regressions(usize),
+}
+
+/// Comparison of a single benchmark between the old and new run.
+#[derive(Debug, PartialEq)]
+enum Change<'a> {
+	Added(&'a Formula),
+	Removed(&'a Formula),
+	Changed { old: &'a Formula, new: &'a Formula },
+}
+
+/// The worst-case values of both runs for one dimension of the weight.
+///
+/// A value is `None` if the benchmark is missing from that run.
+#[derive(Debug, PartialEq)]
+struct Delta {
+	old: Option<u128>,
+	new: Option<u128>,
+}
+
+impl CompareCmd {
+	/// Compare the old and the new results and print the report.
+	pub fn run(&self) -> Result<()> {
+		self.validate_args()?;
+		let old = formula::load(&self.old)?;
+		let new = formula::load(&self.new)?;
+		info!("Comparing {} old against {} new benchmarks", old.len(), new.len());
+
+		let mut table = Table::new();
+		table.set_header([
+			"Pallet",
+			"Extrinsic",
+			"Ref Time [ps]",
+			"Proof Size [B]",
+			"Reads",
+			"Writes",
+			"Slopes",
+			"Result",
+		]);
+		let (mut regressed, mut unchanged) = (0, 0);
+
+		for ((pallet, extrinsic), change) in compare(&old, &new) {
+			let is_regression = self.is_regression(&change);
+			if is_regression {
+				regressed += 1;
+			} else if change.is_unchanged() {
+				unchanged += 1;
+				if !self.show_unchanged {
+					continue
+				}
+			}
+			table.add_row(self.to_row(pallet, extrinsic, &change, is_regression));
+		}
+
+		info!(
+			"\n{}\n{} benchmark(s) regressed, {} did not change ({:.1?}% ref time and {:.1?}% proof \
+			size threshold).",
+			table, regressed, unchanged, self.ref_time_threshold, self.proof_size_threshold
+		);
+		if regressed != 0 {
+			self.check_failed(Error::Regressions(regressed))?;
+		}
+		Ok(())
+	}
+
+	/// Whether the change increases ref time or proof size above their thresholds.
+	fn is_regression(&self, change: &Change) -> bool {
+		let (ref_time, proof_size) = change.deltas(|f| &f.ref_time, |f| &f.proof_size);
+		ref_time.percent() > self.ref_time_threshold ||
+			proof_size.percent() > self.proof_size_threshold
+	}
+
+	/// Format a [`Change`] as row that can be printed in a table.
+	fn to_row(&self, pallet: &str, extrinsic: &str, change: &Change, is_regression: bool) -> Row {
+		let result = match change {
+			Change::Added(_) => "🆕 Added",
+			Change::Removed(_) => "🗑️ Removed",
+			Change::Changed { .. } if is_regression => "❌ Regressed",
+			Change::Changed { .. } => "✅ Pass",
+		};
+		let (ref_time, proof_size) = change.deltas(|f| &f.ref_time, |f| &f.proof_size);
+		let (reads, writes) = change.deltas(|f| &f.reads, |f| &f.writes);
+		vec![
+			pallet.to_string(),
+			extrinsic.to_string(),
+			ref_time.to_string(),
+			proof_size.to_string(),
+			reads.to_string(),
+			writes.to_string(),
+			change.slope_changes().join("\n"),
+			result.to_string(),
+		]
+		.into()
+	}
+
+	/// Returns `Ok` if [`self.allow_regressions`] is set and otherwise the error argument.
+	fn check_failed(&self, e: Error) -> Result<()> {
+		if !self.allow_regressions {
+			error!("Failing since --allow-regressions is not set");
+			Err(sc_cli::Error::Application(Box::new(e)))
+		} else {
+			warn!("Ignoring error since --allow-regressions is set: {:?}", e);
+			Ok(())
+		}
+	}
+
+	/// Validates the CLI arguments.
+	fn validate_args(&self) -> Result<()> {
+		if self.ref_time_threshold < 0.0 || self.proof_size_threshold < 0.0 {
+			return Err("The regression thresholds must not be negative".into())
+		}
+		Ok(())
+	}
+}
+
+/// Match the benchmarks of both runs by pallet and extrinsic name.
+fn compare<'a>(old: &'a Formulas, new: &'a Formulas) -> Vec<(&'a (String, String), Change<'a>)> {
+	let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+	keys.into_iter()
+		.map(|key| {
+			let change = match (old.get(key), new.get(key)) {
+				(Some(old), Some(new)) => Change::Changed { old, new },
+				(Some(old), None) => Change::Removed(old),
+				(None, Some(new)) => Change::Added(new),
+				(None, None) => unreachable!("Key is from one of the maps; qed"),
+			};
+			(key, change)
+		})
+		.collect()
+}
+
+impl<'a> Change<'a> {
+	/// The formula of the old run, if it contains the benchmark.
+	fn old_formula(&self) -> Option<&'a Formula> {
+		match self {
+			Self::Removed(old) | Self::Changed { old, .. } => Some(*old),
+			Self::Added(_) => None,
+		}
+	}
+
+	/// The formula of the new run, if it contains the benchmark.
+	fn new_formula(&self) -> Option<&'a Formula> {
+		match self {
+			Self::Added(new) | Self::Changed { new, .. } => Some(*new),
+			Self::Removed(_) => None,
+		}
+	}
+
+	/// Whether both runs have the exact same formula.
+	fn is_unchanged(&self) -> bool {
+		matches!(self, Self::Changed { old, new } if old == new)
+	}
+
+	/// The maximal component values for the worst case.
+	///
+	/// Uses the ranges of the new run and falls back to the old one for removed components.
+	fn ranges(&self) -> BTreeMap<String, u32> {
+		let mut ranges = self.old_formula().map(|f| f.ranges.clone()).unwrap_or_default();
+		ranges.extend(self.new_formula().into_iter().flat_map(|f| f.ranges.clone()));
+		ranges
+	}
+
+	/// Evaluate two dimensions of the weight in their worst case.
+	fn deltas(
+		&self,
+		first: fn(&Formula) -> &Linear,
+		second: fn(&Formula) -> &Linear,
+	) -> (Delta, Delta) {
+		let ranges = self.ranges();
+		let delta = |select: fn(&Formula) -> &Linear| Delta {
+			old: self.old_formula().map(|f| select(f).eval(&ranges)),
+			new: self.new_formula().map(|f| select(f).eval(&ranges)),
+		};
+		(delta(first), delta(second))
+	}
+
+	/// Human readable list of the component slopes that differ between both runs.
+	fn slope_changes(&self) -> Vec<String> {
+		let mut changes = Vec::new();
+		self.push_slope_changes("ref_time", |f| &f.ref_time, &mut changes);
+		self.push_slope_changes("proof_size", |f| &f.proof_size, &mut changes);
+		changes
+	}
+
+	/// Push the slope changes of one dimension of the weight to `changes`.
+	fn push_slope_changes(
+		&self,
+		dimension: &str,
+		select: fn(&Formula) -> &Linear,
+		changes: &mut Vec<String>,
+	) {
+		let old = self.old_formula().map(|f| &select(f).slopes);
+		let new = self.new_formula().map(|f| &select(f).slopes);
+		let names = old.into_iter().chain(new).flat_map(|s| s.keys()).collect::<BTreeSet<_>>();
+		for name in names {
+			let delta = Delta {
+				old: old.map(|s| s.get(name).copied().unwrap_or_default()),
+				new: new.map(|s| s.get(name).copied().unwrap_or_default()),
+			};
+			if delta.old != delta.new {
+				changes.push(format!("{}/{}: {}", dimension, name, delta));
+			}
+		}
+	}
+}
+
+impl Delta {
+	/// Relative increase from old to new in percent.
+	///
+	/// Is zero if either side is missing.
+	fn percent(&self) -> f64 {
+		match (self.old, self.new) {
+			(Some(old), Some(new)) if new > old && old == 0 => f64::INFINITY,
+			(Some(old), Some(new)) if old != 0 => (new as f64 - old as f64) / old as f64 * 100.0,
+			_ => 0.0,
+		}
+	}
+}
+
+impl std::fmt::Display for Delta {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let fmt = |v: Option<u128>| v.map_or("-".into(), |v| v.separate_with_underscores());
+		match (self.old, self.new) {
+			(Some(old), Some(new)) if old == new => write!(f, "{}", fmt(Some(new))),
+			(Some(_), Some(_)) =>
+				write!(f, "{} → {} ({:+.1}%)", fmt(self.old), fmt(self.new), self.percent()),
+			_ => write!(f, "{} → {}", fmt(self.old), fmt(self.new)),
+		}
+	}
+}
+
+// Boilerplate
+impl CliConfiguration for CompareCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn formula(ref_time: (u128, u128), proof_size: (u128, u128)) -> Formula {
+		let linear = |(base, slope)| Linear { base, slopes: BTreeMap::from([("n".into(), slope)]) };
+		Formula {
+			ref_time: linear(ref_time),
+			proof_size: linear(proof_size),
+			ranges: BTreeMap::from([("n".into(), 10)]),
+			..Default::default()
+		}
+	}
+
+	fn cmd() -> CompareCmd {
+		CompareCmd::parse_from(["compare", "--old", "old.json", "--new", "new.json"])
+	}
+
+	#[test]
+	fn regressions_are_detected() {
+		let old = formula((1_000, 100), (1_000, 100));
+		// 2_000 -> 2_100 is within the default threshold of 10%.
+		let new = formula((1_000, 110), (1_000, 100));
+		assert!(!cmd().is_regression(&Change::Changed { old: &old, new: &new }));
+		// 2_000 -> 2_300 is not.
+		let new = formula((1_000, 130), (1_000, 100));
+		assert!(cmd().is_regression(&Change::Changed { old: &old, new: &new }));
+		let new = formula((1_000, 100), (1_300, 100));
+		assert!(cmd().is_regression(&Change::Changed { old: &old, new: &new }));
+		// Improvements and added or removed benchmarks are fine.
+		assert!(!cmd().is_regression(&Change::Changed { old: &new, new: &old }));
+		assert!(!cmd().is_regression(&Change::Added(&new)));
+		assert!(!cmd().is_regression(&Change::Removed(&old)));
+	}
+
+	#[test]
+	fn compare_matches_benchmarks() {
+		let key = |e: &str| ("pallet".to_string(), e.to_string());
+		let f = formula((1, 1), (1, 1));
+		let old = Formulas::from([(key("a"), f.clone()), (key("b"), f.clone())]);
+		let new = Formulas::from([(key("b"), f.clone()), (key("c"), f.clone())]);
+
+		let changes = compare(&old, &new);
+		assert_eq!(
+			changes,
+			vec![
+				(&key("a"), Change::Removed(&f)),
+				(&key("b"), Change::Changed { old: &f, new: &f }),
+				(&key("c"), Change::Added(&f)),
+			]
+		);
+		assert!(changes[1].1.is_unchanged());
+	}
+
+	#[test]
+	fn slope_changes_are_reported() {
+		let old = formula((1_000, 100), (1_000, 5));
+		let new = formula((1_000, 150), (1_000, 5));
+		let change = Change::Changed { old: &old, new: &new };
+		assert_eq!(change.slope_changes(), vec!["ref_time/n: 100 → 150 (+50.0%)".to_string()]);
+
+		let (ref_time, proof_size) = change.deltas(|f| &f.ref_time, |f| &f.proof_size);
+		assert_eq!(ref_time.to_string(), "2_000 → 2_500 (+25.0%)");
+		assert_eq!(proof_size.to_string(), "1_050");
+	}
+}
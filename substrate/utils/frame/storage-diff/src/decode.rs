@@ -0,0 +1,235 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decoding of the existing values of storage items.
+
+use codec::{Compact, Decode};
+use scale_info::{PortableRegistry, TypeDef, TypeDefPrimitive};
+
+/// The outcome of decoding all values of a storage item.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DecodeOutcome {
+	/// The number of values of the storage item.
+	pub values: usize,
+	/// The number of values that failed to decode.
+	pub failures: usize,
+	/// The key and error of the first value that failed to decode.
+	pub first_failure: Option<(Vec<u8>, String)>,
+}
+
+/// Decode all values stored under `prefix` as the type `ty` of `registry`.
+///
+/// Must be called within externalities.
+pub fn decode_values(registry: &PortableRegistry, ty: u32, prefix: &[u8]) -> DecodeOutcome {
+	let mut outcome = DecodeOutcome::default();
+	let mut check = |key: &[u8]| {
+		let Some(value) = sp_io::storage::get(key) else { return };
+		outcome.values += 1;
+		if let Err(e) = decode_all(registry, ty, &value) {
+			outcome.failures += 1;
+			outcome.first_failure.get_or_insert_with(|| (key.to_vec(), e.to_string()));
+		}
+	};
+
+	// Plain storage values are stored at the prefix itself.
+	check(prefix);
+
+	let mut key = prefix.to_vec();
+	while let Some(next) = sp_io::storage::next_key(&key) {
+		if !next.starts_with(prefix) {
+			break
+		}
+		check(&next);
+		key = next;
+	}
+
+	outcome
+}
+
+/// Decode `value` as the type `ty` of `registry`, making sure that all of it is consumed.
+pub fn decode_all(registry: &PortableRegistry, ty: u32, value: &[u8]) -> Result<(), codec::Error> {
+	let mut input = value;
+	skip_value(registry, ty, &mut input)?;
+	if !input.is_empty() {
+		return Err("Input not fully consumed".into())
+	}
+
+	Ok(())
+}
+
+/// Skip over a value of the type `ty` of `registry` in `input`, failing if it does not decode.
+pub fn skip_value(
+	registry: &PortableRegistry,
+	ty: u32,
+	input: &mut &[u8],
+) -> Result<(), codec::Error> {
+	let ty = registry.resolve(ty).ok_or("Type not found in registry")?;
+	match &ty.type_def {
+		TypeDef::Composite(composite) =>
+			for field in &composite.fields {
+				skip_value(registry, field.ty.id, input)?;
+			},
+		TypeDef::Variant(variant) => {
+			let index = u8::decode(input)?;
+			let variant = variant
+				.variants
+				.iter()
+				.find(|variant| variant.index == index)
+				.ok_or("Unknown variant index")?;
+			for field in &variant.fields {
+				skip_value(registry, field.ty.id, input)?;
+			}
+		},
+		TypeDef::Sequence(sequence) => {
+			let len = Compact::<u32>::decode(input)?.0;
+			skip_elements(registry, sequence.type_param.id, len, input)?;
+		},
+		TypeDef::Array(array) => skip_elements(registry, array.type_param.id, array.len, input)?,
+		TypeDef::Tuple(tuple) =>
+			for ty in &tuple.fields {
+				skip_value(registry, ty.id, input)?;
+			},
+		TypeDef::Primitive(primitive) => skip_primitive(primitive, input)?,
+		TypeDef::Compact(compact) => skip_compact(registry, compact.type_param.id, input)?,
+		TypeDef::BitSequence(bits) => {
+			let len = Compact::<u32>::decode(input)?.0 as usize;
+			let store = match registry.resolve(bits.bit_store_type.id).map(|ty| &ty.type_def) {
+				Some(TypeDef::Primitive(TypeDefPrimitive::U8)) => 1,
+				Some(TypeDef::Primitive(TypeDefPrimitive::U16)) => 2,
+				Some(TypeDef::Primitive(TypeDefPrimitive::U32)) => 4,
+				Some(TypeDef::Primitive(TypeDefPrimitive::U64)) => 8,
+				_ => return Err("Unsupported bit store type".into()),
+			};
+			skip_bytes(input, len.div_ceil(store * 8) * store)?;
+		},
+	}
+
+	Ok(())
+}
+
+/// Skip over `len` values of the type `ty`.
+fn skip_elements(
+	registry: &PortableRegistry,
+	ty: u32,
+	len: u32,
+	input: &mut &[u8],
+) -> Result<(), codec::Error> {
+	// Bytes are common and need no per element decoding.
+	if let Some(TypeDef::Primitive(TypeDefPrimitive::U8)) =
+		registry.resolve(ty).map(|ty| &ty.type_def)
+	{
+		return skip_bytes(input, len as usize)
+	}
+
+	for _ in 0..len {
+		skip_value(registry, ty, input)?;
+	}
+
+	Ok(())
+}
+
+fn skip_primitive(primitive: &TypeDefPrimitive, input: &mut &[u8]) -> Result<(), codec::Error> {
+	match primitive {
+		TypeDefPrimitive::Bool => {
+			bool::decode(input)?;
+		},
+		TypeDefPrimitive::Char => {
+			char::from_u32(u32::decode(input)?).ok_or("Invalid char")?;
+		},
+		TypeDefPrimitive::Str => {
+			String::decode(input)?;
+		},
+		TypeDefPrimitive::U8 | TypeDefPrimitive::I8 => skip_bytes(input, 1)?,
+		TypeDefPrimitive::U16 | TypeDefPrimitive::I16 => skip_bytes(input, 2)?,
+		TypeDefPrimitive::U32 | TypeDefPrimitive::I32 => skip_bytes(input, 4)?,
+		TypeDefPrimitive::U64 | TypeDefPrimitive::I64 => skip_bytes(input, 8)?,
+		TypeDefPrimitive::U128 | TypeDefPrimitive::I128 => skip_bytes(input, 16)?,
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => skip_bytes(input, 32)?,
+	}
+
+	Ok(())
+}
+
+/// Skip over a compact encoded value of the type `ty`.
+///
+/// Compact encoding is supported for unsigned integers and types wrapping them in a single field.
+fn skip_compact(
+	registry: &PortableRegistry,
+	ty: u32,
+	input: &mut &[u8],
+) -> Result<(), codec::Error> {
+	let ty = registry.resolve(ty).ok_or("Type not found in registry")?;
+	match &ty.type_def {
+		TypeDef::Primitive(TypeDefPrimitive::U8) => {
+			Compact::<u8>::decode(input)?;
+		},
+		TypeDef::Primitive(TypeDefPrimitive::U16) => {
+			Compact::<u16>::decode(input)?;
+		},
+		TypeDef::Primitive(TypeDefPrimitive::U32) => {
+			Compact::<u32>::decode(input)?;
+		},
+		TypeDef::Primitive(TypeDefPrimitive::U64) => {
+			Compact::<u64>::decode(input)?;
+		},
+		TypeDef::Primitive(TypeDefPrimitive::U128) => {
+			Compact::<u128>::decode(input)?;
+		},
+		TypeDef::Composite(composite) if composite.fields.len() == 1 =>
+			skip_compact(registry, composite.fields[0].ty.id, input)?,
+		TypeDef::Tuple(tuple) if tuple.fields.is_empty() => {},
+		TypeDef::Composite(composite) if composite.fields.is_empty() => {},
+		_ => return Err("Unsupported compact type".into()),
+	}
+
+	Ok(())
+}
+
+fn skip_bytes(input: &mut &[u8], len: usize) -> Result<(), codec::Error> {
+	if input.len() < len {
+		return Err("Not enough data to fill buffer".into())
+	}
+	*input = &input[len..];
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use scale_info::{meta_type, Registry, TypeInfo};
+
+	fn decode<T: TypeInfo + 'static>(value: &[u8]) -> Result<(), codec::Error> {
+		let mut registry = Registry::new();
+		let id = registry.register_type(&meta_type::<T>()).id;
+		decode_all(&registry.into(), id, value)
+	}
+
+	#[test]
+	fn values_are_checked_against_their_type() {
+		let value = (1u32, Some(vec![1u8, 2]), Compact(5u64), "abc".to_string(), [true; 2]);
+		assert!(decode::<(u32, Option<Vec<u8>>, Compact<u64>, String, [bool; 2])>(&value.encode())
+			.is_ok());
+
+		assert!(decode::<u64>(&1u32.encode()).is_err());
+		assert!(decode::<u32>(&1u64.encode()).is_err());
+		assert!(decode::<bool>(&[2]).is_err());
+		assert!(decode::<Option<u8>>(&[2, 0]).is_err());
+		assert!(decode::<Vec<u16>>(&vec![1u8, 2, 3].encode()).is_err());
+	}
+}
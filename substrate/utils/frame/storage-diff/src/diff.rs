@@ -0,0 +1,371 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison of the storage items of two runtimes.
+
+use crate::{
+	decode::DecodeOutcome,
+	layout::{Compatibility, Layout},
+};
+use frame_metadata::v15::{RuntimeMetadataV15, StorageEntryType, StorageHasher};
+use scale_info::PortableRegistry;
+use std::{collections::BTreeMap, fmt};
+
+/// The storage items of all pallets of a runtime.
+pub struct RuntimeStorage {
+	/// The registry of the types of the storage items.
+	pub registry: PortableRegistry,
+	/// The storage of each pallet, by pallet name.
+	pub pallets: BTreeMap<String, PalletStorage>,
+}
+
+/// The storage items of a pallet.
+pub struct PalletStorage {
+	/// The prefix of the storage keys of the pallet.
+	pub prefix: String,
+	/// The storage items, by name.
+	pub items: BTreeMap<String, StorageItem>,
+}
+
+/// The layout of a storage item.
+pub struct StorageItem {
+	/// The hashers of the keys of a map, empty for plain storage values.
+	pub hashers: Vec<StorageHasher>,
+	/// The layout of the key of a map, `None` for plain storage values.
+	pub key: Option<Layout>,
+	/// The layout of the value.
+	pub value: Layout,
+	/// The type of the value in the registry.
+	pub value_ty: u32,
+}
+
+impl RuntimeStorage {
+	/// Collect the storage items from the metadata of a runtime.
+	pub fn from_metadata(metadata: RuntimeMetadataV15) -> Result<Self, String> {
+		let registry = metadata.types;
+		let mut pallets = BTreeMap::new();
+
+		for pallet in metadata.pallets {
+			let Some(storage) = pallet.storage else { continue };
+
+			let mut items = BTreeMap::new();
+			for entry in storage.entries {
+				let item = match entry.ty {
+					StorageEntryType::Plain(value) => StorageItem {
+						hashers: Vec::new(),
+						key: None,
+						value: Layout::resolve(&registry, value.id)?,
+						value_ty: value.id,
+					},
+					StorageEntryType::Map { hashers, key, value } => StorageItem {
+						hashers,
+						key: Some(Layout::resolve(&registry, key.id)?),
+						value: Layout::resolve(&registry, value.id)?,
+						value_ty: value.id,
+					},
+				};
+				items.insert(entry.name, item);
+			}
+
+			pallets.insert(pallet.name, PalletStorage { prefix: storage.prefix, items });
+		}
+
+		Ok(Self { registry, pallets })
+	}
+}
+
+/// How a storage item changed between two runtimes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ItemChange {
+	/// The item was added.
+	Added,
+	/// The item was removed.
+	Removed,
+	/// The layout of the key or the value of the item changed.
+	Changed {
+		/// How the key changed, including its hashers.
+		key: Compatibility,
+		/// How the value changed.
+		value: Compatibility,
+	},
+}
+
+impl ItemChange {
+	/// Whether existing values of the item may not decode anymore.
+	pub fn is_incompatible(&self) -> bool {
+		matches!(
+			self,
+			Self::Changed { key: Compatibility::Incompatible, .. } |
+				Self::Changed { value: Compatibility::Incompatible, .. }
+		)
+	}
+}
+
+/// A storage item that changed between two runtimes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ItemDiff {
+	/// The name of the storage item.
+	pub name: String,
+	/// How the item changed.
+	pub change: ItemChange,
+	/// The outcome of decoding the existing values of the item with the new layout.
+	pub decoded: Option<DecodeOutcome>,
+}
+
+/// The storage changes of a pallet between two runtimes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PalletDiff {
+	/// The name of the pallet.
+	pub pallet: String,
+	/// The in-code storage version of the old runtime.
+	pub old_version: Option<u16>,
+	/// The in-code storage version of the new runtime.
+	pub new_version: Option<u16>,
+	/// The on-chain storage version before the runtime upgrade.
+	pub on_chain_version: Option<u16>,
+	/// The on-chain storage version after executing the migrations of the new runtime.
+	pub migrated_version: Option<u16>,
+	/// The storage items that changed.
+	pub items: Vec<ItemDiff>,
+}
+
+impl PalletDiff {
+	/// Whether the in-code storage version was bumped.
+	pub fn version_bumped(&self) -> bool {
+		match (self.old_version, self.new_version) {
+			(Some(old), Some(new)) => new > old,
+			_ => false,
+		}
+	}
+
+	/// Whether existing values of some storage item may not decode with the new runtime.
+	pub fn needs_migration(&self) -> bool {
+		self.items.iter().any(|item| item.change.is_incompatible())
+	}
+
+	/// Whether a storage layout changed incompatibly without bumping the storage version.
+	pub fn missing_migration(&self) -> bool {
+		self.needs_migration() && !self.version_bumped()
+	}
+
+	/// Whether the migrations of the new runtime did not bring the on-chain storage version to
+	/// the in-code storage version.
+	pub fn migration_incomplete(&self) -> bool {
+		self.migrated_version.is_some() && self.migrated_version != self.new_version
+	}
+
+	/// The number of existing values that failed to decode with the new layout.
+	pub fn decode_failures(&self) -> usize {
+		self.items
+			.iter()
+			.filter_map(|item| item.decoded.as_ref())
+			.map(|d| d.failures)
+			.sum()
+	}
+
+	/// Whether any problem was detected for this pallet.
+	pub fn has_errors(&self) -> bool {
+		self.missing_migration() || self.migration_incomplete() || self.decode_failures() > 0
+	}
+}
+
+/// Compare the storage of the `old` and the `new` runtime.
+///
+/// Only pallets whose storage items or in-code storage version changed are returned.
+pub fn diff(
+	old: &RuntimeStorage,
+	new: &RuntimeStorage,
+	old_versions: &BTreeMap<String, u16>,
+	new_versions: &BTreeMap<String, u16>,
+) -> Vec<PalletDiff> {
+	let empty = BTreeMap::new();
+	let mut pallets: Vec<&String> = old.pallets.keys().chain(new.pallets.keys()).collect();
+	pallets.sort();
+	pallets.dedup();
+
+	pallets
+		.into_iter()
+		.filter_map(|pallet| {
+			let old_items = old.pallets.get(pallet).map_or(&empty, |storage| &storage.items);
+			let new_items = new.pallets.get(pallet).map_or(&empty, |storage| &storage.items);
+			let mut names: Vec<&String> = old_items.keys().chain(new_items.keys()).collect();
+			names.sort();
+			names.dedup();
+
+			let items: Vec<_> = names
+				.into_iter()
+				.filter_map(|name| {
+					let change = match (old_items.get(name), new_items.get(name)) {
+						(None, _) => ItemChange::Added,
+						(_, None) => ItemChange::Removed,
+						(Some(old), Some(new)) => {
+							let key = match (&old.key, &new.key) {
+								_ if old.hashers != new.hashers => Compatibility::Incompatible,
+								(Some(old), Some(new)) => old.compatibility(new),
+								(None, None) => Compatibility::Identical,
+								_ => Compatibility::Incompatible,
+							};
+							let value = old.value.compatibility(&new.value);
+							if key == Compatibility::Identical && value == Compatibility::Identical
+							{
+								return None
+							}
+							ItemChange::Changed { key, value }
+						},
+					};
+					Some(ItemDiff { name: name.clone(), change, decoded: None })
+				})
+				.collect();
+
+			let diff = PalletDiff {
+				pallet: pallet.clone(),
+				old_version: old_versions.get(pallet).copied(),
+				new_version: new_versions.get(pallet).copied(),
+				on_chain_version: None,
+				migrated_version: None,
+				items,
+			};
+			(!diff.items.is_empty() || diff.old_version != diff.new_version).then_some(diff)
+		})
+		.collect()
+}
+
+fn version(version: Option<u16>) -> String {
+	version.map_or_else(|| "unknown".into(), |version| version.to_string())
+}
+
+impl fmt::Display for PalletDiff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"Pallet {}: storage version {} -> {}",
+			self.pallet,
+			version(self.old_version),
+			version(self.new_version)
+		)?;
+		if self.on_chain_version.is_some() {
+			write!(f, ", on-chain {}", version(self.on_chain_version))?;
+		}
+		if self.migrated_version.is_some() {
+			write!(f, ", after migrations {}", version(self.migrated_version))?;
+		}
+		writeln!(f)?;
+
+		for item in &self.items {
+			match &item.change {
+				ItemChange::Added => write!(f, "  + {}", item.name)?,
+				ItemChange::Removed => write!(f, "  - {}", item.name)?,
+				ItemChange::Changed { key, value } =>
+					write!(f, "  ~ {}: key {key:?}, value {value:?}", item.name)?,
+			}
+			if let Some(decoded) = &item.decoded {
+				write!(f, ", {} values, {} failed to decode", decoded.values, decoded.failures)?;
+				if let Some((key, error)) = &decoded.first_failure {
+					write!(
+						f,
+						" (first: {} at 0x{})",
+						error,
+						sp_core::hexdisplay::HexDisplay::from(key)
+					)?;
+				}
+			}
+			writeln!(f)?;
+		}
+
+		if self.missing_migration() {
+			writeln!(f, "  ! storage layout changed incompatibly without a storage version bump")?;
+		} else if self.needs_migration() {
+			writeln!(f, "  * storage layout changed incompatibly, a migration is required")?;
+		}
+		if self.migration_incomplete() {
+			writeln!(
+				f,
+				"  ! migrations did not reach the in-code storage version {}",
+				version(self.new_version)
+			)?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use scale_info::{meta_type, Registry};
+
+	fn storage(items: &[(&str, scale_info::MetaType)]) -> RuntimeStorage {
+		let mut registry = Registry::new();
+		let ids: Vec<_> = items.iter().map(|(_, ty)| registry.register_type(ty).id).collect();
+		let registry: PortableRegistry = registry.into();
+		let items = items
+			.iter()
+			.zip(ids)
+			.map(|((name, _), id)| {
+				let item = StorageItem {
+					hashers: Vec::new(),
+					key: None,
+					value: Layout::resolve(&registry, id).unwrap(),
+					value_ty: id,
+				};
+				(name.to_string(), item)
+			})
+			.collect();
+		let pallet = PalletStorage { prefix: "Pallet".into(), items };
+		RuntimeStorage { registry, pallets: [("Pallet".to_string(), pallet)].into() }
+	}
+
+	#[test]
+	fn incompatible_changes_require_a_version_bump() {
+		let old = storage(&[("Unchanged", meta_type::<u32>()), ("Changed", meta_type::<u32>())]);
+		let new = storage(&[("Unchanged", meta_type::<u32>()), ("Changed", meta_type::<u64>())]);
+		let versions = |version: u16| [("Pallet".to_string(), version)].into();
+
+		let diffs = diff(&old, &new, &versions(1), &versions(1));
+		assert_eq!(diffs.len(), 1);
+		assert_eq!(
+			diffs[0].items,
+			vec![ItemDiff {
+				name: "Changed".into(),
+				change: ItemChange::Changed {
+					key: Compatibility::Identical,
+					value: Compatibility::Incompatible
+				},
+				decoded: None,
+			}]
+		);
+		assert!(diffs[0].missing_migration());
+
+		let diffs = diff(&old, &new, &versions(1), &versions(2));
+		assert!(diffs[0].needs_migration());
+		assert!(!diffs[0].missing_migration());
+
+		assert!(diff(&old, &old, &versions(1), &versions(1)).is_empty());
+	}
+
+	#[test]
+	fn missing_versions_are_reported_as_unknown() {
+		let old = storage(&[("Changed", meta_type::<u32>())]);
+		let new = storage(&[("Changed", meta_type::<u64>())]);
+
+		let diffs = diff(&old, &new, &BTreeMap::new(), &BTreeMap::new());
+		assert_eq!((diffs[0].old_version, diffs[0].new_version), (None, None));
+		assert!(diffs[0]
+			.to_string()
+			.starts_with("Pallet Pallet: storage version unknown -> unknown"));
+	}
+}
@@ -0,0 +1,247 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Registry independent description of how types are SCALE encoded.
+
+use scale_info::{PortableRegistry, TypeDef, TypeDefPrimitive};
+use std::collections::BTreeMap;
+
+/// The SCALE encoding of a type, independent of the registry that describes it.
+///
+/// Names, docs and paths do not affect the encoding and are ignored. Fields of composites and
+/// tuples are flattened, such that wrapping a type into a new type does not change its layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Layout {
+	/// A primitive type.
+	Primitive(TypeDefPrimitive),
+	/// A compact encoded type.
+	Compact(Box<Layout>),
+	/// A sequence, prefixed with its compact encoded length.
+	Sequence(Box<Layout>),
+	/// An array of fixed length.
+	Array(u32, Box<Layout>),
+	/// The concatenation of the encoding of its fields.
+	Fields(Vec<Layout>),
+	/// An enum, with the layout of the fields of its variants by their index.
+	Variant(BTreeMap<u8, Layout>),
+	/// A bit sequence, with the layout of its store and the path of its order type.
+	BitSequence(Box<Layout>, String),
+	/// A reference to a type with the given path that contains itself.
+	Recursive(String),
+}
+
+/// How the layout of a type changed between two runtimes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Compatibility {
+	/// The layout did not change.
+	Identical,
+	/// Variants were added, such that all values of the old layout decode with the new one.
+	Extended,
+	/// Values of the old layout may not decode with the new one.
+	Incompatible,
+}
+
+impl Layout {
+	/// Resolve the layout of the type `id` in `registry`.
+	pub fn resolve(registry: &PortableRegistry, id: u32) -> Result<Self, String> {
+		Self::resolve_inner(registry, id, &mut Vec::new())
+	}
+
+	fn resolve_inner(
+		registry: &PortableRegistry,
+		id: u32,
+		stack: &mut Vec<u32>,
+	) -> Result<Self, String> {
+		let ty = registry.resolve(id).ok_or_else(|| format!("Type {id} not found in registry"))?;
+		if stack.contains(&id) {
+			return Ok(Self::Recursive(ty.path.segments.join("::")))
+		}
+
+		stack.push(id);
+		let layout = match &ty.type_def {
+			TypeDef::Primitive(primitive) => Self::Primitive(primitive.clone()),
+			TypeDef::Compact(compact) => Self::Compact(Box::new(Self::resolve_inner(
+				registry,
+				compact.type_param.id,
+				stack,
+			)?)),
+			TypeDef::Sequence(sequence) => Self::Sequence(Box::new(Self::resolve_inner(
+				registry,
+				sequence.type_param.id,
+				stack,
+			)?)),
+			TypeDef::Array(array) => Self::Array(
+				array.len,
+				Box::new(Self::resolve_inner(registry, array.type_param.id, stack)?),
+			),
+			TypeDef::Tuple(tuple) =>
+				Self::resolve_fields(registry, tuple.fields.iter().map(|ty| ty.id), stack)?,
+			TypeDef::Composite(composite) => Self::resolve_fields(
+				registry,
+				composite.fields.iter().map(|field| field.ty.id),
+				stack,
+			)?,
+			TypeDef::Variant(variant) => {
+				let mut variants = BTreeMap::new();
+				for variant in &variant.variants {
+					let fields = Self::resolve_fields(
+						registry,
+						variant.fields.iter().map(|field| field.ty.id),
+						stack,
+					)?;
+					variants.insert(variant.index, fields);
+				}
+				Self::Variant(variants)
+			},
+			TypeDef::BitSequence(bits) => {
+				let order = registry.resolve(bits.bit_order_type.id).ok_or_else(|| {
+					format!("Type {} not found in registry", bits.bit_order_type.id)
+				})?;
+				Self::BitSequence(
+					Box::new(Self::resolve_inner(registry, bits.bit_store_type.id, stack)?),
+					order.path.segments.join("::"),
+				)
+			},
+		};
+		stack.pop();
+
+		Ok(layout)
+	}
+
+	/// Resolve the flattened layout of the given fields.
+	fn resolve_fields(
+		registry: &PortableRegistry,
+		ids: impl Iterator<Item = u32>,
+		stack: &mut Vec<u32>,
+	) -> Result<Self, String> {
+		let mut fields = Vec::new();
+		for id in ids {
+			match Self::resolve_inner(registry, id, stack)? {
+				Self::Fields(inner) => fields.extend(inner),
+				layout => fields.push(layout),
+			}
+		}
+
+		if fields.len() == 1 {
+			Ok(fields.remove(0))
+		} else {
+			Ok(Self::Fields(fields))
+		}
+	}
+
+	/// Check whether values encoded with the layout `self` decode with the layout `new`.
+	pub fn compatibility(&self, new: &Self) -> Compatibility {
+		match (self, new) {
+			(Self::Compact(old), Self::Compact(new)) |
+			(Self::Sequence(old), Self::Sequence(new)) => old.compatibility(new),
+			(Self::Array(old_len, old), Self::Array(new_len, new)) if old_len == new_len =>
+				old.compatibility(new),
+			(Self::Fields(old), Self::Fields(new)) if old.len() == new.len() => old
+				.iter()
+				.zip(new)
+				.map(|(old, new)| old.compatibility(new))
+				.max()
+				.unwrap_or(Compatibility::Identical),
+			(Self::Variant(old), Self::Variant(new)) => {
+				let added = if new.keys().any(|index| !old.contains_key(index)) {
+					Compatibility::Extended
+				} else {
+					Compatibility::Identical
+				};
+				old.iter()
+					.map(|(index, old)| {
+						new.get(index)
+							.map_or(Compatibility::Incompatible, |new| old.compatibility(new))
+					})
+					.fold(added, Ord::max)
+			},
+			(old, new) if old == new => Compatibility::Identical,
+			_ => Compatibility::Incompatible,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use scale_info::{meta_type, Registry, TypeInfo};
+
+	fn layout<T: TypeInfo + 'static>() -> Layout {
+		let mut registry = Registry::new();
+		let id = registry.register_type(&meta_type::<T>()).id;
+		Layout::resolve(&registry.into(), id).unwrap()
+	}
+
+	fn compatibility<Old: TypeInfo + 'static, New: TypeInfo + 'static>() -> Compatibility {
+		layout::<Old>().compatibility(&layout::<New>())
+	}
+
+	#[derive(TypeInfo)]
+	#[allow(dead_code)]
+	struct Wrapper(u32, (bool, Vec<u8>));
+
+	#[derive(TypeInfo)]
+	#[allow(dead_code)]
+	enum Old {
+		A,
+		B(u32),
+	}
+
+	#[derive(TypeInfo)]
+	#[allow(dead_code)]
+	enum Extended {
+		A,
+		B(u32),
+		C { value: bool },
+	}
+
+	#[derive(TypeInfo)]
+	#[allow(dead_code)]
+	enum Changed {
+		A,
+		B(u64),
+	}
+
+	#[derive(TypeInfo)]
+	#[allow(dead_code)]
+	struct Recursive {
+		children: Vec<Recursive>,
+	}
+
+	#[test]
+	fn layouts_ignore_names_and_nesting() {
+		assert_eq!(compatibility::<Wrapper, (u32, bool, Vec<u8>)>(), Compatibility::Identical);
+		assert_eq!(compatibility::<(u32,), u32>(), Compatibility::Identical);
+		assert_eq!(compatibility::<Option<Wrapper>, Option<u32>>(), Compatibility::Incompatible);
+		assert_eq!(
+			layout::<Recursive>(),
+			Layout::Sequence(Box::new(Layout::Recursive(
+				"frame_storage_diff::layout::tests::Recursive".into()
+			)))
+		);
+	}
+
+	#[test]
+	fn added_variants_are_compatible() {
+		assert_eq!(compatibility::<Old, Extended>(), Compatibility::Extended);
+		assert_eq!(compatibility::<Vec<(Old, u8)>, Vec<(Extended, u8)>>(), Compatibility::Extended);
+		assert_eq!(compatibility::<Extended, Old>(), Compatibility::Incompatible);
+		assert_eq!(compatibility::<Old, Changed>(), Compatibility::Incompatible);
+		assert_eq!(compatibility::<[u8; 4], [u8; 8]>(), Compatibility::Incompatible);
+		assert_eq!(compatibility::<u32, u64>(), Compatibility::Incompatible);
+	}
+}
@@ -0,0 +1,184 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Detect storage items whose layout changed between two runtimes without a migration.
+//!
+//! The storage items of both runtimes are read from their metadata and their types are compared
+//! by how they are SCALE encoded. Changes that may break the decoding of existing values are
+//! cross-checked against the in-code storage versions of the pallets: a pallet that changes its
+//! storage layout incompatibly must bump its storage version and provide a migration.
+//!
+//! Optionally, the state of a chain is loaded from a remote-externalities snapshot or a node, the
+//! migrations of the new runtime are executed on top of it and all existing values of the
+//! changed storage items are decoded with their new layout.
+
+pub mod decode;
+pub mod diff;
+pub mod layout;
+pub mod runtime;
+
+use codec::Decode;
+use diff::{ItemChange, PalletDiff, RuntimeStorage};
+use runtime::Runtime;
+use sp_core::Hasher;
+use sp_state_machine::TestExternalities;
+
+/// Log target of this crate.
+pub const LOG_TARGET: &str = "storage-diff";
+
+/// Check the existing state in `ext` against the storage changes in `diffs`.
+///
+/// Records the on-chain storage version of each pallet. If `run_migrations` is set, the
+/// migrations of the `new` runtime are executed first, which requires a try-runtime build, and the
+/// resulting storage version recorded. Afterwards, all values of the changed storage items are
+/// decoded with their layout in `storage`, the storage of the new runtime.
+pub fn check_state<H>(
+	ext: &mut TestExternalities<H>,
+	new: &Runtime,
+	storage: &RuntimeStorage,
+	diffs: &mut [PalletDiff],
+	run_migrations: bool,
+) -> Result<(), String>
+where
+	H: Hasher,
+	H::Out: Ord + 'static + codec::Codec,
+{
+	let storage_version = |pallet: &str| {
+		sp_io::storage::get(&runtime::storage_version_key(pallet))
+			.and_then(|version| u16::decode(&mut &version[..]).ok())
+	};
+
+	ext.execute_with(|| {
+		for diff in diffs.iter_mut() {
+			diff.on_chain_version = storage_version(&diff.pallet);
+		}
+	});
+
+	if run_migrations {
+		log::info!(target: LOG_TARGET, "Executing the migrations of the new runtime");
+		new.try_on_runtime_upgrade(&mut ext.ext())?;
+
+		ext.execute_with(|| {
+			for diff in diffs.iter_mut() {
+				diff.migrated_version = storage_version(&diff.pallet);
+			}
+		});
+	}
+
+	ext.execute_with(|| {
+		for diff in diffs.iter_mut() {
+			let Some(pallet) = storage.pallets.get(&diff.pallet) else { continue };
+			for item in &mut diff.items {
+				if !matches!(item.change, ItemChange::Changed { .. }) {
+					continue
+				}
+				let Some(layout) = pallet.items.get(&item.name) else { continue };
+
+				log::debug!(target: LOG_TARGET, "Decoding {}::{}", diff.pallet, item.name);
+				item.decoded = Some(decode::decode_values(
+					&storage.registry,
+					layout.value_ty,
+					&runtime::storage_prefix(&pallet.prefix, &item.name),
+				));
+			}
+		}
+	});
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use diff::{ItemDiff, PalletStorage, StorageItem};
+	use layout::{Compatibility, Layout};
+	use scale_info::{meta_type, PortableRegistry, Registry};
+	use sp_runtime::traits::BlakeTwo256;
+
+	fn storage() -> RuntimeStorage {
+		let mut registry = Registry::new();
+		let id = registry.register_type(&meta_type::<u64>()).id;
+		let registry: PortableRegistry = registry.into();
+		let item = StorageItem {
+			hashers: Vec::new(),
+			key: None,
+			value: Layout::resolve(&registry, id).unwrap(),
+			value_ty: id,
+		};
+		let pallet = PalletStorage {
+			prefix: "Pallet".into(),
+			items: [("Changed".to_string(), item)].into(),
+		};
+		RuntimeStorage { registry, pallets: [("Pallet".to_string(), pallet)].into() }
+	}
+
+	fn diffs() -> Vec<PalletDiff> {
+		vec![PalletDiff {
+			pallet: "Pallet".into(),
+			old_version: Some(1),
+			new_version: Some(2),
+			on_chain_version: None,
+			migrated_version: None,
+			items: vec![ItemDiff {
+				name: "Changed".into(),
+				change: ItemChange::Changed {
+					key: Compatibility::Identical,
+					value: Compatibility::Incompatible,
+				},
+				decoded: None,
+			}],
+		}]
+	}
+
+	fn ext() -> TestExternalities<BlakeTwo256> {
+		let mut ext = TestExternalities::default();
+		ext.insert(runtime::storage_version_key("Pallet"), 1u16.encode());
+		// A value of the old layout, which does not decode as the new one.
+		ext.insert(runtime::storage_prefix("Pallet", "Changed"), 7u32.encode());
+		ext
+	}
+
+	#[test]
+	fn check_state_decodes_changed_items() {
+		let mut diffs = diffs();
+		check_state(&mut ext(), &Runtime::new(Vec::new()), &storage(), &mut diffs, false).unwrap();
+
+		assert_eq!(diffs[0].on_chain_version, Some(1));
+		assert_eq!(diffs[0].migrated_version, None);
+		let decoded = diffs[0].items[0].decoded.as_ref().unwrap();
+		assert_eq!(decoded.values, 1);
+		assert_eq!(decoded.failures, 1);
+		assert_eq!(
+			decoded.first_failure.as_ref().map(|(key, _)| key.clone()),
+			Some(runtime::storage_prefix("Pallet", "Changed")),
+		);
+		assert_eq!(diffs[0].decode_failures(), 1);
+		assert!(diffs[0].has_errors());
+	}
+
+	#[test]
+	fn check_state_fails_if_migrations_fail() {
+		let mut diffs = diffs();
+		let runtime = Runtime::new(b"not a runtime".to_vec());
+
+		assert!(check_state(&mut ext(), &runtime, &storage(), &mut diffs, true).is_err());
+		// The on-chain version is recorded before the migrations are executed.
+		assert_eq!(diffs[0].on_chain_version, Some(1));
+		assert_eq!(diffs[0].items[0].decoded, None);
+	}
+}
@@ -0,0 +1,100 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compare the storage layout of two runtimes to detect missing migrations.
+
+use clap::Parser;
+use frame_storage_diff::{check_state, diff, runtime::Runtime};
+use remote_externalities::{Builder, Mode, OfflineConfig, OnlineConfig, SnapshotConfig, Transport};
+use sp_runtime::{generic, traits::BlakeTwo256, OpaqueExtrinsic};
+use std::path::PathBuf;
+
+/// The block type used to load the state, only its hashing matters.
+type Block = generic::Block<generic::Header<u32, BlakeTwo256>, OpaqueExtrinsic>;
+
+#[derive(Debug, Parser)]
+struct Opt {
+	/// The Wasm blob of the runtime currently on-chain.
+	old: PathBuf,
+
+	/// The Wasm blob of the runtime to upgrade to.
+	new: PathBuf,
+
+	/// Decode the existing values of changed storage items from this state snapshot.
+	#[arg(long, conflicts_with = "uri")]
+	snapshot: Option<PathBuf>,
+
+	/// Decode the existing values of changed storage items from the state of this node.
+	#[arg(long)]
+	uri: Option<String>,
+
+	/// Execute the migrations of the new runtime before decoding, requires a try-runtime build.
+	#[arg(long)]
+	run_migrations: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+	env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+	let opt = Opt::parse();
+
+	let old = Runtime::from_file(&opt.old)?;
+	let new = Runtime::from_file(&opt.new)?;
+	let old_storage = diff::RuntimeStorage::from_metadata(old.metadata()?)?;
+	let new_storage = diff::RuntimeStorage::from_metadata(new.metadata()?)?;
+
+	let storage_versions = |runtime: &Runtime, storage: &diff::RuntimeStorage| {
+		runtime
+			.storage_versions(storage.pallets.keys().map(String::as_str))
+			.map_err(|e| format!("Failed to read the storage versions: {e}"))
+	};
+	let old_versions = storage_versions(&old, &old_storage)?;
+	let new_versions = storage_versions(&new, &new_storage)?;
+
+	let mut diffs = diff::diff(&old_storage, &new_storage, &old_versions, &new_versions);
+
+	let mode = match (opt.snapshot, opt.uri) {
+		(Some(path), _) =>
+			Some(Mode::Offline(OfflineConfig { state_snapshot: SnapshotConfig::new(path) })),
+		(None, Some(uri)) => Some(Mode::Online(OnlineConfig {
+			transport: Transport::from(uri),
+			// The migrations may touch any pallet, otherwise only the changed ones are needed.
+			pallets: if opt.run_migrations {
+				Vec::new()
+			} else {
+				diffs.iter().map(|diff| diff.pallet.clone()).collect()
+			},
+			..Default::default()
+		})),
+		(None, None) => None,
+	};
+	if let Some(mode) = mode {
+		let mut ext = Builder::<Block>::default().mode(mode).build().await?;
+		check_state(&mut ext.inner_ext, &new, &new_storage, &mut diffs, opt.run_migrations)?;
+	}
+
+	for diff in &diffs {
+		println!("{diff}");
+	}
+
+	let errors = diffs.iter().filter(|diff| diff.has_errors()).count();
+	if errors > 0 {
+		return Err(format!("{errors} pallets have storage changes without a working migration"))
+	}
+
+	Ok(())
+}
@@ -0,0 +1,187 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Calls into the runtime blobs that are compared.
+
+use codec::{Decode, Encode};
+use frame_metadata::{v15::RuntimeMetadataV15, RuntimeMetadata, RuntimeMetadataPrefixed};
+use sc_chain_spec::GenesisConfigBuilderRuntimeCaller;
+use sc_executor::WasmExecutor;
+use sp_core::{
+	traits::{CallContext, CodeExecutor, RuntimeCode, WrappedRuntimeCode},
+	OpaqueMetadata,
+};
+use sp_state_machine::BasicExternalities;
+use std::{collections::BTreeMap, path::Path};
+
+/// Runtime API method returning the metadata of a given version.
+const METADATA_AT_VERSION: &str = "Metadata_metadata_at_version";
+/// Runtime API method executing the runtime upgrade, only present in try-runtime builds.
+const TRY_ON_RUNTIME_UPGRADE: &str = "TryRuntime_on_runtime_upgrade";
+/// The version of the metadata that describes the storage layout.
+const METADATA_VERSION: u32 = 15;
+/// The encoded `UpgradeCheckSelect::None`, such that no `pre/post_upgrade` checks are executed.
+const UPGRADE_CHECK_SELECT_NONE: u8 = 0;
+/// Postfix of the storage key of the storage version of a pallet.
+const STORAGE_VERSION_KEY_POSTFIX: &[u8] = b":__STORAGE_VERSION__:";
+
+/// A runtime Wasm blob.
+pub struct Runtime {
+	code: Vec<u8>,
+	hash: Vec<u8>,
+	executor: WasmExecutor<sp_io::SubstrateHostFunctions>,
+}
+
+impl Runtime {
+	/// Create the runtime from its Wasm blob.
+	pub fn new(code: Vec<u8>) -> Self {
+		Self {
+			hash: sp_crypto_hashing::blake2_256(&code).to_vec(),
+			code,
+			executor: WasmExecutor::builder().with_allow_missing_host_functions(true).build(),
+		}
+	}
+
+	/// Read the runtime from the Wasm blob at `path`.
+	pub fn from_file(path: &Path) -> Result<Self, String> {
+		std::fs::read(path)
+			.map(Self::new)
+			.map_err(|e| format!("Failed to read runtime {}: {e}", path.display()))
+	}
+
+	/// Call `method` of the runtime with `data` on top of `ext`.
+	pub fn call(
+		&self,
+		ext: &mut dyn sp_externalities::Externalities,
+		method: &str,
+		data: &[u8],
+	) -> Result<Vec<u8>, String> {
+		let runtime_code = RuntimeCode {
+			code_fetcher: &WrappedRuntimeCode(self.code.as_slice().into()),
+			heap_pages: None,
+			hash: self.hash.clone(),
+		};
+
+		self.executor
+			.call(ext, &runtime_code, method, data, CallContext::Offchain)
+			.0
+			.map_err(|e| format!("Failed to call {method}: {e}"))
+	}
+
+	/// The metadata of the runtime.
+	pub fn metadata(&self) -> Result<RuntimeMetadataV15, String> {
+		let metadata = self.call(
+			&mut BasicExternalities::new_empty(),
+			METADATA_AT_VERSION,
+			&METADATA_VERSION.encode(),
+		)?;
+		let metadata = Option::<OpaqueMetadata>::decode(&mut &metadata[..])
+			.map_err(|e| format!("Failed to decode metadata: {e}"))?
+			.ok_or_else(|| format!("The runtime has no metadata of version {METADATA_VERSION}"))?;
+
+		match RuntimeMetadataPrefixed::decode(&mut &metadata[..])
+			.map_err(|e| format!("Failed to decode metadata: {e}"))?
+			.1
+		{
+			RuntimeMetadata::V15(metadata) => Ok(metadata),
+			_ => Err(format!("Expected metadata of version {METADATA_VERSION}")),
+		}
+	}
+
+	/// The in-code storage versions of the given pallets.
+	///
+	/// These are read from the genesis state of the runtime, which contains the in-code storage
+	/// version of every pallet. The default preset is used, or the first named preset that builds
+	/// if the runtime has no default one. Pallets without a storage version are not returned.
+	pub fn storage_versions<'a>(
+		&self,
+		pallets: impl IntoIterator<Item = &'a str>,
+	) -> Result<BTreeMap<String, u16>, String> {
+		let caller = GenesisConfigBuilderRuntimeCaller::<()>::new(&self.code);
+		let storage = match caller.get_storage_for_named_preset(None) {
+			Ok(storage) => storage,
+			Err(e) => caller
+				.preset_names()?
+				.iter()
+				.find_map(|name| caller.get_storage_for_named_preset(Some(name)).ok())
+				.ok_or_else(|| format!("No genesis preset of the runtime could be built: {e}"))?,
+		};
+
+		Ok(pallets
+			.into_iter()
+			.filter_map(|pallet| {
+				let version = storage.top.get(&storage_version_key(pallet))?;
+				Some((pallet.to_string(), u16::decode(&mut &version[..]).ok()?))
+			})
+			.collect())
+	}
+
+	/// Execute the runtime upgrade on top of `ext`, which requires a try-runtime build.
+	///
+	/// This runs all migrations that are registered in the runtime, without their `pre_upgrade`
+	/// and `post_upgrade` checks.
+	pub fn try_on_runtime_upgrade(
+		&self,
+		ext: &mut dyn sp_externalities::Externalities,
+	) -> Result<(), String> {
+		self.call(ext, TRY_ON_RUNTIME_UPGRADE, &UPGRADE_CHECK_SELECT_NONE.encode())
+			.map(drop)
+	}
+}
+
+/// The key of the storage version of the pallet with the storage `prefix`.
+pub fn storage_version_key(prefix: &str) -> Vec<u8> {
+	storage_prefix(prefix, STORAGE_VERSION_KEY_POSTFIX)
+}
+
+/// The key prefix of the storage item `item` of the pallet with the storage `prefix`.
+pub fn storage_prefix(prefix: &str, item: impl AsRef<[u8]>) -> Vec<u8> {
+	let mut key = sp_crypto_hashing::twox_128(prefix.as_bytes()).to_vec();
+	key.extend(sp_crypto_hashing::twox_128(item.as_ref()));
+	key
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn storage_keys_are_correct() {
+		assert_eq!(
+			storage_prefix("System", "Number"),
+			array_bytes::hex2bytes_unchecked(
+				"26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac"
+			),
+		);
+		assert_eq!(
+			storage_version_key("System"),
+			array_bytes::hex2bytes_unchecked(
+				"26aa394eea5630e07c48ae0c9558cef74e7b9012096b41c4eb3aaf947f6ea429"
+			),
+		);
+	}
+
+	#[test]
+	fn invalid_runtimes_error() {
+		assert!(Runtime::from_file(Path::new("/does/not/exist.wasm")).is_err());
+
+		let runtime = Runtime::new(b"not a runtime".to_vec());
+		assert!(runtime.metadata().is_err());
+		assert!(runtime.storage_versions(["System"]).is_err());
+		assert!(runtime.try_on_runtime_upgrade(&mut BasicExternalities::new_empty()).is_err());
+	}
+}
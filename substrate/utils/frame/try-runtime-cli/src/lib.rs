@@ -0,0 +1,78 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dry-run the migrations of a runtime against the state of a chain.
+//!
+//! The state is loaded with remote-externalities, either from a running node or from a snapshot,
+//! and the runtime, which must be built with the `try-runtime` feature, is executed on top of it
+//! through its `TryRuntime` runtime api.
+
+pub mod multi_block_migrations;
+pub mod runtime;
+
+use remote_externalities::{
+	Builder, Mode, OfflineConfig, OnlineConfig, RemoteExternalities, SnapshotConfig, Transport,
+};
+use sp_runtime::{generic, traits::BlakeTwo256, OpaqueExtrinsic};
+use std::path::PathBuf;
+
+/// Log target of this crate.
+pub const LOG_TARGET: &str = "try-runtime-cli";
+
+/// The header of the blocks that are loaded and simulated.
+pub type Header = generic::Header<u32, BlakeTwo256>;
+/// The block type used to load the state, only its header matters.
+pub type Block = generic::Block<Header, OpaqueExtrinsic>;
+
+/// The source of the state to execute the runtime on.
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum State {
+	/// Download the state of the latest finalized block from a node.
+	Live {
+		/// The URI of the node.
+		#[arg(long)]
+		uri: String,
+
+		/// Store the downloaded state in a snapshot at this path.
+		#[arg(long)]
+		snapshot: Option<PathBuf>,
+	},
+
+	/// Load the state from a snapshot.
+	Snap {
+		/// The path to the snapshot.
+		#[arg(long)]
+		path: PathBuf,
+	},
+}
+
+impl State {
+	/// Load the state into externalities.
+	pub async fn to_ext(&self) -> Result<RemoteExternalities<Block>, String> {
+		let mode = match self {
+			Self::Live { uri, snapshot } => Mode::Online(OnlineConfig {
+				transport: Transport::from(uri.clone()),
+				state_snapshot: snapshot.clone().map(SnapshotConfig::new),
+				..Default::default()
+			}),
+			Self::Snap { path } =>
+				Mode::Offline(OfflineConfig { state_snapshot: SnapshotConfig::new(path.clone()) }),
+		};
+
+		Ok(Builder::<Block>::default().mode(mode).build().await?)
+	}
+}
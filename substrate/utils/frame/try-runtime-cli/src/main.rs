@@ -0,0 +1,70 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dry-run the migrations of a runtime against the state of a chain.
+
+use clap::{Parser, Subcommand};
+use frame_try_runtime_cli::{multi_block_migrations, runtime::Runtime};
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+struct Opt {
+	/// The Wasm blob of the runtime to test, built with the `try-runtime` feature.
+	#[arg(long)]
+	runtime: PathBuf,
+
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+	/// Execute the runtime upgrade and simulate the multi-block migrations block by block until
+	/// they complete.
+	MultiBlockMigrations(multi_block_migrations::Command),
+}
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+	env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+	let opt = Opt::parse();
+
+	let runtime = Runtime::from_file(&opt.runtime)?;
+	match opt.command {
+		Command::MultiBlockMigrations(cmd) => {
+			let report = cmd.run(&runtime).await?;
+			println!(
+				"Simulated {} blocks, total weight {}, max weight per block {}",
+				report.blocks(),
+				report.total_weight(),
+				report.max_weight(),
+			);
+
+			if !report.completed {
+				return Err(format!("Migrations did not complete within {} blocks", cmd.max_blocks))
+			}
+			let overweight = report.overweight_blocks();
+			if overweight > 0 {
+				return Err(format!(
+					"Migrations exceeded their service weight in {overweight} blocks"
+				))
+			}
+		},
+	}
+
+	Ok(())
+}
@@ -0,0 +1,266 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Simulate the multi-block migrations of a runtime block by block.
+//!
+//! The runtime upgrade, which starts the multi-block migrations, is executed first. Afterwards,
+//! blocks that only contain inherents are simulated on top of the state until the migrations
+//! complete. Each block is executed fully, including `on_initialize` and `on_finalize`, and the
+//! weight of the migrations is compared against the weight that they may consume per block.
+//!
+//! The inherents of the simulated blocks are created by the inherent data providers that are
+//! passed to [`Command::run_with`], given the header of the parent block, as when authoring blocks.
+//! Relay chains and parachains provide the inherent data they require this way, for example the
+//! validation data of parachains. The timestamp inherent is always set from the BABE or Aura slot
+//! of the block, or advances by a fixed block time for runtimes without slots.
+
+use crate::{runtime::Runtime, Block, Header, State, LOG_TARGET};
+use codec::{Decode, Encode};
+use frame_try_runtime::{MultiBlockMigrationsReport, UpgradeCheckSelect};
+use sp_consensus_aura::AURA_ENGINE_ID;
+use sp_consensus_babe::{
+	digests::{PreDigest, SecondaryPlainPreDigest},
+	Slot, BABE_ENGINE_ID,
+};
+use sp_inherents::{CreateInherentDataProviders, InherentDataProvider};
+use sp_runtime::{
+	traits::{BlakeTwo256, Header as _},
+	Digest, DigestItem,
+};
+use sp_state_machine::TestExternalities;
+
+/// Execute the runtime upgrade and simulate the multi-block migrations block by block until they
+/// complete.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct Command {
+	/// The maximal number of blocks to simulate.
+	#[arg(long, default_value_t = 1_000)]
+	pub max_blocks: u32,
+
+	/// The checks to execute: `none`, `pre-and-post`, `try-state` or `all`.
+	///
+	/// `pre-and-post` executes the `pre_upgrade` and `post_upgrade` checks of the migrations,
+	/// `try-state` decodes the state and executes the `try_state` hooks once they completed.
+	#[arg(long, default_value = "all")]
+	pub checks: UpgradeCheckSelect,
+
+	/// The time between two blocks in milliseconds, for runtimes without BABE or Aura.
+	#[arg(long, default_value_t = 6_000)]
+	pub block_time: u64,
+
+	#[command(subcommand)]
+	pub state: State,
+}
+
+impl Command {
+	/// Run the command with `runtime`, for runtimes that only require the timestamp inherent.
+	pub async fn run(&self, runtime: &Runtime) -> Result<MultiBlockMigrationsReport, String> {
+		self.run_with(runtime, |_, _| async { Ok(()) }).await
+	}
+
+	/// Run the command with `runtime`, creating the inherent data of every simulated block with
+	/// `create_inherent_data_providers`, which is given the header of the parent block.
+	pub async fn run_with<CIDP>(
+		&self,
+		runtime: &Runtime,
+		create_inherent_data_providers: CIDP,
+	) -> Result<MultiBlockMigrationsReport, String>
+	where
+		CIDP: CreateInherentDataProviders<Block, Header>,
+	{
+		let mut ext = self.state.to_ext().await?;
+		let (weight, _) = runtime.try_on_runtime_upgrade(&mut ext.inner_ext.ext(), self.checks)?;
+		log::info!(target: LOG_TARGET, "The runtime upgrade consumed {weight}");
+
+		let slots = Slots::detect(runtime, &mut ext.inner_ext, self.block_time)?;
+		let mut parent = ext.header.clone();
+		frame_try_runtime::run_multi_block_migrations(self.max_blocks, || {
+			let (header, timestamp) = slots.next_block(&mut ext.inner_ext, &parent)?;
+			let mut inherent_data = futures::executor::block_on(async {
+				create_inherent_data_providers
+					.create_inherent_data_providers(parent.hash(), parent.clone())
+					.await
+					.map_err(|e| e.to_string())?
+					.create_inherent_data()
+					.await
+					.map_err(|e| e.to_string())
+			})
+			.map_err(|e| format!("Failed to create the inherent data: {e}"))?;
+			inherent_data.replace_data(sp_timestamp::INHERENT_IDENTIFIER, &timestamp);
+
+			let step = runtime.try_step_multi_block_migrations(
+				&mut ext.inner_ext.ext(),
+				&header,
+				&inherent_data,
+				self.checks,
+			)?;
+			parent = header;
+			Ok(step)
+		})
+	}
+}
+
+/// How the slot and timestamp of the simulated blocks are derived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slots {
+	/// BABE slots of the given duration in milliseconds.
+	Babe(u64),
+	/// Aura slots of the given duration in milliseconds.
+	Aura(u64),
+	/// No slots, the timestamp advances by the given block time in milliseconds.
+	Timestamp(u64),
+}
+
+impl Slots {
+	/// Detect the slots of `runtime` from the state in `ext`.
+	pub fn detect(
+		runtime: &Runtime,
+		ext: &mut TestExternalities<BlakeTwo256>,
+		block_time: u64,
+	) -> Result<Self, String> {
+		if current_slot(ext, "Babe")?.is_some() {
+			runtime.babe_slot_duration(&mut ext.ext()).map(Self::Babe)
+		} else if current_slot(ext, "Aura")?.is_some() {
+			runtime.aura_slot_duration(&mut ext.ext()).map(Self::Aura)
+		} else {
+			Ok(Self::Timestamp(block_time))
+		}
+	}
+
+	/// The header and the timestamp of the block after `parent` on top of the state in `ext`.
+	pub fn next_block(
+		&self,
+		ext: &mut TestExternalities<BlakeTwo256>,
+		parent: &Header,
+	) -> Result<(Header, u64), String> {
+		let (timestamp, digest) = match *self {
+			Self::Babe(duration) => {
+				let slot = current_slot(ext, "Babe")?.unwrap_or_default() + 1;
+				let pre_digest =
+					PreDigest::SecondaryPlain(SecondaryPlainPreDigest { authority_index: 0, slot });
+				(
+					*slot * duration,
+					Some(DigestItem::PreRuntime(BABE_ENGINE_ID, pre_digest.encode())),
+				)
+			},
+			Self::Aura(duration) => {
+				let slot = current_slot(ext, "Aura")?.unwrap_or_default() + 1;
+				(*slot * duration, Some(DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode())))
+			},
+			Self::Timestamp(block_time) => {
+				let now = storage_value::<u64>(ext, "Timestamp", "Now")?.unwrap_or_default();
+				(now + block_time, None)
+			},
+		};
+		let header = Header::new(
+			parent.number + 1,
+			Default::default(),
+			Default::default(),
+			parent.hash(),
+			Digest { logs: digest.into_iter().collect() },
+		);
+
+		Ok((header, timestamp))
+	}
+}
+
+/// The current slot of the consensus pallet `pallet` in `ext`, if it is present.
+fn current_slot(
+	ext: &mut TestExternalities<BlakeTwo256>,
+	pallet: &str,
+) -> Result<Option<Slot>, String> {
+	storage_value(ext, pallet, "CurrentSlot")
+}
+
+/// The value of the storage item `item` of `pallet` in `ext`, if it is present.
+fn storage_value<T: Decode>(
+	ext: &mut TestExternalities<BlakeTwo256>,
+	pallet: &str,
+	item: &str,
+) -> Result<Option<T>, String> {
+	ext.execute_with(|| sp_io::storage::get(&storage_key(pallet, item)))
+		.map(|value| {
+			T::decode(&mut &value[..])
+				.map_err(|e| format!("Failed to decode {pallet}::{item}: {e}"))
+		})
+		.transpose()
+}
+
+/// The key of the storage item `item` of `pallet`.
+fn storage_key(pallet: &str, item: &str) -> Vec<u8> {
+	let mut key = sp_crypto_hashing::twox_128(pallet.as_bytes()).to_vec();
+	key.extend(sp_crypto_hashing::twox_128(item.as_bytes()));
+	key
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parent() -> Header {
+		Header::new(5, Default::default(), Default::default(), [1u8; 32].into(), Default::default())
+	}
+
+	fn ext(pallet: &str, item: &str, value: u64) -> TestExternalities<BlakeTwo256> {
+		let mut ext = TestExternalities::default();
+		ext.insert(storage_key(pallet, item), value.encode());
+		ext
+	}
+
+	#[test]
+	fn next_block_follows_babe_slots() {
+		let parent = parent();
+		let (header, timestamp) = Slots::Babe(6_000)
+			.next_block(&mut ext("Babe", "CurrentSlot", 10), &parent)
+			.unwrap();
+
+		assert_eq!(header.number, 6);
+		assert_eq!(header.parent_hash, parent.hash());
+		let pre_digest = PreDigest::SecondaryPlain(SecondaryPlainPreDigest {
+			authority_index: 0,
+			slot: 11.into(),
+		});
+		assert_eq!(
+			header.digest.logs,
+			vec![DigestItem::PreRuntime(BABE_ENGINE_ID, pre_digest.encode())]
+		);
+		assert_eq!(timestamp, 66_000);
+	}
+
+	#[test]
+	fn next_block_follows_aura_slots() {
+		let (header, timestamp) = Slots::Aura(12_000)
+			.next_block(&mut ext("Aura", "CurrentSlot", 3), &parent())
+			.unwrap();
+
+		assert_eq!(
+			header.digest.logs,
+			vec![DigestItem::PreRuntime(AURA_ENGINE_ID, Slot::from(4).encode())]
+		);
+		assert_eq!(timestamp, 48_000);
+	}
+
+	#[test]
+	fn next_block_advances_timestamp() {
+		let (header, timestamp) = Slots::Timestamp(6_000)
+			.next_block(&mut ext("Timestamp", "Now", 1_000), &parent())
+			.unwrap();
+
+		assert!(header.digest.logs.is_empty());
+		assert_eq!(timestamp, 7_000);
+	}
+}
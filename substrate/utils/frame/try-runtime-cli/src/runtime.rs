@@ -0,0 +1,133 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Calls into the runtime blob that is tested.
+
+use crate::Header;
+use codec::{Decode, Encode};
+use frame_try_runtime::{MultiBlockMigrationsStep, UpgradeCheckSelect};
+use sc_executor::WasmExecutor;
+use sp_core::traits::{CallContext, CodeExecutor, RuntimeCode, WrappedRuntimeCode};
+use sp_inherents::InherentData;
+use sp_runtime::Weight;
+use std::path::Path;
+
+/// Runtime API method executing the runtime upgrade, only present in try-runtime builds.
+const TRY_ON_RUNTIME_UPGRADE: &str = "TryRuntime_on_runtime_upgrade";
+/// Runtime API method simulating one block of the multi-block migrations.
+const TRY_STEP_MULTI_BLOCK_MIGRATIONS: &str = "TryRuntime_step_multi_block_migrations";
+/// Runtime API method returning the configuration of BABE.
+const BABE_CONFIGURATION: &str = "BabeApi_configuration";
+/// Runtime API method returning the slot duration of Aura.
+const AURA_SLOT_DURATION: &str = "AuraApi_slot_duration";
+
+/// A runtime Wasm blob.
+pub struct Runtime {
+	code: Vec<u8>,
+	hash: Vec<u8>,
+	executor: WasmExecutor<sp_io::SubstrateHostFunctions>,
+}
+
+impl Runtime {
+	/// Read the runtime from the Wasm blob at `path`.
+	pub fn from_file(path: &Path) -> Result<Self, String> {
+		let code = std::fs::read(path)
+			.map_err(|e| format!("Failed to read runtime {}: {e}", path.display()))?;
+
+		Ok(Self {
+			hash: sp_crypto_hashing::blake2_256(&code).to_vec(),
+			code,
+			executor: WasmExecutor::builder().with_allow_missing_host_functions(true).build(),
+		})
+	}
+
+	/// Call `method` of the runtime with `data` on top of `ext`.
+	pub fn call(
+		&self,
+		ext: &mut dyn sp_externalities::Externalities,
+		method: &str,
+		data: &[u8],
+	) -> Result<Vec<u8>, String> {
+		let runtime_code = RuntimeCode {
+			code_fetcher: &WrappedRuntimeCode(self.code.as_slice().into()),
+			heap_pages: None,
+			hash: self.hash.clone(),
+		};
+
+		self.executor
+			.call(ext, &runtime_code, method, data, CallContext::Onchain)
+			.0
+			.map_err(|e| format!("Failed to call {method}: {e}"))
+	}
+
+	/// Call `method` of the runtime with `data` on top of `ext` and decode its result.
+	fn call_decode<R: Decode>(
+		&self,
+		ext: &mut dyn sp_externalities::Externalities,
+		method: &str,
+		data: &[u8],
+	) -> Result<R, String> {
+		let result = self.call(ext, method, data)?;
+		R::decode(&mut &result[..])
+			.map_err(|e| format!("Failed to decode the result of {method}: {e}"))
+	}
+
+	/// Execute the runtime upgrade on top of `ext`, which requires a try-runtime build.
+	///
+	/// Returns the weight consumed by the migrations and the maximal weight of a block.
+	pub fn try_on_runtime_upgrade(
+		&self,
+		ext: &mut dyn sp_externalities::Externalities,
+		checks: UpgradeCheckSelect,
+	) -> Result<(Weight, Weight), String> {
+		self.call_decode(ext, TRY_ON_RUNTIME_UPGRADE, &checks.encode())
+	}
+
+	/// Simulate the block of `header` with the inherents of `inherent_data` on top of `ext` and
+	/// step the multi-block migrations in it.
+	pub fn try_step_multi_block_migrations(
+		&self,
+		ext: &mut dyn sp_externalities::Externalities,
+		header: &Header,
+		inherent_data: &InherentData,
+		checks: UpgradeCheckSelect,
+	) -> Result<MultiBlockMigrationsStep, String> {
+		self.call_decode(
+			ext,
+			TRY_STEP_MULTI_BLOCK_MIGRATIONS,
+			&(header, inherent_data, checks).encode(),
+		)
+	}
+
+	/// The slot duration of BABE in milliseconds.
+	pub fn babe_slot_duration(
+		&self,
+		ext: &mut dyn sp_externalities::Externalities,
+	) -> Result<u64, String> {
+		self.call_decode::<sp_consensus_babe::BabeConfiguration>(ext, BABE_CONFIGURATION, &[])
+			.map(|config| config.slot_duration)
+	}
+
+	/// The slot duration of Aura in milliseconds.
+	pub fn aura_slot_duration(
+		&self,
+		ext: &mut dyn sp_externalities::Externalities,
+	) -> Result<u64, String> {
+		self.call_decode::<sp_consensus_aura::SlotDuration>(ext, AURA_SLOT_DURATION, &[])
+			.map(|duration| duration.as_millis())
+	}
+}